@@ -22,6 +22,12 @@ pub struct Bus {
     devices: HashMap<(DeviceAddress, DeviceSize), DeviceHandler>,
 }
 
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Bus {
     pub fn new() -> Self {
         info!("Creating new bus");
@@ -65,6 +71,40 @@ impl Bus {
         Err(CpuPeripheralsError::InvalidDeviceAddress(address))
     }
 
+    /// Finds the device holding all the `size` bytes at `address`, an access
+    /// running past the end of its device is an error
+    fn find_device_for(
+        &self,
+        address: DeviceAddress,
+        size: DeviceSize,
+    ) -> Result<&DeviceHandler, CpuPeripheralsError> {
+        for (&(start, end), device) in &self.devices {
+            if address >= start && address < end {
+                if address + size > end {
+                    return Err(CpuPeripheralsError::InvalidAddress(address));
+                }
+                return Ok(device);
+            }
+        }
+        Err(CpuPeripheralsError::InvalidDeviceAddress(address))
+    }
+
+    fn find_device_for_mut(
+        &mut self,
+        address: DeviceAddress,
+        size: DeviceSize,
+    ) -> Result<&mut DeviceHandler, CpuPeripheralsError> {
+        for (&(start, end), device) in &mut self.devices {
+            if address >= start && address < end {
+                if address + size > end {
+                    return Err(CpuPeripheralsError::InvalidAddress(address));
+                }
+                return Ok(device);
+            }
+        }
+        Err(CpuPeripheralsError::InvalidDeviceAddress(address))
+    }
+
     pub fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
         let device = self.find_device(address)?;
         let val = device.read_byte(address)?;
@@ -82,7 +122,7 @@ impl Bus {
     }
 
     pub fn read_halfword(&self, address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
-        let device = self.find_device(address)?;
+        let device = self.find_device_for(address, 2)?;
         let val = device.read_halfword(address)?;
         Ok(val)
     }
@@ -91,13 +131,13 @@ impl Bus {
         address: DeviceAddress,
         value: u16,
     ) -> Result<(), CpuPeripheralsError> {
-        let device = self.find_device_mut(address)?;
+        let device = self.find_device_for_mut(address, 2)?;
         device.write_halfword(address, value)?;
         Ok(())
     }
 
     pub fn read_word(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
-        let device = self.find_device(address)?;
+        let device = self.find_device_for(address, 4)?;
         let val = device.read_word(address)?;
         Ok(val)
     }
@@ -106,10 +146,64 @@ impl Bus {
         address: DeviceAddress,
         value: u32,
     ) -> Result<(), CpuPeripheralsError> {
-        let device = self.find_device_mut(address)?;
+        let device = self.find_device_for_mut(address, 4)?;
         device.write_word(address, value)?;
         Ok(())
     }
+
+    /// Reads a doubleword as two little-endian words, the low one first
+    pub fn read_doubleword(&self, address: DeviceAddress) -> Result<u64, CpuPeripheralsError> {
+        let low = self.read_word(address)?;
+        let high = self.read_word(address + 4)?;
+        Ok(((high as u64) << 32) | low as u64)
+    }
+
+    /// Writes a doubleword as two little-endian words, the low one first
+    pub fn write_doubleword(
+        &mut self,
+        address: DeviceAddress,
+        value: u64,
+    ) -> Result<(), CpuPeripheralsError> {
+        // nothing is written unless the high word can be
+        self.find_device_for(address + 4, 4)?;
+        self.write_word(address, value as u32)?;
+        self.write_word(address + 4, (value >> 32) as u32)?;
+        Ok(())
+    }
+
+    /// Advances every device by `cycles` hart clock cycles
+    pub fn tick(&mut self, cycles: u64) {
+        for device in self.devices.values_mut() {
+            device.tick(cycles);
+        }
+    }
+
+    /// Returns the mtime of the CLINT, if there is one on the bus
+    pub fn mtime(&self) -> Option<u64> {
+        self.devices.values().find_map(|device| device.mtime())
+    }
+
+    /// Returns the MIP_* bits of the interrupts asserted by the devices
+    pub fn interrupts(&self) -> u64 {
+        self.devices
+            .values()
+            .fold(0, |interrupts, device| interrupts | device.interrupts())
+    }
+
+    /// Returns the number of cycles until the first scheduled device event
+    pub fn next_event(&self) -> Option<u64> {
+        self.devices
+            .values()
+            .filter_map(|device| device.next_event())
+            .min()
+    }
+
+    /// Flushes the output held by the devices
+    pub fn flush(&mut self) {
+        for device in self.devices.values_mut() {
+            device.flush();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -120,7 +214,7 @@ mod tests {
     #[test]
     fn test_bus_add_and_find_device() {
         let mut bus = Bus::new();
-        let clint = DevicePointer::new(Clint::new());
+        let clint = DevicePointer::new(Clint::default());
         let mem = DevicePointer::new(Mem::new(256));
         let uart = DevicePointer::new(Uart::new("test_uart"));
 
@@ -156,5 +250,49 @@ mod tests {
 
         assert!(bus.write_word(0x1000_0008, 0x1234abcd).is_ok());
         assert_eq!(bus.read_word(0x1000_0008), Ok(0x1234abcd));
+
+        assert!(bus
+            .write_doubleword(0x1000_0010, 0x1122_3344_5566_7788)
+            .is_ok());
+        assert_eq!(bus.read_doubleword(0x1000_0010), Ok(0x1122_3344_5566_7788));
+        assert_eq!(bus.read_word(0x1000_0014), Ok(0x1122_3344));
+    }
+
+    #[test]
+    fn test_bus_access_errors() {
+        let mut bus = Bus::new();
+        let mem = DevicePointer::new(Mem::new(256));
+        let _ = bus.add_device(0x1000_0000, 256, mem);
+
+        assert_eq!(
+            bus.read_byte(0x2000_0000),
+            Err(CpuPeripheralsError::InvalidDeviceAddress(0x2000_0000))
+        );
+        // an access must be in a single device
+        assert_eq!(
+            bus.read_word(0x1000_00fe),
+            Err(CpuPeripheralsError::InvalidAddress(0x1000_00fe))
+        );
+        assert!(bus.write_halfword(0x1000_00ff, 0x1234).is_err());
+        assert!(bus.write_halfword(0x1000_00fe, 0x1234).is_ok());
+
+        // nothing is written if the high word fails
+        assert!(bus
+            .write_doubleword(0x1000_00fc, 0x1122_3344_5566_7788)
+            .is_err());
+        assert_eq!(bus.read_word(0x1000_00fc), Ok(0x1234_0000));
+    }
+
+    #[test]
+    fn test_bus_tick_and_mtime() {
+        let mut bus = Bus::new();
+        let mem = DevicePointer::new(Mem::new(256));
+        let _ = bus.add_device(0x1000_0000, 256, mem);
+        assert_eq!(bus.mtime(), None);
+
+        let clint = DevicePointer::new(Clint::default());
+        let _ = bus.add_device(0x0200_0000, 0x1_0000, clint);
+        bus.tick(3);
+        assert_eq!(bus.mtime(), Some(3));
     }
 }
@@ -5,22 +5,99 @@
 
 // cpu_peripherals/src/clint.rs
 
-#![allow(dead_code)]
-#![allow(unused_variables)]
-
 use tracing::info;
 
-use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType};
+use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType, MIP_MSIP, MIP_MTIP};
+
+// SiFive CLINT register map of a single hart. The 64-bit registers are
+// accessed as two little-endian words.
+const MSIP_OFFSET: DeviceAddress = 0x0000;
+const MSIP_END: DeviceAddress = MSIP_OFFSET + 4;
+const MTIMECMP_OFFSET: DeviceAddress = 0x4000;
+const MTIMECMP_END: DeviceAddress = MTIMECMP_OFFSET + 8;
+const MTIME_OFFSET: DeviceAddress = 0xbff8;
+const MTIME_END: DeviceAddress = MTIME_OFFSET + 8;
+
+// Only the lowest bit of msip is implemented, the others read as zero
+const MSIP_WRITABLE: u32 = 0x1;
 
 pub struct Clint {
-    // Add necessary fields for Clint
     base_addr: DeviceAddress,
+    // asserts MSIP while bit 0 is set
+    msip: u32,
+    // asserts MTIP while mtime >= mtimecmp
+    mtimecmp: u64,
+    // advances by one every ticks_per_increment hart clock cycles
+    mtime: u64,
+    ticks_per_increment: u64,
+    // cycles counted towards the next increment of mtime
+    ticks: u64,
+}
+
+impl Default for Clint {
+    /// A CLINT whose mtime advances by one per hart clock cycle
+    fn default() -> Self {
+        Self::new(1)
+    }
 }
 
 impl Clint {
-    pub fn new() -> Self {
+    /// Creates a CLINT whose mtime advances by one every `ticks_per_increment`
+    /// hart clock cycles, the ratio of the hart clock to the timebase. 0 is
+    /// taken as 1.
+    pub fn new(ticks_per_increment: u64) -> Self {
         info!("Creating a new Clint device");
-        Self { base_addr: 0 }
+        Self {
+            base_addr: 0,
+            msip: 0,
+            // no timer interrupt until the software programs mtimecmp
+            mtimecmp: u64::MAX,
+            mtime: 0,
+            ticks_per_increment: ticks_per_increment.max(1),
+            ticks: 0,
+        }
+    }
+
+    /// Returns the byte `index` of `value`
+    fn get_byte(value: u64, index: DeviceAddress) -> u8 {
+        (value >> (8 * index)) as u8
+    }
+
+    /// Returns `value` with its byte `index` replaced by `byte`
+    fn set_byte(value: u64, index: DeviceAddress, byte: u8) -> u64 {
+        let shift = 8 * index;
+        (value & !(0xff << shift)) | ((byte as u64) << shift)
+    }
+
+    // Registers are read byte by byte, the unimplemented ones read as zero
+    fn read_register_byte(&self, address: DeviceAddress) -> u8 {
+        let offset = address - self.base_addr;
+        match offset {
+            MSIP_OFFSET..MSIP_END => Self::get_byte(self.msip as u64, offset - MSIP_OFFSET),
+            MTIMECMP_OFFSET..MTIMECMP_END => {
+                Self::get_byte(self.mtimecmp, offset - MTIMECMP_OFFSET)
+            }
+            MTIME_OFFSET..MTIME_END => Self::get_byte(self.mtime, offset - MTIME_OFFSET),
+            _ => 0,
+        }
+    }
+
+    // Writes to the unimplemented registers are dropped
+    fn write_register_byte(&mut self, address: DeviceAddress, value: u8) {
+        let offset = address - self.base_addr;
+        match offset {
+            MSIP_OFFSET..MSIP_END => {
+                let msip = Self::set_byte(self.msip as u64, offset - MSIP_OFFSET, value);
+                self.msip = msip as u32 & MSIP_WRITABLE;
+            }
+            MTIMECMP_OFFSET..MTIMECMP_END => {
+                self.mtimecmp = Self::set_byte(self.mtimecmp, offset - MTIMECMP_OFFSET, value);
+            }
+            MTIME_OFFSET..MTIME_END => {
+                self.mtime = Self::set_byte(self.mtime, offset - MTIME_OFFSET, value);
+            }
+            _ => {}
+        }
     }
 }
 
@@ -33,18 +110,17 @@ impl Device for Clint {
     }
 
     fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
-        // Implementation of reading a byte from Clint
-        Ok(0)
+        Ok(self.read_register_byte(address))
     }
 
     fn write_byte(&mut self, address: DeviceAddress, value: u8) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a byte to Clint
+        self.write_register_byte(address, value);
         Ok(())
     }
 
     fn read_halfword(&self, address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
-        // Implementation of reading a halfword from Clint
-        Ok(0)
+        let data = self.read(address, 2)?;
+        Ok(u16::from_le_bytes([data[0], data[1]]))
     }
 
     fn write_halfword(
@@ -52,13 +128,12 @@ impl Device for Clint {
         address: DeviceAddress,
         value: u16,
     ) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a halfword to Clint
-        Ok(())
+        self.write(address, &value.to_le_bytes())
     }
 
     fn read_word(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
-        // Implementation of reading a word from Clint
-        Ok(0)
+        let data = self.read(address, 4)?;
+        Ok(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
     }
 
     fn write_word(
@@ -66,17 +141,128 @@ impl Device for Clint {
         address: DeviceAddress,
         value: u32,
     ) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a word to Clint
-        Ok(())
+        self.write(address, &value.to_le_bytes())
     }
 
     fn read(&self, address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
-        // Implementation of reading an arbitrary length from Clint
-        Ok(vec![0; size])
+        Ok((0..size)
+            .map(|i| self.read_register_byte(address + i))
+            .collect())
     }
 
     fn write(&mut self, address: DeviceAddress, data: &[u8]) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing an arbitrary length to Clint
+        for (i, &value) in data.iter().enumerate() {
+            self.write_register_byte(address + i, value);
+        }
         Ok(())
     }
+
+    fn tick(&mut self, cycles: u64) {
+        let ticks = self.ticks as u128 + cycles as u128;
+        let increments = ticks / self.ticks_per_increment as u128;
+        self.mtime = self.mtime.wrapping_add(increments as u64);
+        self.ticks = (ticks % self.ticks_per_increment as u128) as u64;
+    }
+
+    fn mtime(&self) -> Option<u64> {
+        Some(self.mtime)
+    }
+
+    fn interrupts(&self) -> u64 {
+        let mut interrupts = 0;
+        if self.msip & MSIP_WRITABLE != 0 {
+            interrupts |= MIP_MSIP;
+        }
+        if self.mtime >= self.mtimecmp {
+            interrupts |= MIP_MTIP;
+        }
+        interrupts
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        // the timer interrupt stays asserted once raised
+        if self.mtime < self.mtimecmp {
+            let increments = self.mtimecmp - self.mtime;
+            Some(
+                increments
+                    .saturating_mul(self.ticks_per_increment)
+                    .saturating_sub(self.ticks),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtime() {
+        let mut clint = Clint::default();
+        clint.set_base_addr(0x0200_0000);
+        clint.tick(0x1_0000_0002);
+        assert_eq!(clint.mtime(), Some(0x1_0000_0002));
+        assert_eq!(clint.read_word(0x0200_bff8), Ok(2));
+        assert_eq!(clint.read_word(0x0200_bffc), Ok(1));
+
+        clint.write_word(0x0200_bffc, 0).unwrap();
+        assert_eq!(clint.mtime(), Some(2));
+        assert_eq!(clint.read_word(0x0200_0000), Ok(0));
+    }
+
+    #[test]
+    fn test_msip() {
+        let mut clint = Clint::default();
+        clint.set_base_addr(0x0200_0000);
+        assert_eq!(clint.interrupts(), 0);
+        // only bit 0 is implemented
+        clint.write_word(0x0200_0000, 0xffff_ffff).unwrap();
+        assert_eq!(clint.read_word(0x0200_0000), Ok(1));
+        assert_eq!(clint.interrupts(), MIP_MSIP);
+        clint.write_byte(0x0200_0000, 0).unwrap();
+        assert_eq!(clint.interrupts(), 0);
+    }
+
+    #[test]
+    fn test_mtimecmp() {
+        let mut clint = Clint::default();
+        clint.set_base_addr(0x0200_0000);
+        clint.write_word(0x0200_4000, 10).unwrap();
+        clint.write_word(0x0200_4004, 0).unwrap();
+        assert_eq!(clint.read_word(0x0200_4000), Ok(10));
+        assert_eq!(clint.next_event(), Some(10));
+
+        clint.tick(9);
+        assert_eq!(clint.interrupts(), 0);
+        assert_eq!(clint.next_event(), Some(1));
+        clint.tick(1);
+        assert_eq!(clint.interrupts(), MIP_MTIP);
+        assert_eq!(clint.next_event(), None);
+
+        // a later mtimecmp clears MTIP
+        clint.write_word(0x0200_4004, 1).unwrap();
+        assert_eq!(clint.interrupts(), 0);
+        assert_eq!(clint.next_event(), Some(0x1_0000_0000));
+    }
+
+    #[test]
+    fn test_ticks_per_increment() {
+        let mut clint = Clint::new(3);
+        clint.set_base_addr(0x0200_0000);
+        clint.write_word(0x0200_4000, 2).unwrap();
+        clint.write_word(0x0200_4004, 0).unwrap();
+        assert_eq!(clint.next_event(), Some(6));
+
+        // the cycles short of an increment are kept for the next tick
+        clint.tick(4);
+        assert_eq!(clint.mtime(), Some(1));
+        assert_eq!(clint.next_event(), Some(2));
+        clint.tick(1);
+        assert_eq!(clint.interrupts(), 0);
+        clint.tick(1);
+        assert_eq!(clint.mtime(), Some(2));
+        assert_eq!(clint.interrupts(), MIP_MTIP);
+    }
 }
@@ -8,8 +8,14 @@
 pub mod bus;
 pub mod clint;
 pub mod mem;
+pub mod ns16550;
+pub mod plic;
+pub mod serial;
 pub mod uart;
 
+use std::cell::Cell;
+use std::rc::Rc;
+
 use thiserror::Error;
 
 /// Define error types for the cpu_peripherals crate.
@@ -39,6 +45,9 @@ pub enum CpuPeripheralsError {
 
     #[error("Invalid device address: {0}")]
     InvalidDeviceAddress(DeviceAddress),
+
+    #[error("Invalid character backend: {0}")]
+    InvalidCharBackend(String),
 }
 
 // Enum to define the type of Device
@@ -46,12 +55,49 @@ pub enum CpuPeripheralsError {
 pub enum DeviceType {
     Clint,
     Mem,
+    Ns16550,
+    Plic,
     Uart,
 }
 
 pub type DeviceAddress = usize;
 pub type DeviceSize = usize;
 
+// The mip bits of the interrupts a device can assert
+pub const MIP_MSIP: u64 = 0x008;
+pub const MIP_MTIP: u64 = 0x080;
+pub const MIP_MEIP: u64 = 0x800;
+
+/// A level-triggered interrupt line from a device to an interrupt controller,
+/// the device holds a clone of the line the controller samples
+#[derive(Debug, Clone, Default)]
+pub struct InterruptLine {
+    level: Rc<Cell<bool>>,
+}
+
+impl InterruptLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asserts the line while `level` is true
+    pub fn set(&self, level: bool) {
+        self.level.set(level);
+    }
+
+    pub fn raise(&self) {
+        self.set(true);
+    }
+
+    pub fn lower(&self) {
+        self.set(false);
+    }
+
+    pub fn is_raised(&self) -> bool {
+        self.level.get()
+    }
+}
+
 // Trait to define the interface for a Device
 pub trait Device {
     fn get_type(&self) -> DeviceType;
@@ -73,16 +119,40 @@ pub trait Device {
 
     fn read(&self, address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError>;
     fn write(&mut self, address: DeviceAddress, data: &[u8]) -> Result<(), CpuPeripheralsError>;
+
+    /// Advances the device by `cycles` hart clock cycles
+    fn tick(&mut self, _cycles: u64) {}
+
+    /// Returns the machine timer, only the CLINT has one
+    fn mtime(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the MIP_* bits of the interrupts the device asserts, they are
+    /// level-triggered and stay pending in mip until it deasserts them
+    fn interrupts(&self) -> u64 {
+        0
+    }
+
+    /// Returns the number of cycles until the device asserts an interrupt on
+    /// its own, None if nothing is scheduled. A hart stalled by WFI skips them.
+    fn next_event(&self) -> Option<u64> {
+        None
+    }
+
+    /// Sends out the output the device still holds, called when the target
+    /// exits
+    fn flush(&mut self) {}
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{clint::Clint, mem::Mem, uart::Uart};
+    use crate::{clint::Clint, mem::Mem, ns16550::Ns16550, plic::Plic, uart::Uart};
 
     #[test]
     fn test_clint_device() {
-        let clint = Clint::new();
+        let clint = Clint::default();
         assert_eq!(clint.get_type(), DeviceType::Clint);
     }
 
@@ -92,6 +162,29 @@ mod tests {
         assert_eq!(mem.get_type(), DeviceType::Mem);
     }
 
+    #[test]
+    fn test_ns16550_device() {
+        let uart = Ns16550::new("NS16550");
+        assert_eq!(uart.get_type(), DeviceType::Ns16550);
+    }
+
+    #[test]
+    fn test_plic_device() {
+        let plic = Plic::new();
+        assert_eq!(plic.get_type(), DeviceType::Plic);
+    }
+
+    #[test]
+    fn test_interrupt_line() {
+        let line = InterruptLine::new();
+        let device_end = line.clone();
+        assert!(!line.is_raised());
+        device_end.raise();
+        assert!(line.is_raised());
+        device_end.lower();
+        assert!(!line.is_raised());
+    }
+
     #[test]
     fn test_uart_device() {
         let uart = Uart::new("UARTX");
@@ -38,13 +38,13 @@ impl Device for Mem {
     fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
         // Implementation of reading a byte from Mem
         let addr = address - self.base_addr;
-        Ok(self.data[addr as usize])
+        Ok(self.data[addr])
     }
 
     fn write_byte(&mut self, address: DeviceAddress, value: u8) -> Result<(), CpuPeripheralsError> {
         // Implementation of writing a byte to Mem
         let addr = address - self.base_addr;
-        self.data[addr as usize] = value;
+        self.data[addr] = value;
         Ok(())
     }
 
@@ -0,0 +1,735 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// cpu_peripherals/src/ns16550.rs
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use tracing::{info, trace};
+
+use crate::serial::{CharBackend, StdioBackend};
+use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType, InterruptLine};
+
+// NS16550A, e.g. 0x1000_0000 on the QEMU virt machine
+// The registers are 8 bits wide, 1 << reg_shift bytes apart.
+// 0 RBR/THR/DLL  Receive buffer, transmit holding, divisor latch low
+// 1 IER/DLM      Interrupt enable, divisor latch high
+// 2 IIR/FCR      Interrupt identification, FIFO control
+// 3 LCR          Line control
+// 4 MCR          Modem control
+// 5 LSR          Line status
+// 6 MSR          Modem status
+// 7 SCR          Scratch
+// The divisor latches replace RBR/THR and IER while LCR.DLAB is set.
+pub const UART_RBR: DeviceAddress = 0;
+pub const UART_THR: DeviceAddress = 0;
+pub const UART_DLL: DeviceAddress = 0;
+pub const UART_IER: DeviceAddress = 1;
+pub const UART_DLM: DeviceAddress = 1;
+pub const UART_IIR: DeviceAddress = 2;
+pub const UART_FCR: DeviceAddress = 2;
+pub const UART_LCR: DeviceAddress = 3;
+pub const UART_MCR: DeviceAddress = 4;
+pub const UART_LSR: DeviceAddress = 5;
+pub const UART_MSR: DeviceAddress = 6;
+pub const UART_SCR: DeviceAddress = 7;
+
+// IER: received data available, THR empty, receiver line status and modem
+// status interrupts
+pub const UART_IER_RDI: u8 = 0x01;
+pub const UART_IER_THRI: u8 = 0x02;
+pub const UART_IER_RLSI: u8 = 0x04;
+pub const UART_IER_MSI: u8 = 0x08;
+
+// IIR: the pending interrupt of the highest priority, and the FIFOs enabled
+pub const UART_IIR_NO_INT: u8 = 0x01;
+pub const UART_IIR_MSI: u8 = 0x00;
+pub const UART_IIR_THRI: u8 = 0x02;
+pub const UART_IIR_RDI: u8 = 0x04;
+pub const UART_IIR_RLSI: u8 = 0x06;
+pub const UART_IIR_RX_TIMEOUT: u8 = 0x0c;
+pub const UART_IIR_FIFO_ENABLED: u8 = 0xc0;
+
+// FCR: enable the FIFOs, clear them, and the RX trigger level
+pub const UART_FCR_ENABLE_FIFO: u8 = 0x01;
+pub const UART_FCR_CLEAR_RCVR: u8 = 0x02;
+pub const UART_FCR_CLEAR_XMIT: u8 = 0x04;
+const UART_FCR_TRIGGER_SHIFT: u8 = 6;
+// the RX trigger levels of FCR bits 7:6
+const UART_FCR_TRIGGER_LEVELS: [usize; 4] = [1, 4, 8, 14];
+
+// LCR: word length, stop bits, parity enable and divisor latch access
+const UART_LCR_WLEN_MASK: u8 = 0x03;
+const UART_LCR_STOP: u8 = 0x04;
+const UART_LCR_PARITY: u8 = 0x08;
+pub const UART_LCR_DLAB: u8 = 0x80;
+
+// MCR: DTR, RTS, OUT1, OUT2 and loopback
+const UART_MCR_WRITABLE: u8 = 0x1f;
+pub const UART_MCR_LOOP: u8 = 0x10;
+
+// LSR: data ready, overrun, THR empty and transmitter empty
+pub const UART_LSR_DR: u8 = 0x01;
+pub const UART_LSR_OE: u8 = 0x02;
+pub const UART_LSR_THRE: u8 = 0x20;
+pub const UART_LSR_TEMT: u8 = 0x40;
+
+// MSR: CTS, DSR, RI and DCD
+pub const UART_MSR_CTS: u8 = 0x10;
+pub const UART_MSR_DSR: u8 = 0x20;
+pub const UART_MSR_RI: u8 = 0x40;
+pub const UART_MSR_DCD: u8 = 0x80;
+
+// Depth of the TX and RX FIFOs, a single holding register while disabled
+pub const NS16550_FIFO_DEPTH: usize = 16;
+// The baud rate is the clock divided by 16 * divisor
+const UART_CLOCKS_PER_BIT: u64 = 16;
+// The character timeout interrupt is raised after 4 idle frames
+const UART_TIMEOUT_FRAMES: u64 = 4;
+
+pub struct Ns16550 {
+    name: &'static str,
+    base_addr: DeviceAddress,
+    reg_shift: u32,
+    backend: Box<dyn CharBackend>,
+    // the byte being sent is in the transmit shift register
+    tx_fifo: VecDeque<u8>,
+    tx_shift: Option<u8>,
+    // reading RBR pops the FIFO
+    rx_fifo: RefCell<VecDeque<u8>>,
+    ier: u8,
+    fcr: u8,
+    lcr: u8,
+    mcr: u8,
+    scr: u8,
+    divisor: u16,
+    // reading LSR clears the overrun, reading IIR clears the THR empty
+    // interrupt it reports
+    overrun: Cell<bool>,
+    thr_empty_interrupt: Cell<bool>,
+    // cycles spent on the frames being sent and received, and since the
+    // last character received or read
+    tx_cycles: u64,
+    rx_cycles: u64,
+    rx_idle_cycles: Cell<u64>,
+    interrupt_line: InterruptLine,
+}
+
+impl Ns16550 {
+    /// Creates a UART connected to stdin and stdout, with its registers at
+    /// consecutive bytes
+    pub fn new(name: &'static str) -> Self {
+        info!("Creating a new NS16550 device");
+        Self {
+            name,
+            base_addr: 0,
+            reg_shift: 0,
+            backend: Box::new(StdioBackend::new()),
+            tx_fifo: VecDeque::with_capacity(NS16550_FIFO_DEPTH),
+            tx_shift: None,
+            rx_fifo: RefCell::new(VecDeque::with_capacity(NS16550_FIFO_DEPTH)),
+            ier: 0,
+            fcr: 0,
+            lcr: 0,
+            mcr: 0,
+            scr: 0,
+            divisor: 0,
+            overrun: Cell::new(false),
+            thr_empty_interrupt: Cell::new(false),
+            tx_cycles: 0,
+            rx_cycles: 0,
+            rx_idle_cycles: Cell::new(0),
+            interrupt_line: InterruptLine::new(),
+        }
+    }
+
+    /// Places the registers 1 << `reg_shift` bytes apart, e.g. 2 for a bus
+    /// with 32-bit registers
+    pub fn set_reg_shift(&mut self, reg_shift: u32) {
+        self.reg_shift = reg_shift;
+    }
+
+    /// Connects the UART to the host end the frames are sent to and
+    /// received from
+    pub fn set_backend(&mut self, backend: Box<dyn CharBackend>) {
+        self.backend = backend;
+    }
+
+    /// Connects the interrupt output of the UART, raised while IIR reports
+    /// an interrupt, e.g. to a PLIC source
+    pub fn set_interrupt_line(&mut self, line: InterruptLine) {
+        self.interrupt_line = line;
+        self.update_interrupt_line();
+    }
+
+    fn is_fifo_enabled(&self) -> bool {
+        self.fcr & UART_FCR_ENABLE_FIFO != 0
+    }
+
+    fn fifo_depth(&self) -> usize {
+        if self.is_fifo_enabled() {
+            NS16550_FIFO_DEPTH
+        } else {
+            1
+        }
+    }
+
+    fn rx_trigger_level(&self) -> usize {
+        if self.is_fifo_enabled() {
+            UART_FCR_TRIGGER_LEVELS[(self.fcr >> UART_FCR_TRIGGER_SHIFT) as usize]
+        } else {
+            1
+        }
+    }
+
+    fn is_loopback(&self) -> bool {
+        self.mcr & UART_MCR_LOOP != 0
+    }
+
+    /// Returns the number of cycles to send or receive a frame of a start
+    /// bit, the data bits, the parity bit and the stop bits
+    fn frame_cycles(&self) -> u64 {
+        let data_bits = 5 + (self.lcr & UART_LCR_WLEN_MASK) as u64;
+        let parity_bits = (self.lcr & UART_LCR_PARITY != 0) as u64;
+        let stop_bits = if self.lcr & UART_LCR_STOP != 0 { 2 } else { 1 };
+        let bits = 1 + data_bits + parity_bits + stop_bits;
+        UART_CLOCKS_PER_BIT * self.divisor.max(1) as u64 * bits
+    }
+
+    fn is_receiving(&self) -> bool {
+        !self.is_loopback() && self.rx_fifo.borrow().len() < self.fifo_depth()
+    }
+
+    /// The received characters below the trigger level time out after 4
+    /// idle frames in the FIFO mode
+    fn is_rx_timeout_pending(&self) -> bool {
+        let len = self.rx_fifo.borrow().len();
+        self.is_fifo_enabled()
+            && len > 0
+            && len < self.rx_trigger_level()
+            && self.rx_idle_cycles.get() >= UART_TIMEOUT_FRAMES * self.frame_cycles()
+    }
+
+    fn lsr(&self) -> u8 {
+        let mut lsr = 0;
+        if !self.rx_fifo.borrow().is_empty() {
+            lsr |= UART_LSR_DR;
+        }
+        if self.overrun.get() {
+            lsr |= UART_LSR_OE;
+        }
+        if self.tx_fifo.is_empty() {
+            lsr |= UART_LSR_THRE;
+            if self.tx_shift.is_none() {
+                lsr |= UART_LSR_TEMT;
+            }
+        }
+        lsr
+    }
+
+    /// The modem inputs follow the outputs of MCR in the loopback mode,
+    /// otherwise the host is always ready. The delta bits are never set.
+    fn msr(&self) -> u8 {
+        if !self.is_loopback() {
+            return UART_MSR_DCD | UART_MSR_DSR | UART_MSR_CTS;
+        }
+        let mut msr = 0;
+        for (mcr, msr_bit) in [
+            (0x01, UART_MSR_DSR),
+            (0x02, UART_MSR_CTS),
+            (0x04, UART_MSR_RI),
+            (0x08, UART_MSR_DCD),
+        ] {
+            if self.mcr & mcr != 0 {
+                msr |= msr_bit;
+            }
+        }
+        msr
+    }
+
+    /// Returns the enabled interrupt of the highest priority
+    fn pending_interrupt(&self) -> Option<u8> {
+        if self.ier & UART_IER_RLSI != 0 && self.overrun.get() {
+            return Some(UART_IIR_RLSI);
+        }
+        if self.ier & UART_IER_RDI != 0 {
+            if self.rx_fifo.borrow().len() >= self.rx_trigger_level() {
+                return Some(UART_IIR_RDI);
+            }
+            if self.is_rx_timeout_pending() {
+                return Some(UART_IIR_RX_TIMEOUT);
+            }
+        }
+        if self.ier & UART_IER_THRI != 0 && self.thr_empty_interrupt.get() {
+            return Some(UART_IIR_THRI);
+        }
+        None
+    }
+
+    fn iir(&self) -> u8 {
+        let fifo = if self.is_fifo_enabled() {
+            UART_IIR_FIFO_ENABLED
+        } else {
+            0
+        };
+        fifo | self.pending_interrupt().unwrap_or(UART_IIR_NO_INT)
+    }
+
+    fn update_interrupt_line(&self) {
+        self.interrupt_line.set(self.pending_interrupt().is_some());
+    }
+
+    /// Returns the register at `address`, None if there is none
+    fn register_index(&self, address: DeviceAddress) -> Option<DeviceAddress> {
+        let offset = address.checked_sub(self.base_addr)?;
+        let index = offset >> self.reg_shift;
+        (index <= UART_SCR && offset & ((1 << self.reg_shift) - 1) == 0).then_some(index)
+    }
+
+    fn read_register(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
+        let dlab = self.lcr & UART_LCR_DLAB != 0;
+        let value = match self.register_index(address) {
+            Some(UART_DLL) if dlab => self.divisor as u8,
+            Some(UART_DLM) if dlab => (self.divisor >> 8) as u8,
+            Some(UART_RBR) => {
+                let byte = self.rx_fifo.borrow_mut().pop_front();
+                self.rx_idle_cycles.set(0);
+                self.update_interrupt_line();
+                byte.unwrap_or(0)
+            }
+            Some(UART_IER) => self.ier,
+            Some(UART_IIR) => {
+                let iir = self.iir();
+                if iir & !UART_IIR_FIFO_ENABLED == UART_IIR_THRI {
+                    self.thr_empty_interrupt.set(false);
+                    self.update_interrupt_line();
+                }
+                iir
+            }
+            Some(UART_LCR) => self.lcr,
+            Some(UART_MCR) => self.mcr,
+            Some(UART_LSR) => {
+                let lsr = self.lsr();
+                self.overrun.set(false);
+                self.update_interrupt_line();
+                lsr
+            }
+            Some(UART_MSR) => self.msr(),
+            Some(UART_SCR) => self.scr,
+            _ => return Err(CpuPeripheralsError::DeviceReadFailed(address as u64)),
+        };
+        Ok(value)
+    }
+
+    fn write_register(
+        &mut self,
+        address: DeviceAddress,
+        value: u8,
+    ) -> Result<(), CpuPeripheralsError> {
+        let dlab = self.lcr & UART_LCR_DLAB != 0;
+        match self.register_index(address) {
+            Some(UART_DLL) if dlab => self.divisor = (self.divisor & 0xff00) | value as u16,
+            Some(UART_DLM) if dlab => {
+                self.divisor = (self.divisor & 0x00ff) | ((value as u16) << 8)
+            }
+            // a write to a full FIFO is dropped
+            Some(UART_THR) => {
+                if self.tx_fifo.len() < self.fifo_depth() {
+                    self.tx_fifo.push_back(value);
+                }
+                self.thr_empty_interrupt.set(false);
+                self.load_tx_shift();
+            }
+            Some(UART_IER) => {
+                // enabling the THR empty interrupt while THR is empty raises it
+                if value & !self.ier & UART_IER_THRI != 0 && self.tx_fifo.is_empty() {
+                    self.thr_empty_interrupt.set(true);
+                }
+                self.ier = value & (UART_IER_RDI | UART_IER_THRI | UART_IER_RLSI | UART_IER_MSI);
+            }
+            Some(UART_FCR) => self.write_fcr(value),
+            Some(UART_LCR) => self.lcr = value,
+            Some(UART_MCR) => self.mcr = value & UART_MCR_WRITABLE,
+            // LSR and MSR are read-only
+            Some(UART_LSR | UART_MSR) => {}
+            Some(UART_SCR) => self.scr = value,
+            _ => return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64)),
+        }
+        self.update_interrupt_line();
+        Ok(())
+    }
+
+    /// Enabling or disabling the FIFOs clears them, as do the clear bits
+    fn write_fcr(&mut self, value: u8) {
+        let toggled = (self.fcr ^ value) & UART_FCR_ENABLE_FIFO != 0;
+        if toggled || value & UART_FCR_CLEAR_RCVR != 0 {
+            self.rx_fifo.borrow_mut().clear();
+            self.rx_idle_cycles.set(0);
+        }
+        if toggled || value & UART_FCR_CLEAR_XMIT != 0 {
+            self.tx_fifo.clear();
+            self.load_tx_shift();
+        }
+        // the clear bits are self-clearing
+        self.fcr = value & !(UART_FCR_CLEAR_RCVR | UART_FCR_CLEAR_XMIT);
+    }
+
+    /// Moves the next byte to the idle shift register, THR becomes empty
+    /// with the last one
+    fn load_tx_shift(&mut self) {
+        if self.tx_shift.is_some() {
+            return;
+        }
+        self.tx_shift = self.tx_fifo.pop_front();
+        if self.tx_shift.is_some() && self.tx_fifo.is_empty() {
+            self.thr_empty_interrupt.set(true);
+        }
+    }
+
+    /// Stores a received byte, which is lost if the RX FIFO is full
+    fn receive_byte(&mut self, byte: u8) {
+        let mut rx_fifo = self.rx_fifo.borrow_mut();
+        if rx_fifo.len() < self.fifo_depth() {
+            rx_fifo.push_back(byte);
+        } else {
+            self.overrun.set(true);
+        }
+        self.rx_idle_cycles.set(0);
+    }
+
+    /// Sends the frames completed in `cycles` cycles, back to the receiver
+    /// in the loopback mode
+    fn transmit(&mut self, cycles: u64) {
+        if self.tx_shift.is_none() {
+            self.tx_cycles = 0;
+            return;
+        }
+        self.tx_cycles += cycles;
+        let frame = self.frame_cycles();
+        while self.tx_cycles >= frame {
+            let Some(byte) = self.tx_shift.take() else {
+                self.tx_cycles = 0;
+                break;
+            };
+            if self.is_loopback() {
+                self.receive_byte(byte);
+            } else {
+                trace!("{} sends {:#04x}", self.name, byte);
+                self.backend.write(&[byte]);
+            }
+            self.tx_cycles -= frame;
+            self.load_tx_shift();
+        }
+    }
+
+    /// Receives the frames completed in `cycles` cycles. The backend is
+    /// polled once per frame while the RX FIFO has room, the frames of the
+    /// cycles it had no input for are not received later.
+    fn receive(&mut self, cycles: u64) {
+        if !self.rx_fifo.borrow().is_empty() {
+            self.rx_idle_cycles
+                .set(self.rx_idle_cycles.get().saturating_add(cycles));
+        }
+        if !self.is_receiving() {
+            self.rx_cycles = 0;
+            return;
+        }
+        self.rx_cycles += cycles;
+        let frame = self.frame_cycles();
+        while self.rx_cycles >= frame {
+            if !self.is_receiving() {
+                self.rx_cycles = 0;
+                break;
+            }
+            let Some(byte) = self.backend.read() else {
+                self.rx_cycles %= frame;
+                break;
+            };
+            self.receive_byte(byte);
+            self.rx_cycles -= frame;
+        }
+    }
+}
+
+impl Device for Ns16550 {
+    fn get_type(&self) -> DeviceType {
+        DeviceType::Ns16550
+    }
+
+    fn set_base_addr(&mut self, base_addr: DeviceAddress) {
+        self.base_addr = base_addr;
+    }
+
+    // The registers are 8 bits wide, the wider accesses read them zero
+    // extended and write their low byte
+    fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
+        self.read_register(address)
+    }
+
+    fn write_byte(&mut self, address: DeviceAddress, value: u8) -> Result<(), CpuPeripheralsError> {
+        self.write_register(address, value)
+    }
+
+    fn read_halfword(&self, address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
+        Ok(self.read_register(address)? as u16)
+    }
+
+    fn write_halfword(
+        &mut self,
+        address: DeviceAddress,
+        value: u16,
+    ) -> Result<(), CpuPeripheralsError> {
+        self.write_register(address, value as u8)
+    }
+
+    fn read_word(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
+        Ok(self.read_register(address)? as u32)
+    }
+
+    fn write_word(
+        &mut self,
+        address: DeviceAddress,
+        value: u32,
+    ) -> Result<(), CpuPeripheralsError> {
+        self.write_register(address, value as u8)
+    }
+
+    fn read(&self, address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
+        if size > 4 {
+            return Err(CpuPeripheralsError::DeviceReadFailed(address as u64));
+        }
+        let value = self.read_register(address)? as u32;
+        Ok(value.to_le_bytes()[..size].to_vec())
+    }
+
+    fn write(&mut self, address: DeviceAddress, data: &[u8]) -> Result<(), CpuPeripheralsError> {
+        if data.is_empty() || data.len() > 4 {
+            return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64));
+        }
+        self.write_register(address, data[0])
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.transmit(cycles);
+        self.receive(cycles);
+        self.update_interrupt_line();
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        // the next frame sent, the next poll of the backend, or the
+        // character timeout may change the interrupts
+        let frame = self.frame_cycles();
+        let tx = self
+            .tx_shift
+            .is_some()
+            .then(|| frame.saturating_sub(self.tx_cycles));
+        let rx = self
+            .is_receiving()
+            .then(|| frame.saturating_sub(self.rx_cycles));
+        let len = self.rx_fifo.borrow().len();
+        let timeout = (self.is_fifo_enabled() && len > 0 && len < self.rx_trigger_level())
+            .then(|| (UART_TIMEOUT_FRAMES * frame).saturating_sub(self.rx_idle_cycles.get()))
+            .filter(|cycles| *cycles > 0);
+        tx.into_iter().chain(rx).chain(timeout).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::MemoryBackend;
+
+    const BASE: DeviceAddress = 0x1000_0000;
+    // A frame of 8 data bits and a stop bit takes 160 cycles while the
+    // divisor is 1
+    const FRAME: u64 = 160;
+
+    fn create_uart() -> (Ns16550, MemoryBackend, InterruptLine) {
+        let backend = MemoryBackend::new();
+        let line = InterruptLine::new();
+        let mut uart = Ns16550::new("test_ns16550");
+        uart.set_base_addr(BASE);
+        uart.set_backend(Box::new(backend.clone()));
+        uart.set_interrupt_line(line.clone());
+        // 8 data bits, no parity, 1 stop bit
+        uart.write_byte(BASE + UART_LCR, 0x03).unwrap();
+        (uart, backend, line)
+    }
+
+    #[test]
+    fn test_reset_state() {
+        let (uart, _, line) = create_uart();
+        assert_eq!(uart.read_byte(BASE + UART_IIR), Ok(UART_IIR_NO_INT));
+        assert_eq!(
+            uart.read_byte(BASE + UART_LSR),
+            Ok(UART_LSR_THRE | UART_LSR_TEMT)
+        );
+        assert_eq!(
+            uart.read_byte(BASE + UART_MSR),
+            Ok(UART_MSR_DCD | UART_MSR_DSR | UART_MSR_CTS)
+        );
+        assert_eq!(uart.next_event(), Some(FRAME));
+        assert!(!line.is_raised());
+    }
+
+    #[test]
+    fn test_divisor_latch() {
+        let (mut uart, _, _) = create_uart();
+        uart.write_byte(BASE + UART_IER, UART_IER_RDI).unwrap();
+        uart.write_byte(BASE + UART_LCR, UART_LCR_DLAB | 0x03)
+            .unwrap();
+        uart.write_byte(BASE + UART_DLL, 0x34).unwrap();
+        uart.write_byte(BASE + UART_DLM, 0x12).unwrap();
+        assert_eq!(uart.read_byte(BASE + UART_DLL), Ok(0x34));
+        assert_eq!(uart.read_byte(BASE + UART_DLM), Ok(0x12));
+        assert_eq!(uart.divisor, 0x1234);
+
+        // IER is back once DLAB is cleared
+        uart.write_byte(BASE + UART_LCR, 0x03).unwrap();
+        assert_eq!(uart.read_byte(BASE + UART_IER), Ok(UART_IER_RDI));
+        assert_eq!(uart.frame_cycles(), FRAME * 0x1234);
+    }
+
+    #[test]
+    fn test_transmit() {
+        let (mut uart, backend, _) = create_uart();
+        uart.write_byte(BASE + UART_FCR, UART_FCR_ENABLE_FIFO)
+            .unwrap();
+        for byte in b"hi!" {
+            uart.write_byte(BASE + UART_THR, *byte).unwrap();
+        }
+        // the first byte is in the shift register
+        assert_eq!(uart.read_byte(BASE + UART_LSR), Ok(0));
+        uart.tick(FRAME * 2);
+        assert_eq!(backend.output(), b"hi");
+        assert_eq!(uart.read_byte(BASE + UART_LSR), Ok(UART_LSR_THRE));
+        uart.tick(FRAME);
+        assert_eq!(backend.output(), b"hi!");
+        assert_eq!(
+            uart.read_byte(BASE + UART_LSR),
+            Ok(UART_LSR_THRE | UART_LSR_TEMT)
+        );
+    }
+
+    #[test]
+    fn test_thr_empty_interrupt() {
+        let (mut uart, _, line) = create_uart();
+        // enabling the interrupt while THR is empty raises it
+        uart.write_byte(BASE + UART_IER, UART_IER_THRI).unwrap();
+        assert!(line.is_raised());
+        // reading IIR clears it
+        assert_eq!(uart.read_byte(BASE + UART_IIR), Ok(UART_IIR_THRI));
+        assert_eq!(uart.read_byte(BASE + UART_IIR), Ok(UART_IIR_NO_INT));
+        assert!(!line.is_raised());
+
+        // THR is empty again once the shift register takes the byte
+        uart.write_byte(BASE + UART_THR, b'a').unwrap();
+        assert!(line.is_raised());
+        uart.write_byte(BASE + UART_THR, b'b').unwrap();
+        assert!(!line.is_raised());
+        uart.tick(FRAME);
+        assert!(line.is_raised());
+    }
+
+    #[test]
+    fn test_receive_fifo() {
+        let (mut uart, backend, line) = create_uart();
+        uart.write_byte(BASE + UART_IER, UART_IER_RDI).unwrap();
+        // a trigger level of 4 bytes
+        uart.write_byte(BASE + UART_FCR, UART_FCR_ENABLE_FIFO | 0x40)
+            .unwrap();
+        backend.push_input(b"0123456789abcdefgh");
+        uart.tick(FRAME * 3);
+        assert_eq!(uart.read_byte(BASE + UART_LSR), Ok(0x61));
+        assert!(!line.is_raised());
+        uart.tick(FRAME);
+        assert_eq!(
+            uart.read_byte(BASE + UART_IIR),
+            Ok(UART_IIR_FIFO_ENABLED | UART_IIR_RDI)
+        );
+        assert!(line.is_raised());
+
+        // the input waits while the FIFO is full
+        uart.tick(FRAME * 20);
+        assert_eq!(uart.rx_fifo.borrow().len(), NS16550_FIFO_DEPTH);
+        for byte in b"0123456789abcdef" {
+            assert_eq!(uart.read_byte(BASE + UART_RBR), Ok(*byte));
+        }
+        assert_eq!(uart.read_byte(BASE + UART_LSR), Ok(0x60));
+        assert!(!line.is_raised());
+
+        // clearing the FIFO drops the received bytes
+        uart.tick(FRAME);
+        uart.write_byte(BASE + UART_FCR, UART_FCR_ENABLE_FIFO | UART_FCR_CLEAR_RCVR)
+            .unwrap();
+        assert_eq!(uart.read_byte(BASE + UART_LSR), Ok(0x60));
+    }
+
+    #[test]
+    fn test_receive_timeout() {
+        let (mut uart, backend, line) = create_uart();
+        uart.write_byte(BASE + UART_IER, UART_IER_RDI).unwrap();
+        uart.write_byte(BASE + UART_FCR, UART_FCR_ENABLE_FIFO | 0x40)
+            .unwrap();
+        backend.push_input(b"ab");
+        uart.tick(FRAME * 2);
+        assert!(!line.is_raised());
+
+        // the bytes below the trigger level time out after 4 idle frames
+        assert_eq!(uart.next_event(), Some(FRAME));
+        uart.tick(FRAME * 3);
+        assert!(!line.is_raised());
+        uart.tick(FRAME);
+        assert_eq!(
+            uart.read_byte(BASE + UART_IIR),
+            Ok(UART_IIR_FIFO_ENABLED | UART_IIR_RX_TIMEOUT)
+        );
+        assert!(line.is_raised());
+
+        // a read restarts the timeout
+        assert_eq!(uart.read_byte(BASE + UART_RBR), Ok(b'a'));
+        assert!(!line.is_raised());
+    }
+
+    #[test]
+    fn test_loopback_and_overrun() {
+        let (mut uart, backend, line) = create_uart();
+        uart.write_byte(BASE + UART_IER, UART_IER_RLSI).unwrap();
+        // RTS and OUT2 show up as CTS and DCD
+        uart.write_byte(BASE + UART_MCR, UART_MCR_LOOP | 0x0a)
+            .unwrap();
+        assert_eq!(
+            uart.read_byte(BASE + UART_MSR),
+            Ok(UART_MSR_CTS | UART_MSR_DCD)
+        );
+
+        // without the FIFOs, the second byte overruns the holding register
+        uart.write_byte(BASE + UART_THR, b'a').unwrap();
+        uart.tick(FRAME);
+        uart.write_byte(BASE + UART_THR, b'b').unwrap();
+        uart.tick(FRAME);
+        assert!(backend.output().is_empty());
+        assert!(line.is_raised());
+        assert_eq!(uart.read_byte(BASE + UART_IIR), Ok(UART_IIR_RLSI));
+        assert_eq!(
+            uart.read_byte(BASE + UART_LSR),
+            Ok(UART_LSR_DR | UART_LSR_OE | UART_LSR_THRE | UART_LSR_TEMT)
+        );
+        assert!(!line.is_raised());
+        assert_eq!(uart.read_byte(BASE + UART_RBR), Ok(b'a'));
+    }
+
+    #[test]
+    fn test_reg_shift() {
+        let (mut uart, _, _) = create_uart();
+        uart.set_reg_shift(2);
+        uart.write_word(BASE + (UART_SCR << 2), 0x5a).unwrap();
+        assert_eq!(uart.read_word(BASE + (UART_SCR << 2)), Ok(0x5a));
+        assert_eq!(uart.read_byte(BASE + (UART_LCR << 2)), Ok(0x03));
+        assert!(uart.read_byte(BASE + UART_SCR).is_err());
+        assert!(uart.write_byte(BASE + (8 << 2), 0).is_err());
+    }
+}
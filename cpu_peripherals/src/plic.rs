@@ -0,0 +1,396 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// cpu_peripherals/src/plic.rs
+
+use std::cell::Cell;
+
+use tracing::{info, trace};
+
+use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType, InterruptLine, MIP_MEIP};
+
+// SiFive FE310-G002
+// 0x0C00_0000 0x0FFF_FFFF   RW A   PLIC
+// The registers are 32 bits wide and only accessed as words.
+const PRIORITY_OFFSET: DeviceAddress = 0x00_0000;
+const PENDING_OFFSET: DeviceAddress = 0x00_1000;
+const ENABLE_OFFSET: DeviceAddress = 0x00_2000;
+const ENABLE_STRIDE: DeviceAddress = 0x80;
+const THRESHOLD_OFFSET: DeviceAddress = 0x20_0000;
+const CLAIM_OFFSET: DeviceAddress = 0x20_0004;
+const CONTEXT_STRIDE: DeviceAddress = 0x1000;
+// offset of claim/complete within the registers of a context
+const CLAIM_INDEX: DeviceAddress = CLAIM_OFFSET - THRESHOLD_OFFSET;
+
+// The interrupt sources 1 to 52, the ID 0 means no interrupt
+pub const PLIC_SOURCES: usize = 52;
+// The pending and enable bits of the sources fit in two words
+const SOURCE_WORDS: DeviceAddress = 2;
+// The priorities and the thresholds are 3 bits wide, a source with
+// priority 0 never interrupts
+const PRIORITY_MASK: u32 = 0x7;
+
+/// An interrupt target, a privilege mode of a hart
+struct Context {
+    // the mip bit the context drives
+    mip: u64,
+    enable: u64,
+    threshold: u32,
+}
+
+pub struct Plic {
+    base_addr: DeviceAddress,
+    // indexed by the source ID, the entry 0 is unused
+    priority: [u32; PLIC_SOURCES + 1],
+    lines: Vec<InterruptLine>,
+    // The gateways forward a raised line as pending, a claimed source is
+    // not forwarded again until it is completed. Claims are reads, so the
+    // bits change through shared references.
+    pending: Cell<u64>,
+    claimed: Cell<u64>,
+    contexts: Vec<Context>,
+}
+
+impl Default for Plic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plic {
+    /// Creates the PLIC of the FE310-G002, with the machine mode of its only
+    /// hart as context 0
+    pub fn new() -> Self {
+        info!("Creating a new Plic device");
+        Self {
+            base_addr: 0,
+            priority: [0; PLIC_SOURCES + 1],
+            lines: (0..=PLIC_SOURCES).map(|_| InterruptLine::new()).collect(),
+            pending: Cell::new(0),
+            claimed: Cell::new(0),
+            contexts: vec![Context {
+                mip: MIP_MEIP,
+                enable: 0,
+                threshold: 0,
+            }],
+        }
+    }
+
+    /// Returns the interrupt line of the source `source`, for the device
+    /// raising it. None if there is no such source.
+    pub fn interrupt_line(&self, source: usize) -> Option<InterruptLine> {
+        if (1..=PLIC_SOURCES).contains(&source) {
+            Some(self.lines[source].clone())
+        } else {
+            None
+        }
+    }
+
+    /// Sets the pending bits of the raised lines whose sources are not
+    /// claimed
+    fn update_gateways(&self) {
+        let raised = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.is_raised())
+            .fold(0, |raised, (source, _)| raised | (1 << source));
+        // the source 0 does not exist
+        let forwarded = raised & !self.claimed.get() & !1;
+        self.pending.set(self.pending.get() | forwarded);
+    }
+
+    /// Returns the pending source of the context with the highest priority,
+    /// the lowest ID among equal priorities
+    fn highest_pending(&self, context: &Context) -> Option<usize> {
+        let candidates = self.pending.get() & context.enable;
+        (1..=PLIC_SOURCES)
+            .filter(|&source| candidates & (1 << source) != 0 && self.priority[source] > 0)
+            .min_by_key(|&source| (std::cmp::Reverse(self.priority[source]), source))
+    }
+
+    /// Claims the pending source with the highest priority for `context`,
+    /// returns its ID or 0 if there is none
+    fn claim(&self, context: usize) -> u32 {
+        self.update_gateways();
+        let Some(source) = self.highest_pending(&self.contexts[context]) else {
+            return 0;
+        };
+        trace!("Context {} claims the interrupt source {}", context, source);
+        self.pending.set(self.pending.get() & !(1 << source));
+        self.claimed.set(self.claimed.get() | (1 << source));
+        source as u32
+    }
+
+    /// Completes the handling of `source` by `context`, the completions of the
+    /// sources not enabled for the context are ignored
+    fn complete(&mut self, context: usize, source: u32) {
+        let source = source as usize;
+        if (1..=PLIC_SOURCES).contains(&source)
+            && self.contexts[context].enable & (1 << source) != 0
+        {
+            trace!(
+                "Context {} completes the interrupt source {}",
+                context,
+                source
+            );
+            self.claimed.set(self.claimed.get() & !(1 << source));
+        }
+    }
+
+    /// Returns the context of a per-context register at `offset` and the
+    /// offset within the registers of the context, None for other offsets
+    fn context_register(
+        &self,
+        offset: DeviceAddress,
+        base: DeviceAddress,
+        stride: DeviceAddress,
+    ) -> Option<(usize, DeviceAddress)> {
+        let context = offset.checked_sub(base)? / stride;
+        (context < self.contexts.len()).then(|| (context, offset - base - context * stride))
+    }
+
+    // The unimplemented registers read as zero
+    fn read_register(&self, offset: DeviceAddress) -> u32 {
+        if offset < PENDING_OFFSET {
+            let source = (offset - PRIORITY_OFFSET) / 4;
+            return self.priority.get(source).copied().unwrap_or(0);
+        }
+        if (PENDING_OFFSET..PENDING_OFFSET + 4 * SOURCE_WORDS).contains(&offset) {
+            self.update_gateways();
+            return (self.pending.get() >> (8 * (offset - PENDING_OFFSET))) as u32;
+        }
+        if offset < THRESHOLD_OFFSET {
+            return match self.context_register(offset, ENABLE_OFFSET, ENABLE_STRIDE) {
+                Some((context, index)) if index < 4 * SOURCE_WORDS => {
+                    (self.contexts[context].enable >> (8 * index)) as u32
+                }
+                _ => 0,
+            };
+        }
+        match self.context_register(offset, THRESHOLD_OFFSET, CONTEXT_STRIDE) {
+            Some((context, 0)) => self.contexts[context].threshold,
+            Some((context, CLAIM_INDEX)) => self.claim(context),
+            _ => 0,
+        }
+    }
+
+    // Writes to the unimplemented and read-only registers are dropped
+    fn write_register(&mut self, offset: DeviceAddress, value: u32) {
+        if offset < PENDING_OFFSET {
+            let source = (offset - PRIORITY_OFFSET) / 4;
+            if (1..=PLIC_SOURCES).contains(&source) {
+                self.priority[source] = value & PRIORITY_MASK;
+            }
+            return;
+        }
+        if offset < THRESHOLD_OFFSET {
+            if let Some((context, index)) =
+                self.context_register(offset, ENABLE_OFFSET, ENABLE_STRIDE)
+            {
+                if index < 4 * SOURCE_WORDS {
+                    let shift = 8 * index;
+                    // the source 0 and those above the last one do not exist
+                    let valid = (1u64 << (PLIC_SOURCES + 1)) - 2;
+                    let enable = &mut self.contexts[context].enable;
+                    *enable = (*enable & !(0xffff_ffff << shift)) | ((value as u64) << shift);
+                    *enable &= valid;
+                }
+            }
+            return;
+        }
+        match self.context_register(offset, THRESHOLD_OFFSET, CONTEXT_STRIDE) {
+            Some((context, 0)) => self.contexts[context].threshold = value & PRIORITY_MASK,
+            Some((context, CLAIM_INDEX)) => self.complete(context, value),
+            _ => {}
+        }
+    }
+}
+
+impl Device for Plic {
+    fn get_type(&self) -> DeviceType {
+        DeviceType::Plic
+    }
+
+    fn set_base_addr(&mut self, base_addr: DeviceAddress) {
+        self.base_addr = base_addr;
+    }
+
+    fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
+        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+    }
+
+    fn write_byte(
+        &mut self,
+        address: DeviceAddress,
+        _value: u8,
+    ) -> Result<(), CpuPeripheralsError> {
+        Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
+    }
+
+    fn read_halfword(&self, address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
+        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+    }
+
+    fn write_halfword(
+        &mut self,
+        address: DeviceAddress,
+        _value: u16,
+    ) -> Result<(), CpuPeripheralsError> {
+        Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
+    }
+
+    fn read_word(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
+        let offset = address - self.base_addr;
+        if !offset.is_multiple_of(4) {
+            return Err(CpuPeripheralsError::DeviceReadFailed(address as u64));
+        }
+        Ok(self.read_register(offset))
+    }
+
+    fn write_word(
+        &mut self,
+        address: DeviceAddress,
+        value: u32,
+    ) -> Result<(), CpuPeripheralsError> {
+        let offset = address - self.base_addr;
+        if !offset.is_multiple_of(4) {
+            return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64));
+        }
+        self.write_register(offset, value);
+        Ok(())
+    }
+
+    fn read(&self, address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
+        if size != 4 {
+            return Err(CpuPeripheralsError::DeviceReadFailed(address as u64));
+        }
+        Ok(self.read_word(address)?.to_le_bytes().to_vec())
+    }
+
+    fn write(&mut self, address: DeviceAddress, data: &[u8]) -> Result<(), CpuPeripheralsError> {
+        let Ok(bytes) = <[u8; 4]>::try_from(data) else {
+            return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64));
+        };
+        self.write_word(address, u32::from_le_bytes(bytes))
+    }
+
+    fn tick(&mut self, _cycles: u64) {
+        // a line raised and lowered again between two steps is not lost
+        self.update_gateways();
+    }
+
+    fn interrupts(&self) -> u64 {
+        self.update_gateways();
+        self.contexts
+            .iter()
+            .filter(|context| {
+                self.highest_pending(context)
+                    .is_some_and(|source| self.priority[source] > context.threshold)
+            })
+            .fold(0, |interrupts, context| interrupts | context.mip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: DeviceAddress = 0x0c00_0000;
+    const PENDING: DeviceAddress = BASE + PENDING_OFFSET;
+    const ENABLE: DeviceAddress = BASE + ENABLE_OFFSET;
+    const THRESHOLD: DeviceAddress = BASE + THRESHOLD_OFFSET;
+    const CLAIM: DeviceAddress = BASE + CLAIM_OFFSET;
+
+    fn create_plic() -> Plic {
+        let mut plic = Plic::new();
+        plic.set_base_addr(BASE);
+        plic
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut plic = create_plic();
+        plic.write_word(BASE + 4 * 3, 0xff).unwrap();
+        assert_eq!(plic.read_word(BASE + 4 * 3), Ok(7));
+        // the source 0 does not exist
+        plic.write_word(BASE, 1).unwrap();
+        assert_eq!(plic.read_word(BASE), Ok(0));
+
+        plic.write_word(ENABLE, 0xffff_ffff).unwrap();
+        plic.write_word(ENABLE + 4, 0xffff_ffff).unwrap();
+        assert_eq!(plic.read_word(ENABLE), Ok(0xffff_fffe));
+        assert_eq!(plic.read_word(ENABLE + 4), Ok(0x001f_ffff));
+
+        plic.write_word(THRESHOLD, 0x12).unwrap();
+        assert_eq!(plic.read_word(THRESHOLD), Ok(2));
+
+        // the registers are only accessed as words
+        assert!(plic.read_byte(THRESHOLD).is_err());
+        assert!(plic.write_halfword(THRESHOLD, 0).is_err());
+        assert!(plic.read_word(THRESHOLD + 2).is_err());
+    }
+
+    #[test]
+    fn test_pending_and_meip() {
+        let mut plic = create_plic();
+        let line = plic.interrupt_line(40).unwrap();
+        assert!(plic.interrupt_line(0).is_none());
+        assert!(plic.interrupt_line(PLIC_SOURCES + 1).is_none());
+
+        line.raise();
+        assert_eq!(plic.read_word(PENDING + 4), Ok(1 << 8));
+        // the source is neither enabled nor of a priority above 0
+        assert_eq!(plic.interrupts(), 0);
+        plic.write_word(ENABLE + 4, 1 << 8).unwrap();
+        assert_eq!(plic.interrupts(), 0);
+        plic.write_word(BASE + 4 * 40, 2).unwrap();
+        assert_eq!(plic.interrupts(), MIP_MEIP);
+        // the threshold masks the priorities up to it
+        plic.write_word(THRESHOLD, 2).unwrap();
+        assert_eq!(plic.interrupts(), 0);
+        plic.write_word(THRESHOLD, 1).unwrap();
+        assert_eq!(plic.interrupts(), MIP_MEIP);
+
+        // the pending bit stays set when the line is lowered
+        line.lower();
+        plic.tick(1);
+        assert_eq!(plic.interrupts(), MIP_MEIP);
+    }
+
+    #[test]
+    fn test_claim_and_complete() {
+        let mut plic = create_plic();
+        plic.write_word(ENABLE, 0xffff_ffff).unwrap();
+        for (source, priority) in [(3, 1), (5, 2), (7, 2)] {
+            plic.write_word(BASE + 4 * source, priority).unwrap();
+            plic.interrupt_line(source).unwrap().raise();
+        }
+
+        // the highest priority first, then the lowest ID
+        assert_eq!(plic.read_word(CLAIM), Ok(5));
+        assert_eq!(plic.read_word(CLAIM), Ok(7));
+        assert_eq!(plic.read_word(CLAIM), Ok(3));
+        assert_eq!(plic.read_word(CLAIM), Ok(0));
+        assert_eq!(plic.read_word(PENDING), Ok(0));
+        assert_eq!(plic.interrupts(), 0);
+
+        // a claimed source is forwarded again once completed
+        plic.write_word(CLAIM, 5).unwrap();
+        assert_eq!(plic.read_word(PENDING), Ok(1 << 5));
+        assert_eq!(plic.interrupts(), MIP_MEIP);
+
+        // unless it is no longer raised
+        plic.interrupt_line(7).unwrap().lower();
+        plic.write_word(CLAIM, 7).unwrap();
+        assert_eq!(plic.read_word(PENDING), Ok(1 << 5));
+
+        // the completion of a disabled source is ignored
+        plic.write_word(ENABLE, 0).unwrap();
+        plic.write_word(CLAIM, 3).unwrap();
+        plic.write_word(ENABLE, 0xffff_ffff).unwrap();
+        assert_eq!(plic.read_word(PENDING), Ok(1 << 5));
+    }
+}
@@ -0,0 +1,570 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// cpu_peripherals/src/serial.rs
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use tracing::{info, warn};
+
+use crate::CpuPeripheralsError;
+
+/// The host end of a serial device. The device sends the bytes it
+/// transmits, and polls for the bytes it receives without blocking.
+pub trait CharBackend {
+    /// Sends the bytes transmitted by the device to the host
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Returns the next byte the host sends, None if none is available yet
+    fn read(&mut self) -> Option<u8>;
+}
+
+/// Discards the output and never sends any input
+#[derive(Debug, Default)]
+pub struct NullBackend;
+
+impl CharBackend for NullBackend {
+    fn write(&mut self, _bytes: &[u8]) {}
+
+    fn read(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+/// Connects the device to stdin and stdout of the simulator
+#[derive(Default)]
+pub struct StdioBackend {
+    // fed by a thread blocking on stdin, started by the first read
+    input: Option<Receiver<u8>>,
+    raw_mode: bool,
+}
+
+impl StdioBackend {
+    /// Uses stdin and stdout as they are, stdin is line buffered by a
+    /// terminal
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts the terminal into raw mode: the keys are sent as they are typed,
+    /// without echo or line editing. Ctrl-C still stops the simulator. The
+    /// terminal is restored when the backend is dropped or the process
+    /// exits.
+    pub fn raw() -> Self {
+        Self {
+            input: None,
+            raw_mode: terminal::enter_raw_mode(),
+        }
+    }
+
+    fn spawn_stdin_reader() -> Receiver<u8> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in io::stdin().lock().bytes().map_while(Result::ok) {
+                if sender.send(byte).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+}
+
+impl CharBackend for StdioBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut stdout = io::stdout().lock();
+        if let Err(e) = stdout.write_all(bytes).and_then(|_| stdout.flush()) {
+            warn!("Failed to write to stdout: {}", e);
+        }
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        self.input
+            .get_or_insert_with(Self::spawn_stdin_reader)
+            .try_recv()
+            .ok()
+    }
+}
+
+impl Drop for StdioBackend {
+    fn drop(&mut self) {
+        if self.raw_mode {
+            terminal::restore();
+        }
+    }
+}
+
+/// Connects the device to a new pseudo terminal, e.g. for `screen` or
+/// `picocom`. The kernel buffers the output while no terminal program is
+/// attached, the bytes are dropped once that buffer is full.
+pub struct PtyBackend {
+    master: File,
+    path: PathBuf,
+}
+
+impl PtyBackend {
+    pub fn open() -> io::Result<Self> {
+        let (master, path) = terminal::open_pty()?;
+        info!("Opened the pseudo terminal {}", path.display());
+        Ok(Self { master, path })
+    }
+
+    /// Returns the path of the terminal to attach to, e.g. /dev/pts/3
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CharBackend for PtyBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        // the master is non-blocking, a full buffer drops the bytes
+        let _ = self.master.write_all(bytes);
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        let mut byte = [0];
+        match self.master.read(&mut byte) {
+            Ok(1) => Some(byte[0]),
+            _ => None,
+        }
+    }
+}
+
+/// Listens on a TCP socket, e.g. for `nc` or `telnet`. One client is
+/// served at a time, the output is dropped while none is connected.
+pub struct TcpBackend {
+    listener: TcpListener,
+    stream: Option<TcpStream>,
+}
+
+impl TcpBackend {
+    pub fn bind(address: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            stream: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Blocks until a client connects, so that no output is lost
+    pub fn wait_for_connection(&mut self) -> io::Result<()> {
+        self.listener.set_nonblocking(false)?;
+        let accepted = self.listener.accept();
+        self.listener.set_nonblocking(true)?;
+        self.connect(accepted?.0)
+    }
+
+    fn connect(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        info!("Serial client connected from {}", stream.peer_addr()?);
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// Returns the connected client, accepting a waiting one if there is
+    /// none
+    fn stream(&mut self) -> Option<&mut TcpStream> {
+        if self.stream.is_none() {
+            if let Ok((stream, _)) = self.listener.accept() {
+                if let Err(e) = self.connect(stream) {
+                    warn!("Failed to set up the serial client: {}", e);
+                }
+            }
+        }
+        self.stream.as_mut()
+    }
+}
+
+impl CharBackend for TcpBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        let Some(stream) = self.stream() else {
+            return;
+        };
+        match stream.write_all(bytes) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => self.stream = None,
+        }
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        let stream = self.stream()?;
+        let mut byte = [0];
+        match stream.read(&mut byte) {
+            Ok(1) => Some(byte[0]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            // the client disconnected
+            _ => {
+                self.stream = None;
+                None
+            }
+        }
+    }
+}
+
+/// Writes the output to a file, there is no input
+pub struct FileBackend {
+    file: File,
+}
+
+impl FileBackend {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(path)?,
+        })
+    }
+}
+
+impl CharBackend for FileBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        if let Err(e) = self.file.write_all(bytes) {
+            warn!("Failed to write the serial output: {}", e);
+        }
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+/// Keeps the output in memory and sends the bytes pushed as input, the
+/// clones share the buffers, e.g. for a test to check what a device sends
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    input: Rc<RefCell<VecDeque<u8>>>,
+    output: Rc<RefCell<Vec<u8>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the bytes the device receives
+    pub fn push_input(&self, bytes: &[u8]) {
+        self.input.borrow_mut().extend(bytes);
+    }
+
+    /// Returns the bytes the device has sent
+    pub fn output(&self) -> Vec<u8> {
+        self.output.borrow().clone()
+    }
+
+    /// Returns the bytes the device has sent and clears them
+    pub fn take_output(&self) -> Vec<u8> {
+        self.output.take()
+    }
+}
+
+impl CharBackend for MemoryBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        self.output.borrow_mut().extend_from_slice(bytes);
+    }
+
+    fn read(&mut self) -> Option<u8> {
+        self.input.borrow_mut().pop_front()
+    }
+}
+
+/// Selects a backend, written as `stdio`, `pty`, `tcp:<address>:<port>`,
+/// `file:<path>` or `null`
+#[derive(Debug, Clone, PartialEq)]
+pub enum CharBackendSpec {
+    Stdio,
+    Pty,
+    Tcp(SocketAddr),
+    File(PathBuf),
+    Null,
+}
+
+impl FromStr for CharBackendSpec {
+    type Err = CpuPeripheralsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CpuPeripheralsError::InvalidCharBackend(s.to_string());
+        match s.split_once(':') {
+            None if s == "stdio" => Ok(Self::Stdio),
+            None if s == "pty" => Ok(Self::Pty),
+            None if s == "null" => Ok(Self::Null),
+            Some(("tcp", address)) => address.parse().map(Self::Tcp).map_err(|_| invalid()),
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(PathBuf::from(path))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for CharBackendSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdio => write!(f, "stdio"),
+            Self::Pty => write!(f, "pty"),
+            Self::Tcp(address) => write!(f, "tcp:{}", address),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Null => write!(f, "null"),
+        }
+    }
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::ffi::CStr;
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::FromRawFd;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    // The settings of the terminal before it entered raw mode
+    static ORIGINAL_TERMIOS: OnceLock<libc::termios> = OnceLock::new();
+
+    /// Switches stdin to raw mode if it is a terminal, returns whether it
+    /// did. The output processing and the signal keys are left on.
+    pub(super) fn enter_raw_mode() -> bool {
+        // SAFETY: termios is plain data filled in by tcgetattr
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        // SAFETY: isatty only takes the descriptor, tcgetattr writes to the
+        // termios borrowed here
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1
+            || unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0
+        {
+            return false;
+        }
+        if ORIGINAL_TERMIOS.set(termios).is_ok() {
+            let handler = restore_on_signal as extern "C" fn(libc::c_int);
+            // SAFETY: the handlers only restore the terminal settings
+            unsafe {
+                libc::atexit(restore_at_exit);
+                libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+                libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+            }
+        }
+
+        termios.c_iflag &= !(libc::ICRNL | libc::IXON);
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::IEXTEN);
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+        // SAFETY: termios is a valid value read by tcgetattr above
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) == 0 }
+    }
+
+    pub(super) fn restore() {
+        if let Some(termios) = ORIGINAL_TERMIOS.get() {
+            // SAFETY: tcsetattr is async-signal-safe
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios);
+            }
+        }
+    }
+
+    extern "C" fn restore_at_exit() {
+        restore();
+    }
+
+    extern "C" fn restore_on_signal(signal: libc::c_int) {
+        restore();
+        // SAFETY: the default action of the signal ends the process
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+
+    /// Opens a non-blocking pseudo terminal master in raw mode, returns it
+    /// and the path of its slave
+    pub(super) fn open_pty() -> io::Result<(File, PathBuf)> {
+        // SAFETY: posix_openpt takes no pointers, it returns a new descriptor or -1
+        let fd = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: fd is open and owned by nothing else, the file closes it
+        let master = unsafe { File::from_raw_fd(fd) };
+        // SAFETY: fd is an open pseudo terminal master, kept open by `master`
+        if unsafe { libc::grantpt(fd) } != 0 || unsafe { libc::unlockpt(fd) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: fd is an open pseudo terminal master. ptsname returns a
+        // static buffer, which is copied below before any other call to it.
+        let name = unsafe { libc::ptsname(fd) };
+        if name.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: name is not null and points to a NUL-terminated string
+        let path = PathBuf::from(
+            unsafe { CStr::from_ptr(name) }
+                .to_string_lossy()
+                .into_owned(),
+        );
+
+        // SAFETY: termios is plain data, all zeros is a valid value
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        // SAFETY: fd stays open while `master` lives and termios is a valid,
+        // exclusively borrowed value for tcgetattr, cfmakeraw and tcsetattr
+        unsafe {
+            if libc::tcgetattr(fd, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok((master, path))
+    }
+}
+
+#[cfg(not(unix))]
+mod terminal {
+    use std::fs::File;
+    use std::io;
+    use std::path::PathBuf;
+
+    pub(super) fn enter_raw_mode() -> bool {
+        false
+    }
+
+    pub(super) fn restore() {}
+
+    pub(super) fn open_pty() -> io::Result<(File, PathBuf)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "pseudo terminals are not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_backend() {
+        let backend = MemoryBackend::new();
+        let mut device_end: Box<dyn CharBackend> = Box::new(backend.clone());
+        backend.push_input(b"ab");
+        assert_eq!(device_end.read(), Some(b'a'));
+        assert_eq!(device_end.read(), Some(b'b'));
+        assert_eq!(device_end.read(), None);
+
+        device_end.write(b"hello");
+        assert_eq!(backend.output(), b"hello");
+        assert_eq!(backend.take_output(), b"hello");
+        assert!(backend.output().is_empty());
+    }
+
+    #[test]
+    fn test_file_backend() {
+        let path = std::env::temp_dir().join(format!("serial_{}.txt", std::process::id()));
+        let mut backend = FileBackend::create(&path).unwrap();
+        backend.write(b"hello\n");
+        assert_eq!(backend.read(), None);
+        drop(backend);
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tcp_backend() {
+        let mut backend = TcpBackend::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        // the output is dropped while no client is connected
+        backend.write(b"lost");
+        assert_eq!(backend.read(), None);
+
+        let mut client = TcpStream::connect(backend.local_addr().unwrap()).unwrap();
+        backend.wait_for_connection().unwrap();
+        backend.write(b"hi");
+        let mut received = [0; 2];
+        client.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"hi");
+
+        client.write_all(b"x").unwrap();
+        let byte = (0..1000).find_map(|_| {
+            thread::sleep(std::time::Duration::from_millis(1));
+            backend.read()
+        });
+        assert_eq!(byte, Some(b'x'));
+
+        // the backend serves the next client after a disconnection
+        drop(client);
+        while backend.stream.is_some() {
+            thread::sleep(std::time::Duration::from_millis(1));
+            backend.read();
+        }
+        let _client = TcpStream::connect(backend.local_addr().unwrap()).unwrap();
+        backend.wait_for_connection().unwrap();
+        assert!(backend.stream.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pty_backend() {
+        let Ok(mut backend) = PtyBackend::open() else {
+            // no pseudo terminals in this environment
+            return;
+        };
+        let mut slave = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(backend.path())
+            .unwrap();
+        backend.write(b"hi");
+        let mut received = [0; 2];
+        slave.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"hi");
+
+        slave.write_all(b"x").unwrap();
+        let byte = (0..1000).find_map(|_| {
+            thread::sleep(std::time::Duration::from_millis(1));
+            backend.read()
+        });
+        assert_eq!(byte, Some(b'x'));
+    }
+
+    #[test]
+    fn test_backend_spec() {
+        assert_eq!("stdio".parse(), Ok(CharBackendSpec::Stdio));
+        assert_eq!("pty".parse(), Ok(CharBackendSpec::Pty));
+        assert_eq!("null".parse(), Ok(CharBackendSpec::Null));
+        assert_eq!(
+            "tcp:127.0.0.1:4444".parse(),
+            Ok(CharBackendSpec::Tcp("127.0.0.1:4444".parse().unwrap()))
+        );
+        assert_eq!(
+            "file:uart.log".parse(),
+            Ok(CharBackendSpec::File(PathBuf::from("uart.log")))
+        );
+        for spec in ["tcp:4444", "file:", "udp:127.0.0.1:4444", "stdout"] {
+            assert_eq!(
+                spec.parse::<CharBackendSpec>(),
+                Err(CpuPeripheralsError::InvalidCharBackend(spec.to_string()))
+            );
+        }
+        assert_eq!(
+            CharBackendSpec::Tcp("127.0.0.1:4444".parse().unwrap()).to_string(),
+            "tcp:127.0.0.1:4444"
+        );
+    }
+}
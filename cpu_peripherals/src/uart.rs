@@ -5,62 +5,242 @@
 
 // cpu_peripherals/src/uart.rs
 
-use tracing::info;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 
-use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType};
+use tracing::{info, trace};
+
+use crate::serial::{CharBackend, StdioBackend};
+use crate::{CpuPeripheralsError, Device, DeviceAddress, DeviceType, InterruptLine};
 
 // SiFive FE310-G002
 // 0x1001_3000 0x1001_3FFF   RWA    UART 0
 // 0x1002_3000 0x1002_3FFF   RWA    UART 1
 // 0x00 txdata  Transmit data register
 // 0x04 rxdata  Receive data register
+// 0x08 txctrl  Transmit control register
+// 0x0c rxctrl  Receive control register
+// 0x10 ie      UART interrupt enable
+// 0x14 ip      UART interrupt pending
+// 0x18 div     Baud rate divisor
 pub const UART_TXDATA: DeviceAddress = 0x00;
 pub const UART_RXDATA: DeviceAddress = 0x04;
+pub const UART_TXCTRL: DeviceAddress = 0x08;
+pub const UART_RXCTRL: DeviceAddress = 0x0c;
+pub const UART_IE: DeviceAddress = 0x10;
+pub const UART_IP: DeviceAddress = 0x14;
+pub const UART_DIV: DeviceAddress = 0x18;
+
+// txdata.full and rxdata.empty
+pub const UART_TXDATA_FULL: u32 = 0x8000_0000;
+pub const UART_RXDATA_EMPTY: u32 = 0x8000_0000;
+// txctrl.txen, txctrl.nstop and rxctrl.rxen
+pub const UART_TXCTRL_TXEN: u32 = 0x1;
+pub const UART_TXCTRL_NSTOP: u32 = 0x2;
+pub const UART_RXCTRL_RXEN: u32 = 0x1;
+// The watermark counts txcnt and rxcnt of txctrl and rxctrl
+const UART_CTRL_CNT_SHIFT: u32 = 16;
+const UART_CTRL_CNT_MASK: u32 = 0x7;
+const UART_TXCTRL_WRITABLE: u32 = 0x0007_0003;
+const UART_RXCTRL_WRITABLE: u32 = 0x0007_0001;
+// The txwm and rxwm bits of ie and ip
+pub const UART_IP_TXWM: u32 = 0x1;
+pub const UART_IP_RXWM: u32 = 0x2;
+const UART_DIV_MASK: u32 = 0xffff;
+// Unlike the FE310, whose transmitter is disabled at reset, txen is set so
+// that firmware writing txdata without configuring the UART is heard
+const UART_TXCTRL_RESET: u32 = UART_TXCTRL_TXEN;
+
+// Depth of the TX and RX FIFOs
+pub const UART_FIFO_DEPTH: usize = 8;
+// A frame has a start bit, 8 data bits and the stop bits
+const UART_FRAME_BITS: u64 = 9;
 
 pub struct Uart {
     // Add necessary fields for Uart
     name: &'static str,
     base_addr: DeviceAddress,
-    tx_buffer: Vec<u8>,
+    backend: Box<dyn CharBackend>,
+    tx_fifo: VecDeque<u8>,
+    // reading rxdata pops the FIFO
+    rx_fifo: RefCell<VecDeque<u8>>,
+    txctrl: u32,
+    rxctrl: u32,
+    ie: u32,
+    div: u32,
+    // cycles spent on the frames being sent and received
+    tx_cycles: u64,
+    rx_cycles: u64,
+    interrupt_line: InterruptLine,
 }
 
 impl Uart {
+    /// Creates a UART connected to stdin and stdout
     pub fn new(name: &'static str) -> Self {
         info!("Creating a new UART device");
-        let mut uart = Self {
+        Self {
             name,
             base_addr: 0,
-            tx_buffer: vec![],
+            backend: Box::new(StdioBackend::new()),
+            tx_fifo: VecDeque::with_capacity(UART_FIFO_DEPTH),
+            rx_fifo: RefCell::new(VecDeque::with_capacity(UART_FIFO_DEPTH)),
+            txctrl: UART_TXCTRL_RESET,
+            rxctrl: 0,
+            ie: 0,
+            div: 0,
+            tx_cycles: 0,
+            rx_cycles: 0,
+            interrupt_line: InterruptLine::new(),
+        }
+    }
+
+    /// Connects the UART to the host end the frames are sent to and
+    /// received from
+    pub fn set_backend(&mut self, backend: Box<dyn CharBackend>) {
+        self.backend = backend;
+    }
+
+    /// Connects the interrupt of the UART, raised while an enabled
+    /// watermark interrupt is pending, e.g. to a PLIC source
+    pub fn set_interrupt_line(&mut self, line: InterruptLine) {
+        self.interrupt_line = line;
+        self.update_interrupt_line();
+    }
+
+    /// Returns the number of cycles to send or receive a frame, the baud
+    /// rate is the clock divided by div + 1
+    fn frame_cycles(&self, stop_bits: u64) -> u64 {
+        (self.div as u64 + 1) * (UART_FRAME_BITS + stop_bits)
+    }
+
+    fn tx_frame_cycles(&self) -> u64 {
+        let stop_bits = if self.txctrl & UART_TXCTRL_NSTOP != 0 {
+            2
+        } else {
+            1
         };
+        self.frame_cycles(stop_bits)
+    }
 
-        uart.add_head_to_tx_buffer();
+    fn is_transmitting(&self) -> bool {
+        self.txctrl & UART_TXCTRL_TXEN != 0 && !self.tx_fifo.is_empty()
+    }
 
-        uart
+    fn is_receiving(&self) -> bool {
+        self.rxctrl & UART_RXCTRL_RXEN != 0 && self.rx_fifo.borrow().len() < UART_FIFO_DEPTH
     }
 
-    fn is_txdata_addr(&self, address: DeviceAddress) -> bool {
-        address == (UART_TXDATA + self.base_addr)
+    fn watermark(ctrl: u32) -> usize {
+        ((ctrl >> UART_CTRL_CNT_SHIFT) & UART_CTRL_CNT_MASK) as usize
     }
 
-    fn write_tx_buffer(&mut self, value: u8) {
-        self.tx_buffer.push(value);
-        if value == b'\n' {
-            self.reset_tx_buffer();
+    /// txwm is pending while the TX FIFO holds less than txcnt entries,
+    /// rxwm while the RX FIFO holds more than rxcnt
+    fn ip(&self) -> u32 {
+        let mut ip = 0;
+        if self.tx_fifo.len() < Self::watermark(self.txctrl) {
+            ip |= UART_IP_TXWM;
         }
+        if self.rx_fifo.borrow().len() > Self::watermark(self.rxctrl) {
+            ip |= UART_IP_RXWM;
+        }
+        ip
+    }
+
+    fn update_interrupt_line(&self) {
+        self.interrupt_line.set(self.ie & self.ip() != 0);
     }
 
-    fn add_head_to_tx_buffer(&mut self) {
-        self.tx_buffer.push(b'[' as u8);
-        self.tx_buffer.extend_from_slice(self.name.as_bytes());
-        self.tx_buffer.push(b']' as u8);
-        self.tx_buffer.push(b' ' as u8);
+    /// Returns the register at `address`, None if there is none
+    fn register_offset(&self, address: DeviceAddress) -> Option<DeviceAddress> {
+        let offset = address.checked_sub(self.base_addr)?;
+        (offset <= UART_DIV && offset % 4 == 0).then_some(offset)
     }
 
-    fn reset_tx_buffer(&mut self) {
-        // self.tx_buffer.push(b'\0');
-        print!("{}", String::from_utf8_lossy(&self.tx_buffer));
-        self.tx_buffer.clear();
-        self.add_head_to_tx_buffer();
+    fn read_register(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
+        let value = match self.register_offset(address) {
+            Some(UART_TXDATA) if self.tx_fifo.len() == UART_FIFO_DEPTH => UART_TXDATA_FULL,
+            Some(UART_TXDATA) => 0,
+            Some(UART_RXDATA) => {
+                let byte = self.rx_fifo.borrow_mut().pop_front();
+                self.update_interrupt_line();
+                byte.map_or(UART_RXDATA_EMPTY, |byte| byte as u32)
+            }
+            Some(UART_TXCTRL) => self.txctrl,
+            Some(UART_RXCTRL) => self.rxctrl,
+            Some(UART_IE) => self.ie,
+            Some(UART_IP) => self.ip(),
+            Some(UART_DIV) => self.div,
+            _ => return Err(CpuPeripheralsError::DeviceReadFailed(address as u64)),
+        };
+        Ok(value)
+    }
+
+    fn write_register(
+        &mut self,
+        address: DeviceAddress,
+        value: u32,
+    ) -> Result<(), CpuPeripheralsError> {
+        match self.register_offset(address) {
+            // a write to a full FIFO is dropped
+            Some(UART_TXDATA) => {
+                if self.tx_fifo.len() < UART_FIFO_DEPTH {
+                    self.tx_fifo.push_back(value as u8);
+                }
+            }
+            // rxdata and ip are read-only
+            Some(UART_RXDATA | UART_IP) => {}
+            Some(UART_TXCTRL) => self.txctrl = value & UART_TXCTRL_WRITABLE,
+            Some(UART_RXCTRL) => self.rxctrl = value & UART_RXCTRL_WRITABLE,
+            Some(UART_IE) => self.ie = value & (UART_IP_TXWM | UART_IP_RXWM),
+            Some(UART_DIV) => self.div = value & UART_DIV_MASK,
+            _ => return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64)),
+        }
+        self.update_interrupt_line();
+        Ok(())
+    }
+
+    /// Sends the frames of the TX FIFO completed in `cycles` cycles
+    fn transmit(&mut self, cycles: u64) {
+        if !self.is_transmitting() {
+            self.tx_cycles = 0;
+            return;
+        }
+        self.tx_cycles += cycles;
+        let frame = self.tx_frame_cycles();
+        while self.tx_cycles >= frame {
+            let Some(byte) = self.tx_fifo.pop_front() else {
+                self.tx_cycles = 0;
+                break;
+            };
+            trace!("{} sends {:#04x}", self.name, byte);
+            self.backend.write(&[byte]);
+            self.tx_cycles -= frame;
+        }
+    }
+
+    /// Receives the frames completed in `cycles` cycles. The backend is
+    /// polled once per frame while the RX FIFO has room, the frames of the
+    /// cycles it had no input for are not received later.
+    fn receive(&mut self, cycles: u64) {
+        if !self.is_receiving() {
+            self.rx_cycles = 0;
+            return;
+        }
+        self.rx_cycles += cycles;
+        let frame = self.frame_cycles(1);
+        while self.rx_cycles >= frame {
+            if !self.is_receiving() {
+                self.rx_cycles = 0;
+                break;
+            }
+            let Some(byte) = self.backend.read() else {
+                self.rx_cycles %= frame;
+                break;
+            };
+            self.rx_fifo.borrow_mut().push_back(byte);
+            self.rx_cycles -= frame;
+        }
     }
 }
 
@@ -72,38 +252,31 @@ impl Device for Uart {
     fn set_base_addr(&mut self, base_addr: DeviceAddress) {
         self.base_addr = base_addr;
     }
+
+    // The registers are 32 bits wide, the narrower accesses read and write
+    // their low bits
     fn read_byte(&self, address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
-        // Implementation of reading a byte from Uart
-        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+        Ok(self.read_register(address)? as u8)
     }
 
     fn write_byte(&mut self, address: DeviceAddress, value: u8) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a byte to Uart
-        if self.is_txdata_addr(address) {
-            self.write_tx_buffer(value);
-            Ok(())
-        } else {
-            Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
-        }
+        self.write_register(address, value as u32)
     }
 
     fn read_halfword(&self, address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
-        // Implementation of reading a halfword from Uart
-        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+        Ok(self.read_register(address)? as u16)
     }
 
     fn write_halfword(
         &mut self,
         address: DeviceAddress,
-        _value: u16,
+        value: u16,
     ) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a halfword to Uart
-        Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
+        self.write_register(address, value as u32)
     }
 
     fn read_word(&self, address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
-        // Implementation of reading a word from Uart
-        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+        self.read_register(address)
     }
 
     fn write_word(
@@ -111,59 +284,108 @@ impl Device for Uart {
         address: DeviceAddress,
         value: u32,
     ) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing a word to Uart
-        if self.is_txdata_addr(address) {
-            self.write_tx_buffer((value & 0xFF) as u8);
-            Ok(())
-        } else {
-            Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
-        }
+        self.write_register(address, value)
     }
 
-    fn read(&self, address: DeviceAddress, _size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
-        // Implementation of reading an arbitrary length from Uart
-        // Ok(vec![0; size])
-        Err(CpuPeripheralsError::DeviceReadFailed(address as u64))
+    fn read(&self, address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
+        if size > 4 {
+            return Err(CpuPeripheralsError::DeviceReadFailed(address as u64));
+        }
+        let value = self.read_register(address)?;
+        Ok(value.to_le_bytes()[..size].to_vec())
     }
 
     fn write(&mut self, address: DeviceAddress, data: &[u8]) -> Result<(), CpuPeripheralsError> {
-        // Implementation of writing an arbitrary length to Uart
-        if self.is_txdata_addr(address) {
+        // the bytes written to txdata are queued one after another
+        if self.register_offset(address) == Some(UART_TXDATA) {
             for byte in data {
-                self.write_tx_buffer(*byte);
+                self.write_register(address, *byte as u32)?;
             }
-            Ok(())
-        } else {
-            Err(CpuPeripheralsError::DeviceWriteFailed(address as u64))
+            return Ok(());
+        }
+        if data.len() > 4 {
+            return Err(CpuPeripheralsError::DeviceWriteFailed(address as u64));
+        }
+        let mut bytes = [0; 4];
+        bytes[..data.len()].copy_from_slice(data);
+        self.write_register(address, u32::from_le_bytes(bytes))
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.transmit(cycles);
+        self.receive(cycles);
+        self.update_interrupt_line();
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        // the next frame sent, or the next poll of the backend, may change
+        // the watermark interrupts
+        let tx = self
+            .is_transmitting()
+            .then(|| self.tx_frame_cycles().saturating_sub(self.tx_cycles));
+        let rx = self
+            .is_receiving()
+            .then(|| self.frame_cycles(1).saturating_sub(self.rx_cycles));
+        tx.into_iter().chain(rx).min()
+    }
+
+    /// Sends the whole TX FIFO at once, the frames still queued when the
+    /// target exits are not lost
+    fn flush(&mut self) {
+        if self.tx_fifo.is_empty() {
+            return;
         }
+        let bytes: Vec<u8> = self.tx_fifo.drain(..).collect();
+        trace!("{} flushes {} bytes", self.name, bytes.len());
+        self.backend.write(&bytes);
+        self.tx_cycles = 0;
+        self.update_interrupt_line();
+    }
+}
+
+impl Drop for Uart {
+    fn drop(&mut self) {
+        self.flush();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::serial::MemoryBackend;
+
+    const BASE: DeviceAddress = 0x1000;
+    // A frame with one stop bit takes 10 cycles while div is 0
+    const FRAME: u64 = 10;
+
+    fn create_uart() -> (Uart, MemoryBackend) {
+        let backend = MemoryBackend::new();
+        let mut uart = Uart::new("test_uart");
+        uart.set_base_addr(BASE);
+        uart.set_backend(Box::new(backend.clone()));
+        (uart, backend)
+    }
 
     #[test]
     fn test_uart_new() {
         let uart = Uart::new("test");
         assert_eq!(uart.name, "test");
         assert_eq!(uart.base_addr, 0);
-        assert_eq!(uart.tx_buffer.len(), "test".len() + 3);
+        assert!(uart.tx_fifo.is_empty());
     }
 
     #[test]
     fn uart_write_byte_to_txdata_address_should_succeed() {
-        let mut uart = Uart::new("test_uart");
-        uart.set_base_addr(0x1000);
+        let (mut uart, backend) = create_uart();
+        uart.write_word(BASE + UART_TXCTRL, UART_TXCTRL_TXEN)
+            .unwrap();
 
-        let address: DeviceAddress = 0x1000 + UART_TXDATA;
+        let address: DeviceAddress = BASE + UART_TXDATA;
         let data = b'A';
 
         assert!(uart.write_byte(address, data).is_ok());
-        assert_eq!(
-            uart.tx_buffer,
-            vec![b'[', b't', b'e', b's', b't', b'_', b'u', b'a', b'r', b't', b']', b' ', b'A']
-        );
+        uart.tick(FRAME);
+        assert_eq!(backend.output(), b"A");
     }
 
     #[test]
@@ -179,31 +401,140 @@ mod tests {
 
     #[test]
     fn uart_write_word_to_txdata_address_should_succeed() {
-        let mut uart = Uart::new("test_uart");
-        uart.set_base_addr(0x1000);
+        let (mut uart, backend) = create_uart();
+        uart.write_word(BASE + UART_TXCTRL, UART_TXCTRL_TXEN)
+            .unwrap();
 
-        let address: DeviceAddress = 0x1000 + UART_TXDATA;
+        let address: DeviceAddress = BASE + UART_TXDATA;
         let data = 0xABCD;
 
         assert!(uart.write_word(address, data).is_ok());
-        assert_eq!(
-            uart.tx_buffer,
-            vec![b'[', b't', b'e', b's', b't', b'_', b'u', b'a', b'r', b't', b']', b' ', 0xcd]
-        );
+        uart.tick(FRAME);
+        assert_eq!(backend.output(), [0xcd]);
     }
 
     #[test]
     fn uart_write_arbitrary_length_to_txdata_address_should_succeed() {
-        let mut uart = Uart::new("test_uart");
-        uart.set_base_addr(0x1000);
+        let (mut uart, backend) = create_uart();
+        uart.write_word(BASE + UART_TXCTRL, UART_TXCTRL_TXEN)
+            .unwrap();
 
-        let address: DeviceAddress = 0x1000 + UART_TXDATA;
+        let address: DeviceAddress = BASE + UART_TXDATA;
         let data = b"Hello World\n";
 
-        assert!(uart.write(address, data).is_ok());
-        assert_eq!(
-            uart.tx_buffer,
-            vec![b'[', b't', b'e', b's', b't', b'_', b'u', b'a', b'r', b't', b']', b' ']
-        );
+        // the FIFO holds 8 bytes
+        for chunk in data.chunks(UART_FIFO_DEPTH) {
+            assert!(uart.write(address, chunk).is_ok());
+            uart.tick(FRAME * chunk.len() as u64);
+        }
+        assert_eq!(backend.output(), data);
+    }
+
+    #[test]
+    fn test_tx_fifo() {
+        let (mut uart, backend) = create_uart();
+        // the transmitter is disabled, the FIFO fills up
+        uart.write_word(BASE + UART_TXCTRL, 0).unwrap();
+        for byte in 0..=UART_FIFO_DEPTH as u32 {
+            uart.write_word(BASE + UART_TXDATA, byte).unwrap();
+        }
+        assert_eq!(uart.read_word(BASE + UART_TXDATA), Ok(UART_TXDATA_FULL));
+        uart.tick(FRAME);
+        assert_eq!(uart.tx_fifo.len(), UART_FIFO_DEPTH);
+
+        // one frame per 10 * (div + 1) cycles, or 11 with two stop bits
+        uart.write_word(BASE + UART_DIV, 1).unwrap();
+        uart.write_word(BASE + UART_TXCTRL, UART_TXCTRL_TXEN | UART_TXCTRL_NSTOP)
+            .unwrap();
+        assert_eq!(uart.next_event(), Some(22));
+        uart.tick(21);
+        assert_eq!(uart.tx_fifo.len(), UART_FIFO_DEPTH);
+        uart.tick(1);
+        assert_eq!(uart.read_word(BASE + UART_TXDATA), Ok(0));
+        assert_eq!(uart.tx_fifo, (1..UART_FIFO_DEPTH as u8).collect::<Vec<_>>());
+        assert_eq!(backend.output(), [0]);
+    }
+
+    #[test]
+    fn test_tx_fifo_flush() {
+        let (mut uart, backend) = create_uart();
+        // the transmitter is enabled at reset
+        assert_eq!(uart.read_word(BASE + UART_TXCTRL), Ok(UART_TXCTRL_TXEN));
+        uart.write(BASE + UART_TXDATA, b"abc").unwrap();
+        uart.tick(FRAME);
+        assert_eq!(backend.output(), b"a");
+
+        // the frames still queued are sent at once, or when the UART is dropped
+        uart.flush();
+        assert_eq!(backend.output(), b"abc");
+        assert_eq!(uart.next_event(), None);
+        uart.write(BASE + UART_TXDATA, b"de").unwrap();
+        drop(uart);
+        assert_eq!(backend.output(), b"abcde");
+    }
+
+    #[test]
+    fn test_rx_fifo() {
+        let (mut uart, backend) = create_uart();
+        backend.push_input(b"0123456789");
+        assert_eq!(uart.read_word(BASE + UART_RXDATA), Ok(UART_RXDATA_EMPTY));
+        // nothing is received while the receiver is disabled
+        uart.tick(FRAME);
+        assert_eq!(uart.next_event(), None);
+
+        // the backend is polled once per frame
+        uart.write_word(BASE + UART_RXCTRL, UART_RXCTRL_RXEN)
+            .unwrap();
+        assert_eq!(uart.next_event(), Some(FRAME));
+        uart.tick(FRAME * 2);
+        assert_eq!(uart.read_word(BASE + UART_RXDATA), Ok(b'0' as u32));
+        assert_eq!(uart.read_byte(BASE + UART_RXDATA), Ok(b'1'));
+
+        // the input waits while the FIFO is full
+        uart.tick(FRAME * 20);
+        assert_eq!(uart.rx_fifo.borrow().len(), UART_FIFO_DEPTH);
+        assert_eq!(uart.next_event(), None);
+        for byte in b"23456789" {
+            assert_eq!(uart.read_word(BASE + UART_RXDATA), Ok(*byte as u32));
+        }
+        assert_eq!(uart.read_word(BASE + UART_RXDATA), Ok(UART_RXDATA_EMPTY));
+
+        // the frames polled without input are not received later
+        uart.tick(FRAME * 3 + 1);
+        backend.push_input(b"ab");
+        assert_eq!(uart.next_event(), Some(FRAME - 1));
+        uart.tick(FRAME - 1);
+        assert_eq!(uart.rx_fifo.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_watermark_interrupts() {
+        let (mut uart, backend) = create_uart();
+        let line = InterruptLine::new();
+        uart.set_interrupt_line(line.clone());
+        assert_eq!(uart.read_word(BASE + UART_IP), Ok(0));
+
+        // txwm while the TX FIFO holds less than txcnt entries
+        uart.write_word(BASE + UART_TXCTRL, 1 << 16).unwrap();
+        assert_eq!(uart.read_word(BASE + UART_IP), Ok(UART_IP_TXWM));
+        assert!(!line.is_raised());
+        uart.write_word(BASE + UART_IE, UART_IP_TXWM).unwrap();
+        assert!(line.is_raised());
+        uart.write_word(BASE + UART_TXDATA, b'A' as u32).unwrap();
+        assert_eq!(uart.read_word(BASE + UART_IP), Ok(0));
+        assert!(!line.is_raised());
+
+        // rxwm while the RX FIFO holds more than rxcnt entries
+        uart.write_word(BASE + UART_IE, UART_IP_RXWM).unwrap();
+        uart.write_word(BASE + UART_RXCTRL, (1 << 16) | UART_RXCTRL_RXEN)
+            .unwrap();
+        backend.push_input(b"ab");
+        uart.tick(FRAME);
+        assert!(!line.is_raised());
+        uart.tick(FRAME);
+        assert_eq!(uart.read_word(BASE + UART_IP), Ok(UART_IP_RXWM));
+        assert!(line.is_raised());
+        uart.read_word(BASE + UART_RXDATA).unwrap();
+        assert!(!line.is_raised());
     }
 }
@@ -16,10 +16,13 @@ use tracing_subscriber::fmt::time::UtcTime;
 use tracing_subscriber::FmtSubscriber;
 
 use cpu_peripherals::bus::{Bus, DevicePointer};
-use cpu_peripherals::{clint::Clint, mem::Mem, uart::Uart, DeviceAddress, DeviceSize};
+use cpu_peripherals::serial::{
+    CharBackend, CharBackendSpec, FileBackend, NullBackend, PtyBackend, StdioBackend, TcpBackend,
+};
+use cpu_peripherals::{clint::Clint, mem::Mem, plic::Plic, uart::Uart, DeviceAddress, DeviceSize};
 use sim_lib::loader::Loader;
-use sim_lib::simulator::Simulator;
-use sim_lib::ProgramCounter;
+use sim_lib::simulator::{Simulator, UnhandledTrap};
+use sim_lib::{Isa, MachineInfo, MisalignedAccess, ProgramCounter};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 enum LogLevel {
@@ -31,7 +34,7 @@ enum LogLevel {
 }
 
 impl LogLevel {
-    pub fn to_tracing_level(&self) -> tracing::Level {
+    pub fn to_tracing_level(self) -> tracing::Level {
         match self {
             LogLevel::Error => tracing::Level::ERROR,
             LogLevel::Warn => tracing::Level::WARN,
@@ -42,6 +45,21 @@ impl LogLevel {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Misaligned {
+    Trap,
+    Emulate,
+}
+
+impl Misaligned {
+    pub fn to_policy(self) -> MisalignedAccess {
+        match self {
+            Misaligned::Trap => MisalignedAccess::Trap,
+            Misaligned::Emulate => MisalignedAccess::Emulate,
+        }
+    }
+}
+
 /// Command line arguments for the RISC-V ISS
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -62,6 +80,23 @@ struct Args {
     #[arg(short, long, value_enum, default_value_t = LogLevel::Warn)]
     log_level: LogLevel,
 
+    /// The ISA of the hart, e.g. rv32imac_zicsr_zifencei_zba, XLEN follows the ELF file if not set
+    #[arg(long, value_parser = parse_isa)]
+    isa: Option<Isa>,
+
+    /// How misaligned loads and stores are handled: trap, or emulate them
+    #[arg(long, value_enum, default_value_t = Misaligned::Emulate)]
+    misaligned: Misaligned,
+
+    /// Stop with an error on a trap the program has no handler for, instead of
+    /// jumping to the trap vector 0
+    #[arg(long, action = ArgAction::SetTrue)]
+    stop_on_unhandled_trap: bool,
+
+    /// The host end of UART0: stdio, pty, tcp:<address>:<port>, file:<path> or null
+    #[arg(long, value_parser = parse_serial, default_value = "stdio")]
+    serial0: CharBackendSpec,
+
     /// If log file of RVV-ISS running with no ansi color
     #[arg(short = 'n', long = "no-ansi", action = ArgAction::SetTrue)]
     no_ansi: bool,
@@ -71,6 +106,14 @@ fn parse_hex_address(s: &str) -> Result<DeviceAddress, std::num::ParseIntError>
     u64::from_str_radix(s.trim_start_matches("0x"), 16).map(|v| v as DeviceAddress)
 }
 
+fn parse_isa(s: &str) -> Result<Isa, String> {
+    s.parse::<Isa>().map_err(|e| e.to_string())
+}
+
+fn parse_serial(s: &str) -> Result<CharBackendSpec, String> {
+    s.parse::<CharBackendSpec>().map_err(|e| e.to_string())
+}
+
 // const MEMORY_BASE_ADDRESS: DeviceAddress = 0x1_0000;
 const FLASH_BASE_ADDRESS: DeviceAddress = 0x8000_0000;
 const FLASH_SIZE: DeviceSize = 512 * 1024;
@@ -79,15 +122,22 @@ const RAM_BASE_ADDRESS: DeviceAddress = 0x8008_0000;
 const RAM_SIZE: DeviceSize = 512 * 1024;
 
 const CLINT_BASE_ADDRESS: DeviceAddress = 0x200_0000;
-const CLINT_SIZE: DeviceSize = 0x1000;
+const CLINT_SIZE: DeviceSize = 0x1_0000;
+// mtime advances with every hart clock cycle
+const CLINT_TICKS_PER_INCREMENT: u64 = 1;
+
+const PLIC_BASE_ADDRESS: DeviceAddress = 0x0c00_0000;
+const PLIC_SIZE: DeviceSize = 0x400_0000;
 
 // UART0 base address
 const UART_BASE_ADDRESS: DeviceAddress = 0x1001_3000;
 const UART_SIZE: DeviceSize = 0x1000;
+// The PLIC source of UART0
+const UART_PLIC_SOURCE: usize = 3;
 
 fn init_tracing(args: &Args) {
     let level = args.log_level.to_tracing_level();
-    let time_format = time::format_description::parse(
+    let time_format = time::format_description::parse_borrowed::<1>(
         "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]",
     )
     .expect("format string should be valid!");
@@ -102,6 +152,32 @@ fn init_tracing(args: &Args) {
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 }
 
+/// Opens the host end of a serial device, waiting for a client of a TCP
+/// socket so that no output is lost
+fn open_serial(name: &str, spec: &CharBackendSpec) -> io::Result<Box<dyn CharBackend>> {
+    let backend: Box<dyn CharBackend> = match spec {
+        CharBackendSpec::Stdio => Box::new(StdioBackend::raw()),
+        CharBackendSpec::Pty => {
+            let pty = PtyBackend::open()?;
+            eprintln!("{} is connected to {}", name, pty.path().display());
+            Box::new(pty)
+        }
+        CharBackendSpec::Tcp(address) => {
+            let mut tcp = TcpBackend::bind(*address)?;
+            eprintln!(
+                "{} is waiting for a connection on {}",
+                name,
+                tcp.local_addr()?
+            );
+            tcp.wait_for_connection()?;
+            Box::new(tcp)
+        }
+        CharBackendSpec::File(path) => Box::new(FileBackend::create(path)?),
+        CharBackendSpec::Null => Box::new(NullBackend),
+    };
+    Ok(backend)
+}
+
 fn is_elf_file(file_path: &str) -> io::Result<bool> {
     let mut file = File::open(file_path)?;
     let mut buffer = [0u8; 4];
@@ -126,17 +202,44 @@ fn main() {
     let memory = DevicePointer::new(Mem::new(RAM_SIZE));
     let _ = bus.add_device(RAM_BASE_ADDRESS, RAM_SIZE, memory);
 
-    let clint = DevicePointer::new(Clint::new());
+    let clint = DevicePointer::new(Clint::new(CLINT_TICKS_PER_INCREMENT));
     let _ = bus.add_device(CLINT_BASE_ADDRESS, CLINT_SIZE, clint);
-    let uart = DevicePointer::new(Uart::new("UART0"));
-    let _ = bus.add_device(UART_BASE_ADDRESS, UART_SIZE, uart);
+    let plic = Plic::new();
+    let mut uart = Uart::new("UART0");
+    match open_serial("UART0", &args.serial0) {
+        Ok(backend) => uart.set_backend(backend),
+        Err(e) => {
+            eprintln!(
+                "Error: Open the serial backend({}) failed: {}",
+                args.serial0, e
+            );
+            std::process::exit(1);
+        }
+    }
+    if let Some(line) = plic.interrupt_line(UART_PLIC_SOURCE) {
+        uart.set_interrupt_line(line);
+    }
+    let _ = bus.add_device(PLIC_BASE_ADDRESS, PLIC_SIZE, DevicePointer::new(plic));
+    let _ = bus.add_device(UART_BASE_ADDRESS, UART_SIZE, DevicePointer::new(uart));
 
     // step 3. create a simulator
     let mut sim = Simulator::new(bus);
+    // the hart reports the identification of the FE310-G002 the bus models
+    sim.set_machine_info(MachineInfo::FE310_G002);
+    sim.set_misaligned_access(args.misaligned.to_policy());
+    if args.stop_on_unhandled_trap {
+        sim.set_unhandled_trap(UnhandledTrap::Stop);
+    }
     if let Some(instr_file) = args.instr_file {
         sim.prepare_log_file(&instr_file);
     }
 
+    // a change of XLEN resets the core, the ISA is set before the reset vector
+    if let Some(isa) = args.isa.as_ref() {
+        info!("ISA: {}", isa);
+        sim.set_isa(isa.clone());
+    }
+
     // step 4. load the ELF/bin program into memory
     let file_path = PathBuf::from(&args.file_path);
     info!("ELF/bin file path: {:?}", file_path);
@@ -148,6 +251,17 @@ fn main() {
                 .unwrap();
 
             assert_eq!(loader.entry_point(), 0x8000_0000, "Unexpected entry point");
+            if let Some(isa) = args.isa.as_ref() {
+                if isa.xlen() != loader.xlen() {
+                    eprintln!(
+                        "Error: The ELF file is RV{}, but the ISA is {}.",
+                        loader.xlen().bits(),
+                        isa
+                    );
+                    std::process::exit(1);
+                }
+            }
+            sim.set_xlen(loader.xlen());
             let entry_point = loader.entry_point();
             sim.set_reset_vector(entry_point as ProgramCounter);
         } else {
@@ -173,10 +287,13 @@ fn main() {
 
     // step 5. run the simulator
     let start = std::time::Instant::now();
-    sim.run(None).expect("Simulation failed");
+    if let Err(e) = sim.run(None) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
     let duration = start.elapsed();
     println!("Target application exit code: {}", sim.get_exit_code());
-    
+
     let secs = duration.as_secs_f64();
     let instructions = sim.get_run_instrctions();
     // println!("Time elapsed: {:?}, secs {}, instructions {}", duration, secs, instructions);
@@ -184,7 +301,10 @@ fn main() {
     // step 6. print the statistics
     println!("Simulation statistics:");
     let ips = instructions as f64 / secs;
-    println!("\tIPS(Instructions Per Second): {:.2} KIPS, {:.2} MIPS", 
-        ips/(1000 as f64), ips/((1000*1000) as f64));
+    println!(
+        "\tIPS(Instructions Per Second): {:.2} KIPS, {:.2} MIPS",
+        ips / 1000.0,
+        ips / ((1000 * 1000) as f64)
+    );
     println!("Rust RISC-V ISS has finished running.");
 }
@@ -7,17 +7,23 @@
 
 use std::collections::HashMap;
 
-use tracing::info;
+use tracing::{debug, info};
 
-use crate::decode::ExecutionReturnData;
+use crate::decode::{ExecutionReturnData, INSTRUCTION_LENGTH};
 use crate::inst_csr_reg::*;
-use crate::trap::{Exception, Trap};
+use crate::isa::Isa;
+use crate::mmu::{AccessType, Mmu};
+use crate::pmp;
+use crate::trap::{Exception, Interrupt, Trap};
 use crate::{
     csr::{self, Csr},
-    GprUnsigned, ProgramCounter, RegisterIndex, RvCoreError,
+    FprUnsigned, GprSigned, GprUnsigned, MachineInstruction, ProgramCounter, RegisterIndex,
+    RvCoreError, Xlen,
 };
+use cpu_peripherals::{bus::Bus, CpuPeripheralsError, DeviceAddress};
 
 const REGISTER_NUM: usize = 32;
+const FREGISTER_NUM: usize = 32;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RegName {
@@ -143,14 +149,71 @@ impl From<RegName> for usize {
 }
 
 pub struct Core {
+    xlen: Xlen,
     pc: ProgramCounter,
     registers: [GprUnsigned; REGISTER_NUM],
+    fregisters: [FprUnsigned; FREGISTER_NUM],
     csr: Csr,
     reg_name_map: HashMap<String, RegName>,
     trap: Option<Trap>,
+    // xtval of the pending trap
+    trap_value: GprUnsigned,
     privilege_mode: PrivilegeMode,
+    // Address and size of the word reserved by LR, cleared by SC, stores and traps
+    reservation: Option<(GprUnsigned, GprUnsigned)>,
+    // Length in bytes of the instruction being executed, 2 for compressed ones
+    instruction_length: ProgramCounter,
+    // Bits of the instruction being executed as fetched, the 16 bits of a
+    // compressed one rather than its expansion
+    instruction_bits: MachineInstruction,
+    // Set by FENCE.I, instructions fetched before it may be stale
+    fence_i_pending: bool,
+    // Set by WFI until an interrupt is pending
+    waiting: bool,
+    misaligned_access: MisalignedAccess,
+    // misa.Extensions of the configured ISA
+    misa_extensions: GprUnsigned,
+    machine_info: MachineInfo,
+    mmu: Mmu,
 }
 
+/// How the platform handles the loads and stores which are not aligned to
+/// their size. AMOs, LR and SC always trap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MisalignedAccess {
+    /// Raise an address-misaligned exception, xtval is the address
+    Trap,
+    /// Access the bytes one by one, they may span two pages or two devices
+    #[default]
+    Emulate,
+}
+
+/// The values of the read-only machine information CSRs, which identify the
+/// hart and its implementation. Zero means not implemented, or a
+/// non-commercial implementation for mvendorid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MachineInfo {
+    /// JEDEC bank and offset of the vendor
+    pub mvendorid: GprUnsigned,
+    pub marchid: GprUnsigned,
+    pub mimpid: GprUnsigned,
+    pub mhartid: GprUnsigned,
+    /// Address of the configuration data structure
+    pub mconfigptr: GprUnsigned,
+}
+
+impl MachineInfo {
+    /// The E31 hart of the SiFive FE310-G002
+    pub const FE310_G002: MachineInfo = MachineInfo {
+        mvendorid: 0x489,
+        marchid: 0x8000_0007,
+        mimpid: 0x2018_1004,
+        mhartid: 0,
+        mconfigptr: 0,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrivilegeMode {
     User,
     Supervisor,
@@ -172,41 +235,117 @@ pub fn get_privilege_encoding(mode: &PrivilegeMode) -> u8 {
     match mode {
         PrivilegeMode::User => 0,
         PrivilegeMode::Supervisor => 1,
-        PrivilegeMode::Reserved => panic!(),
+        PrivilegeMode::Reserved => 2,
         PrivilegeMode::Machine => 3,
     }
 }
 
-/// Returns `PrivilegeMode` from encoded privilege mode bits
+/// Returns `PrivilegeMode` from encoded privilege mode bits, only the low two
+/// bits are used
 pub fn get_privilege_mode(encoding: u8) -> PrivilegeMode {
-    match encoding {
+    match encoding & 0b11 {
         0 => PrivilegeMode::User,
         1 => PrivilegeMode::Supervisor,
-        3 => PrivilegeMode::Machine,
-        _ => panic!("Unknown privilege uncoding"),
+        2 => PrivilegeMode::Reserved,
+        _ => PrivilegeMode::Machine,
+    }
+}
+
+impl Default for Core {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Core {
     pub fn new() -> Self {
-        info!("Creating a new core");
+        Self::with_xlen(Xlen::Rv32)
+    }
+
+    pub fn with_xlen(xlen: Xlen) -> Self {
+        info!("Creating a new RV{} core", xlen.bits());
         Self {
+            xlen,
             pc: 0,
             registers: [0; REGISTER_NUM],
-            csr: Csr::new(),
+            fregisters: [0; FREGISTER_NUM],
+            csr: Csr::new(xlen),
             reg_name_map: Self::new_reg_name_map(),
             trap: None,
+            trap_value: 0,
             privilege_mode: PrivilegeMode::Machine,
+            reservation: None,
+            instruction_length: INSTRUCTION_LENGTH,
+            instruction_bits: 0,
+            fence_i_pending: false,
+            waiting: false,
+            misaligned_access: MisalignedAccess::default(),
+            misa_extensions: Isa::default().misa_extensions() | csr::MISA_S | csr::MISA_U,
+            machine_info: MachineInfo::default(),
+            mmu: Mmu::new(),
         }
     }
 
     pub fn reset(&mut self) {
         self.pc = 0;
         self.registers = [0; REGISTER_NUM];
+        self.fregisters = [0; FREGISTER_NUM];
         self.csr.reset();
         self.reg_name_map = Self::new_reg_name_map();
         self.trap = None;
+        self.trap_value = 0;
         self.privilege_mode = PrivilegeMode::Machine;
+        self.reservation = None;
+        self.instruction_length = INSTRUCTION_LENGTH;
+        self.instruction_bits = 0;
+        self.fence_i_pending = false;
+        self.waiting = false;
+        self.mmu.flush(None, None);
+    }
+
+    pub fn get_xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    /// Configures the core for `isa`: misa reports its extensions. The core is
+    /// reset if XLEN changes.
+    pub fn set_isa(&mut self, isa: &Isa) {
+        if isa.xlen() != self.xlen {
+            self.set_xlen(isa.xlen());
+        }
+        // misa also reports the privilege modes below M
+        self.misa_extensions = isa.misa_extensions() | csr::MISA_S | csr::MISA_U;
+        self.csr.set_misa_extensions(self.misa_extensions);
+    }
+
+    pub fn get_misaligned_access(&self) -> MisalignedAccess {
+        self.misaligned_access
+    }
+
+    /// Sets how misaligned loads and stores are handled, a reset keeps it
+    pub fn set_misaligned_access(&mut self, policy: MisalignedAccess) {
+        self.misaligned_access = policy;
+    }
+
+    /// Changes the width of the core, which is reset as all its registers change width
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        info!("Switching the core to RV{}", xlen.bits());
+        self.xlen = xlen;
+        self.csr = Csr::new(xlen);
+        self.csr.set_misa_extensions(self.misa_extensions);
+        self.csr.set_machine_info(&self.machine_info);
+        self.reset();
+    }
+
+    pub fn get_machine_info(&self) -> &MachineInfo {
+        &self.machine_info
+    }
+
+    /// Sets the values of mvendorid, marchid, mimpid, mhartid and mconfigptr,
+    /// a reset keeps them
+    pub fn set_machine_info(&mut self, info: MachineInfo) {
+        self.machine_info = info;
+        self.csr.set_machine_info(&self.machine_info);
     }
 
     pub fn get_reg_name_by_index(&self, index: RegisterIndex) -> Option<&'static str> {
@@ -221,7 +360,21 @@ impl Core {
         self.pc
     }
     pub fn set_pc(&mut self, pc: ProgramCounter) {
-        self.pc = pc;
+        self.pc = self.xlen.truncate(pc);
+    }
+
+    pub fn set_instruction_length(&mut self, length: ProgramCounter) {
+        self.instruction_length = length;
+    }
+
+    pub fn set_instruction_bits(&mut self, bits: MachineInstruction) {
+        self.instruction_bits = bits;
+    }
+
+    /// Returns the address of the instruction following the current one
+    pub fn get_next_pc(&self) -> ProgramCounter {
+        self.xlen
+            .truncate(self.pc.wrapping_add(self.instruction_length))
     }
 
     pub fn read_register(&self, index: RegisterIndex) -> Result<GprUnsigned, RvCoreError> {
@@ -245,7 +398,36 @@ impl Core {
             return Ok(());
         }
 
-        self.registers[index] = value;
+        self.registers[index] = self.xlen.truncate(value);
+        Ok(())
+    }
+
+    /// Reads the register `index` as a signed XLEN-bit number
+    pub(crate) fn read_register_signed(
+        &self,
+        index: RegisterIndex,
+    ) -> Result<GprSigned, RvCoreError> {
+        Ok(self.xlen.sign_extend(self.read_register(index)?))
+    }
+
+    pub fn read_fregister(&self, index: RegisterIndex) -> Result<FprUnsigned, RvCoreError> {
+        if index >= FREGISTER_NUM {
+            return Err(RvCoreError::InvalidRegisterIndex(index as RegisterIndex));
+        }
+
+        Ok(self.fregisters[index])
+    }
+
+    pub fn write_fregister(
+        &mut self,
+        index: RegisterIndex,
+        value: FprUnsigned,
+    ) -> Result<(), RvCoreError> {
+        if index >= FREGISTER_NUM {
+            return Err(RvCoreError::InvalidRegisterIndex(index as RegisterIndex));
+        }
+
+        self.fregisters[index] = value;
         Ok(())
     }
 
@@ -265,7 +447,7 @@ impl Core {
     pub fn write_reg_by_name(&mut self, name: &str, value: GprUnsigned) -> Result<(), RvCoreError> {
         if let Some(&reg) = self.reg_name_map.get(name) {
             if reg != RegName::Zero {
-                self.registers[reg.to_index()] = value;
+                self.registers[reg.to_index()] = self.xlen.truncate(value);
             }
             return Ok(());
         }
@@ -273,23 +455,111 @@ impl Core {
         Err(RvCoreError::InvalidRegisterName(name.to_string()))
     }
 
-    pub fn read_csr(&self, addr: csr::CsrAddrType) -> Result<u32, csr::CsrError> {
+    pub fn read_csr(&self, addr: csr::CsrAddrType) -> Result<GprUnsigned, csr::CsrError> {
         self.csr.read(addr)
     }
 
-    pub(crate) fn set_trap(&mut self, trap: Trap, tval: u32) -> Result<(), RvCoreError> {
-        self.csr.write(CSR_MTVAL, tval)?;
+    /// Writes a CSR from the host, whatever the privilege mode. The fields are
+    /// legalized as for a CSR instruction, read-only CSRs can not be written.
+    pub fn write_csr(
+        &mut self,
+        addr: csr::CsrAddrType,
+        value: GprUnsigned,
+    ) -> Result<(), csr::CsrError> {
+        self.csr.write(addr, value)
+    }
+
+    /// Reads a 64-bit CSR, on RV32 its upper half is the `high` CSR
+    fn read_csr64(
+        &self,
+        low: csr::CsrAddrType,
+        high: csr::CsrAddrType,
+    ) -> Result<u64, csr::CsrError> {
+        let value = self.csr.read(low)?;
+        match self.xlen {
+            Xlen::Rv32 => Ok(value | self.csr.read(high)? << 32),
+            Xlen::Rv64 => Ok(value),
+        }
+    }
+
+    /// Writes a 64-bit CSR, on RV32 its upper half is the `high` CSR
+    fn write_csr64(
+        &mut self,
+        low: csr::CsrAddrType,
+        high: csr::CsrAddrType,
+        value: u64,
+    ) -> Result<(), csr::CsrError> {
+        match self.xlen {
+            Xlen::Rv32 => {
+                self.csr.write(low, value & 0xffff_ffff)?;
+                self.csr.write(high, value >> 32)
+            }
+            Xlen::Rv64 => self.csr.write(low, value),
+        }
+    }
+
+    /// Returns mstatus, with mstatush as its upper half on RV32
+    pub fn get_mstatus(&self) -> Result<u64, csr::CsrError> {
+        self.read_csr64(CSR_MSTATUS, CSR_MSTATUSH)
+    }
+
+    /// Writes mstatus, and mstatush on RV32, from the host
+    pub fn set_mstatus(&mut self, value: u64) -> Result<(), csr::CsrError> {
+        self.write_csr64(CSR_MSTATUS, CSR_MSTATUSH, value)
+    }
+
+    /// Returns menvcfg, with menvcfgh as its upper half on RV32
+    pub fn get_menvcfg(&self) -> Result<u64, csr::CsrError> {
+        self.read_csr64(CSR_MENVCFG, CSR_MENVCFGH)
+    }
+
+    /// Writes menvcfg, and menvcfgh on RV32, from the host
+    pub fn set_menvcfg(&mut self, value: u64) -> Result<(), csr::CsrError> {
+        self.write_csr64(CSR_MENVCFG, CSR_MENVCFGH, value)
+    }
+
+    /// Returns mcounteren, which is 32 bits wide for every XLEN
+    pub fn get_mcounteren(&self) -> Result<u32, csr::CsrError> {
+        Ok(self.csr.read(CSR_MCOUNTEREN)? as u32)
+    }
+
+    pub fn set_mcounteren(&mut self, value: u32) -> Result<(), csr::CsrError> {
+        self.csr.write(CSR_MCOUNTEREN, value as GprUnsigned)
+    }
+
+    /// Returns mcountinhibit, which is 32 bits wide for every XLEN
+    pub fn get_mcountinhibit(&self) -> Result<u32, csr::CsrError> {
+        Ok(self.csr.read(CSR_MCOUNTINHIBIT)? as u32)
+    }
+
+    pub fn set_mcountinhibit(&mut self, value: u32) -> Result<(), csr::CsrError> {
+        self.csr.write(CSR_MCOUNTINHIBIT, value as GprUnsigned)
+    }
+
+    /// Sets the trap raised by the current instruction, `tval` is written to
+    /// mtval or stval when it is taken
+    pub(crate) fn set_trap(&mut self, trap: Trap, tval: GprUnsigned) -> Result<(), RvCoreError> {
+        self.trap_value = tval;
         self.trap = Some(trap);
         Ok(())
     }
 
+    /// Sets an illegal instruction trap, with the fetched instruction bits as mtval
+    pub fn raise_illegal_instruction(&mut self) -> Result<(), RvCoreError> {
+        let bits = self.instruction_bits;
+        self.set_trap(
+            Trap::Exception(Exception::IllegalInstruction(bits)),
+            bits as GprUnsigned,
+        )
+    }
+
     pub fn is_ecall(trap: &Trap) -> bool {
-        match trap {
+        matches!(
+            trap,
             Trap::Exception(Exception::ECallFromUMode)
-            | Trap::Exception(Exception::ECallFromSMode)
-            | Trap::Exception(Exception::ECallFromMMode) => true,
-            _ => false,
-        }
+                | Trap::Exception(Exception::ECallFromSMode)
+                | Trap::Exception(Exception::ECallFromMMode)
+        )
     }
 
     pub fn take_trap(&mut self) -> Option<Trap> {
@@ -301,9 +571,461 @@ impl Core {
         trap: &Trap,
         new_pc: ProgramCounter,
     ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-        self.set_mstatus_before_handle_trap()?;
+        let delegated = self.is_delegated(trap)?;
+        if delegated {
+            self.set_sstatus_before_handle_trap()?;
+        } else {
+            self.set_mstatus_before_handle_trap()?;
+        }
+        // A trap always breaks the LR/SC sequence
+        self.reservation = None;
         let current_pc = self.get_pc();
-        trap.handle_trap(&mut self.csr, current_pc, new_pc)
+        let tval = std::mem::take(&mut self.trap_value);
+        trap.handle_trap(&mut self.csr, current_pc, new_pc, tval, delegated)
+    }
+
+    /// Returns true if the mode taking `trap` has a trap handler: the base
+    /// address in its xtvec is not 0
+    pub fn has_trap_handler(&self, trap: &Trap) -> Result<bool, RvCoreError> {
+        let tvec = if self.is_delegated(trap)? {
+            CSR_STVEC
+        } else {
+            CSR_MTVEC
+        };
+        Ok(self.csr.read(tvec)? & !0b11 != 0)
+    }
+
+    /// Returns xtval of the pending trap
+    pub fn get_trap_value(&self) -> GprUnsigned {
+        self.trap_value
+    }
+
+    /// Returns true if `trap` is taken in S-mode: its bit is set in medeleg or
+    /// mideleg and it is raised below M-mode. Traps never go to a lower mode.
+    fn is_delegated(&self, trap: &Trap) -> Result<bool, RvCoreError> {
+        if get_privilege_encoding(&self.privilege_mode)
+            > get_privilege_encoding(&PrivilegeMode::Supervisor)
+        {
+            return Ok(false);
+        }
+        let deleg = if trap.is_interrupt() {
+            self.csr.read(CSR_MIDELEG)?
+        } else {
+            self.csr.read(CSR_MEDELEG)?
+        };
+        Ok(deleg & (1 << trap.code()) != 0)
+    }
+
+    /// Registers a reservation on the `size` bytes at `address` (LR)
+    pub(crate) fn set_reservation(&mut self, address: GprUnsigned, size: GprUnsigned) {
+        self.reservation = Some((address, size));
+    }
+
+    /// Returns true if `address` is reserved, the reservation is dropped either way (SC)
+    pub(crate) fn take_reservation(&mut self, address: GprUnsigned, size: GprUnsigned) -> bool {
+        self.reservation.take() == Some((address, size))
+    }
+
+    /// Drops the reservation, an xRET leaves the code the LR/SC sequence was in
+    pub(crate) fn clear_reservation(&mut self) {
+        self.reservation = None;
+    }
+
+    /// Drops the reservation if a store of `size` bytes at `address` touches the reserved word
+    pub(crate) fn invalidate_reservation(&mut self, address: GprUnsigned, size: GprUnsigned) {
+        if let Some((reserved, reserved_size)) = self.reservation {
+            let end = address.wrapping_add(size);
+            if address < reserved.wrapping_add(reserved_size) && reserved < end {
+                self.reservation = None;
+            }
+        }
+    }
+
+    pub fn get_reservation(&self) -> Option<GprUnsigned> {
+        self.reservation.map(|(address, _)| address)
+    }
+
+    /// Counts the cycle of the instruction just executed, `retired` is false
+    /// when it raised an exception
+    pub fn advance_counters(&mut self, retired: bool) {
+        self.csr.advance_counters(retired);
+    }
+
+    /// Counts the `cycles` cycles the hart stalled in WFI
+    pub fn skip_cycles(&mut self, cycles: u64) {
+        self.csr.skip_cycles(cycles);
+    }
+
+    /// Sets the value read from the time CSR, a copy of the CLINT mtime
+    pub fn set_time(&mut self, time: u64) {
+        self.csr.set_time(time);
+    }
+
+    /// Sets the mip bits asserted by the devices, see `Bus::interrupts`
+    pub fn set_interrupt_lines(&mut self, lines: GprUnsigned) {
+        self.csr.set_interrupt_lines(lines);
+    }
+
+    /// Returns the interrupt to take before the next instruction: of the ones
+    /// pending in mip and enabled in mie, those taken in M-mode come first,
+    /// then those delegated to S-mode, each in the order of
+    /// `Interrupt::PRIORITY`. They are globally enabled in lower modes, and
+    /// by mstatus.MIE or mstatus.SIE in the mode they are taken in.
+    pub fn pending_interrupt(&self) -> Result<Option<Interrupt>, RvCoreError> {
+        let pending = self.csr.read(CSR_MIP)? & self.csr.read(CSR_MIE)?;
+        let mideleg = self.csr.read(CSR_MIDELEG)?;
+        let mstatus = self.csr.read(CSR_MSTATUS)?;
+        let machine_enabled = match self.privilege_mode {
+            PrivilegeMode::Machine => mstatus & csr::MSTATUS_MIE != 0,
+            _ => true,
+        };
+        let supervisor_enabled = match self.privilege_mode {
+            PrivilegeMode::Machine => false,
+            PrivilegeMode::Supervisor => mstatus & csr::MSTATUS_SIE != 0,
+            _ => true,
+        };
+
+        let machine = if machine_enabled {
+            pending & !mideleg
+        } else {
+            0
+        };
+        let supervisor = if supervisor_enabled {
+            pending & mideleg
+        } else {
+            0
+        };
+        let ready = if machine != 0 { machine } else { supervisor };
+        Ok(Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| ready & (1 << interrupt.code()) != 0))
+    }
+
+    /// Returns false if the current privilege mode may not read the counter CSR
+    /// at `address`, which mcounteren controls below M-mode and scounteren
+    /// also controls in U-mode
+    pub(crate) fn is_counter_accessible(
+        &self,
+        address: csr::CsrAddrType,
+    ) -> Result<bool, RvCoreError> {
+        let index = match address {
+            CSR_CYCLE..=CSR_HPMCOUNTER31 => address - CSR_CYCLE,
+            CSR_CYCLEH..=CSR_HPMCOUNTER31H => address - CSR_CYCLEH,
+            _ => return Ok(true),
+        };
+        let enabled = match self.privilege_mode {
+            PrivilegeMode::Machine => return Ok(true),
+            PrivilegeMode::User => {
+                self.csr.read(CSR_MCOUNTEREN)? & self.csr.read(CSR_SCOUNTEREN)?
+            }
+            _ => self.csr.read(CSR_MCOUNTEREN)?,
+        };
+        Ok(enabled & (1 << index) != 0)
+    }
+
+    /// Returns true if the access to the CSR at `address` traps because of
+    /// mstatus.TVM, which makes satp inaccessible in S-mode
+    pub(crate) fn is_trapped_by_tvm(&self, address: csr::CsrAddrType) -> Result<bool, RvCoreError> {
+        Ok(address == CSR_SATP
+            && self.privilege_mode == PrivilegeMode::Supervisor
+            && self.csr.read(CSR_MSTATUS)? & csr::MSTATUS_TVM != 0)
+    }
+
+    /// Returns false if the current privilege mode is below the lowest one
+    /// allowed to access the CSR at `address`, encoded in its bits 9:8
+    pub(crate) fn has_csr_privilege(&self, address: csr::CsrAddrType) -> bool {
+        let required = ((address >> 8) & 0b11) as u8;
+        get_privilege_encoding(&self.privilege_mode) >= required
+    }
+
+    /// Returns the privilege mode loads and stores are checked against:
+    /// mstatus.MPP while mstatus.MPRV is set in M-mode, the current one otherwise
+    pub fn data_privilege_mode(&self) -> Result<PrivilegeMode, RvCoreError> {
+        let mstatus = self.csr.read(CSR_MSTATUS)?;
+        if self.privilege_mode == PrivilegeMode::Machine && mstatus & csr::MSTATUS_MPRV != 0 {
+            let mpp = (mstatus & csr::MSTATUS_MPP) >> 11;
+            Ok(get_privilege_mode(mpp as u8))
+        } else {
+            Ok(self.privilege_mode)
+        }
+    }
+
+    /// Translates the virtual `address` of an access of `size` bytes to a
+    /// physical address and checks it against PMP. Fetches use the current
+    /// privilege mode, loads and stores the one of `data_privilege_mode`.
+    /// Returns None if the access raised a page fault or an access fault,
+    /// which is then the pending trap.
+    pub fn translate(
+        &mut self,
+        address: GprUnsigned,
+        size: GprUnsigned,
+        access: AccessType,
+        bus: &mut Bus,
+    ) -> Result<Option<DeviceAddress>, RvCoreError> {
+        let privilege = match access {
+            AccessType::Fetch => self.privilege_mode,
+            _ => self.data_privilege_mode()?,
+        };
+        let translated = self
+            .mmu
+            .translate(address, access, privilege, &self.csr, bus)
+            .and_then(|physical| {
+                if pmp::is_allowed(&self.csr, physical, size, access, privilege) {
+                    Ok(physical)
+                } else {
+                    Err(access.access_fault())
+                }
+            });
+        match translated {
+            Ok(physical) => Ok(Some(physical as DeviceAddress)),
+            Err(exception) => {
+                self.set_trap(Trap::Exception(exception), address)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Translates the virtual `address` of an access of `size` bytes, then
+    /// runs `operation` on the bus at the physical address. An error of the
+    /// bus or of a device, such as an unmapped address, raises the access
+    /// fault of `access` for the virtual address. Returns None if the access
+    /// raised an exception, which is then the pending trap.
+    pub fn access_memory<T>(
+        &mut self,
+        address: GprUnsigned,
+        size: GprUnsigned,
+        access: AccessType,
+        bus: &mut Bus,
+        operation: impl FnOnce(&mut Bus, DeviceAddress) -> Result<T, CpuPeripheralsError>,
+    ) -> Result<Option<T>, RvCoreError> {
+        let Some(physical) = self.translate(address, size, access, bus)? else {
+            return Ok(None);
+        };
+        match operation(bus, physical) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => {
+                debug!("{:?} at {:#x} failed: {}", access, address, error);
+                self.set_trap(Trap::Exception(access.access_fault()), address)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Loads `size` bytes, 1, 2, 4 or 8, from the virtual `address` and returns
+    /// them zero-extended. A misaligned load traps or is emulated depending
+    /// on the misaligned access policy. Returns None if the load raised an
+    /// exception, which is then the pending trap.
+    pub fn load(
+        &mut self,
+        address: GprUnsigned,
+        size: GprUnsigned,
+        bus: &mut Bus,
+    ) -> Result<Option<u64>, RvCoreError> {
+        if address.is_multiple_of(size) {
+            return self.access_memory(address, size, AccessType::Load, bus, |bus, address| {
+                match size {
+                    1 => bus.read_byte(address).map(u64::from),
+                    2 => bus.read_halfword(address).map(u64::from),
+                    4 => bus.read_word(address).map(u64::from),
+                    _ => bus.read_doubleword(address),
+                }
+            });
+        }
+        if !self.is_misaligned_emulated(address, AccessType::Load)? {
+            return Ok(None);
+        }
+
+        let mut value = 0;
+        for offset in 0..size {
+            let byte_address = self.xlen.truncate(address.wrapping_add(offset));
+            let read_byte = |bus: &mut Bus, address| bus.read_byte(address);
+            let Some(byte) =
+                self.access_memory(byte_address, 1, AccessType::Load, bus, read_byte)?
+            else {
+                return Ok(None);
+            };
+            value |= (byte as u64) << (8 * offset);
+        }
+        Ok(Some(value))
+    }
+
+    /// Stores the low `size` bytes of `value`, see `load`. A store drops an
+    /// overlapping reservation. Returns false if the store raised an
+    /// exception, nothing is then written.
+    pub fn store(
+        &mut self,
+        address: GprUnsigned,
+        size: GprUnsigned,
+        value: u64,
+        bus: &mut Bus,
+    ) -> Result<bool, RvCoreError> {
+        let stored = if address.is_multiple_of(size) {
+            self.access_memory(
+                address,
+                size,
+                AccessType::Store,
+                bus,
+                |bus, address| match size {
+                    1 => bus.write_byte(address, value as u8),
+                    2 => bus.write_halfword(address, value as u16),
+                    4 => bus.write_word(address, value as u32),
+                    _ => bus.write_doubleword(address, value),
+                },
+            )?
+            .is_some()
+        } else {
+            self.is_misaligned_emulated(address, AccessType::Store)?
+                && self.store_bytes(address, size, value, bus)?
+        };
+        if stored {
+            self.invalidate_reservation(address, size);
+        }
+        Ok(stored)
+    }
+
+    /// Stores a misaligned value one byte at a time. All the bytes are
+    /// translated and found on the bus first, a faulting store writes none.
+    fn store_bytes(
+        &mut self,
+        address: GprUnsigned,
+        size: GprUnsigned,
+        value: u64,
+        bus: &mut Bus,
+    ) -> Result<bool, RvCoreError> {
+        let mut bytes = Vec::new();
+        for offset in 0..size {
+            let byte_address = self.xlen.truncate(address.wrapping_add(offset));
+            let find_byte = |bus: &mut Bus, address| bus.find_device(address).map(|_| address);
+            let Some(physical) =
+                self.access_memory(byte_address, 1, AccessType::Store, bus, find_byte)?
+            else {
+                return Ok(false);
+            };
+            bytes.push((byte_address, physical, (value >> (8 * offset)) as u8));
+        }
+        for (byte_address, physical, byte) in bytes {
+            if let Err(error) = bus.write_byte(physical, byte) {
+                debug!("Store at {:#x} failed: {}", byte_address, error);
+                self.set_trap(
+                    Trap::Exception(Exception::StoreAmoAccessFault),
+                    byte_address,
+                )?;
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns true if the misaligned access at `address` is to be emulated,
+    /// otherwise raises the address-misaligned exception of `access`
+    fn is_misaligned_emulated(
+        &mut self,
+        address: GprUnsigned,
+        access: AccessType,
+    ) -> Result<bool, RvCoreError> {
+        match self.misaligned_access {
+            MisalignedAccess::Emulate => Ok(true),
+            MisalignedAccess::Trap => {
+                self.set_trap(Trap::Exception(access.misaligned()), address)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Checks the target of a jump or a taken branch is aligned to IALIGN: 16
+    /// bits with the C extension, 32 bits without it. Otherwise raises an
+    /// instruction-address-misaligned exception with the target as xtval, the
+    /// jump then does not write rd, and returns false.
+    pub(crate) fn check_jump_target(
+        &mut self,
+        target: ProgramCounter,
+    ) -> Result<bool, RvCoreError> {
+        let ialign = if self.csr.read(CSR_MISA)? & csr::MISA_C != 0 {
+            2
+        } else {
+            4
+        };
+        if target.is_multiple_of(ialign) {
+            return Ok(true);
+        }
+        self.set_trap(
+            Trap::Exception(Exception::InstructionAddressMisaligned),
+            target as GprUnsigned,
+        )?;
+        Ok(false)
+    }
+
+    /// Drops the cached translations of the page holding `address` and of the
+    /// address space `asid`, all of them if both are None (SFENCE.VMA)
+    pub(crate) fn flush_tlb(&mut self, address: Option<GprUnsigned>, asid: Option<GprUnsigned>) {
+        self.mmu.flush(address, asid);
+    }
+
+    /// Records a FENCE.I, the stores before it must be visible to the next fetches
+    pub(crate) fn request_fence_i(&mut self) {
+        self.fence_i_pending = true;
+    }
+
+    /// Returns true once after each FENCE.I, the caller drops what it cached from
+    /// earlier fetches
+    pub fn take_fence_i(&mut self) -> bool {
+        std::mem::take(&mut self.fence_i_pending)
+    }
+
+    /// Stalls the hart after the current instruction (WFI)
+    pub(crate) fn wait_for_interrupt(&mut self) {
+        self.waiting = true;
+    }
+
+    /// Returns true while the hart is stalled by WFI
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    /// Returns true if an interrupt is pending in mip and enabled in mie,
+    /// which wakes the hart up from WFI even if not globally enabled
+    pub fn is_interrupt_pending(&self) -> Result<bool, RvCoreError> {
+        Ok(self.csr.read(CSR_MIP)? & self.csr.read(CSR_MIE)? != 0)
+    }
+
+    /// Wakes the hart up from WFI if an interrupt is pending, returns true
+    /// if it is running
+    pub fn wake_up(&mut self) -> Result<bool, RvCoreError> {
+        if self.waiting && self.is_interrupt_pending()? {
+            self.waiting = false;
+        }
+        Ok(!self.waiting)
+    }
+
+    /// Returns true if the bit of the extension `extension` ('A'-'Z') is set in misa
+    pub(crate) fn is_extension_enabled(&self, extension: char) -> Result<bool, RvCoreError> {
+        let misa = self.csr.read(CSR_MISA)?;
+        let bit = extension as GprUnsigned - 'A' as GprUnsigned;
+        Ok(misa & (1 << bit) != 0)
+    }
+
+    /// Returns false while mstatus.FS is Off or misa.F is clear, FP instructions
+    /// and CSRs are illegal then
+    pub(crate) fn is_fp_enabled(&self) -> Result<bool, RvCoreError> {
+        let mstatus = self.csr.read(CSR_MSTATUS)?;
+        Ok(mstatus & csr::MSTATUS_FS != csr::MSTATUS_FS_OFF && self.is_extension_enabled('F')?)
+    }
+
+    /// Sets mstatus.FS to Dirty after the FP state has been modified
+    pub(crate) fn set_fp_dirty(&mut self) -> Result<(), RvCoreError> {
+        let mstatus = self.csr.read(CSR_MSTATUS)?;
+        self.csr
+            .write(CSR_MSTATUS, mstatus | csr::MSTATUS_FS_DIRTY)?;
+        Ok(())
+    }
+
+    /// Accumulates exception flags into fflags, marking the FP state dirty if any is raised
+    pub(crate) fn accrue_fflags(&mut self, flags: u32) -> Result<(), RvCoreError> {
+        if flags != 0 {
+            self.csr.csrrs(CSR_FFLAGS, flags as GprUnsigned)?;
+            self.set_fp_dirty()?;
+        }
+        Ok(())
     }
 
     pub(crate) fn get_csr_mut(&mut self) -> &mut Csr {
@@ -314,7 +1036,7 @@ impl Core {
         self.privilege_mode = mode;
     }
 
-    pub(crate) fn get_privilege_mode(&self) -> &PrivilegeMode {
+    pub fn get_privilege_mode(&self) -> &PrivilegeMode {
         &self.privilege_mode
     }
 
@@ -322,8 +1044,8 @@ impl Core {
         let old_val = self.csr.read(CSR_MSTATUS)?;
         let current_mode = self.get_privilege_mode();
         // Save the privilege mode before the trap into mstatus.MPP
-        let mut new_value =
-            (old_val & !(csr::MSTATUS_MPP)) | ((get_privilege_encoding(current_mode) as u32) << 11);
+        let mut new_value = (old_val & !(csr::MSTATUS_MPP))
+            | ((get_privilege_encoding(current_mode) as GprUnsigned) << 11);
 
         // Change the privilege mode to Machine mode.
         self.set_privilege_mode(PrivilegeMode::Machine);
@@ -339,6 +1061,25 @@ impl Core {
         Ok(())
     }
 
+    fn set_sstatus_before_handle_trap(&mut self) -> Result<(), RvCoreError> {
+        let old_val = self.csr.read(CSR_MSTATUS)?;
+        // Save the privilege mode before the trap into sstatus.SPP, it is U or S
+        let mut new_value = old_val & !(csr::MSTATUS_SPP);
+        if self.privilege_mode == PrivilegeMode::Supervisor {
+            new_value |= csr::MSTATUS_SPP;
+        }
+
+        self.set_privilege_mode(PrivilegeMode::Supervisor);
+
+        // Save the previous sstatus.SIE into sstatus.SPIE and disable interrupts
+        new_value = (new_value & !(csr::MSTATUS_SPIE)) | ((old_val & csr::MSTATUS_SIE) << 4);
+        new_value &= !(csr::MSTATUS_SIE);
+
+        self.csr.write(CSR_MSTATUS, new_value)?;
+
+        Ok(())
+    }
+
     fn new_reg_name_map() -> HashMap<String, RegName> {
         let mut reg_map = HashMap::new();
         reg_map.insert("zero".to_string(), RegName::Zero);
@@ -380,7 +1121,7 @@ impl Core {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::trap::{Exception, Trap};
+    use crate::trap::{Exception, Interrupt, Trap};
     use crate::RvCoreError;
 
     #[test]
@@ -404,6 +1145,231 @@ mod tests {
         assert_eq!(core.take_trap(), None);
     }
 
+    #[test]
+    fn test_counter_access_below_m_mode() {
+        let mut core = Core::new();
+        assert!(core.is_counter_accessible(CSR_CYCLE).unwrap());
+
+        core.set_privilege_mode(PrivilegeMode::User);
+        assert!(!core.is_counter_accessible(CSR_CYCLE).unwrap());
+        assert!(core.is_counter_accessible(CSR_MSCRATCH).unwrap());
+        // U-mode also needs the counter enabled in scounteren
+        core.csr.write(CSR_MCOUNTEREN, csr::COUNTER_TM).unwrap();
+        assert!(!core.is_counter_accessible(CSR_TIMEH).unwrap());
+        core.csr
+            .write(CSR_SCOUNTEREN, csr::COUNTER_TM | csr::COUNTER_IR)
+            .unwrap();
+        assert!(core.is_counter_accessible(CSR_TIMEH).unwrap());
+        assert!(!core.is_counter_accessible(CSR_INSTRET).unwrap());
+
+        // S-mode only needs mcounteren
+        core.set_privilege_mode(PrivilegeMode::Supervisor);
+        core.csr.write(CSR_SCOUNTEREN, 0).unwrap();
+        assert!(core.is_counter_accessible(CSR_TIME).unwrap());
+        assert!(!core.is_counter_accessible(CSR_CYCLE).unwrap());
+    }
+
+    #[test]
+    fn test_pending_interrupt() {
+        let mut core = Core::new();
+        core.csr.write(CSR_MIE, GprUnsigned::MAX).unwrap();
+        core.csr
+            .write(CSR_MIP, csr::MIP_SSIP | csr::MIP_STIP)
+            .unwrap();
+        core.set_interrupt_lines(csr::MIP_MTIP | csr::MIP_MSIP);
+
+        // M-mode only takes interrupts while mstatus.MIE is set
+        assert_eq!(core.pending_interrupt(), Ok(None));
+        core.csr.write(CSR_MSTATUS, csr::MSTATUS_MIE).unwrap();
+        assert_eq!(
+            core.pending_interrupt(),
+            Ok(Some(Interrupt::MachineSoftwareInterrupt))
+        );
+        core.set_interrupt_lines(csr::MIP_MTIP | csr::MIP_MEIP);
+        assert_eq!(
+            core.pending_interrupt(),
+            Ok(Some(Interrupt::MachineExternalInterrupt))
+        );
+
+        // the machine interrupts come before the delegated ones, which M-mode
+        // never takes
+        core.csr.write(CSR_MIDELEG, csr::MIP_SSIP).unwrap();
+        core.set_interrupt_lines(0);
+        assert_eq!(
+            core.pending_interrupt(),
+            Ok(Some(Interrupt::SupervisorTimerInterrupt))
+        );
+        core.csr.write(CSR_MIDELEG, GprUnsigned::MAX).unwrap();
+        assert_eq!(core.pending_interrupt(), Ok(None));
+
+        // S-mode takes the delegated ones while mstatus.SIE is set, U-mode always
+        core.set_privilege_mode(PrivilegeMode::Supervisor);
+        assert_eq!(core.pending_interrupt(), Ok(None));
+        core.set_privilege_mode(PrivilegeMode::User);
+        assert_eq!(
+            core.pending_interrupt(),
+            Ok(Some(Interrupt::SupervisorSoftwareInterrupt))
+        );
+        core.csr.write(CSR_MIE, csr::MIP_STIP).unwrap();
+        assert_eq!(
+            core.pending_interrupt(),
+            Ok(Some(Interrupt::SupervisorTimerInterrupt))
+        );
+    }
+
+    #[test]
+    fn test_csr_privilege() {
+        let mut core = Core::new();
+        assert!(core.has_csr_privilege(CSR_MSCRATCH));
+
+        core.set_privilege_mode(PrivilegeMode::User);
+        assert!(!core.has_csr_privilege(CSR_MSCRATCH));
+        assert!(!core.has_csr_privilege(CSR_MCYCLE));
+        assert!(core.has_csr_privilege(CSR_CYCLE));
+        assert!(core.has_csr_privilege(CSR_FCSR));
+    }
+
+    #[test]
+    fn test_machine_info() {
+        let mut core = Core::new();
+        core.set_machine_info(MachineInfo {
+            mhartid: 2,
+            ..MachineInfo::FE310_G002
+        });
+        assert_eq!(core.read_csr(CSR_MHARTID), Ok(2));
+        // the values survive a reset and a change of XLEN
+        core.reset();
+        core.set_xlen(Xlen::Rv64);
+        assert_eq!(core.read_csr(CSR_MVENDORID), Ok(0x489));
+        assert_eq!(core.read_csr(CSR_MHARTID), Ok(2));
+        assert_eq!(core.get_machine_info().mhartid, 2);
+
+        assert_eq!(core.write_csr(CSR_MHARTID, 0), Err(csr::CsrError::ReadOnly));
+        core.write_csr(CSR_MCOUNTEREN, csr::COUNTER_CY).unwrap();
+        assert_eq!(core.read_csr(CSR_MCOUNTEREN), Ok(csr::COUNTER_CY));
+    }
+
+    #[test]
+    fn test_configuration_csrs() {
+        let mut core = Core::new();
+        core.set_mcounteren(u32::MAX).unwrap();
+        assert_eq!(core.get_mcounteren(), Ok(u32::MAX));
+        core.set_mcountinhibit(u32::MAX).unwrap();
+        assert_eq!(
+            core.get_mcountinhibit(),
+            Ok((csr::COUNTER_CY | csr::COUNTER_IR) as u32)
+        );
+        // on RV32 the upper halves are mstatush and menvcfgh, read-only zero
+        core.set_menvcfg(u64::MAX).unwrap();
+        assert_eq!(core.get_menvcfg(), Ok(csr::MENVCFG_FIOM));
+        core.set_mstatus(0x0000_0001_0000_1808).unwrap();
+        assert_eq!(core.get_mstatus(), Ok(0x1808));
+        assert_eq!(core.read_csr(CSR_MSTATUSH), Ok(0));
+
+        // on RV64 they are the upper halves of mstatus and menvcfg
+        core.set_xlen(Xlen::Rv64);
+        assert_eq!(core.get_mstatus(), Ok(0xa_0000_1800));
+        core.set_mstatus(0x1808).unwrap();
+        assert_eq!(core.get_mstatus(), Ok(0xa_0000_1808));
+        core.set_menvcfg(u64::MAX).unwrap();
+        assert_eq!(core.get_menvcfg(), Ok(csr::MENVCFG_FIOM));
+    }
+
+    #[test]
+    fn test_fence_i() {
+        let mut core = Core::new();
+        assert!(!core.take_fence_i());
+        core.request_fence_i();
+        assert!(core.take_fence_i());
+        assert!(!core.take_fence_i());
+    }
+
+    #[test]
+    fn test_reservation() {
+        let mut core = Core::new();
+        assert!(!core.take_reservation(0x100, 4));
+
+        core.set_reservation(0x100, 4);
+        assert!(core.take_reservation(0x100, 4));
+        assert_eq!(core.get_reservation(), None);
+
+        // SC to another address fails and still clears the reservation
+        core.set_reservation(0x100, 4);
+        assert!(!core.take_reservation(0x104, 4));
+        assert_eq!(core.get_reservation(), None);
+
+        // so does an SC of another size
+        core.set_reservation(0x100, 4);
+        assert!(!core.take_reservation(0x100, 8));
+        assert_eq!(core.get_reservation(), None);
+
+        // stores next to the reserved word keep it
+        core.set_reservation(0x100, 4);
+        core.invalidate_reservation(0xfc, 4);
+        core.invalidate_reservation(0x104, 1);
+        assert_eq!(core.get_reservation(), Some(0x100));
+
+        // a byte store into the reserved word drops it
+        core.invalidate_reservation(0x103, 1);
+        assert_eq!(core.get_reservation(), None);
+
+        // a doubleword reservation covers 8 bytes
+        core.set_reservation(0x100, 8);
+        core.invalidate_reservation(0x104, 1);
+        assert_eq!(core.get_reservation(), None);
+
+        // so does a trap
+        core.set_reservation(0x100, 4);
+        let trap = Trap::Exception(Exception::Breakpoint);
+        core.handle_trap(&trap, 0).unwrap();
+        assert_eq!(core.get_reservation(), None);
+    }
+
+    #[test]
+    fn test_fp_state() {
+        let mut core = Core::new();
+        assert_eq!(core.is_fp_enabled(), Ok(false));
+        core.write_fregister(0, 0x3f80_0000).unwrap();
+        assert_eq!(core.read_fregister(0), Ok(0x3f80_0000));
+        assert!(core.read_fregister(32).is_err());
+
+        // FS is Initial
+        core.csr.write(CSR_MSTATUS, 0x2000).unwrap();
+        assert_eq!(core.is_fp_enabled(), Ok(true));
+        // no flag raised, FS is left as is
+        core.accrue_fflags(0).unwrap();
+        assert_eq!(core.read_csr(CSR_MSTATUS), Ok(0x2000));
+
+        core.accrue_fflags(0x1).unwrap();
+        core.accrue_fflags(0x10).unwrap();
+        assert_eq!(core.read_csr(CSR_FFLAGS), Ok(0x11));
+        assert_eq!(
+            core.read_csr(CSR_MSTATUS),
+            Ok(csr::MSTATUS32_SD | csr::MSTATUS_FS_DIRTY)
+        );
+    }
+
+    #[test]
+    fn test_xlen() {
+        let mut core = Core::new();
+        assert_eq!(core.get_xlen(), Xlen::Rv32);
+        core.write_register(1, 0xffff_ffff_8000_0000).unwrap();
+        assert_eq!(core.read_register(1), Ok(0x8000_0000));
+        assert_eq!(core.read_register_signed(1), Ok(-0x8000_0000));
+        core.set_pc(0xffff_fffe);
+        core.set_instruction_length(4);
+        assert_eq!(core.get_next_pc(), 2);
+
+        core.set_xlen(Xlen::Rv64);
+        assert_eq!(core.read_register(1), Ok(0));
+        assert_eq!(core.read_csr(CSR_MISA).unwrap() >> 62, 2);
+        core.write_register(1, 0xffff_ffff_8000_0000).unwrap();
+        assert_eq!(core.read_register(1), Ok(0xffff_ffff_8000_0000));
+        assert_eq!(core.read_register_signed(1), Ok(-0x8000_0000));
+        core.set_pc(0xffff_fffe);
+        assert_eq!(core.get_next_pc(), 0x1_0000_0002);
+    }
+
     #[test]
     fn test_read_register_zero_index() {
         let core = Core::new();
@@ -9,33 +9,111 @@ use std::collections::HashMap;
 
 use tracing::info;
 
+use crate::core::MachineInfo;
 use crate::inst_csr_reg::*;
-use crate::GprUnsigned;
+use crate::pmp::{self, PMP_ENTRIES};
+use crate::{GprUnsigned, Xlen};
 
 pub type CsrAddrType = u16;
 
+pub const MSTATUS_SIE: GprUnsigned = 0x00000002;
 pub const MSTATUS_MIE: GprUnsigned = 0x00000008;
+pub const MSTATUS_SPIE: GprUnsigned = 0x00000020;
 pub const MSTATUS_MPIE: GprUnsigned = 0x00000080;
+pub const MSTATUS_SPP: GprUnsigned = 0x00000100;
 pub const MSTATUS_MPP: GprUnsigned = 0x00001800;
+pub const MSTATUS_FS: GprUnsigned = 0x00006000;
 pub const MSTATUS_MPRV: GprUnsigned = 0x00020000;
+pub const MSTATUS_SUM: GprUnsigned = 0x00040000;
+pub const MSTATUS_MXR: GprUnsigned = 0x00080000;
+pub const MSTATUS_TVM: GprUnsigned = 0x00100000;
+pub const MSTATUS_TW: GprUnsigned = 0x00200000;
+pub const MSTATUS_TSR: GprUnsigned = 0x00400000;
+pub const MSTATUS_UXL: GprUnsigned = 0x0000000300000000;
+pub const MSTATUS_SXL: GprUnsigned = 0x0000000c00000000;
+// UXL and SXL report that U-mode and S-mode run with XLEN 64 on RV64
+const MSTATUS64_XL: GprUnsigned = 0x0000000a00000000;
+pub const MSTATUS32_SD: GprUnsigned = 0x80000000;
+const MSTATUS_MPP_SHIFT: u32 = 11;
+// MPP=2 encodes the reserved privilege mode
+const MSTATUS_MPP_RESERVED: GprUnsigned = 2;
+pub const MSTATUS64_SD: GprUnsigned = 0x8000000000000000;
+
+// The mstatus fields visible through sstatus, SD and UXL are read-only
+const SSTATUS_WRITABLE: GprUnsigned =
+    MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_FS | MSTATUS_SUM | MSTATUS_MXR;
+
+// mstatus.FS states
+pub const MSTATUS_FS_OFF: GprUnsigned = 0x00000000;
+pub const MSTATUS_FS_DIRTY: GprUnsigned = 0x00006000;
+
+// fflags and frm are views of the low bits of fcsr
+pub const FCSR_FFLAGS: GprUnsigned = 0x0000001f;
+pub const FCSR_FRM: GprUnsigned = 0x000000e0;
+const FCSR_FRM_SHIFT: u32 = 5;
+
+// mcountinhibit and mcounteren bits, the hpmcounters count nothing so only
+// cycle and instret can be inhibited
+pub const COUNTER_CY: GprUnsigned = 0x00000001;
+pub const COUNTER_TM: GprUnsigned = 0x00000002;
+pub const COUNTER_IR: GprUnsigned = 0x00000004;
+const MCOUNTINHIBIT_WRITABLE: GprUnsigned = COUNTER_CY | COUNTER_IR;
+const MCOUNTEREN_WRITABLE: GprUnsigned = 0xffffffff;
+
+// mip and mie bits of the interrupts
+pub const MIP_SSIP: GprUnsigned = 0x00000002;
+pub const MIP_MSIP: GprUnsigned = 0x00000008;
+pub const MIP_STIP: GprUnsigned = 0x00000020;
+pub const MIP_MTIP: GprUnsigned = 0x00000080;
+pub const MIP_SEIP: GprUnsigned = 0x00000200;
+pub const MIP_MEIP: GprUnsigned = 0x00000800;
+// Software sets the supervisor interrupts, the devices drive the machine ones
+const MIP_WRITABLE: GprUnsigned = MIP_SSIP | MIP_STIP | MIP_SEIP;
+const MIP_DEVICE_LINES: GprUnsigned = MIP_MSIP | MIP_MTIP | MIP_MEIP;
+
+// Only the supervisor interrupts can be delegated
+const MIDELEG_WRITABLE: GprUnsigned = MIP_SSIP | MIP_STIP | MIP_SEIP;
+// Every exception but the ECALL from M-mode and the reserved causes 10 and 14
+const MEDELEG_WRITABLE: GprUnsigned = 0x0000b3ff;
+
+// misa.C, IALIGN is 16 bits while it is set
+pub const MISA_C: GprUnsigned = 0x00000004;
+// misa.S and misa.U, supervisor and user mode are implemented
+pub const MISA_S: GprUnsigned = 0x00040000;
+pub const MISA_U: GprUnsigned = 0x00100000;
+// I, M, A, F, D, C, S and U, misa.MXL is read-only
+const MISA_INITIAL_EXTENSIONS: GprUnsigned = 0x0014112d;
+
+// xtvec.MODE, the modes 2 and 3 are reserved
+const TVEC_MODE: GprUnsigned = 0x00000003;
+const TVEC_MODE_VECTORED: GprUnsigned = 1;
+
+// menvcfg.FIOM is the only implemented field, the fences of the I/O
+// accesses already order the memory accesses
+pub const MENVCFG_FIOM: GprUnsigned = 0x00000001;
+
+// pmpaddr holds the address bits 55:2 on RV64
+const PMPADDR64_WRITABLE: GprUnsigned = 0x003f_ffff_ffff_ffff;
 
 // Error type for CSR operations
 #[derive(Debug, thiserror::Error, PartialEq)]
 pub enum CsrError {
     #[error("Invalid CSR address")]
     InvalidAddress,
+    #[error("CSR is read-only")]
+    ReadOnly,
 }
 
 /// Represents a single CSR register
 struct CsrRegister {
-    value: u32,
-    writable_bits: u32, // Bit mask indicating writable bits
-    initial_value: u32,
+    value: GprUnsigned,
+    writable_bits: GprUnsigned, // Bit mask indicating writable bits
+    initial_value: GprUnsigned,
 }
 
 impl CsrRegister {
     /// Creates a new CsrRegister with the specified writable bits and initial value
-    fn new(writable_bits: u32, initial_value: u32) -> Self {
+    fn new(writable_bits: GprUnsigned, initial_value: GprUnsigned) -> Self {
         info!("Creating new CSR");
         Self {
             value: initial_value,
@@ -45,12 +123,12 @@ impl CsrRegister {
     }
 
     /// Reads the value of the CSR register
-    fn read(&self) -> u32 {
+    fn read(&self) -> GprUnsigned {
         self.value
     }
 
     /// Writes a value to the CSR register, only modifying writable bits
-    fn write(&mut self, value: u32) {
+    fn write(&mut self, value: GprUnsigned) {
         let masked_value = value & self.writable_bits;
         self.value = (self.value & !self.writable_bits) | masked_value;
     }
@@ -63,29 +141,154 @@ impl CsrRegister {
 /// Represents a RISC-V Control and Status Registers (CSR)
 pub(crate) struct Csr {
     registers: HashMap<CsrAddrType, CsrRegister>,
+    xlen: Xlen,
+    // The counters are 64 bits wide for every XLEN, RV32 accesses the upper
+    // half through the *h CSRs
+    mcycle: u64,
+    minstret: u64,
+    // Copy of the CLINT mtime, refreshed before time is read
+    time: u64,
+    // COUNTER_CY/COUNTER_IR of the counters written by the current instruction,
+    // which then do not count it
+    counters_written: GprUnsigned,
+    // The mip bits asserted by the devices, read through mip
+    interrupt_lines: GprUnsigned,
 }
 
 impl Csr {
-    /// Creates a new CSR instance with all registers initialized to their initial values
-    pub fn new() -> Self {
+    /// Creates a new CSR instance with all registers initialized to their initial values,
+    /// the registers are `xlen` bits wide
+    pub fn new(xlen: Xlen) -> Self {
         let mut registers = HashMap::new();
+        let all = xlen.truncate(GprUnsigned::MAX);
 
         // Initialize the registers with their writable bits and initial values
-        registers.insert(CSR_MSTATUS, CsrRegister::new(0xFFFFFFFF, 0x00001800));
-        registers.insert(CSR_MISA, CsrRegister::new(0xFFFFFFFF, 0x40001100));
-        registers.insert(CSR_MIE, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MTVEC, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MSCRATCH, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MEPC, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MCAUSE, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MTVAL, CsrRegister::new(0xFFFFFFFF, 0x00000000));
-        registers.insert(CSR_MIP, CsrRegister::new(0xFFFFFFFF, 0x00000000));
+        let mstatus = match xlen {
+            Xlen::Rv32 => CsrRegister::new(all, 0x00001800),
+            Xlen::Rv64 => CsrRegister::new(
+                all & !(MSTATUS_UXL | MSTATUS_SXL),
+                MSTATUS64_XL | 0x00001800,
+            ),
+        };
+        registers.insert(CSR_MSTATUS, mstatus);
+        // mstatush holds the endianness of M-mode and S-mode, always little
+        if xlen == Xlen::Rv32 {
+            registers.insert(CSR_MSTATUSH, CsrRegister::new(0, 0x00000000));
+        }
+        registers.insert(CSR_MIE, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MTVEC, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MSCRATCH, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MEPC, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MCAUSE, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MTVAL, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_MIP, CsrRegister::new(MIP_WRITABLE, 0x00000000));
+        registers.insert(CSR_MEDELEG, CsrRegister::new(MEDELEG_WRITABLE, 0x00000000));
+        registers.insert(CSR_MIDELEG, CsrRegister::new(MIDELEG_WRITABLE, 0x00000000));
+        registers.insert(CSR_STVEC, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_SSCRATCH, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_SEPC, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_SCAUSE, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_STVAL, CsrRegister::new(all, 0x00000000));
+        registers.insert(CSR_SATP, CsrRegister::new(all, 0x00000000));
+        registers.insert(
+            CSR_SCOUNTEREN,
+            CsrRegister::new(MCOUNTEREN_WRITABLE, 0x00000000),
+        );
+        registers.insert(
+            CSR_FCSR,
+            CsrRegister::new(FCSR_FFLAGS | FCSR_FRM, 0x00000000),
+        );
+        registers.insert(
+            CSR_MCOUNTINHIBIT,
+            CsrRegister::new(MCOUNTINHIBIT_WRITABLE, 0x00000000),
+        );
+        registers.insert(
+            CSR_MCOUNTEREN,
+            CsrRegister::new(MCOUNTEREN_WRITABLE, 0x00000000),
+        );
+        registers.insert(CSR_MENVCFG, CsrRegister::new(MENVCFG_FIOM, 0x00000000));
+        if xlen == Xlen::Rv32 {
+            registers.insert(CSR_MENVCFGH, CsrRegister::new(0, 0x00000000));
+        }
+        // RV64 packs eight entries in each of the even pmpcfg CSRs
+        for address in CSR_PMPCFG0..=CSR_PMPCFG3 {
+            if xlen == Xlen::Rv32 || address % 2 == 0 {
+                registers.insert(address, CsrRegister::new(all, 0x00000000));
+            }
+        }
+        let pmpaddr_writable = match xlen {
+            Xlen::Rv32 => all,
+            Xlen::Rv64 => PMPADDR64_WRITABLE,
+        };
+        for address in CSR_PMPADDR0..=CSR_PMPADDR15 {
+            registers.insert(address, CsrRegister::new(pmpaddr_writable, 0x00000000));
+        }
+
+        let mut csr = Csr {
+            registers,
+            xlen,
+            mcycle: 0,
+            minstret: 0,
+            time: 0,
+            counters_written: 0,
+            interrupt_lines: 0,
+        };
+        csr.set_misa_extensions(MISA_INITIAL_EXTENSIONS);
+        csr.set_machine_info(&MachineInfo::default());
+        csr
+    }
 
-        Csr { registers }
+    /// Sets the values of the read-only machine information CSRs
+    pub fn set_machine_info(&mut self, info: &MachineInfo) {
+        let values = [
+            (CSR_MVENDORID, info.mvendorid),
+            (CSR_MARCHID, info.marchid),
+            (CSR_MIMPID, info.mimpid),
+            (CSR_MHARTID, info.mhartid),
+            (CSR_MCONFIGPTR, info.mconfigptr),
+        ];
+        for (address, value) in values {
+            let value = self.xlen.truncate(value);
+            self.registers.insert(address, CsrRegister::new(0, value));
+        }
+    }
+
+    /// Sets misa.Extensions to `extensions`, the bits of the implemented
+    /// extensions. misa is read-only (a legal WARL), the extensions are only
+    /// configured through the ISA of the core.
+    pub fn set_misa_extensions(&mut self, extensions: GprUnsigned) {
+        let misa = (self.xlen.mxl() << (self.xlen.bits() - 2)) | extensions;
+        self.registers.insert(CSR_MISA, CsrRegister::new(0, misa));
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
     }
 
     /// Reads the value of a CSR register
-    pub fn read(&self, address: CsrAddrType) -> Result<u32, CsrError> {
+    pub fn read(&self, address: CsrAddrType) -> Result<GprUnsigned, CsrError> {
+        match address {
+            CSR_FFLAGS => return Ok(self.read(CSR_FCSR)? & FCSR_FFLAGS),
+            CSR_FRM => return Ok((self.read(CSR_FCSR)? & FCSR_FRM) >> FCSR_FRM_SHIFT),
+            CSR_SSTATUS => return Ok(self.read(CSR_MSTATUS)? & self.sstatus_mask()),
+            // sie and sip only show the delegated interrupts
+            CSR_SIE => return Ok(self.read(CSR_MIE)? & self.read(CSR_MIDELEG)?),
+            CSR_SIP => return Ok(self.read(CSR_MIP)? & self.read(CSR_MIDELEG)?),
+            CSR_MIP => return Ok(self.read_register(CSR_MIP)? | self.interrupt_lines),
+            _ => {}
+        }
+        if let Some(counter) = self.counter(address) {
+            return self.read_counter(address, counter?);
+        }
+        if (CSR_MHPMEVENT3..=CSR_MHPMEVENT31).contains(&address) {
+            // no events are implemented, the selectors are hardwired to zero
+            return Ok(0);
+        }
+
+        self.read_register(address)
+    }
+
+    fn read_register(&self, address: CsrAddrType) -> Result<GprUnsigned, CsrError> {
         if let Some(register) = self.registers.get(&address) {
             Ok(register.read())
         } else {
@@ -93,44 +296,300 @@ impl Csr {
         }
     }
 
-    /// Writes a value to a CSR register
-    pub fn write(&mut self, address: CsrAddrType, value: u32) -> Result<(), CsrError> {
+    /// Returns true if the CSR at `address` is read-only, which bits 11:10
+    /// encode as 0b11
+    pub fn is_read_only(address: CsrAddrType) -> bool {
+        (address >> 10) & 0b11 == 0b11
+    }
+
+    /// Writes a value to a CSR register, the WARL fields keep their legal
+    /// values
+    pub fn write(&mut self, address: CsrAddrType, value: GprUnsigned) -> Result<(), CsrError> {
+        if Self::is_read_only(address) {
+            return Err(CsrError::ReadOnly);
+        }
+        match address {
+            CSR_FFLAGS => {
+                let fcsr = self.read(CSR_FCSR)?;
+                return self.write(CSR_FCSR, (fcsr & !FCSR_FFLAGS) | (value & FCSR_FFLAGS));
+            }
+            CSR_FRM => {
+                let fcsr = self.read(CSR_FCSR)?;
+                let frm = (value << FCSR_FRM_SHIFT) & FCSR_FRM;
+                return self.write(CSR_FCSR, (fcsr & !FCSR_FRM) | frm);
+            }
+            CSR_SSTATUS => return self.write_view(CSR_MSTATUS, SSTATUS_WRITABLE, value),
+            CSR_SIE => {
+                let mask = self.read(CSR_MIDELEG)?;
+                return self.write_view(CSR_MIE, mask, value);
+            }
+            CSR_SIP => {
+                // only the software interrupt is set by software
+                let mask = self.read(CSR_MIDELEG)? & MIP_SSIP;
+                return self.write_view(CSR_MIP, mask, value);
+            }
+            // a write of an unimplemented mode is ignored
+            CSR_SATP if !self.is_satp_mode_supported(value) => return Ok(()),
+            CSR_PMPCFG0..=CSR_PMPCFG3 => return self.write_pmpcfg(address, value),
+            CSR_PMPADDR0..=CSR_PMPADDR15
+                if self.is_pmpaddr_locked((address - CSR_PMPADDR0) as usize) =>
+            {
+                return Ok(())
+            }
+            _ => {}
+        }
+        if let Some(counter) = self.counter(address) {
+            return self.write_counter(address, counter?, value);
+        }
+        if (CSR_MHPMEVENT3..=CSR_MHPMEVENT31).contains(&address) {
+            return Ok(());
+        }
+
+        let value = self.legalize(address, value);
         if let Some(register) = self.registers.get_mut(&address) {
             register.write(value);
+            if address == CSR_MSTATUS {
+                // SD is read-only, it summarizes the dirty state of FS
+                let sd = match self.xlen {
+                    Xlen::Rv32 => MSTATUS32_SD,
+                    Xlen::Rv64 => MSTATUS64_SD,
+                };
+                let dirty = register.value & MSTATUS_FS == MSTATUS_FS_DIRTY;
+                register.value = (register.value & !sd) | if dirty { sd } else { 0 };
+            }
             Ok(())
         } else {
             Err(CsrError::InvalidAddress)
         }
     }
 
+    /// Replaces the illegal values of the WARL fields written to the CSR at
+    /// `address` by their current values
+    fn legalize(&self, address: CsrAddrType, value: GprUnsigned) -> GprUnsigned {
+        let keep = |mask: GprUnsigned| {
+            let old = self.read_register(address).unwrap_or(0);
+            (value & !mask) | (old & mask)
+        };
+        match address {
+            CSR_MTVEC | CSR_STVEC if value & TVEC_MODE > TVEC_MODE_VECTORED => keep(TVEC_MODE),
+            CSR_MSTATUS if (value & MSTATUS_MPP) >> MSTATUS_MPP_SHIFT == MSTATUS_MPP_RESERVED => {
+                keep(MSTATUS_MPP)
+            }
+            // the low bits below IALIGN, 16 bits with C and 32 without, are zero
+            CSR_MEPC | CSR_SEPC => {
+                let c = self.read(CSR_MISA).unwrap_or(0) & MISA_C != 0;
+                value & if c { !0x1 } else { !0x3 }
+            }
+            _ => value,
+        }
+    }
+
+    /// The mstatus bits read through sstatus
+    fn sstatus_mask(&self) -> GprUnsigned {
+        match self.xlen {
+            Xlen::Rv32 => SSTATUS_WRITABLE | MSTATUS32_SD,
+            Xlen::Rv64 => SSTATUS_WRITABLE | MSTATUS_UXL | MSTATUS64_SD,
+        }
+    }
+
+    /// Writes the `mask` bits of the register viewed through another CSR
+    fn write_view(
+        &mut self,
+        address: CsrAddrType,
+        mask: GprUnsigned,
+        value: GprUnsigned,
+    ) -> Result<(), CsrError> {
+        let old = self.read(address)?;
+        self.write(address, (old & !mask) | (value & mask))
+    }
+
+    /// Returns true if satp.MODE of `value` is Bare, or Sv32 on RV32
+    fn is_satp_mode_supported(&self, value: GprUnsigned) -> bool {
+        match self.xlen {
+            Xlen::Rv32 => true,
+            Xlen::Rv64 => (value >> 60) & 0xf == 0,
+        }
+    }
+
+    /// Returns the configuration and pmpaddr of the PMP entry `index`
+    pub fn pmp_entry(&self, index: usize) -> (u8, GprUnsigned) {
+        // on RV64 the even pmpcfg CSRs hold eight entries each
+        let (cfg_address, shift) = match self.xlen {
+            Xlen::Rv32 => (CSR_PMPCFG0 + (index / 4) as CsrAddrType, index % 4 * 8),
+            Xlen::Rv64 => (CSR_PMPCFG0 + (index / 8 * 2) as CsrAddrType, index % 8 * 8),
+        };
+        let cfg = self.read(cfg_address).unwrap_or(0) >> shift;
+        let pmpaddr = self.read(CSR_PMPADDR0 + index as CsrAddrType).unwrap_or(0);
+        (cfg as u8, pmpaddr)
+    }
+
+    /// Writes the configurations of a pmpcfg CSR, except the locked ones
+    fn write_pmpcfg(&mut self, address: CsrAddrType, value: GprUnsigned) -> Result<(), CsrError> {
+        let old = self.read(address)?;
+        let mut new = 0;
+        for shift in (0..self.xlen.bits()).step_by(8) {
+            let old_cfg = (old >> shift) as u8;
+            let mut cfg = (value >> shift) as u8 & pmp::PMP_CFG_WRITABLE;
+            if old_cfg & pmp::PMP_L != 0 {
+                cfg = old_cfg;
+            } else if cfg & pmp::PMP_R == 0 {
+                // R=0 and W=1 is reserved
+                cfg &= !pmp::PMP_W;
+            }
+            new |= (cfg as GprUnsigned) << shift;
+        }
+        if let Some(register) = self.registers.get_mut(&address) {
+            register.write(new);
+        }
+        Ok(())
+    }
+
+    /// Returns true if pmpaddr of the entry `index` is locked, by the entry
+    /// itself or by the next one if that is a locked TOR entry
+    fn is_pmpaddr_locked(&self, index: usize) -> bool {
+        let locked = |index| self.pmp_entry(index).0 & pmp::PMP_L != 0;
+        let is_tor = |index| self.pmp_entry(index).0 & pmp::PMP_A == pmp::PMP_A_TOR;
+        locked(index) || (index + 1 < PMP_ENTRIES && locked(index + 1) && is_tor(index + 1))
+    }
+
     pub fn reset(&mut self) {
         for register in self.registers.values_mut() {
             register.reset();
         }
+        self.mcycle = 0;
+        self.minstret = 0;
+        self.time = 0;
+        self.counters_written = 0;
+        self.interrupt_lines = 0;
+    }
+
+    /// Counts one cycle and, if `retired`, one instruction, unless inhibited by
+    /// mcountinhibit or written by the instruction itself
+    pub fn advance_counters(&mut self, retired: bool) {
+        let inhibit = self.read(CSR_MCOUNTINHIBIT).unwrap_or(0) | self.counters_written;
+        if inhibit & COUNTER_CY == 0 {
+            self.mcycle = self.mcycle.wrapping_add(1);
+        }
+        if retired && inhibit & COUNTER_IR == 0 {
+            self.minstret = self.minstret.wrapping_add(1);
+        }
+        self.counters_written = 0;
+    }
+
+    /// Counts `cycles` cycles in which no instruction retires, unless
+    /// inhibited by mcountinhibit
+    pub fn skip_cycles(&mut self, cycles: u64) {
+        if self.read(CSR_MCOUNTINHIBIT).unwrap_or(0) & COUNTER_CY == 0 {
+            self.mcycle = self.mcycle.wrapping_add(cycles);
+        }
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    /// Sets the MSIP, MTIP and MEIP bits of mip asserted by the devices
+    pub fn set_interrupt_lines(&mut self, lines: GprUnsigned) {
+        self.interrupt_lines = lines & MIP_DEVICE_LINES;
+    }
+
+    /// Returns the COUNTER_* bit of a counter CSR, which is also its index in
+    /// mcounteren and mcountinhibit. The *h CSRs only exist on RV32.
+    fn counter(&self, address: CsrAddrType) -> Option<Result<GprUnsigned, CsrError>> {
+        // there is no machine-mode time CSR next to mcycle, only the CLINT mtime
+        let index = match address {
+            CSR_CYCLE..=CSR_HPMCOUNTER31 => address - CSR_CYCLE,
+            CSR_MCYCLE..=CSR_MHPMCOUNTER31 if address != CSR_MCYCLE + 1 => address - CSR_MCYCLE,
+            CSR_CYCLEH..=CSR_HPMCOUNTER31H => address - CSR_CYCLEH,
+            CSR_MCYCLEH..=CSR_MHPMCOUNTER31H if address != CSR_MCYCLEH + 1 => address - CSR_MCYCLEH,
+            _ => return None,
+        };
+        if Self::is_high_half(address) && self.xlen != Xlen::Rv32 {
+            return Some(Err(CsrError::InvalidAddress));
+        }
+        Some(Ok(1 << index))
+    }
+
+    fn is_high_half(address: CsrAddrType) -> bool {
+        matches!(address, CSR_CYCLEH..=CSR_HPMCOUNTER31H | CSR_MCYCLEH..=CSR_MHPMCOUNTER31H)
+    }
+
+    fn read_counter(
+        &self,
+        address: CsrAddrType,
+        counter: GprUnsigned,
+    ) -> Result<GprUnsigned, CsrError> {
+        // the hpmcounters count no event and are hardwired to zero
+        let value = match counter {
+            COUNTER_CY => self.mcycle,
+            COUNTER_TM => self.time,
+            COUNTER_IR => self.minstret,
+            _ => 0,
+        };
+        if Self::is_high_half(address) {
+            Ok(value >> 32)
+        } else {
+            Ok(self.xlen.truncate(value))
+        }
+    }
+
+    fn write_counter(
+        &mut self,
+        address: CsrAddrType,
+        counter: GprUnsigned,
+        value: GprUnsigned,
+    ) -> Result<(), CsrError> {
+        let high = Self::is_high_half(address);
+        let xlen = self.xlen;
+        let update = |old: u64| match (high, xlen) {
+            (true, _) => (old & 0xffff_ffff) | (value << 32),
+            (false, Xlen::Rv32) => (old & !0xffff_ffff) | (value & 0xffff_ffff),
+            (false, Xlen::Rv64) => value,
+        };
+        match counter {
+            COUNTER_CY => self.mcycle = update(self.mcycle),
+            COUNTER_IR => self.minstret = update(self.minstret),
+            _ => {}
+        }
+        self.counters_written |= counter;
+        Ok(())
     }
 }
 
 impl Csr {
     /// Reads the value of a CSR and writes a new value
-    pub fn csrrw(&mut self, address: CsrAddrType, rs1_val: u32) -> Result<u32, CsrError> {
+    pub fn csrrw(
+        &mut self,
+        address: CsrAddrType,
+        rs1_val: GprUnsigned,
+    ) -> Result<GprUnsigned, CsrError> {
         let old_val = self.read(address)?;
         self.write(address, rs1_val)?;
         Ok(old_val)
     }
 
-    /// Reads the value of a CSR and sets the bits specified by the mask
-    pub fn csrrs(&mut self, address: CsrAddrType, rs1_val: u32) -> Result<u32, CsrError> {
+    /// Reads the value of a CSR and sets the bits specified by the mask.
+    /// The CSR is written even if the mask is zero, the instructions with
+    /// rs1=x0 or uimm=0 only read it.
+    pub fn csrrs(
+        &mut self,
+        address: CsrAddrType,
+        rs1_val: GprUnsigned,
+    ) -> Result<GprUnsigned, CsrError> {
         let old_val = self.read(address)?;
-        let new_val = old_val | rs1_val;
-        self.write(address, new_val)?;
+        self.write(address, old_val | rs1_val)?;
         Ok(old_val)
     }
 
-    /// Reads the value of a CSR and clears the bits specified by the mask
-    pub fn csrrc(&mut self, address: CsrAddrType, rs1_val: u32) -> Result<u32, CsrError> {
+    /// Reads the value of a CSR and clears the bits specified by the mask,
+    /// see `csrrs`
+    pub fn csrrc(
+        &mut self,
+        address: CsrAddrType,
+        rs1_val: GprUnsigned,
+    ) -> Result<GprUnsigned, CsrError> {
         let old_val = self.read(address)?;
-        let new_val = old_val & !rs1_val;
-        self.write(address, new_val)?;
+        self.write(address, old_val & !rs1_val)?;
         Ok(old_val)
     }
 }
@@ -141,7 +600,7 @@ mod tests {
 
     #[test]
     fn test_csr_read_write() {
-        let mut csr = Csr::new();
+        let mut csr = Csr::new(Xlen::Rv32);
         assert_eq!(csr.read(CSR_MSTATUS), Ok(0x00001800));
         csr.write(CSR_MSTATUS, 42).unwrap();
         assert_eq!(csr.read(CSR_MSTATUS), Ok(42)); // Only writable bits are modified
@@ -151,7 +610,7 @@ mod tests {
 
     #[test]
     fn test_csr_instructions() {
-        let mut csr = Csr::new();
+        let mut csr = Csr::new(Xlen::Rv32);
         csr.write(CSR_MSTATUS, 42).unwrap();
         assert_eq!(csr.csrrw(CSR_MSTATUS, 10).unwrap(), 42);
         assert_eq!(csr.read(CSR_MSTATUS).unwrap(), 10);
@@ -160,4 +619,229 @@ mod tests {
         assert_eq!(csr.csrrc(CSR_MSTATUS, 3).unwrap(), (10 | 5));
         assert_eq!(csr.read(CSR_MSTATUS).unwrap(), (10 | 5) & (!3));
     }
+
+    #[test]
+    fn test_fcsr_views() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_FCSR, 0xffff_ffff).unwrap();
+        assert_eq!(csr.read(CSR_FCSR), Ok(0xff));
+        assert_eq!(csr.read(CSR_FFLAGS), Ok(0x1f));
+        assert_eq!(csr.read(CSR_FRM), Ok(0x7));
+
+        csr.write(CSR_FFLAGS, 0x21).unwrap();
+        assert_eq!(csr.read(CSR_FCSR), Ok(0xe1));
+        csr.write(CSR_FRM, 0x9).unwrap();
+        assert_eq!(csr.read(CSR_FCSR), Ok(0x21));
+        assert_eq!(csr.csrrs(CSR_FFLAGS, 0x2).unwrap(), 0x1);
+        assert_eq!(csr.read(CSR_FCSR), Ok(0x23));
+    }
+
+    #[test]
+    fn test_mstatus_sd_follows_fs() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_MSTATUS, MSTATUS_FS_DIRTY).unwrap();
+        assert_eq!(csr.read(CSR_MSTATUS), Ok(MSTATUS32_SD | MSTATUS_FS_DIRTY));
+        // FS is Clean
+        csr.write(CSR_MSTATUS, MSTATUS32_SD | 0x4000).unwrap();
+        assert_eq!(csr.read(CSR_MSTATUS), Ok(0x4000));
+
+        let mut csr = Csr::new(Xlen::Rv64);
+        csr.write(CSR_MSTATUS, MSTATUS_FS_DIRTY).unwrap();
+        assert_eq!(
+            csr.read(CSR_MSTATUS),
+            Ok(MSTATUS64_SD | MSTATUS64_XL | MSTATUS_FS_DIRTY)
+        );
+    }
+
+    #[test]
+    fn test_supervisor_views() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        // sstatus only reaches the supervisor fields of mstatus
+        csr.write(CSR_SSTATUS, GprUnsigned::MAX).unwrap();
+        assert_eq!(
+            csr.read(CSR_MSTATUS),
+            Ok(MSTATUS32_SD | SSTATUS_WRITABLE | MSTATUS_MPP)
+        );
+        csr.write(CSR_MSTATUS, MSTATUS_MIE | MSTATUS_SPP).unwrap();
+        assert_eq!(csr.read(CSR_SSTATUS), Ok(MSTATUS_SPP));
+
+        // sie and sip only show the delegated interrupts
+        csr.write(CSR_MIE, 0xaaa).unwrap();
+        csr.set_interrupt_lines(MIP_MTIP);
+        assert_eq!(csr.read(CSR_SIE), Ok(0));
+        csr.write(CSR_MIDELEG, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MIDELEG), Ok(MIP_SSIP | MIP_STIP | MIP_SEIP));
+        assert_eq!(csr.read(CSR_SIE), Ok(0x222));
+        csr.write(CSR_SIE, 0).unwrap();
+        assert_eq!(csr.read(CSR_MIE), Ok(0x888));
+        // of sip only SSIP is writable
+        csr.write(CSR_SIP, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_SIP), Ok(MIP_SSIP));
+        assert_eq!(csr.read(CSR_MIP), Ok(0x082));
+
+        let mut csr = Csr::new(Xlen::Rv64);
+        assert_eq!(csr.read(CSR_SSTATUS), Ok(0x2_0000_0000));
+        csr.write(CSR_MSTATUS, 0).unwrap();
+        assert_eq!(csr.read(CSR_MSTATUS), Ok(MSTATUS64_XL));
+    }
+
+    #[test]
+    fn test_mip_lines() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        // the machine interrupts are only asserted by the devices
+        csr.write(CSR_MIP, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MIP), Ok(MIP_SSIP | MIP_STIP | MIP_SEIP));
+        csr.write(CSR_MIP, 0).unwrap();
+        csr.set_interrupt_lines(GprUnsigned::MAX);
+        assert_eq!(csr.read(CSR_MIP), Ok(MIP_MSIP | MIP_MTIP | MIP_MEIP));
+        csr.set_interrupt_lines(MIP_MEIP);
+        assert_eq!(csr.read(CSR_MIP), Ok(MIP_MEIP));
+    }
+
+    #[test]
+    fn test_satp_modes() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_SATP, 0x8000_0001).unwrap();
+        assert_eq!(csr.read(CSR_SATP), Ok(0x8000_0001));
+
+        // Sv39 is not implemented, the write is ignored
+        let mut csr = Csr::new(Xlen::Rv64);
+        csr.write(CSR_SATP, 0x1234).unwrap();
+        csr.write(CSR_SATP, 0x8000_0000_0000_0001).unwrap();
+        assert_eq!(csr.read(CSR_SATP), Ok(0x1234));
+    }
+
+    #[test]
+    fn test_xlen() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        assert_eq!(csr.read(CSR_MISA), Ok(0x4014112d));
+        csr.write(CSR_MSCRATCH, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MSCRATCH), Ok(0xffff_ffff));
+
+        let mut csr = Csr::new(Xlen::Rv64);
+        assert_eq!(csr.read(CSR_MISA), Ok(0x8000_0000_0014_112d));
+        csr.write(CSR_MSCRATCH, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MSCRATCH), Ok(GprUnsigned::MAX));
+        // misa is read-only, neither MXL nor the extensions can be changed
+        csr.write(CSR_MISA, 0).unwrap();
+        assert_eq!(csr.read(CSR_MISA), Ok(0x8000_0000_0014_112d));
+        csr.csrrc(CSR_MISA, MISA_C).unwrap();
+        assert_eq!(csr.read(CSR_MISA), Ok(0x8000_0000_0014_112d));
+    }
+
+    #[test]
+    fn test_counters_rv32() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_MCYCLE, 0xffff_ffff).unwrap();
+        csr.advance_counters(true);
+        // the write is seen by the next instruction, which counts from there
+        assert_eq!(csr.read(CSR_MCYCLE), Ok(0xffff_ffff));
+        csr.advance_counters(true);
+        assert_eq!(csr.read(CSR_CYCLE), Ok(0));
+        assert_eq!(csr.read(CSR_CYCLEH), Ok(1));
+        assert_eq!(csr.read(CSR_MCYCLEH), Ok(1));
+        assert_eq!(csr.read(CSR_INSTRET), Ok(2));
+
+        csr.write(CSR_MINSTRETH, 0x12).unwrap();
+        assert_eq!(csr.read(CSR_INSTRETH), Ok(0x12));
+        assert_eq!(csr.read(CSR_MINSTRET), Ok(2));
+        csr.advance_counters(true);
+        assert_eq!(csr.read(CSR_MINSTRET), Ok(2));
+
+        // an exception does not retire the instruction
+        csr.advance_counters(false);
+        assert_eq!(csr.read(CSR_MINSTRET), Ok(2));
+        assert_eq!(csr.read(CSR_MCYCLE), Ok(2));
+
+        csr.set_time(0x3_0000_0004);
+        assert_eq!(csr.read(CSR_TIME), Ok(4));
+        assert_eq!(csr.read(CSR_TIMEH), Ok(3));
+
+        assert_eq!(csr.read(CSR_HPMCOUNTER3), Ok(0));
+        assert_eq!(csr.read(CSR_MHPMCOUNTER31H), Ok(0));
+        assert_eq!(csr.read(CSR_MHPMEVENT3), Ok(0));
+        assert_eq!(csr.write(CSR_CYCLE, 0), Err(CsrError::ReadOnly));
+        assert_eq!(csr.read(CSR_MCYCLE + 1), Err(CsrError::InvalidAddress));
+    }
+
+    #[test]
+    fn test_counters_rv64() {
+        let mut csr = Csr::new(Xlen::Rv64);
+        csr.write(CSR_MINSTRET, 0x1_0000_0000).unwrap();
+        csr.advance_counters(true);
+        csr.advance_counters(true);
+        assert_eq!(csr.read(CSR_INSTRET), Ok(0x1_0000_0001));
+        assert_eq!(csr.read(CSR_CYCLEH), Err(CsrError::InvalidAddress));
+        assert_eq!(csr.read(CSR_MCYCLEH), Err(CsrError::InvalidAddress));
+    }
+
+    #[test]
+    fn test_mcountinhibit() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        // TM can not be inhibited
+        csr.write(CSR_MCOUNTINHIBIT, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MCOUNTINHIBIT), Ok(COUNTER_CY | COUNTER_IR));
+        csr.advance_counters(true);
+        assert_eq!(csr.read(CSR_MCYCLE), Ok(0));
+        assert_eq!(csr.read(CSR_MINSTRET), Ok(0));
+
+        csr.write(CSR_MCOUNTINHIBIT, COUNTER_IR).unwrap();
+        csr.advance_counters(true);
+        assert_eq!(csr.read(CSR_MCYCLE), Ok(1));
+        assert_eq!(csr.read(CSR_MINSTRET), Ok(0));
+    }
+
+    #[test]
+    fn test_read_only_csrs() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        assert_eq!(csr.read(CSR_MVENDORID), Ok(0));
+        csr.set_machine_info(&MachineInfo::FE310_G002);
+        assert_eq!(csr.read(CSR_MVENDORID), Ok(0x489));
+        assert_eq!(csr.read(CSR_MARCHID), Ok(0x8000_0007));
+        assert_eq!(csr.write(CSR_MHARTID, 1), Err(CsrError::ReadOnly));
+        assert_eq!(csr.csrrs(CSR_MCONFIGPTR, 0), Err(CsrError::ReadOnly));
+        csr.reset();
+        assert_eq!(csr.read(CSR_MIMPID), Ok(0x2018_1004));
+    }
+
+    #[test]
+    fn test_warl_fields() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_MTVEC, 0x1001).unwrap();
+        assert_eq!(csr.read(CSR_MTVEC), Ok(0x1001));
+        // the reserved modes keep the current one
+        csr.write(CSR_MTVEC, 0x2002).unwrap();
+        assert_eq!(csr.read(CSR_MTVEC), Ok(0x2001));
+        csr.write(CSR_STVEC, 0x3003).unwrap();
+        assert_eq!(csr.read(CSR_STVEC), Ok(0x3000));
+
+        csr.write(CSR_MSTATUS, 0x0800).unwrap();
+        csr.write(CSR_MSTATUS, MSTATUS_MPP_RESERVED << MSTATUS_MPP_SHIFT)
+            .unwrap();
+        assert_eq!(csr.read(CSR_MSTATUS), Ok(0x0800));
+
+        csr.write(CSR_MEPC, 0x1003).unwrap();
+        assert_eq!(csr.read(CSR_MEPC), Ok(0x1002));
+        // without C the instructions are 4-byte aligned
+        csr.set_misa_extensions(MISA_INITIAL_EXTENSIONS & !MISA_C);
+        csr.write(CSR_SEPC, 0x1003).unwrap();
+        assert_eq!(csr.read(CSR_SEPC), Ok(0x1000));
+    }
+
+    #[test]
+    fn test_configuration_csrs() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_MENVCFG, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MENVCFG), Ok(MENVCFG_FIOM));
+        csr.write(CSR_MENVCFGH, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MENVCFGH), Ok(0));
+        // little-endian only
+        csr.write(CSR_MSTATUSH, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_MSTATUSH), Ok(0));
+
+        // the upper halves only exist on RV32
+        let csr = Csr::new(Xlen::Rv64);
+        assert_eq!(csr.read(CSR_MSTATUSH), Err(CsrError::InvalidAddress));
+        assert_eq!(csr.read(CSR_MENVCFGH), Err(CsrError::InvalidAddress));
+    }
 }
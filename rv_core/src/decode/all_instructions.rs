@@ -4,11 +4,19 @@
 // LICENSE file in the root directory of this source tree.
 
 use crate::decode::{
-    inst_rv32_i::*, inst_rv_i::*, inst_rv_system::*, inst_rv_zicsr::*, InstructionsEntry,
+    inst_rv32_i::*, inst_rv32_zbb, inst_rv32_zbs, inst_rv64_a, inst_rv64_d, inst_rv64_f,
+    inst_rv64_i, inst_rv64_m, inst_rv64_zba, inst_rv64_zbb, inst_rv64_zbs, inst_rv_a::*,
+    inst_rv_d::*, inst_rv_f::*, inst_rv_i::*, inst_rv_m::*, inst_rv_system::*, inst_rv_zba,
+    inst_rv_zbb, inst_rv_zbc, inst_rv_zbs, inst_rv_zicsr::*, inst_rv_zifencei::*,
+    InstructionsEntry,
+};
+use crate::execute::{
+    rv32_i, rv64_a, rv64_d, rv64_f, rv64_i, rv64_m, rv64_zba, rv64_zbb, rv_a, rv_d, rv_f, rv_i,
+    rv_m, rv_system, rv_zba, rv_zbb, rv_zbc, rv_zbs, rv_zicsr, rv_zifencei,
 };
-use crate::execute::{rv32_i, rv_i, rv_system, rv_zicsr};
 
-pub(crate) const ALL_INSTRUCTIONS: [InstructionsEntry; 48] = [
+/// I instructions decoded for every XLEN, with the privileged ones
+pub(crate) const I_INSTRUCTIONS: [InstructionsEntry; 41] = [
     InstructionsEntry {
         name: "ADD",
         mask: MASK_ADD,
@@ -231,6 +239,18 @@ pub(crate) const ALL_INSTRUCTIONS: [InstructionsEntry; 48] = [
         match_val: MATCH_XORI,
         execute: rv_i::execute_xori,
     },
+    InstructionsEntry {
+        name: "SFENCE_VMA",
+        mask: MASK_SFENCE_VMA,
+        match_val: MATCH_SFENCE_VMA,
+        execute: rv_system::execute_sfence_vma,
+    },
+    InstructionsEntry {
+        name: "SRET",
+        mask: MASK_SRET,
+        match_val: MATCH_SRET,
+        execute: rv_system::execute_sret,
+    },
     InstructionsEntry {
         name: "MRET",
         mask: MASK_MRET,
@@ -243,6 +263,740 @@ pub(crate) const ALL_INSTRUCTIONS: [InstructionsEntry; 48] = [
         match_val: MATCH_WFI,
         execute: rv_system::execute_wfi,
     },
+];
+
+/// I instructions only decoded on RV32, the RV64 shifts take a 6-bit shamt
+pub(crate) const RV32_I_INSTRUCTIONS: [InstructionsEntry; 3] = [
+    InstructionsEntry {
+        name: "SLLI",
+        mask: MASK_SLLI,
+        match_val: MATCH_SLLI,
+        execute: rv32_i::execute_slli,
+    },
+    InstructionsEntry {
+        name: "SRAI",
+        mask: MASK_SRAI,
+        match_val: MATCH_SRAI,
+        execute: rv32_i::execute_srai,
+    },
+    InstructionsEntry {
+        name: "SRLI",
+        mask: MASK_SRLI,
+        match_val: MATCH_SRLI,
+        execute: rv32_i::execute_srli,
+    },
+];
+
+/// I instructions only decoded on RV64
+pub(crate) const RV64_I_INSTRUCTIONS: [InstructionsEntry; 15] = [
+    InstructionsEntry {
+        name: "ADDIW",
+        mask: inst_rv64_i::MASK_ADDIW,
+        match_val: inst_rv64_i::MATCH_ADDIW,
+        execute: rv64_i::execute_addiw,
+    },
+    InstructionsEntry {
+        name: "ADDW",
+        mask: inst_rv64_i::MASK_ADDW,
+        match_val: inst_rv64_i::MATCH_ADDW,
+        execute: rv64_i::execute_addw,
+    },
+    InstructionsEntry {
+        name: "LD",
+        mask: inst_rv64_i::MASK_LD,
+        match_val: inst_rv64_i::MATCH_LD,
+        execute: rv64_i::execute_ld,
+    },
+    InstructionsEntry {
+        name: "LWU",
+        mask: inst_rv64_i::MASK_LWU,
+        match_val: inst_rv64_i::MATCH_LWU,
+        execute: rv64_i::execute_lwu,
+    },
+    InstructionsEntry {
+        name: "SD",
+        mask: inst_rv64_i::MASK_SD,
+        match_val: inst_rv64_i::MATCH_SD,
+        execute: rv64_i::execute_sd,
+    },
+    InstructionsEntry {
+        name: "SLLI",
+        mask: inst_rv64_i::MASK_SLLI,
+        match_val: inst_rv64_i::MATCH_SLLI,
+        execute: rv64_i::execute_slli,
+    },
+    InstructionsEntry {
+        name: "SLLIW",
+        mask: inst_rv64_i::MASK_SLLIW,
+        match_val: inst_rv64_i::MATCH_SLLIW,
+        execute: rv64_i::execute_slliw,
+    },
+    InstructionsEntry {
+        name: "SLLW",
+        mask: inst_rv64_i::MASK_SLLW,
+        match_val: inst_rv64_i::MATCH_SLLW,
+        execute: rv64_i::execute_sllw,
+    },
+    InstructionsEntry {
+        name: "SRAI",
+        mask: inst_rv64_i::MASK_SRAI,
+        match_val: inst_rv64_i::MATCH_SRAI,
+        execute: rv64_i::execute_srai,
+    },
+    InstructionsEntry {
+        name: "SRAIW",
+        mask: inst_rv64_i::MASK_SRAIW,
+        match_val: inst_rv64_i::MATCH_SRAIW,
+        execute: rv64_i::execute_sraiw,
+    },
+    InstructionsEntry {
+        name: "SRAW",
+        mask: inst_rv64_i::MASK_SRAW,
+        match_val: inst_rv64_i::MATCH_SRAW,
+        execute: rv64_i::execute_sraw,
+    },
+    InstructionsEntry {
+        name: "SRLI",
+        mask: inst_rv64_i::MASK_SRLI,
+        match_val: inst_rv64_i::MATCH_SRLI,
+        execute: rv64_i::execute_srli,
+    },
+    InstructionsEntry {
+        name: "SRLIW",
+        mask: inst_rv64_i::MASK_SRLIW,
+        match_val: inst_rv64_i::MATCH_SRLIW,
+        execute: rv64_i::execute_srliw,
+    },
+    InstructionsEntry {
+        name: "SRLW",
+        mask: inst_rv64_i::MASK_SRLW,
+        match_val: inst_rv64_i::MATCH_SRLW,
+        execute: rv64_i::execute_srlw,
+    },
+    InstructionsEntry {
+        name: "SUBW",
+        mask: inst_rv64_i::MASK_SUBW,
+        match_val: inst_rv64_i::MATCH_SUBW,
+        execute: rv64_i::execute_subw,
+    },
+];
+
+/// M instructions decoded for every XLEN
+pub(crate) const M_INSTRUCTIONS: [InstructionsEntry; 8] = [
+    InstructionsEntry {
+        name: "DIV",
+        mask: MASK_DIV,
+        match_val: MATCH_DIV,
+        execute: rv_m::execute_div,
+    },
+    InstructionsEntry {
+        name: "DIVU",
+        mask: MASK_DIVU,
+        match_val: MATCH_DIVU,
+        execute: rv_m::execute_divu,
+    },
+    InstructionsEntry {
+        name: "MUL",
+        mask: MASK_MUL,
+        match_val: MATCH_MUL,
+        execute: rv_m::execute_mul,
+    },
+    InstructionsEntry {
+        name: "MULH",
+        mask: MASK_MULH,
+        match_val: MATCH_MULH,
+        execute: rv_m::execute_mulh,
+    },
+    InstructionsEntry {
+        name: "MULHSU",
+        mask: MASK_MULHSU,
+        match_val: MATCH_MULHSU,
+        execute: rv_m::execute_mulhsu,
+    },
+    InstructionsEntry {
+        name: "MULHU",
+        mask: MASK_MULHU,
+        match_val: MATCH_MULHU,
+        execute: rv_m::execute_mulhu,
+    },
+    InstructionsEntry {
+        name: "REM",
+        mask: MASK_REM,
+        match_val: MATCH_REM,
+        execute: rv_m::execute_rem,
+    },
+    InstructionsEntry {
+        name: "REMU",
+        mask: MASK_REMU,
+        match_val: MATCH_REMU,
+        execute: rv_m::execute_remu,
+    },
+];
+
+/// M instructions only decoded on RV64
+pub(crate) const RV64_M_INSTRUCTIONS: [InstructionsEntry; 5] = [
+    InstructionsEntry {
+        name: "DIVUW",
+        mask: inst_rv64_m::MASK_DIVUW,
+        match_val: inst_rv64_m::MATCH_DIVUW,
+        execute: rv64_m::execute_divuw,
+    },
+    InstructionsEntry {
+        name: "DIVW",
+        mask: inst_rv64_m::MASK_DIVW,
+        match_val: inst_rv64_m::MATCH_DIVW,
+        execute: rv64_m::execute_divw,
+    },
+    InstructionsEntry {
+        name: "MULW",
+        mask: inst_rv64_m::MASK_MULW,
+        match_val: inst_rv64_m::MATCH_MULW,
+        execute: rv64_m::execute_mulw,
+    },
+    InstructionsEntry {
+        name: "REMUW",
+        mask: inst_rv64_m::MASK_REMUW,
+        match_val: inst_rv64_m::MATCH_REMUW,
+        execute: rv64_m::execute_remuw,
+    },
+    InstructionsEntry {
+        name: "REMW",
+        mask: inst_rv64_m::MASK_REMW,
+        match_val: inst_rv64_m::MATCH_REMW,
+        execute: rv64_m::execute_remw,
+    },
+];
+
+/// A instructions decoded for every XLEN
+pub(crate) const A_INSTRUCTIONS: [InstructionsEntry; 11] = [
+    InstructionsEntry {
+        name: "AMOADD_W",
+        mask: MASK_AMOADD_W,
+        match_val: MATCH_AMOADD_W,
+        execute: rv_a::execute_amoadd_w,
+    },
+    InstructionsEntry {
+        name: "AMOAND_W",
+        mask: MASK_AMOAND_W,
+        match_val: MATCH_AMOAND_W,
+        execute: rv_a::execute_amoand_w,
+    },
+    InstructionsEntry {
+        name: "AMOMAXU_W",
+        mask: MASK_AMOMAXU_W,
+        match_val: MATCH_AMOMAXU_W,
+        execute: rv_a::execute_amomaxu_w,
+    },
+    InstructionsEntry {
+        name: "AMOMAX_W",
+        mask: MASK_AMOMAX_W,
+        match_val: MATCH_AMOMAX_W,
+        execute: rv_a::execute_amomax_w,
+    },
+    InstructionsEntry {
+        name: "AMOMINU_W",
+        mask: MASK_AMOMINU_W,
+        match_val: MATCH_AMOMINU_W,
+        execute: rv_a::execute_amominu_w,
+    },
+    InstructionsEntry {
+        name: "AMOMIN_W",
+        mask: MASK_AMOMIN_W,
+        match_val: MATCH_AMOMIN_W,
+        execute: rv_a::execute_amomin_w,
+    },
+    InstructionsEntry {
+        name: "AMOOR_W",
+        mask: MASK_AMOOR_W,
+        match_val: MATCH_AMOOR_W,
+        execute: rv_a::execute_amoor_w,
+    },
+    InstructionsEntry {
+        name: "AMOSWAP_W",
+        mask: MASK_AMOSWAP_W,
+        match_val: MATCH_AMOSWAP_W,
+        execute: rv_a::execute_amoswap_w,
+    },
+    InstructionsEntry {
+        name: "AMOXOR_W",
+        mask: MASK_AMOXOR_W,
+        match_val: MATCH_AMOXOR_W,
+        execute: rv_a::execute_amoxor_w,
+    },
+    InstructionsEntry {
+        name: "LR_W",
+        mask: MASK_LR_W,
+        match_val: MATCH_LR_W,
+        execute: rv_a::execute_lr_w,
+    },
+    InstructionsEntry {
+        name: "SC_W",
+        mask: MASK_SC_W,
+        match_val: MATCH_SC_W,
+        execute: rv_a::execute_sc_w,
+    },
+];
+
+/// A instructions only decoded on RV64
+pub(crate) const RV64_A_INSTRUCTIONS: [InstructionsEntry; 11] = [
+    InstructionsEntry {
+        name: "AMOADD_D",
+        mask: inst_rv64_a::MASK_AMOADD_D,
+        match_val: inst_rv64_a::MATCH_AMOADD_D,
+        execute: rv64_a::execute_amoadd_d,
+    },
+    InstructionsEntry {
+        name: "AMOAND_D",
+        mask: inst_rv64_a::MASK_AMOAND_D,
+        match_val: inst_rv64_a::MATCH_AMOAND_D,
+        execute: rv64_a::execute_amoand_d,
+    },
+    InstructionsEntry {
+        name: "AMOMAXU_D",
+        mask: inst_rv64_a::MASK_AMOMAXU_D,
+        match_val: inst_rv64_a::MATCH_AMOMAXU_D,
+        execute: rv64_a::execute_amomaxu_d,
+    },
+    InstructionsEntry {
+        name: "AMOMAX_D",
+        mask: inst_rv64_a::MASK_AMOMAX_D,
+        match_val: inst_rv64_a::MATCH_AMOMAX_D,
+        execute: rv64_a::execute_amomax_d,
+    },
+    InstructionsEntry {
+        name: "AMOMINU_D",
+        mask: inst_rv64_a::MASK_AMOMINU_D,
+        match_val: inst_rv64_a::MATCH_AMOMINU_D,
+        execute: rv64_a::execute_amominu_d,
+    },
+    InstructionsEntry {
+        name: "AMOMIN_D",
+        mask: inst_rv64_a::MASK_AMOMIN_D,
+        match_val: inst_rv64_a::MATCH_AMOMIN_D,
+        execute: rv64_a::execute_amomin_d,
+    },
+    InstructionsEntry {
+        name: "AMOOR_D",
+        mask: inst_rv64_a::MASK_AMOOR_D,
+        match_val: inst_rv64_a::MATCH_AMOOR_D,
+        execute: rv64_a::execute_amoor_d,
+    },
+    InstructionsEntry {
+        name: "AMOSWAP_D",
+        mask: inst_rv64_a::MASK_AMOSWAP_D,
+        match_val: inst_rv64_a::MATCH_AMOSWAP_D,
+        execute: rv64_a::execute_amoswap_d,
+    },
+    InstructionsEntry {
+        name: "AMOXOR_D",
+        mask: inst_rv64_a::MASK_AMOXOR_D,
+        match_val: inst_rv64_a::MATCH_AMOXOR_D,
+        execute: rv64_a::execute_amoxor_d,
+    },
+    InstructionsEntry {
+        name: "LR_D",
+        mask: inst_rv64_a::MASK_LR_D,
+        match_val: inst_rv64_a::MATCH_LR_D,
+        execute: rv64_a::execute_lr_d,
+    },
+    InstructionsEntry {
+        name: "SC_D",
+        mask: inst_rv64_a::MASK_SC_D,
+        match_val: inst_rv64_a::MATCH_SC_D,
+        execute: rv64_a::execute_sc_d,
+    },
+];
+
+/// F instructions decoded for every XLEN
+pub(crate) const F_INSTRUCTIONS: [InstructionsEntry; 26] = [
+    InstructionsEntry {
+        name: "FADD_S",
+        mask: MASK_FADD_S,
+        match_val: MATCH_FADD_S,
+        execute: rv_f::execute_fadd_s,
+    },
+    InstructionsEntry {
+        name: "FCLASS_S",
+        mask: MASK_FCLASS_S,
+        match_val: MATCH_FCLASS_S,
+        execute: rv_f::execute_fclass_s,
+    },
+    InstructionsEntry {
+        name: "FCVT_S_W",
+        mask: MASK_FCVT_S_W,
+        match_val: MATCH_FCVT_S_W,
+        execute: rv_f::execute_fcvt_s_w,
+    },
+    InstructionsEntry {
+        name: "FCVT_S_WU",
+        mask: MASK_FCVT_S_WU,
+        match_val: MATCH_FCVT_S_WU,
+        execute: rv_f::execute_fcvt_s_wu,
+    },
+    InstructionsEntry {
+        name: "FCVT_W_S",
+        mask: MASK_FCVT_W_S,
+        match_val: MATCH_FCVT_W_S,
+        execute: rv_f::execute_fcvt_w_s,
+    },
+    InstructionsEntry {
+        name: "FCVT_WU_S",
+        mask: MASK_FCVT_WU_S,
+        match_val: MATCH_FCVT_WU_S,
+        execute: rv_f::execute_fcvt_wu_s,
+    },
+    InstructionsEntry {
+        name: "FDIV_S",
+        mask: MASK_FDIV_S,
+        match_val: MATCH_FDIV_S,
+        execute: rv_f::execute_fdiv_s,
+    },
+    InstructionsEntry {
+        name: "FEQ_S",
+        mask: MASK_FEQ_S,
+        match_val: MATCH_FEQ_S,
+        execute: rv_f::execute_feq_s,
+    },
+    InstructionsEntry {
+        name: "FLE_S",
+        mask: MASK_FLE_S,
+        match_val: MATCH_FLE_S,
+        execute: rv_f::execute_fle_s,
+    },
+    InstructionsEntry {
+        name: "FLT_S",
+        mask: MASK_FLT_S,
+        match_val: MATCH_FLT_S,
+        execute: rv_f::execute_flt_s,
+    },
+    InstructionsEntry {
+        name: "FLW",
+        mask: MASK_FLW,
+        match_val: MATCH_FLW,
+        execute: rv_f::execute_flw,
+    },
+    InstructionsEntry {
+        name: "FMADD_S",
+        mask: MASK_FMADD_S,
+        match_val: MATCH_FMADD_S,
+        execute: rv_f::execute_fmadd_s,
+    },
+    InstructionsEntry {
+        name: "FMAX_S",
+        mask: MASK_FMAX_S,
+        match_val: MATCH_FMAX_S,
+        execute: rv_f::execute_fmax_s,
+    },
+    InstructionsEntry {
+        name: "FMIN_S",
+        mask: MASK_FMIN_S,
+        match_val: MATCH_FMIN_S,
+        execute: rv_f::execute_fmin_s,
+    },
+    InstructionsEntry {
+        name: "FMSUB_S",
+        mask: MASK_FMSUB_S,
+        match_val: MATCH_FMSUB_S,
+        execute: rv_f::execute_fmsub_s,
+    },
+    InstructionsEntry {
+        name: "FMUL_S",
+        mask: MASK_FMUL_S,
+        match_val: MATCH_FMUL_S,
+        execute: rv_f::execute_fmul_s,
+    },
+    InstructionsEntry {
+        name: "FMV_W_X",
+        mask: MASK_FMV_W_X,
+        match_val: MATCH_FMV_W_X,
+        execute: rv_f::execute_fmv_w_x,
+    },
+    InstructionsEntry {
+        name: "FMV_X_W",
+        mask: MASK_FMV_X_W,
+        match_val: MATCH_FMV_X_W,
+        execute: rv_f::execute_fmv_x_w,
+    },
+    InstructionsEntry {
+        name: "FNMADD_S",
+        mask: MASK_FNMADD_S,
+        match_val: MATCH_FNMADD_S,
+        execute: rv_f::execute_fnmadd_s,
+    },
+    InstructionsEntry {
+        name: "FNMSUB_S",
+        mask: MASK_FNMSUB_S,
+        match_val: MATCH_FNMSUB_S,
+        execute: rv_f::execute_fnmsub_s,
+    },
+    InstructionsEntry {
+        name: "FSGNJN_S",
+        mask: MASK_FSGNJN_S,
+        match_val: MATCH_FSGNJN_S,
+        execute: rv_f::execute_fsgnjn_s,
+    },
+    InstructionsEntry {
+        name: "FSGNJ_S",
+        mask: MASK_FSGNJ_S,
+        match_val: MATCH_FSGNJ_S,
+        execute: rv_f::execute_fsgnj_s,
+    },
+    InstructionsEntry {
+        name: "FSGNJX_S",
+        mask: MASK_FSGNJX_S,
+        match_val: MATCH_FSGNJX_S,
+        execute: rv_f::execute_fsgnjx_s,
+    },
+    InstructionsEntry {
+        name: "FSQRT_S",
+        mask: MASK_FSQRT_S,
+        match_val: MATCH_FSQRT_S,
+        execute: rv_f::execute_fsqrt_s,
+    },
+    InstructionsEntry {
+        name: "FSUB_S",
+        mask: MASK_FSUB_S,
+        match_val: MATCH_FSUB_S,
+        execute: rv_f::execute_fsub_s,
+    },
+    InstructionsEntry {
+        name: "FSW",
+        mask: MASK_FSW,
+        match_val: MATCH_FSW,
+        execute: rv_f::execute_fsw,
+    },
+];
+
+/// F instructions only decoded on RV64
+pub(crate) const RV64_F_INSTRUCTIONS: [InstructionsEntry; 4] = [
+    InstructionsEntry {
+        name: "FCVT_L_S",
+        mask: inst_rv64_f::MASK_FCVT_L_S,
+        match_val: inst_rv64_f::MATCH_FCVT_L_S,
+        execute: rv64_f::execute_fcvt_l_s,
+    },
+    InstructionsEntry {
+        name: "FCVT_LU_S",
+        mask: inst_rv64_f::MASK_FCVT_LU_S,
+        match_val: inst_rv64_f::MATCH_FCVT_LU_S,
+        execute: rv64_f::execute_fcvt_lu_s,
+    },
+    InstructionsEntry {
+        name: "FCVT_S_L",
+        mask: inst_rv64_f::MASK_FCVT_S_L,
+        match_val: inst_rv64_f::MATCH_FCVT_S_L,
+        execute: rv64_f::execute_fcvt_s_l,
+    },
+    InstructionsEntry {
+        name: "FCVT_S_LU",
+        mask: inst_rv64_f::MASK_FCVT_S_LU,
+        match_val: inst_rv64_f::MATCH_FCVT_S_LU,
+        execute: rv64_f::execute_fcvt_s_lu,
+    },
+];
+
+/// D instructions decoded for every XLEN
+pub(crate) const D_INSTRUCTIONS: [InstructionsEntry; 26] = [
+    InstructionsEntry {
+        name: "FADD_D",
+        mask: MASK_FADD_D,
+        match_val: MATCH_FADD_D,
+        execute: rv_d::execute_fadd_d,
+    },
+    InstructionsEntry {
+        name: "FCLASS_D",
+        mask: MASK_FCLASS_D,
+        match_val: MATCH_FCLASS_D,
+        execute: rv_d::execute_fclass_d,
+    },
+    InstructionsEntry {
+        name: "FCVT_D_S",
+        mask: MASK_FCVT_D_S,
+        match_val: MATCH_FCVT_D_S,
+        execute: rv_d::execute_fcvt_d_s,
+    },
+    InstructionsEntry {
+        name: "FCVT_D_W",
+        mask: MASK_FCVT_D_W,
+        match_val: MATCH_FCVT_D_W,
+        execute: rv_d::execute_fcvt_d_w,
+    },
+    InstructionsEntry {
+        name: "FCVT_D_WU",
+        mask: MASK_FCVT_D_WU,
+        match_val: MATCH_FCVT_D_WU,
+        execute: rv_d::execute_fcvt_d_wu,
+    },
+    InstructionsEntry {
+        name: "FCVT_S_D",
+        mask: MASK_FCVT_S_D,
+        match_val: MATCH_FCVT_S_D,
+        execute: rv_d::execute_fcvt_s_d,
+    },
+    InstructionsEntry {
+        name: "FCVT_W_D",
+        mask: MASK_FCVT_W_D,
+        match_val: MATCH_FCVT_W_D,
+        execute: rv_d::execute_fcvt_w_d,
+    },
+    InstructionsEntry {
+        name: "FCVT_WU_D",
+        mask: MASK_FCVT_WU_D,
+        match_val: MATCH_FCVT_WU_D,
+        execute: rv_d::execute_fcvt_wu_d,
+    },
+    InstructionsEntry {
+        name: "FDIV_D",
+        mask: MASK_FDIV_D,
+        match_val: MATCH_FDIV_D,
+        execute: rv_d::execute_fdiv_d,
+    },
+    InstructionsEntry {
+        name: "FEQ_D",
+        mask: MASK_FEQ_D,
+        match_val: MATCH_FEQ_D,
+        execute: rv_d::execute_feq_d,
+    },
+    InstructionsEntry {
+        name: "FLD",
+        mask: MASK_FLD,
+        match_val: MATCH_FLD,
+        execute: rv_d::execute_fld,
+    },
+    InstructionsEntry {
+        name: "FLE_D",
+        mask: MASK_FLE_D,
+        match_val: MATCH_FLE_D,
+        execute: rv_d::execute_fle_d,
+    },
+    InstructionsEntry {
+        name: "FLT_D",
+        mask: MASK_FLT_D,
+        match_val: MATCH_FLT_D,
+        execute: rv_d::execute_flt_d,
+    },
+    InstructionsEntry {
+        name: "FMADD_D",
+        mask: MASK_FMADD_D,
+        match_val: MATCH_FMADD_D,
+        execute: rv_d::execute_fmadd_d,
+    },
+    InstructionsEntry {
+        name: "FMAX_D",
+        mask: MASK_FMAX_D,
+        match_val: MATCH_FMAX_D,
+        execute: rv_d::execute_fmax_d,
+    },
+    InstructionsEntry {
+        name: "FMIN_D",
+        mask: MASK_FMIN_D,
+        match_val: MATCH_FMIN_D,
+        execute: rv_d::execute_fmin_d,
+    },
+    InstructionsEntry {
+        name: "FMSUB_D",
+        mask: MASK_FMSUB_D,
+        match_val: MATCH_FMSUB_D,
+        execute: rv_d::execute_fmsub_d,
+    },
+    InstructionsEntry {
+        name: "FMUL_D",
+        mask: MASK_FMUL_D,
+        match_val: MATCH_FMUL_D,
+        execute: rv_d::execute_fmul_d,
+    },
+    InstructionsEntry {
+        name: "FNMADD_D",
+        mask: MASK_FNMADD_D,
+        match_val: MATCH_FNMADD_D,
+        execute: rv_d::execute_fnmadd_d,
+    },
+    InstructionsEntry {
+        name: "FNMSUB_D",
+        mask: MASK_FNMSUB_D,
+        match_val: MATCH_FNMSUB_D,
+        execute: rv_d::execute_fnmsub_d,
+    },
+    InstructionsEntry {
+        name: "FSD",
+        mask: MASK_FSD,
+        match_val: MATCH_FSD,
+        execute: rv_d::execute_fsd,
+    },
+    InstructionsEntry {
+        name: "FSGNJN_D",
+        mask: MASK_FSGNJN_D,
+        match_val: MATCH_FSGNJN_D,
+        execute: rv_d::execute_fsgnjn_d,
+    },
+    InstructionsEntry {
+        name: "FSGNJ_D",
+        mask: MASK_FSGNJ_D,
+        match_val: MATCH_FSGNJ_D,
+        execute: rv_d::execute_fsgnj_d,
+    },
+    InstructionsEntry {
+        name: "FSGNJX_D",
+        mask: MASK_FSGNJX_D,
+        match_val: MATCH_FSGNJX_D,
+        execute: rv_d::execute_fsgnjx_d,
+    },
+    InstructionsEntry {
+        name: "FSQRT_D",
+        mask: MASK_FSQRT_D,
+        match_val: MATCH_FSQRT_D,
+        execute: rv_d::execute_fsqrt_d,
+    },
+    InstructionsEntry {
+        name: "FSUB_D",
+        mask: MASK_FSUB_D,
+        match_val: MATCH_FSUB_D,
+        execute: rv_d::execute_fsub_d,
+    },
+];
+
+/// D instructions only decoded on RV64
+pub(crate) const RV64_D_INSTRUCTIONS: [InstructionsEntry; 6] = [
+    InstructionsEntry {
+        name: "FCVT_D_L",
+        mask: inst_rv64_d::MASK_FCVT_D_L,
+        match_val: inst_rv64_d::MATCH_FCVT_D_L,
+        execute: rv64_d::execute_fcvt_d_l,
+    },
+    InstructionsEntry {
+        name: "FCVT_D_LU",
+        mask: inst_rv64_d::MASK_FCVT_D_LU,
+        match_val: inst_rv64_d::MATCH_FCVT_D_LU,
+        execute: rv64_d::execute_fcvt_d_lu,
+    },
+    InstructionsEntry {
+        name: "FCVT_L_D",
+        mask: inst_rv64_d::MASK_FCVT_L_D,
+        match_val: inst_rv64_d::MATCH_FCVT_L_D,
+        execute: rv64_d::execute_fcvt_l_d,
+    },
+    InstructionsEntry {
+        name: "FCVT_LU_D",
+        mask: inst_rv64_d::MASK_FCVT_LU_D,
+        match_val: inst_rv64_d::MATCH_FCVT_LU_D,
+        execute: rv64_d::execute_fcvt_lu_d,
+    },
+    InstructionsEntry {
+        name: "FMV_D_X",
+        mask: inst_rv64_d::MASK_FMV_D_X,
+        match_val: inst_rv64_d::MATCH_FMV_D_X,
+        execute: rv64_d::execute_fmv_d_x,
+    },
+    InstructionsEntry {
+        name: "FMV_X_D",
+        mask: inst_rv64_d::MASK_FMV_X_D,
+        match_val: inst_rv64_d::MATCH_FMV_X_D,
+        execute: rv64_d::execute_fmv_x_d,
+    },
+];
+
+/// Zicsr instructions
+pub(crate) const ZICSR_INSTRUCTIONS: [InstructionsEntry; 6] = [
     InstructionsEntry {
         name: "CSRRC",
         mask: MASK_CSRRC,
@@ -279,22 +1033,349 @@ pub(crate) const ALL_INSTRUCTIONS: [InstructionsEntry; 48] = [
         match_val: MATCH_CSRRWI,
         execute: rv_zicsr::execute_csrrwi,
     },
+];
+
+/// Zifencei instructions
+pub(crate) const ZIFENCEI_INSTRUCTIONS: [InstructionsEntry; 1] = [InstructionsEntry {
+    name: "FENCE_I",
+    mask: MASK_FENCE_I,
+    match_val: MATCH_FENCE_I,
+    execute: rv_zifencei::execute_fence_i,
+}];
+
+/// Zba instructions decoded for every XLEN
+pub(crate) const ZBA_INSTRUCTIONS: [InstructionsEntry; 3] = [
     InstructionsEntry {
-        name: "SLLI",
-        mask: MASK_SLLI,
-        match_val: MATCH_SLLI,
-        execute: rv32_i::execute_slli,
+        name: "SH1ADD",
+        mask: inst_rv_zba::MASK_SH1ADD,
+        match_val: inst_rv_zba::MATCH_SH1ADD,
+        execute: rv_zba::execute_sh1add,
     },
     InstructionsEntry {
-        name: "SRAI",
-        mask: MASK_SRAI,
-        match_val: MATCH_SRAI,
-        execute: rv32_i::execute_srai,
+        name: "SH2ADD",
+        mask: inst_rv_zba::MASK_SH2ADD,
+        match_val: inst_rv_zba::MATCH_SH2ADD,
+        execute: rv_zba::execute_sh2add,
     },
     InstructionsEntry {
-        name: "SRLI",
-        mask: MASK_SRLI,
-        match_val: MATCH_SRLI,
-        execute: rv32_i::execute_srli,
+        name: "SH3ADD",
+        mask: inst_rv_zba::MASK_SH3ADD,
+        match_val: inst_rv_zba::MATCH_SH3ADD,
+        execute: rv_zba::execute_sh3add,
+    },
+];
+
+/// Zba instructions only decoded on RV64
+pub(crate) const RV64_ZBA_INSTRUCTIONS: [InstructionsEntry; 5] = [
+    InstructionsEntry {
+        name: "ADD_UW",
+        mask: inst_rv64_zba::MASK_ADD_UW,
+        match_val: inst_rv64_zba::MATCH_ADD_UW,
+        execute: rv64_zba::execute_add_uw,
+    },
+    InstructionsEntry {
+        name: "SH1ADD_UW",
+        mask: inst_rv64_zba::MASK_SH1ADD_UW,
+        match_val: inst_rv64_zba::MATCH_SH1ADD_UW,
+        execute: rv64_zba::execute_sh1add_uw,
+    },
+    InstructionsEntry {
+        name: "SH2ADD_UW",
+        mask: inst_rv64_zba::MASK_SH2ADD_UW,
+        match_val: inst_rv64_zba::MATCH_SH2ADD_UW,
+        execute: rv64_zba::execute_sh2add_uw,
+    },
+    InstructionsEntry {
+        name: "SH3ADD_UW",
+        mask: inst_rv64_zba::MASK_SH3ADD_UW,
+        match_val: inst_rv64_zba::MATCH_SH3ADD_UW,
+        execute: rv64_zba::execute_sh3add_uw,
+    },
+    InstructionsEntry {
+        name: "SLLI_UW",
+        mask: inst_rv64_zba::MASK_SLLI_UW,
+        match_val: inst_rv64_zba::MATCH_SLLI_UW,
+        execute: rv64_zba::execute_slli_uw,
+    },
+];
+
+/// Zbb instructions decoded for every XLEN
+pub(crate) const ZBB_INSTRUCTIONS: [InstructionsEntry; 15] = [
+    InstructionsEntry {
+        name: "ANDN",
+        mask: inst_rv_zbb::MASK_ANDN,
+        match_val: inst_rv_zbb::MATCH_ANDN,
+        execute: rv_zbb::execute_andn,
+    },
+    InstructionsEntry {
+        name: "CLZ",
+        mask: inst_rv_zbb::MASK_CLZ,
+        match_val: inst_rv_zbb::MATCH_CLZ,
+        execute: rv_zbb::execute_clz,
+    },
+    InstructionsEntry {
+        name: "CPOP",
+        mask: inst_rv_zbb::MASK_CPOP,
+        match_val: inst_rv_zbb::MATCH_CPOP,
+        execute: rv_zbb::execute_cpop,
+    },
+    InstructionsEntry {
+        name: "CTZ",
+        mask: inst_rv_zbb::MASK_CTZ,
+        match_val: inst_rv_zbb::MATCH_CTZ,
+        execute: rv_zbb::execute_ctz,
+    },
+    InstructionsEntry {
+        name: "MAX",
+        mask: inst_rv_zbb::MASK_MAX,
+        match_val: inst_rv_zbb::MATCH_MAX,
+        execute: rv_zbb::execute_max,
+    },
+    InstructionsEntry {
+        name: "MAXU",
+        mask: inst_rv_zbb::MASK_MAXU,
+        match_val: inst_rv_zbb::MATCH_MAXU,
+        execute: rv_zbb::execute_maxu,
+    },
+    InstructionsEntry {
+        name: "MIN",
+        mask: inst_rv_zbb::MASK_MIN,
+        match_val: inst_rv_zbb::MATCH_MIN,
+        execute: rv_zbb::execute_min,
+    },
+    InstructionsEntry {
+        name: "MINU",
+        mask: inst_rv_zbb::MASK_MINU,
+        match_val: inst_rv_zbb::MATCH_MINU,
+        execute: rv_zbb::execute_minu,
+    },
+    InstructionsEntry {
+        name: "ORC_B",
+        mask: inst_rv_zbb::MASK_ORC_B,
+        match_val: inst_rv_zbb::MATCH_ORC_B,
+        execute: rv_zbb::execute_orc_b,
+    },
+    InstructionsEntry {
+        name: "ORN",
+        mask: inst_rv_zbb::MASK_ORN,
+        match_val: inst_rv_zbb::MATCH_ORN,
+        execute: rv_zbb::execute_orn,
+    },
+    InstructionsEntry {
+        name: "ROL",
+        mask: inst_rv_zbb::MASK_ROL,
+        match_val: inst_rv_zbb::MATCH_ROL,
+        execute: rv_zbb::execute_rol,
+    },
+    InstructionsEntry {
+        name: "ROR",
+        mask: inst_rv_zbb::MASK_ROR,
+        match_val: inst_rv_zbb::MATCH_ROR,
+        execute: rv_zbb::execute_ror,
+    },
+    InstructionsEntry {
+        name: "SEXT_B",
+        mask: inst_rv_zbb::MASK_SEXT_B,
+        match_val: inst_rv_zbb::MATCH_SEXT_B,
+        execute: rv_zbb::execute_sext_b,
+    },
+    InstructionsEntry {
+        name: "SEXT_H",
+        mask: inst_rv_zbb::MASK_SEXT_H,
+        match_val: inst_rv_zbb::MATCH_SEXT_H,
+        execute: rv_zbb::execute_sext_h,
+    },
+    InstructionsEntry {
+        name: "XNOR",
+        mask: inst_rv_zbb::MASK_XNOR,
+        match_val: inst_rv_zbb::MATCH_XNOR,
+        execute: rv_zbb::execute_xnor,
+    },
+];
+
+/// Zbb instructions only decoded on RV32, the encodings of RORI, REV8 and ZEXT.H
+/// depend on XLEN
+pub(crate) const RV32_ZBB_INSTRUCTIONS: [InstructionsEntry; 3] = [
+    InstructionsEntry {
+        name: "REV8",
+        mask: inst_rv32_zbb::MASK_REV8,
+        match_val: inst_rv32_zbb::MATCH_REV8,
+        execute: rv_zbb::execute_rev8,
+    },
+    InstructionsEntry {
+        name: "RORI",
+        mask: inst_rv32_zbb::MASK_RORI,
+        match_val: inst_rv32_zbb::MATCH_RORI,
+        execute: rv_zbb::execute_rori,
+    },
+    InstructionsEntry {
+        name: "ZEXT_H",
+        mask: inst_rv32_zbb::MASK_ZEXT_H,
+        match_val: inst_rv32_zbb::MATCH_ZEXT_H,
+        execute: rv_zbb::execute_zext_h,
+    },
+];
+
+/// Zbb instructions only decoded on RV64
+pub(crate) const RV64_ZBB_INSTRUCTIONS: [InstructionsEntry; 9] = [
+    InstructionsEntry {
+        name: "CLZW",
+        mask: inst_rv64_zbb::MASK_CLZW,
+        match_val: inst_rv64_zbb::MATCH_CLZW,
+        execute: rv64_zbb::execute_clzw,
+    },
+    InstructionsEntry {
+        name: "CPOPW",
+        mask: inst_rv64_zbb::MASK_CPOPW,
+        match_val: inst_rv64_zbb::MATCH_CPOPW,
+        execute: rv64_zbb::execute_cpopw,
+    },
+    InstructionsEntry {
+        name: "CTZW",
+        mask: inst_rv64_zbb::MASK_CTZW,
+        match_val: inst_rv64_zbb::MATCH_CTZW,
+        execute: rv64_zbb::execute_ctzw,
+    },
+    InstructionsEntry {
+        name: "REV8",
+        mask: inst_rv64_zbb::MASK_REV8,
+        match_val: inst_rv64_zbb::MATCH_REV8,
+        execute: rv_zbb::execute_rev8,
+    },
+    InstructionsEntry {
+        name: "ROLW",
+        mask: inst_rv64_zbb::MASK_ROLW,
+        match_val: inst_rv64_zbb::MATCH_ROLW,
+        execute: rv64_zbb::execute_rolw,
+    },
+    InstructionsEntry {
+        name: "RORI",
+        mask: inst_rv64_zbb::MASK_RORI,
+        match_val: inst_rv64_zbb::MATCH_RORI,
+        execute: rv_zbb::execute_rori,
+    },
+    InstructionsEntry {
+        name: "RORIW",
+        mask: inst_rv64_zbb::MASK_RORIW,
+        match_val: inst_rv64_zbb::MATCH_RORIW,
+        execute: rv64_zbb::execute_roriw,
+    },
+    InstructionsEntry {
+        name: "RORW",
+        mask: inst_rv64_zbb::MASK_RORW,
+        match_val: inst_rv64_zbb::MATCH_RORW,
+        execute: rv64_zbb::execute_rorw,
+    },
+    InstructionsEntry {
+        name: "ZEXT_H",
+        mask: inst_rv64_zbb::MASK_ZEXT_H,
+        match_val: inst_rv64_zbb::MATCH_ZEXT_H,
+        execute: rv_zbb::execute_zext_h,
+    },
+];
+
+/// Zbc instructions decoded for every XLEN
+pub(crate) const ZBC_INSTRUCTIONS: [InstructionsEntry; 3] = [
+    InstructionsEntry {
+        name: "CLMUL",
+        mask: inst_rv_zbc::MASK_CLMUL,
+        match_val: inst_rv_zbc::MATCH_CLMUL,
+        execute: rv_zbc::execute_clmul,
+    },
+    InstructionsEntry {
+        name: "CLMULH",
+        mask: inst_rv_zbc::MASK_CLMULH,
+        match_val: inst_rv_zbc::MATCH_CLMULH,
+        execute: rv_zbc::execute_clmulh,
+    },
+    InstructionsEntry {
+        name: "CLMULR",
+        mask: inst_rv_zbc::MASK_CLMULR,
+        match_val: inst_rv_zbc::MATCH_CLMULR,
+        execute: rv_zbc::execute_clmulr,
+    },
+];
+
+/// Zbs instructions decoded for every XLEN
+pub(crate) const ZBS_INSTRUCTIONS: [InstructionsEntry; 4] = [
+    InstructionsEntry {
+        name: "BCLR",
+        mask: inst_rv_zbs::MASK_BCLR,
+        match_val: inst_rv_zbs::MATCH_BCLR,
+        execute: rv_zbs::execute_bclr,
+    },
+    InstructionsEntry {
+        name: "BEXT",
+        mask: inst_rv_zbs::MASK_BEXT,
+        match_val: inst_rv_zbs::MATCH_BEXT,
+        execute: rv_zbs::execute_bext,
+    },
+    InstructionsEntry {
+        name: "BINV",
+        mask: inst_rv_zbs::MASK_BINV,
+        match_val: inst_rv_zbs::MATCH_BINV,
+        execute: rv_zbs::execute_binv,
+    },
+    InstructionsEntry {
+        name: "BSET",
+        mask: inst_rv_zbs::MASK_BSET,
+        match_val: inst_rv_zbs::MATCH_BSET,
+        execute: rv_zbs::execute_bset,
+    },
+];
+
+/// Zbs instructions only decoded on RV32, the immediate forms take a 5-bit shamt
+pub(crate) const RV32_ZBS_INSTRUCTIONS: [InstructionsEntry; 4] = [
+    InstructionsEntry {
+        name: "BCLRI",
+        mask: inst_rv32_zbs::MASK_BCLRI,
+        match_val: inst_rv32_zbs::MATCH_BCLRI,
+        execute: rv_zbs::execute_bclri,
+    },
+    InstructionsEntry {
+        name: "BEXTI",
+        mask: inst_rv32_zbs::MASK_BEXTI,
+        match_val: inst_rv32_zbs::MATCH_BEXTI,
+        execute: rv_zbs::execute_bexti,
+    },
+    InstructionsEntry {
+        name: "BINVI",
+        mask: inst_rv32_zbs::MASK_BINVI,
+        match_val: inst_rv32_zbs::MATCH_BINVI,
+        execute: rv_zbs::execute_binvi,
+    },
+    InstructionsEntry {
+        name: "BSETI",
+        mask: inst_rv32_zbs::MASK_BSETI,
+        match_val: inst_rv32_zbs::MATCH_BSETI,
+        execute: rv_zbs::execute_bseti,
+    },
+];
+
+/// Zbs instructions only decoded on RV64
+pub(crate) const RV64_ZBS_INSTRUCTIONS: [InstructionsEntry; 4] = [
+    InstructionsEntry {
+        name: "BCLRI",
+        mask: inst_rv64_zbs::MASK_BCLRI,
+        match_val: inst_rv64_zbs::MATCH_BCLRI,
+        execute: rv_zbs::execute_bclri,
+    },
+    InstructionsEntry {
+        name: "BEXTI",
+        mask: inst_rv64_zbs::MASK_BEXTI,
+        match_val: inst_rv64_zbs::MATCH_BEXTI,
+        execute: rv_zbs::execute_bexti,
+    },
+    InstructionsEntry {
+        name: "BINVI",
+        mask: inst_rv64_zbs::MASK_BINVI,
+        match_val: inst_rv64_zbs::MATCH_BINVI,
+        execute: rv_zbs::execute_binvi,
+    },
+    InstructionsEntry {
+        name: "BSETI",
+        mask: inst_rv64_zbs::MASK_BSETI,
+        match_val: inst_rv64_zbs::MATCH_BSETI,
+        execute: rv_zbs::execute_bseti,
     },
 ];
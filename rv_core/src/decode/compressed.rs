@@ -0,0 +1,944 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// rv_core/src/decode/compressed.rs
+
+// Every RVC instruction is expanded to its 32-bit equivalent, which is then
+// decoded and executed like any other instruction. Only the disassembly and
+// the PC advance (2 instead of 4) tell them apart.
+//
+// Quadrant 0, 1 and 2 are selected by inst[1:0], rd', rs1' and rs2' are the
+// 3-bit register fields that address x8-x15.
+
+use crate::decode::{
+    inst_rv32_c::*, inst_rv32_c_f::*, inst_rv32_i::*, inst_rv64_c, inst_rv64_i, inst_rv_c::*,
+    inst_rv_c_d::*, inst_rv_d::*, inst_rv_f::*, inst_rv_i::*,
+};
+use crate::MachineInstruction;
+
+/// Returns the 32-bit equivalent, or None for a reserved encoding
+pub(crate) type ExpandInstructionFn = fn(MachineInstruction) -> Option<MachineInstruction>;
+
+pub(crate) struct CompressedEntry {
+    pub name: &'static str,
+    pub mask: MachineInstruction,
+    pub match_val: MachineInstruction,
+    pub expand: ExpandInstructionFn,
+}
+
+// The order matters: the entries with the narrower masks (C.NOP, C.ADDI16SP,
+// C.JR, C.EBREAK, C.JALR) must come before the ones they overlap with.
+//
+// The instructions decoded for every XLEN are in ALL_COMPRESSED_INSTRUCTIONS,
+// the ones whose meaning depends on XLEN are in the RV32 and RV64 tables.
+pub(crate) const ALL_COMPRESSED_INSTRUCTIONS: [CompressedEntry; 27] = [
+    // Quadrant 0
+    CompressedEntry {
+        name: "C_ADDI4SPN",
+        mask: MASK_C_ADDI4SPN,
+        match_val: MATCH_C_ADDI4SPN,
+        expand: expand_c_addi4spn,
+    },
+    CompressedEntry {
+        name: "C_FLD",
+        mask: MASK_C_FLD,
+        match_val: MATCH_C_FLD,
+        expand: expand_c_fld,
+    },
+    CompressedEntry {
+        name: "C_LW",
+        mask: MASK_C_LW,
+        match_val: MATCH_C_LW,
+        expand: expand_c_lw,
+    },
+    CompressedEntry {
+        name: "C_SW",
+        mask: MASK_C_SW,
+        match_val: MATCH_C_SW,
+        expand: expand_c_sw,
+    },
+    CompressedEntry {
+        name: "C_FSD",
+        mask: MASK_C_FSD,
+        match_val: MATCH_C_FSD,
+        expand: expand_c_fsd,
+    },
+    // Quadrant 1
+    CompressedEntry {
+        name: "C_NOP",
+        mask: MASK_C_NOP,
+        match_val: MATCH_C_NOP,
+        expand: expand_c_addi,
+    },
+    CompressedEntry {
+        name: "C_ADDI",
+        mask: MASK_C_ADDI,
+        match_val: MATCH_C_ADDI,
+        expand: expand_c_addi,
+    },
+    CompressedEntry {
+        name: "C_LI",
+        mask: MASK_C_LI,
+        match_val: MATCH_C_LI,
+        expand: expand_c_li,
+    },
+    CompressedEntry {
+        name: "C_ADDI16SP",
+        mask: MASK_C_ADDI16SP,
+        match_val: MATCH_C_ADDI16SP,
+        expand: expand_c_addi16sp,
+    },
+    CompressedEntry {
+        name: "C_LUI",
+        mask: MASK_C_LUI,
+        match_val: MATCH_C_LUI,
+        expand: expand_c_lui,
+    },
+    CompressedEntry {
+        name: "C_ANDI",
+        mask: MASK_C_ANDI,
+        match_val: MATCH_C_ANDI,
+        expand: expand_c_andi,
+    },
+    CompressedEntry {
+        name: "C_SUB",
+        mask: MASK_C_SUB,
+        match_val: MATCH_C_SUB,
+        expand: expand_c_sub,
+    },
+    CompressedEntry {
+        name: "C_XOR",
+        mask: MASK_C_XOR,
+        match_val: MATCH_C_XOR,
+        expand: expand_c_xor,
+    },
+    CompressedEntry {
+        name: "C_OR",
+        mask: MASK_C_OR,
+        match_val: MATCH_C_OR,
+        expand: expand_c_or,
+    },
+    CompressedEntry {
+        name: "C_AND",
+        mask: MASK_C_AND,
+        match_val: MATCH_C_AND,
+        expand: expand_c_and,
+    },
+    CompressedEntry {
+        name: "C_J",
+        mask: MASK_C_J,
+        match_val: MATCH_C_J,
+        expand: expand_c_j,
+    },
+    CompressedEntry {
+        name: "C_BEQZ",
+        mask: MASK_C_BEQZ,
+        match_val: MATCH_C_BEQZ,
+        expand: expand_c_beqz,
+    },
+    CompressedEntry {
+        name: "C_BNEZ",
+        mask: MASK_C_BNEZ,
+        match_val: MATCH_C_BNEZ,
+        expand: expand_c_bnez,
+    },
+    // Quadrant 2
+    CompressedEntry {
+        name: "C_FLDSP",
+        mask: MASK_C_FLDSP,
+        match_val: MATCH_C_FLDSP,
+        expand: expand_c_fldsp,
+    },
+    CompressedEntry {
+        name: "C_LWSP",
+        mask: MASK_C_LWSP,
+        match_val: MATCH_C_LWSP,
+        expand: expand_c_lwsp,
+    },
+    CompressedEntry {
+        name: "C_JR",
+        mask: MASK_C_JR,
+        match_val: MATCH_C_JR,
+        expand: expand_c_jr,
+    },
+    CompressedEntry {
+        name: "C_MV",
+        mask: MASK_C_MV,
+        match_val: MATCH_C_MV,
+        expand: expand_c_mv,
+    },
+    CompressedEntry {
+        name: "C_EBREAK",
+        mask: MASK_C_EBREAK,
+        match_val: MATCH_C_EBREAK,
+        expand: expand_c_ebreak,
+    },
+    CompressedEntry {
+        name: "C_JALR",
+        mask: MASK_C_JALR,
+        match_val: MATCH_C_JALR,
+        expand: expand_c_jalr,
+    },
+    CompressedEntry {
+        name: "C_ADD",
+        mask: MASK_C_ADD,
+        match_val: MATCH_C_ADD,
+        expand: expand_c_add,
+    },
+    CompressedEntry {
+        name: "C_FSDSP",
+        mask: MASK_C_FSDSP,
+        match_val: MATCH_C_FSDSP,
+        expand: expand_c_fsdsp,
+    },
+    CompressedEntry {
+        name: "C_SWSP",
+        mask: MASK_C_SWSP,
+        match_val: MATCH_C_SWSP,
+        expand: expand_c_swsp,
+    },
+];
+
+/// Compressed instructions only decoded on RV32, the RV32 shifts take a 5-bit shamt
+/// and C.FLW/C.FSW take the encodings of RV64 C.LD/C.SD
+pub(crate) const RV32_COMPRESSED_INSTRUCTIONS: [CompressedEntry; 8] = [
+    // Quadrant 0
+    CompressedEntry {
+        name: "C_FLW",
+        mask: MASK_C_FLW,
+        match_val: MATCH_C_FLW,
+        expand: expand_c_flw,
+    },
+    CompressedEntry {
+        name: "C_FSW",
+        mask: MASK_C_FSW,
+        match_val: MATCH_C_FSW,
+        expand: expand_c_fsw,
+    },
+    // Quadrant 1
+    CompressedEntry {
+        name: "C_JAL",
+        mask: MASK_C_JAL,
+        match_val: MATCH_C_JAL,
+        expand: expand_c_jal,
+    },
+    CompressedEntry {
+        name: "C_SRLI",
+        mask: MASK_C_SRLI,
+        match_val: MATCH_C_SRLI,
+        expand: expand_c_srli,
+    },
+    CompressedEntry {
+        name: "C_SRAI",
+        mask: MASK_C_SRAI,
+        match_val: MATCH_C_SRAI,
+        expand: expand_c_srai,
+    },
+    // Quadrant 2
+    CompressedEntry {
+        name: "C_SLLI",
+        mask: MASK_C_SLLI,
+        match_val: MATCH_C_SLLI,
+        expand: expand_c_slli,
+    },
+    CompressedEntry {
+        name: "C_FLWSP",
+        mask: MASK_C_FLWSP,
+        match_val: MATCH_C_FLWSP,
+        expand: expand_c_flwsp,
+    },
+    CompressedEntry {
+        name: "C_FSWSP",
+        mask: MASK_C_FSWSP,
+        match_val: MATCH_C_FSWSP,
+        expand: expand_c_fswsp,
+    },
+];
+
+/// Compressed instructions only decoded on RV64
+pub(crate) const RV64_COMPRESSED_INSTRUCTIONS: [CompressedEntry; 10] = [
+    // Quadrant 0
+    CompressedEntry {
+        name: "C_LD",
+        mask: inst_rv64_c::MASK_C_LD,
+        match_val: inst_rv64_c::MATCH_C_LD,
+        expand: expand_c_ld,
+    },
+    CompressedEntry {
+        name: "C_SD",
+        mask: inst_rv64_c::MASK_C_SD,
+        match_val: inst_rv64_c::MATCH_C_SD,
+        expand: expand_c_sd,
+    },
+    // Quadrant 1
+    CompressedEntry {
+        name: "C_ADDIW",
+        mask: inst_rv64_c::MASK_C_ADDIW,
+        match_val: inst_rv64_c::MATCH_C_ADDIW,
+        expand: expand_c_addiw,
+    },
+    CompressedEntry {
+        name: "C_SRLI",
+        mask: inst_rv64_c::MASK_C_SRLI,
+        match_val: inst_rv64_c::MATCH_C_SRLI,
+        expand: expand_c_srli,
+    },
+    CompressedEntry {
+        name: "C_SRAI",
+        mask: inst_rv64_c::MASK_C_SRAI,
+        match_val: inst_rv64_c::MATCH_C_SRAI,
+        expand: expand_c_srai,
+    },
+    CompressedEntry {
+        name: "C_SUBW",
+        mask: inst_rv64_c::MASK_C_SUBW,
+        match_val: inst_rv64_c::MATCH_C_SUBW,
+        expand: expand_c_subw,
+    },
+    CompressedEntry {
+        name: "C_ADDW",
+        mask: inst_rv64_c::MASK_C_ADDW,
+        match_val: inst_rv64_c::MATCH_C_ADDW,
+        expand: expand_c_addw,
+    },
+    // Quadrant 2
+    CompressedEntry {
+        name: "C_SLLI",
+        mask: inst_rv64_c::MASK_C_SLLI,
+        match_val: inst_rv64_c::MATCH_C_SLLI,
+        expand: expand_c_slli,
+    },
+    CompressedEntry {
+        name: "C_LDSP",
+        mask: inst_rv64_c::MASK_C_LDSP,
+        match_val: inst_rv64_c::MATCH_C_LDSP,
+        expand: expand_c_ldsp,
+    },
+    CompressedEntry {
+        name: "C_SDSP",
+        mask: inst_rv64_c::MASK_C_SDSP,
+        match_val: inst_rv64_c::MATCH_C_SDSP,
+        expand: expand_c_sdsp,
+    },
+];
+
+const REG_RA: u32 = 1;
+const REG_SP: u32 = 2;
+
+/// Returns inst[hi:lo]
+fn bits(inst: MachineInstruction, hi: u32, lo: u32) -> u32 {
+    (inst >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+fn sign_extend(value: u32, width: u32) -> u32 {
+    let shift = u32::BITS - width;
+    (((value << shift) as i32) >> shift) as u32
+}
+
+/// rd/rs1 in inst[11:7]
+fn rd(inst: MachineInstruction) -> u32 {
+    bits(inst, 11, 7)
+}
+
+/// rs2 in inst[6:2]
+fn rs2(inst: MachineInstruction) -> u32 {
+    bits(inst, 6, 2)
+}
+
+/// rd'/rs1' in inst[9:7]
+fn rs1_prime(inst: MachineInstruction) -> u32 {
+    bits(inst, 9, 7) + 8
+}
+
+/// rd'/rs2' in inst[4:2]
+fn rs2_prime(inst: MachineInstruction) -> u32 {
+    bits(inst, 4, 2) + 8
+}
+
+/// imm[5] = inst[12], imm[4:0] = inst[6:2], sign-extended
+fn ci_imm(inst: MachineInstruction) -> u32 {
+    sign_extend((bits(inst, 12, 12) << 5) | bits(inst, 6, 2), 6)
+}
+
+/// offset[11|4|9:8|10|6|7|3:1|5] = inst[12:2], sign-extended
+fn cj_offset(inst: MachineInstruction) -> u32 {
+    let offset = (bits(inst, 12, 12) << 11)
+        | (bits(inst, 11, 11) << 4)
+        | (bits(inst, 10, 9) << 8)
+        | (bits(inst, 8, 8) << 10)
+        | (bits(inst, 7, 7) << 6)
+        | (bits(inst, 6, 6) << 7)
+        | (bits(inst, 5, 3) << 1)
+        | (bits(inst, 2, 2) << 5);
+    sign_extend(offset, 12)
+}
+
+/// offset[8|4:3] = inst[12:10], offset[7:6|2:1|5] = inst[6:2], sign-extended
+fn cb_offset(inst: MachineInstruction) -> u32 {
+    let offset = (bits(inst, 12, 12) << 8)
+        | (bits(inst, 11, 10) << 3)
+        | (bits(inst, 6, 5) << 6)
+        | (bits(inst, 4, 3) << 1)
+        | (bits(inst, 2, 2) << 5);
+    sign_extend(offset, 9)
+}
+
+/// shamt[5] = inst[12], shamt[4:0] = inst[6:2], shamt[5] is only set on RV64
+fn ci_shamt(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 12) << 5) | bits(inst, 6, 2)
+}
+
+/// uimm[5:3] = inst[12:10], uimm[2|6] = inst[6:5]
+fn cl_cs_word_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 10) << 3) | (bits(inst, 6, 6) << 2) | (bits(inst, 5, 5) << 6)
+}
+
+/// uimm[5:3] = inst[12:10], uimm[7:6] = inst[6:5]
+fn cl_cs_doubleword_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 10) << 3) | (bits(inst, 6, 5) << 6)
+}
+
+/// uimm[5] = inst[12], uimm[4:2|7:6] = inst[6:2]
+fn ci_word_sp_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 12) << 5) | (bits(inst, 6, 4) << 2) | (bits(inst, 3, 2) << 6)
+}
+
+/// uimm[5] = inst[12], uimm[4:3|8:6] = inst[6:2]
+fn ci_doubleword_sp_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 12) << 5) | (bits(inst, 6, 5) << 3) | (bits(inst, 4, 2) << 6)
+}
+
+/// uimm[5:2|7:6] = inst[12:7]
+fn css_word_sp_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 9) << 2) | (bits(inst, 8, 7) << 6)
+}
+
+/// uimm[5:3|8:6] = inst[12:7]
+fn css_doubleword_sp_offset(inst: MachineInstruction) -> u32 {
+    (bits(inst, 12, 10) << 3) | (bits(inst, 9, 7) << 6)
+}
+
+fn encode_r(match_val: MachineInstruction, rd: u32, rs1: u32, rs2: u32) -> MachineInstruction {
+    match_val | (rs2 << 20) | (rs1 << 15) | (rd << 7)
+}
+
+fn encode_i(match_val: MachineInstruction, rd: u32, rs1: u32, imm: u32) -> MachineInstruction {
+    match_val | ((imm & 0xfff) << 20) | (rs1 << 15) | (rd << 7)
+}
+
+fn encode_s(match_val: MachineInstruction, rs1: u32, rs2: u32, imm: u32) -> MachineInstruction {
+    match_val | (bits(imm, 11, 5) << 25) | (rs2 << 20) | (rs1 << 15) | (bits(imm, 4, 0) << 7)
+}
+
+fn encode_b(match_val: MachineInstruction, rs1: u32, rs2: u32, imm: u32) -> MachineInstruction {
+    match_val
+        | (bits(imm, 12, 12) << 31)
+        | (bits(imm, 10, 5) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (bits(imm, 4, 1) << 8)
+        | (bits(imm, 11, 11) << 7)
+}
+
+fn encode_u(match_val: MachineInstruction, rd: u32, imm: u32) -> MachineInstruction {
+    match_val | (imm & 0xffff_f000) | (rd << 7)
+}
+
+fn encode_j(match_val: MachineInstruction, rd: u32, imm: u32) -> MachineInstruction {
+    match_val
+        | (bits(imm, 20, 20) << 31)
+        | (bits(imm, 10, 1) << 21)
+        | (bits(imm, 11, 11) << 20)
+        | (bits(imm, 19, 12) << 12)
+        | (rd << 7)
+}
+
+// c.addi4spn rd', nzuimm => addi rd', x2, nzuimm
+fn expand_c_addi4spn(inst: MachineInstruction) -> Option<MachineInstruction> {
+    // nzuimm[5:4|9:6|2|3] = inst[12:5]
+    let nzuimm = (bits(inst, 12, 11) << 4)
+        | (bits(inst, 10, 7) << 6)
+        | (bits(inst, 6, 6) << 2)
+        | (bits(inst, 5, 5) << 3);
+    if nzuimm == 0 {
+        // also covers the all-zero illegal instruction
+        return None;
+    }
+    Some(encode_i(MATCH_ADDI, rs2_prime(inst), REG_SP, nzuimm))
+}
+
+// c.lw rd', uimm(rs1') => lw rd', uimm(rs1')
+fn expand_c_lw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        MATCH_LW,
+        rs2_prime(inst),
+        rs1_prime(inst),
+        cl_cs_word_offset(inst),
+    ))
+}
+
+// c.sw rs2', uimm(rs1') => sw rs2', uimm(rs1')
+fn expand_c_sw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_SW,
+        rs1_prime(inst),
+        rs2_prime(inst),
+        cl_cs_word_offset(inst),
+    ))
+}
+
+// c.fld rd', uimm(rs1') => fld rd', uimm(rs1')
+fn expand_c_fld(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        MATCH_FLD,
+        rs2_prime(inst),
+        rs1_prime(inst),
+        cl_cs_doubleword_offset(inst),
+    ))
+}
+
+// c.fsd rs2', uimm(rs1') => fsd rs2', uimm(rs1')
+fn expand_c_fsd(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_FSD,
+        rs1_prime(inst),
+        rs2_prime(inst),
+        cl_cs_doubleword_offset(inst),
+    ))
+}
+
+// c.addi rd, imm => addi rd, rd, imm (c.nop is c.addi x0, 0)
+fn expand_c_addi(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(MATCH_ADDI, rd(inst), rd(inst), ci_imm(inst)))
+}
+
+// c.jal offset => jal x1, offset
+fn expand_c_jal(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_j(MATCH_JAL, REG_RA, cj_offset(inst)))
+}
+
+// c.li rd, imm => addi rd, x0, imm
+fn expand_c_li(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(MATCH_ADDI, rd(inst), 0, ci_imm(inst)))
+}
+
+// c.addi16sp nzimm => addi x2, x2, nzimm
+fn expand_c_addi16sp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    // nzimm[9] = inst[12], nzimm[4|6|8:7|5] = inst[6:2]
+    let nzimm = (bits(inst, 12, 12) << 9)
+        | (bits(inst, 6, 6) << 4)
+        | (bits(inst, 5, 5) << 6)
+        | (bits(inst, 4, 3) << 7)
+        | (bits(inst, 2, 2) << 5);
+    if nzimm == 0 {
+        return None;
+    }
+    Some(encode_i(MATCH_ADDI, REG_SP, REG_SP, sign_extend(nzimm, 10)))
+}
+
+// c.lui rd, nzimm => lui rd, nzimm
+fn expand_c_lui(inst: MachineInstruction) -> Option<MachineInstruction> {
+    // nzimm[17] = inst[12], nzimm[16:12] = inst[6:2]
+    let nzimm = (bits(inst, 12, 12) << 17) | (bits(inst, 6, 2) << 12);
+    if nzimm == 0 {
+        return None;
+    }
+    Some(encode_u(MATCH_LUI, rd(inst), sign_extend(nzimm, 18)))
+}
+
+// c.srli rd', shamt => srli rd', rd', shamt
+fn expand_c_srli(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_i(MATCH_SRLI, rd, rd, ci_shamt(inst)))
+}
+
+// c.srai rd', shamt => srai rd', rd', shamt
+fn expand_c_srai(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_i(MATCH_SRAI, rd, rd, ci_shamt(inst)))
+}
+
+// c.andi rd', imm => andi rd', rd', imm
+fn expand_c_andi(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_i(MATCH_ANDI, rd, rd, ci_imm(inst)))
+}
+
+// c.sub rd', rs2' => sub rd', rd', rs2'
+fn expand_c_sub(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(MATCH_SUB, rd, rd, rs2_prime(inst)))
+}
+
+// c.xor rd', rs2' => xor rd', rd', rs2'
+fn expand_c_xor(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(MATCH_XOR, rd, rd, rs2_prime(inst)))
+}
+
+// c.or rd', rs2' => or rd', rd', rs2'
+fn expand_c_or(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(MATCH_OR, rd, rd, rs2_prime(inst)))
+}
+
+// c.and rd', rs2' => and rd', rd', rs2'
+fn expand_c_and(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(MATCH_AND, rd, rd, rs2_prime(inst)))
+}
+
+// c.j offset => jal x0, offset
+fn expand_c_j(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_j(MATCH_JAL, 0, cj_offset(inst)))
+}
+
+// c.beqz rs1', offset => beq rs1', x0, offset
+fn expand_c_beqz(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_b(MATCH_BEQ, rs1_prime(inst), 0, cb_offset(inst)))
+}
+
+// c.bnez rs1', offset => bne rs1', x0, offset
+fn expand_c_bnez(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_b(MATCH_BNE, rs1_prime(inst), 0, cb_offset(inst)))
+}
+
+// c.slli rd, shamt => slli rd, rd, shamt
+fn expand_c_slli(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(MATCH_SLLI, rd(inst), rd(inst), ci_shamt(inst)))
+}
+
+// c.lwsp rd, uimm(x2) => lw rd, uimm(x2)
+fn expand_c_lwsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    if rd(inst) == 0 {
+        return None;
+    }
+    Some(encode_i(
+        MATCH_LW,
+        rd(inst),
+        REG_SP,
+        ci_word_sp_offset(inst),
+    ))
+}
+
+// c.fldsp rd, uimm(x2) => fld rd, uimm(x2), rd = f0 is allowed
+fn expand_c_fldsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        MATCH_FLD,
+        rd(inst),
+        REG_SP,
+        ci_doubleword_sp_offset(inst),
+    ))
+}
+
+// c.jr rs1 => jalr x0, 0(rs1)
+fn expand_c_jr(inst: MachineInstruction) -> Option<MachineInstruction> {
+    if rd(inst) == 0 {
+        return None;
+    }
+    Some(encode_i(MATCH_JALR, 0, rd(inst), 0))
+}
+
+// c.mv rd, rs2 => add rd, x0, rs2
+fn expand_c_mv(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_r(MATCH_ADD, rd(inst), 0, rs2(inst)))
+}
+
+// c.ebreak => ebreak
+fn expand_c_ebreak(_inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(MATCH_EBREAK)
+}
+
+// c.jalr rs1 => jalr x1, 0(rs1)
+fn expand_c_jalr(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(MATCH_JALR, REG_RA, rd(inst), 0))
+}
+
+// c.add rd, rs2 => add rd, rd, rs2
+fn expand_c_add(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_r(MATCH_ADD, rd(inst), rd(inst), rs2(inst)))
+}
+
+// c.swsp rs2, uimm(x2) => sw rs2, uimm(x2)
+fn expand_c_swsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_SW,
+        REG_SP,
+        rs2(inst),
+        css_word_sp_offset(inst),
+    ))
+}
+
+// c.fsdsp rs2, uimm(x2) => fsd rs2, uimm(x2)
+fn expand_c_fsdsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_FSD,
+        REG_SP,
+        rs2(inst),
+        css_doubleword_sp_offset(inst),
+    ))
+}
+
+// c.ld rd', uimm(rs1') => ld rd', uimm(rs1')
+fn expand_c_ld(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        inst_rv64_i::MATCH_LD,
+        rs2_prime(inst),
+        rs1_prime(inst),
+        cl_cs_doubleword_offset(inst),
+    ))
+}
+
+// c.sd rs2', uimm(rs1') => sd rs2', uimm(rs1')
+fn expand_c_sd(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        inst_rv64_i::MATCH_SD,
+        rs1_prime(inst),
+        rs2_prime(inst),
+        cl_cs_doubleword_offset(inst),
+    ))
+}
+
+// c.addiw rd, imm => addiw rd, rd, imm
+fn expand_c_addiw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    if rd(inst) == 0 {
+        return None;
+    }
+    Some(encode_i(
+        inst_rv64_i::MATCH_ADDIW,
+        rd(inst),
+        rd(inst),
+        ci_imm(inst),
+    ))
+}
+
+// c.subw rd', rs2' => subw rd', rd', rs2'
+fn expand_c_subw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(inst_rv64_i::MATCH_SUBW, rd, rd, rs2_prime(inst)))
+}
+
+// c.addw rd', rs2' => addw rd', rd', rs2'
+fn expand_c_addw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    let rd = rs1_prime(inst);
+    Some(encode_r(inst_rv64_i::MATCH_ADDW, rd, rd, rs2_prime(inst)))
+}
+
+// c.ldsp rd, uimm(x2) => ld rd, uimm(x2)
+fn expand_c_ldsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    if rd(inst) == 0 {
+        return None;
+    }
+    Some(encode_i(
+        inst_rv64_i::MATCH_LD,
+        rd(inst),
+        REG_SP,
+        ci_doubleword_sp_offset(inst),
+    ))
+}
+
+// c.sdsp rs2, uimm(x2) => sd rs2, uimm(x2)
+fn expand_c_sdsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        inst_rv64_i::MATCH_SD,
+        REG_SP,
+        rs2(inst),
+        css_doubleword_sp_offset(inst),
+    ))
+}
+
+// c.flw rd', uimm(rs1') => flw rd', uimm(rs1')
+fn expand_c_flw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        MATCH_FLW,
+        rs2_prime(inst),
+        rs1_prime(inst),
+        cl_cs_word_offset(inst),
+    ))
+}
+
+// c.fsw rs2', uimm(rs1') => fsw rs2', uimm(rs1')
+fn expand_c_fsw(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_FSW,
+        rs1_prime(inst),
+        rs2_prime(inst),
+        cl_cs_word_offset(inst),
+    ))
+}
+
+// c.flwsp rd, uimm(x2) => flw rd, uimm(x2), unlike c.lwsp rd = f0 is allowed
+fn expand_c_flwsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_i(
+        MATCH_FLW,
+        rd(inst),
+        REG_SP,
+        ci_word_sp_offset(inst),
+    ))
+}
+
+// c.fswsp rs2, uimm(x2) => fsw rs2, uimm(x2)
+fn expand_c_fswsp(inst: MachineInstruction) -> Option<MachineInstruction> {
+    Some(encode_s(
+        MATCH_FSW,
+        REG_SP,
+        rs2(inst),
+        css_word_sp_offset(inst),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_with(
+        table: &[CompressedEntry],
+        inst: MachineInstruction,
+    ) -> Option<MachineInstruction> {
+        ALL_COMPRESSED_INSTRUCTIONS
+            .iter()
+            .chain(table)
+            .find(|entry| (inst & entry.mask) == entry.match_val)
+            .and_then(|entry| (entry.expand)(inst))
+    }
+
+    fn expand(inst: MachineInstruction) -> Option<MachineInstruction> {
+        expand_with(&RV32_COMPRESSED_INSTRUCTIONS, inst)
+    }
+
+    fn expand_rv64(inst: MachineInstruction) -> Option<MachineInstruction> {
+        expand_with(&RV64_COMPRESSED_INSTRUCTIONS, inst)
+    }
+
+    // The expected values are the encodings printed by objdump for the
+    // uncompressed form of each instruction.
+    #[test]
+    fn test_expand_quadrant_0() {
+        // c.addi4spn s0, sp, 16 => addi s0, sp, 16
+        assert_eq!(expand(0x0800), Some(0x01010413));
+        // c.lw a0, 4(a1) => lw a0, 4(a1)
+        assert_eq!(expand(0x41c8), Some(0x0045a503));
+        // c.sw a0, 64(a1) => sw a0, 64(a1)
+        assert_eq!(expand(0xc1a8), Some(0x04a5a023));
+        // the all-zero instruction is illegal
+        assert_eq!(expand(0x0000), None);
+    }
+
+    #[test]
+    fn test_expand_quadrant_1() {
+        // c.nop => addi x0, x0, 0
+        assert_eq!(expand(0x0001), Some(0x00000013));
+        // c.addi sp, -16 => addi sp, sp, -16
+        assert_eq!(expand(0x1141), Some(0xff010113));
+        // c.li a0, 5 => addi a0, x0, 5
+        assert_eq!(expand(0x4515), Some(0x00500513));
+        // c.addi16sp sp, -48 => addi sp, sp, -48
+        assert_eq!(expand(0x7179), Some(0xfd010113));
+        // c.lui a5, 0x1 => lui a5, 0x1
+        assert_eq!(expand(0x6785), Some(0x000017b7));
+        // c.lui a5, 0xfffff => lui a5, 0xfffff
+        assert_eq!(expand(0x77fd), Some(0xfffff7b7));
+        // c.srli a0, 3 => srli a0, a0, 3
+        assert_eq!(expand(0x810d), Some(0x00355513));
+        // c.srai a0, 3 => srai a0, a0, 3
+        assert_eq!(expand(0x850d), Some(0x40355513));
+        // c.andi a0, -1 => andi a0, a0, -1
+        assert_eq!(expand(0x997d), Some(0xfff57513));
+        // c.sub a0, a1 => sub a0, a0, a1
+        assert_eq!(expand(0x8d0d), Some(0x40b50533));
+        // c.and a0, a1 => and a0, a0, a1
+        assert_eq!(expand(0x8d6d), Some(0x00b57533));
+        // c.jal -2 => jal ra, -2
+        assert_eq!(expand(0x3ffd), Some(0xfffff0ef));
+        // c.j 6 => jal x0, 6
+        assert_eq!(expand(0xa019), Some(0x0060006f));
+        // c.beqz a0, -4 => beq a0, x0, -4
+        assert_eq!(expand(0xdd75), Some(0xfe050ee3));
+        // c.bnez a0, 8 => bne a0, x0, 8
+        assert_eq!(expand(0xe501), Some(0x00051463));
+    }
+
+    #[test]
+    fn test_expand_quadrant_2() {
+        // c.slli a0, 2 => slli a0, a0, 2
+        assert_eq!(expand(0x050a), Some(0x00251513));
+        // c.lwsp ra, 12(sp) => lw ra, 12(sp)
+        assert_eq!(expand(0x40b2), Some(0x00c12083));
+        // c.jr ra => jalr x0, 0(ra)
+        assert_eq!(expand(0x8082), Some(0x00008067));
+        // c.mv a2, a1 => add a2, x0, a1
+        assert_eq!(expand(0x862e), Some(0x00b00633));
+        // c.ebreak => ebreak
+        assert_eq!(expand(0x9002), Some(0x00100073));
+        // c.jalr a5 => jalr ra, 0(a5)
+        assert_eq!(expand(0x9782), Some(0x000780e7));
+        // c.add a0, a1 => add a0, a0, a1
+        assert_eq!(expand(0x952e), Some(0x00b50533));
+        // c.swsp ra, 12(sp) => sw ra, 12(sp)
+        assert_eq!(expand(0xc606), Some(0x00112623));
+        // c.lwsp and c.jr with x0 are reserved
+        assert_eq!(expand(0x4002), None);
+        assert_eq!(expand(0x8002), None);
+    }
+
+    #[test]
+    fn test_expand_rv32_fp() {
+        // c.flw fa0, 4(a1) => flw fa0, 4(a1)
+        assert_eq!(expand(0x61c8), Some(0x0045a507));
+        // c.fsw fa0, 64(a1) => fsw fa0, 64(a1)
+        assert_eq!(expand(0xe1a8), Some(0x04a5a027));
+        // c.flwsp fa0, 12(sp) => flw fa0, 12(sp)
+        assert_eq!(expand(0x6532), Some(0x00c12507));
+        // c.flwsp ft0, 252(sp) => flw ft0, 252(sp)
+        assert_eq!(expand(0x707e), Some(0x0fc12007));
+        // c.fswsp fa0, 12(sp) => fsw fa0, 12(sp)
+        assert_eq!(expand(0xe62a), Some(0x00a12627));
+        // the same encoding is c.ld a0, 128(a1) on RV64
+        assert_eq!(expand_rv64(0x61c8), Some(0x0805b503));
+    }
+
+    #[test]
+    fn test_expand_double_fp() {
+        // c.fld fa0, 8(a1) => fld fa0, 8(a1)
+        assert_eq!(expand(0x2588), Some(0x0085b507));
+        // c.fsd fa0, 8(a1) => fsd fa0, 8(a1)
+        assert_eq!(expand(0xa588), Some(0x00a5b427));
+        // c.fldsp fa0, 8(sp) => fld fa0, 8(sp)
+        assert_eq!(expand(0x2522), Some(0x00813507));
+        // c.fldsp ft0, 504(sp) => fld ft0, 504(sp)
+        assert_eq!(expand(0x307e), Some(0x1f813007));
+        // c.fsdsp fa0, 8(sp) => fsd fa0, 8(sp)
+        assert_eq!(expand(0xa42a), Some(0x00a13427));
+        // they are decoded on RV64 too
+        assert_eq!(expand_rv64(0x2588), Some(0x0085b507));
+    }
+
+    #[test]
+    fn test_expand_rv64() {
+        // c.ld a0, 8(a1) => ld a0, 8(a1)
+        assert_eq!(expand_rv64(0x6588), Some(0x0085b503));
+        // c.sd a0, 8(a1) => sd a0, 8(a1)
+        assert_eq!(expand_rv64(0xe588), Some(0x00a5b423));
+        // c.addiw a0, -1 => addiw a0, a0, -1
+        assert_eq!(expand_rv64(0x357d), Some(0xfff5051b));
+        // c.subw a0, a1 => subw a0, a0, a1
+        assert_eq!(expand_rv64(0x9d0d), Some(0x40b5053b));
+        // c.addw a0, a1 => addw a0, a0, a1
+        assert_eq!(expand_rv64(0x9d2d), Some(0x00b5053b));
+        // c.ldsp ra, 8(sp) => ld ra, 8(sp)
+        assert_eq!(expand_rv64(0x60a2), Some(0x00813083));
+        // c.sdsp ra, 8(sp) => sd ra, 8(sp)
+        assert_eq!(expand_rv64(0xe406), Some(0x00113423));
+        // c.slli a0, 32 => slli a0, a0, 32
+        assert_eq!(expand_rv64(0x1502), Some(0x02051513));
+        // c.srli a0, 32 => srli a0, a0, 32
+        assert_eq!(expand_rv64(0x9101), Some(0x02055513));
+        // shamt[5] is reserved on RV32
+        assert_eq!(expand(0x1502), None);
+        // c.addiw with rd = x0 and c.ldsp with rd = x0 are reserved
+        assert_eq!(expand_rv64(0x2001), None);
+        assert_eq!(expand_rv64(0x6002), None);
+    }
+}
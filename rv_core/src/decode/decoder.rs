@@ -5,25 +5,155 @@
 
 // rv_core/src/decoder.rs
 
-// use std::collections::HashMap;
 use tracing::info;
 
-use crate::decode::all_instructions::ALL_INSTRUCTIONS;
-use crate::decode::DecodedInstruction;
-use crate::{MachineInstruction, RvCoreError};
+use crate::decode::all_instructions::{
+    A_INSTRUCTIONS, D_INSTRUCTIONS, F_INSTRUCTIONS, I_INSTRUCTIONS, M_INSTRUCTIONS,
+    RV32_I_INSTRUCTIONS, RV32_ZBB_INSTRUCTIONS, RV32_ZBS_INSTRUCTIONS, RV64_A_INSTRUCTIONS,
+    RV64_D_INSTRUCTIONS, RV64_F_INSTRUCTIONS, RV64_I_INSTRUCTIONS, RV64_M_INSTRUCTIONS,
+    RV64_ZBA_INSTRUCTIONS, RV64_ZBB_INSTRUCTIONS, RV64_ZBS_INSTRUCTIONS, ZBA_INSTRUCTIONS,
+    ZBB_INSTRUCTIONS, ZBC_INSTRUCTIONS, ZBS_INSTRUCTIONS, ZICSR_INSTRUCTIONS,
+    ZIFENCEI_INSTRUCTIONS,
+};
+use crate::decode::compressed::{
+    CompressedEntry, ALL_COMPRESSED_INSTRUCTIONS, RV32_COMPRESSED_INSTRUCTIONS,
+    RV64_COMPRESSED_INSTRUCTIONS,
+};
+use crate::decode::{
+    instruction_length, DecodedInstruction, InstructionsEntry, COMPRESSED_INSTRUCTION_LENGTH,
+    INSTRUCTION_LENGTH,
+};
+use crate::isa::{Extension, Isa};
+use crate::{MachineInstruction, RvCoreError, Xlen};
 
 pub struct Decoder {
-    // instructions: HashMap<&'static str, InstructionsEntry>,
+    isa: Isa,
+    // The entries of the enabled extensions, rebuilt when the ISA changes
+    instructions: Vec<&'static InstructionsEntry>,
+    compressed_instructions: Vec<&'static CompressedEntry>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Decoder {
     pub fn new() -> Self {
-        info!("Decoder created");
-        Self {}
+        Self::with_isa(Isa::default())
+    }
+
+    /// Creates a decoder for the default extensions on `xlen`
+    pub fn with_xlen(xlen: Xlen) -> Self {
+        let mut isa = Isa::default();
+        isa.set_xlen(xlen);
+        Self::with_isa(isa)
+    }
+
+    pub fn with_isa(isa: Isa) -> Self {
+        info!("Decoder created for {}", isa);
+        Self::build(isa)
+    }
+
+    fn build(isa: Isa) -> Self {
+        let mut decoder = Self {
+            isa,
+            instructions: Vec::new(),
+            compressed_instructions: Vec::new(),
+        };
+        decoder.build_tables();
+        decoder
+    }
+
+    pub fn get_xlen(&self) -> Xlen {
+        self.isa.xlen()
+    }
+
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        self.isa.set_xlen(xlen);
+        self.build_tables();
+    }
+
+    pub fn get_isa(&self) -> &Isa {
+        &self.isa
+    }
+
+    pub fn set_isa(&mut self, isa: Isa) {
+        self.isa = isa;
+        self.build_tables();
+    }
+
+    pub fn is_extension_enabled(&self, extension: Extension) -> bool {
+        self.isa.is_extension_enabled(extension)
+    }
+
+    /// Enables or disables decoding of `extension`, the instructions of a
+    /// disabled extension are invalid
+    pub fn set_extension_enabled(&mut self, extension: Extension, enabled: bool) {
+        self.isa.set_extension_enabled(extension, enabled);
+        self.build_tables();
+    }
+
+    fn build_tables(&mut self) {
+        let xlen = self.isa.xlen();
+        self.instructions = self
+            .isa
+            .extensions()
+            .flat_map(|extension| Self::extension_instructions(extension, xlen))
+            .flatten()
+            .collect();
+
+        self.compressed_instructions = if self.isa.is_extension_enabled(Extension::C) {
+            let xlen_compressed: &'static [CompressedEntry] = match xlen {
+                Xlen::Rv32 => &RV32_COMPRESSED_INSTRUCTIONS,
+                Xlen::Rv64 => &RV64_COMPRESSED_INSTRUCTIONS,
+            };
+            ALL_COMPRESSED_INSTRUCTIONS
+                .iter()
+                .chain(xlen_compressed)
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    // The entries of `extension` on `xlen`, common ones first
+    fn extension_instructions(
+        extension: Extension,
+        xlen: Xlen,
+    ) -> [&'static [InstructionsEntry]; 2] {
+        match (extension, xlen) {
+            (Extension::I, Xlen::Rv32) => [&I_INSTRUCTIONS, &RV32_I_INSTRUCTIONS],
+            (Extension::I, Xlen::Rv64) => [&I_INSTRUCTIONS, &RV64_I_INSTRUCTIONS],
+            (Extension::M, Xlen::Rv32) => [&M_INSTRUCTIONS, &[]],
+            (Extension::M, Xlen::Rv64) => [&M_INSTRUCTIONS, &RV64_M_INSTRUCTIONS],
+            (Extension::A, Xlen::Rv32) => [&A_INSTRUCTIONS, &[]],
+            (Extension::A, Xlen::Rv64) => [&A_INSTRUCTIONS, &RV64_A_INSTRUCTIONS],
+            (Extension::F, Xlen::Rv32) => [&F_INSTRUCTIONS, &[]],
+            (Extension::F, Xlen::Rv64) => [&F_INSTRUCTIONS, &RV64_F_INSTRUCTIONS],
+            (Extension::D, Xlen::Rv32) => [&D_INSTRUCTIONS, &[]],
+            (Extension::D, Xlen::Rv64) => [&D_INSTRUCTIONS, &RV64_D_INSTRUCTIONS],
+            // expanded by the compressed tables
+            (Extension::C, _) => [&[], &[]],
+            (Extension::Zicsr, _) => [&ZICSR_INSTRUCTIONS, &[]],
+            (Extension::Zifencei, _) => [&ZIFENCEI_INSTRUCTIONS, &[]],
+            (Extension::Zba, Xlen::Rv32) => [&ZBA_INSTRUCTIONS, &[]],
+            (Extension::Zba, Xlen::Rv64) => [&ZBA_INSTRUCTIONS, &RV64_ZBA_INSTRUCTIONS],
+            (Extension::Zbb, Xlen::Rv32) => [&ZBB_INSTRUCTIONS, &RV32_ZBB_INSTRUCTIONS],
+            (Extension::Zbb, Xlen::Rv64) => [&ZBB_INSTRUCTIONS, &RV64_ZBB_INSTRUCTIONS],
+            (Extension::Zbc, _) => [&ZBC_INSTRUCTIONS, &[]],
+            (Extension::Zbs, Xlen::Rv32) => [&ZBS_INSTRUCTIONS, &RV32_ZBS_INSTRUCTIONS],
+            (Extension::Zbs, Xlen::Rv64) => [&ZBS_INSTRUCTIONS, &RV64_ZBS_INSTRUCTIONS],
+        }
     }
 
     pub fn decode(&self, inst: MachineInstruction) -> Result<DecodedInstruction, RvCoreError> {
-        for entry in ALL_INSTRUCTIONS {
+        if instruction_length(inst) == COMPRESSED_INSTRUCTION_LENGTH {
+            return self.decode_compressed(inst);
+        }
+
+        for entry in &self.instructions {
             if (inst & entry.mask) != entry.match_val {
                 continue;
             }
@@ -31,6 +161,35 @@ impl Decoder {
             return Ok(DecodedInstruction {
                 name: entry.name,
                 execute: entry.execute,
+                instruction: inst,
+                length: INSTRUCTION_LENGTH,
+            });
+        }
+        Err(RvCoreError::InvalidInstruction(inst))
+    }
+
+    // Expands the 16-bit instruction and decodes its 32-bit equivalent
+    fn decode_compressed(
+        &self,
+        inst: MachineInstruction,
+    ) -> Result<DecodedInstruction, RvCoreError> {
+        let inst = inst & 0xffff;
+        for entry in &self.compressed_instructions {
+            if (inst & entry.mask) != entry.match_val {
+                continue;
+            }
+
+            let expanded = (entry.expand)(inst).ok_or(RvCoreError::InvalidInstruction(inst))?;
+            // the expansion is decoded against the enabled extensions, so c.flw
+            // without F or c.fld without D is invalid as well
+            let decoded = self
+                .decode(expanded)
+                .map_err(|_| RvCoreError::InvalidInstruction(inst))?;
+            return Ok(DecodedInstruction {
+                name: entry.name,
+                execute: decoded.execute,
+                instruction: expanded,
+                length: COMPRESSED_INSTRUCTION_LENGTH,
             });
         }
         Err(RvCoreError::InvalidInstruction(inst))
@@ -38,6 +197,7 @@ impl Decoder {
 }
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
     use crate::execute::rv_i;
@@ -47,8 +207,101 @@ mod tests {
         let inst: u32 = 0b0000000_00010_00001_000_00011_0110011;
         let decoded = decoder.decode(inst).unwrap();
         assert_eq!(decoded.name, "ADD");
-        if decoded.execute != rv_i::execute_add {
+        if !std::ptr::fn_addr_eq(
+            decoded.execute,
+            rv_i::execute_add as crate::decode::ExecuteInstructionFn,
+        ) {
+            debug_assert!(false, "Invalid execute function");
+        }
+    }
+
+    #[test]
+    fn test_decode_m_extension() {
+        let decoder = Decoder::new();
+        // mul x3, x1, x2
+        let decoded = decoder
+            .decode(0b0000001_00010_00001_000_00011_0110011)
+            .unwrap();
+        assert_eq!(decoded.name, "MUL");
+        // remu x3, x1, x2
+        let decoded = decoder
+            .decode(0b0000001_00010_00001_111_00011_0110011)
+            .unwrap();
+        assert_eq!(decoded.name, "REMU");
+    }
+
+    #[test]
+    fn test_decode_c_extension() {
+        let decoder = Decoder::new();
+        // c.addi sp, -16 => addi sp, sp, -16
+        let decoded = decoder.decode(0x1141).unwrap();
+        assert_eq!(decoded.name, "C_ADDI");
+        assert_eq!(decoded.instruction, 0xff010113);
+        assert_eq!(decoded.length, 2);
+        if !std::ptr::fn_addr_eq(
+            decoded.execute,
+            rv_i::execute_addi as crate::decode::ExecuteInstructionFn,
+        ) {
             debug_assert!(false, "Invalid execute function");
         }
+        // only the lower halfword is looked at
+        assert_eq!(decoder.decode(0xffff_1141).unwrap().name, "C_ADDI");
+        // the all-zero instruction is illegal
+        assert_eq!(
+            decoder.decode(0x0000).err(),
+            Some(RvCoreError::InvalidInstruction(0))
+        );
+    }
+
+    #[test]
+    fn test_decode_rv64() {
+        let rv32 = Decoder::new();
+        let rv64 = Decoder::with_xlen(Xlen::Rv64);
+        // addw a0, a0, a1
+        assert_eq!(rv64.decode(0x00b5053b).unwrap().name, "ADDW");
+        assert!(rv32.decode(0x00b5053b).is_err());
+        // ld a0, 8(a1)
+        assert_eq!(rv64.decode(0x0085b503).unwrap().name, "LD");
+        // slli a0, a0, 32 needs shamt[5], which is reserved on RV32
+        assert_eq!(rv64.decode(0x02051513).unwrap().name, "SLLI");
+        assert!(rv32.decode(0x02051513).is_err());
+        assert_eq!(rv32.decode(0x00251513).unwrap().name, "SLLI");
+        // 0x2001 is c.jal on RV32 and c.addiw on RV64
+        assert_eq!(rv32.decode(0x2005).unwrap().name, "C_JAL");
+        assert_eq!(rv64.decode(0x2505).unwrap().name, "C_ADDIW");
+    }
+
+    #[test]
+    fn test_decode_bitmanip_extensions() {
+        let mut rv32 = Decoder::new();
+        let rv64 = Decoder::with_xlen(Xlen::Rv64);
+        // sh1add a0, a1, a2
+        assert_eq!(rv32.decode(0x20c5a533).unwrap().name, "SH1ADD");
+        // andn a0, a1, a2
+        assert_eq!(rv32.decode(0x40c5f533).unwrap().name, "ANDN");
+        // clz a0, a1 ; cpop a0, a1
+        assert_eq!(rv32.decode(0x60059513).unwrap().name, "CLZ");
+        assert_eq!(rv32.decode(0x60259513).unwrap().name, "CPOP");
+        // rev8 a0, a1 has a different encoding on RV64
+        assert_eq!(rv32.decode(0x6985d513).unwrap().name, "REV8");
+        assert!(rv32.decode(0x6b85d513).is_err());
+        assert_eq!(rv64.decode(0x6b85d513).unwrap().name, "REV8");
+        // clmul a0, a1, a2 ; bset a0, a1, a2
+        assert_eq!(rv32.decode(0x0ac59533).unwrap().name, "CLMUL");
+        assert_eq!(rv32.decode(0x28c59533).unwrap().name, "BSET");
+        // bseti a0, a1, 33 and add.uw a0, a1, a2 are RV64 only
+        assert!(rv32.decode(0x2a159513).is_err());
+        assert_eq!(rv64.decode(0x2a159513).unwrap().name, "BSETI");
+        assert_eq!(rv64.decode(0x08c5853b).unwrap().name, "ADD_UW");
+
+        // each extension is switched off on its own
+        rv32.set_extension_enabled(Extension::Zbb, false);
+        assert!(!rv32.is_extension_enabled(Extension::Zbb));
+        assert!(rv32.decode(0x40c5f533).is_err());
+        assert!(rv32.decode(0x6985d513).is_err());
+        assert_eq!(rv32.decode(0x20c5a533).unwrap().name, "SH1ADD");
+        assert_eq!(rv32.decode(0x28c59533).unwrap().name, "BSET");
+        rv32.set_extension_enabled(Extension::Zbb, true);
+        assert_eq!(rv32.decode(0x40c5f533).unwrap().name, "ANDN");
     }
 }
@@ -5,7 +5,8 @@
 
 use crate::{
     core::Core,
-    decode::{FormatB, FormatI, FormatJ, FormatR, FormatS, FormatU},
+    decode::{FormatAmo, FormatB, FormatI, FormatJ, FormatR, FormatS, FormatU},
+    GprSigned, RegisterIndex,
 };
 
 pub(crate) fn disasm_format_r(
@@ -45,6 +46,22 @@ pub(crate) fn disasm_format_i(
     }
 }
 
+/// Unary instructions such as `clz rd, rs1`, whose rs2/imm field is part of the opcode
+pub(crate) fn disasm_format_unary(
+    name: &str,
+    operands: &FormatI,
+    core: &Core,
+    use_reg_name: bool,
+) -> String {
+    if use_reg_name {
+        let rd = core.get_reg_name_by_index(operands.rd).unwrap();
+        let rs1 = core.get_reg_name_by_index(operands.rs1).unwrap();
+        format!("{} {}, {}", name, rd, rs1)
+    } else {
+        format!("{} x{}, x{}", name, operands.rd, operands.rs1)
+    }
+}
+
 pub(crate) fn disasm_format_s(
     name: &str,
     operands: &FormatS,
@@ -108,3 +125,79 @@ pub(crate) fn disasm_format_j(
         format!("{} x{}, {}", name, operands.rd, operands.imm)
     }
 }
+
+fn amo_ordering_suffix(operands: &FormatAmo) -> &'static str {
+    match (operands.aq, operands.rl) {
+        (false, false) => "",
+        (true, false) => ".AQ",
+        (false, true) => ".RL",
+        (true, true) => ".AQRL",
+    }
+}
+
+pub(crate) fn disasm_format_amo(
+    name: &str,
+    operands: &FormatAmo,
+    core: &Core,
+    use_reg_name: bool,
+) -> String {
+    let suffix = amo_ordering_suffix(operands);
+    if use_reg_name {
+        let rd = core.get_reg_name_by_index(operands.rd).unwrap();
+        let rs1 = core.get_reg_name_by_index(operands.rs1).unwrap();
+        let rs2 = core.get_reg_name_by_index(operands.rs2).unwrap();
+        format!("{}{} {}, {}, ({})", name, suffix, rd, rs2, rs1)
+    } else {
+        format!(
+            "{}{} x{}, x{}, (x{})",
+            name, suffix, operands.rd, operands.rs2, operands.rs1
+        )
+    }
+}
+
+pub(crate) fn disasm_format_lr(
+    name: &str,
+    operands: &FormatAmo,
+    core: &Core,
+    use_reg_name: bool,
+) -> String {
+    let suffix = amo_ordering_suffix(operands);
+    if use_reg_name {
+        let rd = core.get_reg_name_by_index(operands.rd).unwrap();
+        let rs1 = core.get_reg_name_by_index(operands.rs1).unwrap();
+        format!("{}{} {}, ({})", name, suffix, rd, rs1)
+    } else {
+        format!("{}{} x{}, (x{})", name, suffix, operands.rd, operands.rs1)
+    }
+}
+
+/// Operand of a floating-point instruction
+pub(crate) enum FpOperand {
+    X(RegisterIndex),
+    F(RegisterIndex),
+    Imm(GprSigned),
+}
+
+/// Formats floating-point instructions, which mix f and x registers,
+/// e.g. "FCVT.W.S x10, f11" or "FLW f1, x2, 8"
+pub(crate) fn disasm_format_fp(name: &str, operands: &[FpOperand]) -> String {
+    let operands: Vec<String> = operands
+        .iter()
+        .map(|operand| match operand {
+            FpOperand::X(index) => format!("x{}", index),
+            FpOperand::F(index) => format!("f{}", index),
+            FpOperand::Imm(imm) => format!("{}", imm),
+        })
+        .collect();
+    format!("{} {}", name, operands.join(", "))
+}
+
+/// Replaces the mnemonic of the expanded instruction with the compressed one,
+/// e.g. "C_ADDI" and "ADDI x2, x2, -16" give "C.ADDI x2, x2, -16"
+pub(crate) fn disasm_compressed(name: &str, disasm: &str) -> String {
+    let mnemonic = name.replace('_', ".");
+    match disasm.split_once(' ') {
+        Some((_, operands)) => format!("{} {}", mnemonic, operands),
+        None => mnemonic,
+    }
+}
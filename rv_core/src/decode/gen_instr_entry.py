@@ -3,13 +3,32 @@
 # python3 gen_instr_entry.py ../execute/rv_system.rs >> tmp.txt
 # python3 gen_instr_entry.py ../execute/rv_zicsr.rs >> tmp.txt
 # python3 gen_instr_entry.py ../execute/rv32_i.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_m.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_a.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_f.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_d.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_zifencei.rs >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_i.rs inst_rv64_i >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_m.rs inst_rv64_m >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_a.rs inst_rv64_a >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_f.rs inst_rv64_f >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_d.rs inst_rv64_d >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_zba.rs inst_rv_zba >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_zba.rs inst_rv64_zba >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_zbb.rs inst_rv_zbb >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv64_zbb.rs inst_rv64_zbb >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_zbc.rs inst_rv_zbc >> tmp.txt
+# python3 gen_instr_entry.py ../execute/rv_zbs.rs inst_rv_zbs >> tmp.txt
+#
+# The optional second argument qualifies the MASK_/MATCH_ constants with
+# their module, for the RV64 constants that share names with the RV32 ones.
 #
 import sys
 import os
 import re
 
 
-def find_and_transform(file_path, module_name):
+def find_and_transform(file_path, module_name, prefix):
     # Define the pattern to search for
     pattern = r'pub\(crate\) fn execute_(\w+)'
 
@@ -32,8 +51,8 @@ def find_and_transform(file_path, module_name):
                 new_line = (
                     f'    InstructionsEntry {{\n'
                     f'        name: "{func_name}",\n'
-                    f'        mask: MASK_{func_name},\n'
-                    f'        match_val: MATCH_{func_name},\n'
+                    f'        mask: {prefix}MASK_{func_name},\n'
+                    f'        match_val: {prefix}MATCH_{func_name},\n'
                     f'        execute: {module_name}::execute_{
                         func_name.lower()},\n'
                     f'    }},\n'
@@ -47,8 +66,8 @@ def find_and_transform(file_path, module_name):
 
 
 # Check if a file path is provided as a command-line argument
-if len(sys.argv) != 2:
-    print("Usage: python script.py <file_path>")
+if len(sys.argv) not in (2, 3):
+    print("Usage: python script.py <file_path> [inst_module]")
     sys.exit(1)
 
 # Get the file path from the command-line argument
@@ -59,7 +78,9 @@ module_name = os.path.splitext(os.path.basename(file_path))[0]
 
 # print(module_name)
 
+prefix = f'{sys.argv[2]}::' if len(sys.argv) == 3 else ''
+
 # Call the function and print the results
-results = find_and_transform(file_path, module_name)
+results = find_and_transform(file_path, module_name, prefix)
 for result in results:
     print(result)
@@ -0,0 +1,14 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_C_JAL: u32 = 0x2001;
+pub(crate) const MASK_C_JAL: u32 = 0xe003;
+pub(crate) const MATCH_C_SLLI: u32 = 0x2;
+pub(crate) const MASK_C_SLLI: u32 = 0xf003;
+pub(crate) const MATCH_C_SRAI: u32 = 0x8401;
+pub(crate) const MASK_C_SRAI: u32 = 0xfc03;
+pub(crate) const MATCH_C_SRLI: u32 = 0x8001;
+pub(crate) const MASK_C_SRLI: u32 = 0xfc03;
@@ -0,0 +1,14 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_C_FLW: u32 = 0x6000;
+pub(crate) const MASK_C_FLW: u32 = 0xe003;
+pub(crate) const MATCH_C_FLWSP: u32 = 0x6002;
+pub(crate) const MASK_C_FLWSP: u32 = 0xe003;
+pub(crate) const MATCH_C_FSW: u32 = 0xe000;
+pub(crate) const MASK_C_FSW: u32 = 0xe003;
+pub(crate) const MATCH_C_FSWSP: u32 = 0xe002;
+pub(crate) const MASK_C_FSWSP: u32 = 0xe003;
@@ -0,0 +1,12 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_REV8: u32 = 0x69805013;
+pub(crate) const MASK_REV8: u32 = 0xfff0707f;
+pub(crate) const MATCH_RORI: u32 = 0x60005013;
+pub(crate) const MASK_RORI: u32 = 0xfe00707f;
+pub(crate) const MATCH_ZEXT_H: u32 = 0x8004033;
+pub(crate) const MASK_ZEXT_H: u32 = 0xfff0707f;
@@ -0,0 +1,14 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_BCLRI: u32 = 0x48001013;
+pub(crate) const MASK_BCLRI: u32 = 0xfe00707f;
+pub(crate) const MATCH_BEXTI: u32 = 0x48005013;
+pub(crate) const MASK_BEXTI: u32 = 0xfe00707f;
+pub(crate) const MATCH_BINVI: u32 = 0x68001013;
+pub(crate) const MASK_BINVI: u32 = 0xfe00707f;
+pub(crate) const MATCH_BSETI: u32 = 0x28001013;
+pub(crate) const MASK_BSETI: u32 = 0xfe00707f;
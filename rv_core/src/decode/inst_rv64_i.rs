@@ -0,0 +1,36 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_ADDIW: u32 = 0x1b;
+pub(crate) const MASK_ADDIW: u32 = 0x707f;
+pub(crate) const MATCH_ADDW: u32 = 0x3b;
+pub(crate) const MASK_ADDW: u32 = 0xfe00707f;
+pub(crate) const MATCH_LD: u32 = 0x3003;
+pub(crate) const MASK_LD: u32 = 0x707f;
+pub(crate) const MATCH_LWU: u32 = 0x6003;
+pub(crate) const MASK_LWU: u32 = 0x707f;
+pub(crate) const MATCH_SD: u32 = 0x3023;
+pub(crate) const MASK_SD: u32 = 0x707f;
+pub(crate) const MATCH_SLLI: u32 = 0x1013;
+pub(crate) const MASK_SLLI: u32 = 0xfc00707f;
+pub(crate) const MATCH_SLLIW: u32 = 0x101b;
+pub(crate) const MASK_SLLIW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SLLW: u32 = 0x103b;
+pub(crate) const MASK_SLLW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SRAI: u32 = 0x40005013;
+pub(crate) const MASK_SRAI: u32 = 0xfc00707f;
+pub(crate) const MATCH_SRAIW: u32 = 0x4000501b;
+pub(crate) const MASK_SRAIW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SRAW: u32 = 0x4000503b;
+pub(crate) const MASK_SRAW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SRLI: u32 = 0x5013;
+pub(crate) const MASK_SRLI: u32 = 0xfc00707f;
+pub(crate) const MATCH_SRLIW: u32 = 0x501b;
+pub(crate) const MASK_SRLIW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SRLW: u32 = 0x503b;
+pub(crate) const MASK_SRLW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SUBW: u32 = 0x4000003b;
+pub(crate) const MASK_SUBW: u32 = 0xfe00707f;
@@ -0,0 +1,16 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_ADD_UW: u32 = 0x800003b;
+pub(crate) const MASK_ADD_UW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SH1ADD_UW: u32 = 0x2000203b;
+pub(crate) const MASK_SH1ADD_UW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SH2ADD_UW: u32 = 0x2000403b;
+pub(crate) const MASK_SH2ADD_UW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SH3ADD_UW: u32 = 0x2000603b;
+pub(crate) const MASK_SH3ADD_UW: u32 = 0xfe00707f;
+pub(crate) const MATCH_SLLI_UW: u32 = 0x800101b;
+pub(crate) const MASK_SLLI_UW: u32 = 0xfc00707f;
@@ -0,0 +1,24 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_CLZW: u32 = 0x6000101b;
+pub(crate) const MASK_CLZW: u32 = 0xfff0707f;
+pub(crate) const MATCH_CPOPW: u32 = 0x6020101b;
+pub(crate) const MASK_CPOPW: u32 = 0xfff0707f;
+pub(crate) const MATCH_CTZW: u32 = 0x6010101b;
+pub(crate) const MASK_CTZW: u32 = 0xfff0707f;
+pub(crate) const MATCH_REV8: u32 = 0x6b805013;
+pub(crate) const MASK_REV8: u32 = 0xfff0707f;
+pub(crate) const MATCH_ROLW: u32 = 0x6000103b;
+pub(crate) const MASK_ROLW: u32 = 0xfe00707f;
+pub(crate) const MATCH_RORI: u32 = 0x60005013;
+pub(crate) const MASK_RORI: u32 = 0xfc00707f;
+pub(crate) const MATCH_RORIW: u32 = 0x6000501b;
+pub(crate) const MASK_RORIW: u32 = 0xfe00707f;
+pub(crate) const MATCH_RORW: u32 = 0x6000503b;
+pub(crate) const MASK_RORW: u32 = 0xfe00707f;
+pub(crate) const MATCH_ZEXT_H: u32 = 0x800403b;
+pub(crate) const MASK_ZEXT_H: u32 = 0xfff0707f;
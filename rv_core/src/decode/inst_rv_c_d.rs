@@ -0,0 +1,14 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_C_FLD: u32 = 0x2000;
+pub(crate) const MASK_C_FLD: u32 = 0xe003;
+pub(crate) const MATCH_C_FLDSP: u32 = 0x2002;
+pub(crate) const MASK_C_FLDSP: u32 = 0xe003;
+pub(crate) const MATCH_C_FSD: u32 = 0xa000;
+pub(crate) const MASK_C_FSD: u32 = 0xe003;
+pub(crate) const MATCH_C_FSDSP: u32 = 0xa002;
+pub(crate) const MASK_C_FSDSP: u32 = 0xe003;
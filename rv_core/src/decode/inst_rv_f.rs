@@ -0,0 +1,58 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_FADD_S: u32 = 0x53;
+pub(crate) const MASK_FADD_S: u32 = 0xfe00007f;
+pub(crate) const MATCH_FCLASS_S: u32 = 0xe0001053;
+pub(crate) const MASK_FCLASS_S: u32 = 0xfff0707f;
+pub(crate) const MATCH_FCVT_S_W: u32 = 0xd0000053;
+pub(crate) const MASK_FCVT_S_W: u32 = 0xfff0007f;
+pub(crate) const MATCH_FCVT_S_WU: u32 = 0xd0100053;
+pub(crate) const MASK_FCVT_S_WU: u32 = 0xfff0007f;
+pub(crate) const MATCH_FCVT_W_S: u32 = 0xc0000053;
+pub(crate) const MASK_FCVT_W_S: u32 = 0xfff0007f;
+pub(crate) const MATCH_FCVT_WU_S: u32 = 0xc0100053;
+pub(crate) const MASK_FCVT_WU_S: u32 = 0xfff0007f;
+pub(crate) const MATCH_FDIV_S: u32 = 0x18000053;
+pub(crate) const MASK_FDIV_S: u32 = 0xfe00007f;
+pub(crate) const MATCH_FEQ_S: u32 = 0xa0002053;
+pub(crate) const MASK_FEQ_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FLE_S: u32 = 0xa0000053;
+pub(crate) const MASK_FLE_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FLT_S: u32 = 0xa0001053;
+pub(crate) const MASK_FLT_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FLW: u32 = 0x2007;
+pub(crate) const MASK_FLW: u32 = 0x707f;
+pub(crate) const MATCH_FMADD_S: u32 = 0x43;
+pub(crate) const MASK_FMADD_S: u32 = 0x600007f;
+pub(crate) const MATCH_FMAX_S: u32 = 0x28001053;
+pub(crate) const MASK_FMAX_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FMIN_S: u32 = 0x28000053;
+pub(crate) const MASK_FMIN_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FMSUB_S: u32 = 0x47;
+pub(crate) const MASK_FMSUB_S: u32 = 0x600007f;
+pub(crate) const MATCH_FMUL_S: u32 = 0x10000053;
+pub(crate) const MASK_FMUL_S: u32 = 0xfe00007f;
+pub(crate) const MATCH_FMV_W_X: u32 = 0xf0000053;
+pub(crate) const MASK_FMV_W_X: u32 = 0xfff0707f;
+pub(crate) const MATCH_FMV_X_W: u32 = 0xe0000053;
+pub(crate) const MASK_FMV_X_W: u32 = 0xfff0707f;
+pub(crate) const MATCH_FNMADD_S: u32 = 0x4f;
+pub(crate) const MASK_FNMADD_S: u32 = 0x600007f;
+pub(crate) const MATCH_FNMSUB_S: u32 = 0x4b;
+pub(crate) const MASK_FNMSUB_S: u32 = 0x600007f;
+pub(crate) const MATCH_FSGNJ_S: u32 = 0x20000053;
+pub(crate) const MASK_FSGNJ_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FSGNJN_S: u32 = 0x20001053;
+pub(crate) const MASK_FSGNJN_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FSGNJX_S: u32 = 0x20002053;
+pub(crate) const MASK_FSGNJX_S: u32 = 0xfe00707f;
+pub(crate) const MATCH_FSQRT_S: u32 = 0x58000053;
+pub(crate) const MASK_FSQRT_S: u32 = 0xfff0007f;
+pub(crate) const MATCH_FSUB_S: u32 = 0x8000053;
+pub(crate) const MASK_FSUB_S: u32 = 0xfe00007f;
+pub(crate) const MATCH_FSW: u32 = 0x2027;
+pub(crate) const MASK_FSW: u32 = 0x707f;
@@ -0,0 +1,22 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_DIV: u32 = 0x2004033;
+pub(crate) const MASK_DIV: u32 = 0xfe00707f;
+pub(crate) const MATCH_DIVU: u32 = 0x2005033;
+pub(crate) const MASK_DIVU: u32 = 0xfe00707f;
+pub(crate) const MATCH_MUL: u32 = 0x2000033;
+pub(crate) const MASK_MUL: u32 = 0xfe00707f;
+pub(crate) const MATCH_MULH: u32 = 0x2001033;
+pub(crate) const MASK_MULH: u32 = 0xfe00707f;
+pub(crate) const MATCH_MULHSU: u32 = 0x2002033;
+pub(crate) const MASK_MULHSU: u32 = 0xfe00707f;
+pub(crate) const MATCH_MULHU: u32 = 0x2003033;
+pub(crate) const MASK_MULHU: u32 = 0xfe00707f;
+pub(crate) const MATCH_REM: u32 = 0x2006033;
+pub(crate) const MASK_REM: u32 = 0xfe00707f;
+pub(crate) const MATCH_REMU: u32 = 0x2007033;
+pub(crate) const MASK_REMU: u32 = 0xfe00707f;
@@ -4,6 +4,10 @@
 // LICENSE file in the root directory of this source tree.
 
 /* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_SFENCE_VMA: u32 = 0x12000073;
+pub(crate) const MASK_SFENCE_VMA: u32 = 0xfe007fff;
+pub(crate) const MATCH_SRET: u32 = 0x10200073;
+pub(crate) const MASK_SRET: u32 = 0xffffffff;
 pub(crate) const MATCH_MRET: u32 = 0x30200073;
 pub(crate) const MASK_MRET: u32 = 0xffffffff;
 pub(crate) const MATCH_WFI: u32 = 0x10500073;
@@ -0,0 +1,12 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_SH1ADD: u32 = 0x20002033;
+pub(crate) const MASK_SH1ADD: u32 = 0xfe00707f;
+pub(crate) const MATCH_SH2ADD: u32 = 0x20004033;
+pub(crate) const MASK_SH2ADD: u32 = 0xfe00707f;
+pub(crate) const MATCH_SH3ADD: u32 = 0x20006033;
+pub(crate) const MASK_SH3ADD: u32 = 0xfe00707f;
@@ -0,0 +1,36 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_ANDN: u32 = 0x40007033;
+pub(crate) const MASK_ANDN: u32 = 0xfe00707f;
+pub(crate) const MATCH_CLZ: u32 = 0x60001013;
+pub(crate) const MASK_CLZ: u32 = 0xfff0707f;
+pub(crate) const MATCH_CPOP: u32 = 0x60201013;
+pub(crate) const MASK_CPOP: u32 = 0xfff0707f;
+pub(crate) const MATCH_CTZ: u32 = 0x60101013;
+pub(crate) const MASK_CTZ: u32 = 0xfff0707f;
+pub(crate) const MATCH_MAX: u32 = 0xa006033;
+pub(crate) const MASK_MAX: u32 = 0xfe00707f;
+pub(crate) const MATCH_MAXU: u32 = 0xa007033;
+pub(crate) const MASK_MAXU: u32 = 0xfe00707f;
+pub(crate) const MATCH_MIN: u32 = 0xa004033;
+pub(crate) const MASK_MIN: u32 = 0xfe00707f;
+pub(crate) const MATCH_MINU: u32 = 0xa005033;
+pub(crate) const MASK_MINU: u32 = 0xfe00707f;
+pub(crate) const MATCH_ORC_B: u32 = 0x28705013;
+pub(crate) const MASK_ORC_B: u32 = 0xfff0707f;
+pub(crate) const MATCH_ORN: u32 = 0x40006033;
+pub(crate) const MASK_ORN: u32 = 0xfe00707f;
+pub(crate) const MATCH_ROL: u32 = 0x60001033;
+pub(crate) const MASK_ROL: u32 = 0xfe00707f;
+pub(crate) const MATCH_ROR: u32 = 0x60005033;
+pub(crate) const MASK_ROR: u32 = 0xfe00707f;
+pub(crate) const MATCH_SEXT_B: u32 = 0x60401013;
+pub(crate) const MASK_SEXT_B: u32 = 0xfff0707f;
+pub(crate) const MATCH_SEXT_H: u32 = 0x60501013;
+pub(crate) const MASK_SEXT_H: u32 = 0xfff0707f;
+pub(crate) const MATCH_XNOR: u32 = 0x40004033;
+pub(crate) const MASK_XNOR: u32 = 0xfe00707f;
@@ -0,0 +1,12 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_CLMUL: u32 = 0xa001033;
+pub(crate) const MASK_CLMUL: u32 = 0xfe00707f;
+pub(crate) const MATCH_CLMULH: u32 = 0xa003033;
+pub(crate) const MASK_CLMULH: u32 = 0xfe00707f;
+pub(crate) const MATCH_CLMULR: u32 = 0xa002033;
+pub(crate) const MASK_CLMULR: u32 = 0xfe00707f;
@@ -0,0 +1,14 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_BCLR: u32 = 0x48001033;
+pub(crate) const MASK_BCLR: u32 = 0xfe00707f;
+pub(crate) const MATCH_BEXT: u32 = 0x48005033;
+pub(crate) const MASK_BEXT: u32 = 0xfe00707f;
+pub(crate) const MATCH_BINV: u32 = 0x68001033;
+pub(crate) const MASK_BINV: u32 = 0xfe00707f;
+pub(crate) const MATCH_BSET: u32 = 0x28001033;
+pub(crate) const MASK_BSET: u32 = 0xfe00707f;
@@ -0,0 +1,8 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+/* Automatically generated by parse_opcodes */
+pub(crate) const MATCH_FENCE_I: u32 = 0x100f;
+pub(crate) const MASK_FENCE_I: u32 = 0x707f;
@@ -7,10 +7,35 @@ pub mod decoder;
 pub(crate) mod disassemble;
 
 mod all_instructions;
+mod compressed;
+mod inst_rv32_c;
+mod inst_rv32_c_f;
 mod inst_rv32_i;
+mod inst_rv32_zbb;
+mod inst_rv32_zbs;
+mod inst_rv64_a;
+mod inst_rv64_c;
+mod inst_rv64_d;
+mod inst_rv64_f;
+mod inst_rv64_i;
+mod inst_rv64_m;
+mod inst_rv64_zba;
+mod inst_rv64_zbb;
+mod inst_rv64_zbs;
+mod inst_rv_a;
+mod inst_rv_c;
+mod inst_rv_c_d;
+mod inst_rv_d;
+mod inst_rv_f;
 mod inst_rv_i;
+mod inst_rv_m;
 mod inst_rv_system;
+mod inst_rv_zba;
+mod inst_rv_zbb;
+mod inst_rv_zbc;
+mod inst_rv_zbs;
 mod inst_rv_zicsr;
+mod inst_rv_zifencei;
 
 use crate::{
     core::Core, GprSigned, GprUnsigned, MachineInstruction, ProgramCounter, RegisterIndex,
@@ -30,9 +55,38 @@ pub struct ExecutionReturnData {
     pub disasm: Option<String>,
 }
 
+#[derive(Clone, Copy)]
 pub struct DecodedInstruction {
     pub name: &'static str,
     pub execute: ExecuteInstructionFn,
+    /// The instruction passed to `execute`, compressed instructions are expanded to 32 bits
+    pub instruction: MachineInstruction,
+    /// Length in bytes of the fetched instruction, 2 or 4
+    pub length: ProgramCounter,
+}
+
+impl DecodedInstruction {
+    /// Returns the disassembly with the `c.` mnemonic for compressed instructions
+    pub fn format_disasm(&self, disasm: &str) -> String {
+        if self.length == COMPRESSED_INSTRUCTION_LENGTH {
+            disassemble::disasm_compressed(self.name, disasm)
+        } else {
+            disasm.to_string()
+        }
+    }
+}
+
+pub const COMPRESSED_INSTRUCTION_LENGTH: ProgramCounter = 2;
+pub const INSTRUCTION_LENGTH: ProgramCounter = 4;
+
+/// Returns the length in bytes of the instruction starting with the halfword `inst`
+pub fn instruction_length(inst: MachineInstruction) -> ProgramCounter {
+    // inst[1:0] is 0b11 for all 32-bit instructions
+    if inst & 0b11 == 0b11 {
+        INSTRUCTION_LENGTH
+    } else {
+        COMPRESSED_INSTRUCTION_LENGTH
+    }
 }
 
 struct InstructionsEntry {
@@ -49,6 +103,25 @@ pub struct FormatR {
     pub rs2: RegisterIndex,
 }
 
+/// R-type layout used by the A extension, with the acquire/release bits
+#[derive(Debug, PartialEq)]
+pub struct FormatAmo {
+    pub rd: RegisterIndex,
+    pub rs1: RegisterIndex,
+    pub rs2: RegisterIndex,
+    pub aq: bool,
+    pub rl: bool,
+}
+
+/// R4-type layout used by the fused multiply-add instructions
+#[derive(Debug, PartialEq)]
+pub struct FormatR4 {
+    pub rd: RegisterIndex,
+    pub rs1: RegisterIndex,
+    pub rs2: RegisterIndex,
+    pub rs3: RegisterIndex,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct FormatI {
     pub rd: RegisterIndex,
@@ -89,6 +162,30 @@ pub(crate) fn parse_r_type(inst: MachineInstruction) -> FormatR {
     }
 }
 
+pub(crate) fn parse_amo_type(inst: MachineInstruction) -> FormatAmo {
+    FormatAmo {
+        rd: ((inst >> 7) & 0x1f) as RegisterIndex,
+        rs1: ((inst >> 15) & 0x1f) as RegisterIndex,
+        rs2: ((inst >> 20) & 0x1f) as RegisterIndex,
+        aq: (inst >> 26) & 1 != 0,
+        rl: (inst >> 25) & 1 != 0,
+    }
+}
+
+pub(crate) fn parse_r4_type(inst: MachineInstruction) -> FormatR4 {
+    FormatR4 {
+        rd: ((inst >> 7) & 0x1f) as RegisterIndex,
+        rs1: ((inst >> 15) & 0x1f) as RegisterIndex,
+        rs2: ((inst >> 20) & 0x1f) as RegisterIndex,
+        rs3: ((inst >> 27) & 0x1f) as RegisterIndex,
+    }
+}
+
+/// rm field of the floating-point instructions, inst[14:12]
+pub(crate) fn parse_rm(inst: MachineInstruction) -> u32 {
+    (inst >> 12) & 0x7
+}
+
 // Constant representing the sign bit
 const SIGN_BIT: u32 = 0x8000_0000;
 
@@ -248,6 +345,7 @@ pub(crate) fn parse_j_type(inst: MachineInstruction) -> FormatJ {
 // println!("0xff9ff0ef: {:#034b}", 0xff9ff0ef as u32);
 //
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
 
@@ -264,6 +362,36 @@ mod tests {
         assert_eq!(parse_r_type(inst), expected);
     }
 
+    // amoswap.w.aq x12, x11, (x10) # amoswap.w rd, rs2, (rs1)
+    // 0x0cb5262f: 0b00001100101101010010011000101111
+    #[test]
+    fn test_parse_amo_type() {
+        let inst = 0b00001_1_0_01011_01010_010_01100_0101111;
+        let expected = FormatAmo {
+            rd: 12,
+            rs1: 10,
+            rs2: 11,
+            aq: true,
+            rl: false,
+        };
+        assert_eq!(parse_amo_type(inst), expected);
+    }
+
+    // fmadd.s f1, f2, f3, f4, rtz # fmadd.s rd, rs1, rs2, rs3, rm
+    // 0x203110c3: 0b00100000001100010001000011000011
+    #[test]
+    fn test_parse_r4_type() {
+        let inst = 0b00100_00_00011_00010_001_00001_1000011;
+        let expected = FormatR4 {
+            rd: 1,
+            rs1: 2,
+            rs2: 3,
+            rs3: 4,
+        };
+        assert_eq!(parse_r4_type(inst), expected);
+        assert_eq!(parse_rm(inst), 0b001);
+    }
+
     // addi x15, x16, -2 # addi rd, rs1, imm
     // 0xffe80793: 0b11111111111010000000011110010011
     #[test]
@@ -318,7 +446,7 @@ mod tests {
         let expected = FormatU {
             rd: 20,
             // imm[31:12]: 00000001001000110100
-            imm: 0b00000001001000110100_000000000000 as i32 as GprSigned,
+            imm: 0b00000001001000110100_000000000000 as GprSigned,
         };
         assert_eq!(expected.imm, 0x1234 << 12);
         assert_eq!(parse_u_type(inst), expected);
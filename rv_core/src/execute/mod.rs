@@ -4,6 +4,22 @@
 // LICENSE file in the root directory of this source tree.
 
 pub(crate) mod rv32_i;
+pub(crate) mod rv64_a;
+pub(crate) mod rv64_d;
+pub(crate) mod rv64_f;
+pub(crate) mod rv64_i;
+pub(crate) mod rv64_m;
+pub(crate) mod rv64_zba;
+pub(crate) mod rv64_zbb;
+pub(crate) mod rv_a;
+pub(crate) mod rv_d;
+pub(crate) mod rv_f;
 pub(crate) mod rv_i;
+pub(crate) mod rv_m;
 pub(crate) mod rv_system;
+pub(crate) mod rv_zba;
+pub(crate) mod rv_zbb;
+pub(crate) mod rv_zbc;
+pub(crate) mod rv_zbs;
 pub(crate) mod rv_zicsr;
+pub(crate) mod rv_zifencei;
@@ -6,7 +6,7 @@
 use tracing::trace;
 
 use crate::{core::Core, MachineInstruction, RvCoreError};
-use crate::{GprSigned, GprUnsigned};
+use crate::{GprSigned, GprUnsigned, Xlen};
 use cpu_peripherals::bus::Bus;
 
 use crate::decode::{self, disassemble::disasm_format_i, ExecutionReturnData};
@@ -16,7 +16,7 @@ pub(crate) fn execute_slli(
     _bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_shift_instruction(raw, core, disasm, "SLLI", |rs1, shamt| {
+    execute_shift_instruction(raw, core, disasm, "SLLI", |rs1, shamt, _| {
         (rs1 << shamt) as GprUnsigned
     })
 }
@@ -27,7 +27,7 @@ pub(crate) fn execute_srai(
     _bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_shift_instruction(raw, core, disasm, "SRAI", |rs1, shamt| {
+    execute_shift_instruction(raw, core, disasm, "SRAI", |rs1, shamt, _| {
         (rs1 >> shamt) as GprUnsigned
     })
 }
@@ -38,37 +38,39 @@ pub(crate) fn execute_srli(
     _bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_shift_instruction(raw, core, disasm, "SRLI", |rs1, shamt| {
-        (rs1 as GprUnsigned) >> (shamt as GprUnsigned)
+    execute_shift_instruction(raw, core, disasm, "SRLI", |rs1, shamt, xlen| {
+        xlen.truncate(rs1 as GprUnsigned) >> shamt
     })
 }
 
-fn check_shamt(shamt: GprUnsigned) -> Result<(), RvCoreError> {
-    if (shamt & 0x20) != 0 {
+fn check_shamt(shamt: GprUnsigned, xlen: Xlen) -> Result<(), RvCoreError> {
+    if shamt >= xlen.bits() as GprUnsigned {
         Err(RvCoreError::ShamtIsInvalid(shamt))
     } else {
         Ok(())
     }
 }
 
-/// Common function for shift instructions
-fn execute_shift_instruction(
+/// Common function for shift instructions, `operation` gets rs1 sign-extended
+/// from XLEN bits and the shift amount.
+/// Also used by the RV64 variants, whose shift amount is 6 bits wide.
+pub(crate) fn execute_shift_instruction(
     raw: MachineInstruction,
     core: &mut Core,
     disasm: bool,
     mnemonic: &'static str,
-    operation: fn(GprSigned, GprSigned) -> GprUnsigned,
+    operation: fn(GprSigned, u32, Xlen) -> GprUnsigned,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_i_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
 
-    let rs1 = core.read_register(operands.rs1)?;
+    let rs1 = core.read_register_signed(operands.rs1)?;
     let shamt = (operands.imm as GprUnsigned) & 0x3f;
-    check_shamt(shamt)?;
+    check_shamt(shamt, core.get_xlen())?;
 
-    let val = operation(rs1 as GprSigned, shamt as GprSigned);
+    let val = operation(rs1, shamt as u32, core.get_xlen());
 
-    let _ = core.write_register(operands.rd, val)?;
+    core.write_register(operands.rd, val)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
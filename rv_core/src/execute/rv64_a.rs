@@ -0,0 +1,219 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::mmu::AccessType;
+use crate::trap::{Exception, Trap};
+use crate::{core::Core, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{
+    self,
+    disassemble::{disasm_format_amo, disasm_format_lr},
+    ExecutionReturnData,
+};
+
+// The doubleword variants of rv_a, only decoded on RV64.
+
+pub(crate) fn execute_amoadd_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOADD.D", |mem, rs2| {
+        mem.wrapping_add(rs2)
+    })
+}
+
+pub(crate) fn execute_amoand_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOAND.D", |mem, rs2| mem & rs2)
+}
+
+pub(crate) fn execute_amomaxu_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOMAXU.D", |mem, rs2| mem.max(rs2))
+}
+
+pub(crate) fn execute_amomax_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOMAX.D", |mem, rs2| {
+        (mem as i64).max(rs2 as i64) as u64
+    })
+}
+
+pub(crate) fn execute_amominu_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOMINU.D", |mem, rs2| mem.min(rs2))
+}
+
+pub(crate) fn execute_amomin_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOMIN.D", |mem, rs2| {
+        (mem as i64).min(rs2 as i64) as u64
+    })
+}
+
+pub(crate) fn execute_amoor_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOOR.D", |mem, rs2| mem | rs2)
+}
+
+pub(crate) fn execute_amoswap_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOSWAP.D", |_mem, rs2| rs2)
+}
+
+pub(crate) fn execute_amoxor_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_d(raw, core, bus, disasm, "AMOXOR.D", |mem, rs2| mem ^ rs2)
+}
+
+pub(crate) fn execute_lr_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing LR.D with operands: {:?}", operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 8 != 0 {
+        core.set_trap(Trap::Exception(Exception::LoadAddressMisaligned), mem_addr)?;
+    } else if let Some(val) =
+        core.access_memory(mem_addr, 8, AccessType::Load, bus, |bus, address| {
+            bus.read_doubleword(address)
+        })?
+    {
+        core.set_reservation(mem_addr, 8);
+        core.write_register(operands.rd, val)?;
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_lr("LR.D", &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+pub(crate) fn execute_sc_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing SC.D with operands: {:?}", operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 8 != 0 {
+        core.set_trap(
+            Trap::Exception(Exception::StoreAmoAddressMisaligned),
+            mem_addr,
+        )?;
+    } else if core.take_reservation(mem_addr, 8) {
+        let rs2 = core.read_register(operands.rs2)?;
+        if core
+            .access_memory(mem_addr, 8, AccessType::Store, bus, |bus, address| {
+                bus.write_doubleword(address, rs2)
+            })?
+            .is_some()
+        {
+            core.write_register(operands.rd, 0)?;
+        }
+    } else {
+        // the reservation was lost, nothing is written
+        core.write_register(operands.rd, 1)?;
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_amo("SC.D", &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Common function for the read-modify-write AMO instructions.
+/// `operation` gets the loaded doubleword and rs2, and returns the doubleword to store.
+fn execute_amo_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+    mnemonic: &'static str,
+    operation: fn(u64, u64) -> u64,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 8 != 0 {
+        core.set_trap(
+            Trap::Exception(Exception::StoreAmoAddressMisaligned),
+            mem_addr,
+        )?;
+    } else {
+        let rs2 = core.read_register(operands.rs2)?;
+        let amo = |bus: &mut Bus, address| {
+            let val = bus.read_doubleword(address)?;
+            bus.write_doubleword(address, operation(val, rs2))?;
+            Ok(val)
+        };
+        if let Some(val) = core.access_memory(mem_addr, 8, AccessType::Store, bus, amo)? {
+            core.invalidate_reservation(mem_addr, 8);
+            core.write_register(operands.rd, val)?;
+        }
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_amo(mnemonic, &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
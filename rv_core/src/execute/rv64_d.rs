@@ -0,0 +1,100 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::execute::rv_f::{
+    check_fp_enabled, execute_fp_from_int, execute_fp_to_int, fp_return_data, write_fp,
+};
+use crate::softfloat::{self, F64};
+use crate::{core::Core, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{self, disassemble::FpOperand, ExecutionReturnData};
+
+// The conversions between double-precision and 64-bit integers and the moves
+// of the raw bits, only decoded on RV64.
+
+pub(crate) fn execute_fcvt_d_l(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.D.L", &F64, |fmt, rs1, rm| {
+        softfloat::from_i64(fmt, rs1 as i64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_d_lu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.D.LU", &F64, |fmt, rs1, rm| {
+        softfloat::from_u64(fmt, rs1, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_l_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.L.D", &F64, true, 64)
+}
+
+pub(crate) fn execute_fcvt_lu_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.LU.D", &F64, false, 64)
+}
+
+pub(crate) fn execute_fmv_d_x(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing FMV.D.X with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F64)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        write_fp(core, &F64, operands.rd, rs1)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FMV.D.X",
+        &[FpOperand::F(operands.rd), FpOperand::X(operands.rs1)],
+    )
+}
+
+pub(crate) fn execute_fmv_x_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing FMV.X.D with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F64)? {
+        let rs1 = core.read_fregister(operands.rs1)?;
+        core.write_register(operands.rd, rs1)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FMV.X.D",
+        &[FpOperand::X(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv_f::{execute_fp_from_int, execute_fp_to_int};
+use crate::softfloat::{self, F32};
+use crate::{core::Core, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// The conversions between single-precision and 64-bit integers, only decoded
+// on RV64.
+
+pub(crate) fn execute_fcvt_l_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.L.S", &F32, true, 64)
+}
+
+pub(crate) fn execute_fcvt_lu_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.LU.S", &F32, false, 64)
+}
+
+pub(crate) fn execute_fcvt_s_l(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.S.L", &F32, |fmt, rs1, rm| {
+        softfloat::from_i64(fmt, rs1 as i64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_s_lu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.S.LU", &F32, |fmt, rs1, rm| {
+        softfloat::from_u64(fmt, rs1, rm)
+    })
+}
@@ -0,0 +1,218 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::execute::rv32_i::execute_shift_instruction;
+use crate::execute::rv_i::{
+    execute_arithmetic_logical_compare_i, execute_load_i_type,
+    execute_shift_arithmetic_logical_compare_r,
+};
+use crate::{core::Core, GprSigned, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{self, disassemble::disasm_format_s, ExecutionReturnData};
+
+// The *W instructions operate on the low 32 bits of their operands and
+// sign-extend the 32-bit result to 64 bits.
+
+pub(crate) fn execute_addiw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_arithmetic_logical_compare_i(
+        raw,
+        core,
+        disasm,
+        |rs1, imm| (rs1 as i32).wrapping_add(imm as i32) as GprSigned as GprUnsigned,
+        "ADDIW",
+    )
+}
+
+pub(crate) fn execute_addw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs1 as i32).wrapping_add(rs2 as i32) as GprSigned as GprUnsigned,
+        "ADDW",
+    )
+}
+
+pub(crate) fn execute_ld(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_load_i_type(8, |val| val as GprUnsigned, "LD", raw, core, bus, disasm)
+}
+
+pub(crate) fn execute_lwu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_load_i_type(4, |val| val as GprUnsigned, "LWU", raw, core, bus, disasm)
+}
+
+pub(crate) fn execute_sd(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_s_type(raw);
+    trace!("Executing SD with operands: {:?}", operands);
+    let rs1 = core.read_register(operands.rs1)?;
+    let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
+
+    let rs2 = core.read_register(operands.rs2)?;
+
+    core.store(mem_addr, 8, rs2, bus)?;
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_s("SD", &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+pub(crate) fn execute_slli(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SLLI", |rs1, shamt, _| {
+        (rs1 << shamt) as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_slliw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SLLIW", |rs1, shamt, _| {
+        ((rs1 as u32) << shamt) as i32 as GprSigned as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_sllw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| ((rs1 as u32) << (rs2 & 0x1f)) as i32 as GprSigned as GprUnsigned,
+        "SLLW",
+    )
+}
+
+pub(crate) fn execute_srai(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SRAI", |rs1, shamt, _| {
+        (rs1 >> shamt) as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_sraiw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SRAIW", |rs1, shamt, _| {
+        ((rs1 as i32) >> shamt) as GprSigned as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_sraw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| ((rs1 as i32) >> (rs2 & 0x1f)) as GprSigned as GprUnsigned,
+        "SRAW",
+    )
+}
+
+pub(crate) fn execute_srli(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SRLI", |rs1, shamt, xlen| {
+        xlen.truncate(rs1 as GprUnsigned) >> shamt
+    })
+}
+
+pub(crate) fn execute_srliw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SRLIW", |rs1, shamt, _| {
+        ((rs1 as u32) >> shamt) as i32 as GprSigned as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_srlw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| ((rs1 as u32) >> (rs2 & 0x1f)) as i32 as GprSigned as GprUnsigned,
+        "SRLW",
+    )
+}
+
+pub(crate) fn execute_subw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs1 as i32).wrapping_sub(rs2 as i32) as GprSigned as GprUnsigned,
+        "SUBW",
+    )
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprSigned, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// The *W instructions divide the low 32 bits of their operands, the 32-bit
+// results are sign-extended. Division by zero and overflow are handled as
+// in rv_m, with XLEN = 32.
+
+pub(crate) fn execute_divuw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| match (rs1 as u32).checked_div(rs2 as u32) {
+            Some(quotient) => quotient as i32 as GprSigned as GprUnsigned,
+            None => GprUnsigned::MAX,
+        },
+        "DIVUW",
+    )
+}
+
+pub(crate) fn execute_divw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            if rs2 as i32 == 0 {
+                GprUnsigned::MAX
+            } else {
+                (rs1 as i32).wrapping_div(rs2 as i32) as GprSigned as GprUnsigned
+            }
+        },
+        "DIVW",
+    )
+}
+
+pub(crate) fn execute_mulw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs1 as i32).wrapping_mul(rs2 as i32) as GprSigned as GprUnsigned,
+        "MULW",
+    )
+}
+
+pub(crate) fn execute_remuw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| match (rs1 as u32).checked_rem(rs2 as u32) {
+            Some(remainder) => remainder as i32 as GprSigned as GprUnsigned,
+            None => rs1 as i32 as GprSigned as GprUnsigned,
+        },
+        "REMUW",
+    )
+}
+
+pub(crate) fn execute_remw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            if rs2 as i32 == 0 {
+                rs1 as i32 as GprSigned as GprUnsigned
+            } else {
+                (rs1 as i32).wrapping_rem(rs2 as i32) as GprSigned as GprUnsigned
+            }
+        },
+        "REMW",
+    )
+}
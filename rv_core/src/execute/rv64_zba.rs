@@ -0,0 +1,84 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv32_i::execute_shift_instruction;
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// The .uw variants zero-extend the low word of rs1 before shifting and adding
+
+pub(crate) fn execute_add_uw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add(rs1 as u32 as GprUnsigned),
+        "ADD.UW",
+    )
+}
+
+pub(crate) fn execute_sh1add_uw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as u32 as GprUnsigned) << 1),
+        "SH1ADD.UW",
+    )
+}
+
+pub(crate) fn execute_sh2add_uw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as u32 as GprUnsigned) << 2),
+        "SH2ADD.UW",
+    )
+}
+
+pub(crate) fn execute_sh3add_uw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as u32 as GprUnsigned) << 3),
+        "SH3ADD.UW",
+    )
+}
+
+pub(crate) fn execute_slli_uw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "SLLI.UW", |rs1, shamt, _| {
+        (rs1 as u32 as GprUnsigned) << shamt
+    })
+}
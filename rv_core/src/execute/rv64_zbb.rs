@@ -0,0 +1,105 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv32_i::execute_shift_instruction;
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::execute::rv_zbb::execute_unary_instruction;
+use crate::{core::Core, GprSigned, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// The word variants operate on the low 32 bits of rs1, rotations sign-extend
+// their 32-bit result
+
+pub(crate) fn execute_clzw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| (rs1 as u32).leading_zeros() as GprUnsigned,
+        "CLZW",
+    )
+}
+
+pub(crate) fn execute_cpopw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| (rs1 as u32).count_ones() as GprUnsigned,
+        "CPOPW",
+    )
+}
+
+pub(crate) fn execute_ctzw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| (rs1 as u32).trailing_zeros() as GprUnsigned,
+        "CTZW",
+    )
+}
+
+pub(crate) fn execute_rolw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            (rs1 as u32).rotate_left(rs2 as u32 & 0x1f) as i32 as GprSigned as GprUnsigned
+        },
+        "ROLW",
+    )
+}
+
+pub(crate) fn execute_roriw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "RORIW", |rs1, shamt, _| {
+        (rs1 as u32).rotate_right(shamt) as i32 as GprSigned as GprUnsigned
+    })
+}
+
+pub(crate) fn execute_rorw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            (rs1 as u32).rotate_right(rs2 as u32 & 0x1f) as i32 as GprSigned as GprUnsigned
+        },
+        "RORW",
+    )
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::mmu::AccessType;
+use crate::trap::{Exception, Trap};
+use crate::{core::Core, GprSigned, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{
+    self,
+    disassemble::{disasm_format_amo, disasm_format_lr},
+    ExecutionReturnData,
+};
+
+// The aq/rl bits only order memory accesses between harts, so they are
+// decoded and disassembled but otherwise have no effect on a single hart.
+
+pub(crate) fn execute_amoadd_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOADD.W", |mem, rs2| {
+        mem.wrapping_add(rs2)
+    })
+}
+
+pub(crate) fn execute_amoand_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOAND.W", |mem, rs2| mem & rs2)
+}
+
+pub(crate) fn execute_amomaxu_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOMAXU.W", |mem, rs2| mem.max(rs2))
+}
+
+pub(crate) fn execute_amomax_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOMAX.W", |mem, rs2| {
+        (mem as i32).max(rs2 as i32) as u32
+    })
+}
+
+pub(crate) fn execute_amominu_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOMINU.W", |mem, rs2| mem.min(rs2))
+}
+
+pub(crate) fn execute_amomin_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOMIN.W", |mem, rs2| {
+        (mem as i32).min(rs2 as i32) as u32
+    })
+}
+
+pub(crate) fn execute_amoor_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOOR.W", |mem, rs2| mem | rs2)
+}
+
+pub(crate) fn execute_amoswap_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOSWAP.W", |_mem, rs2| rs2)
+}
+
+pub(crate) fn execute_amoxor_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_amo_w(raw, core, bus, disasm, "AMOXOR.W", |mem, rs2| mem ^ rs2)
+}
+
+pub(crate) fn execute_lr_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing LR.W with operands: {:?}", operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 4 != 0 {
+        core.set_trap(Trap::Exception(Exception::LoadAddressMisaligned), mem_addr)?;
+    } else if let Some(val) =
+        core.access_memory(mem_addr, 4, AccessType::Load, bus, |bus, address| {
+            bus.read_word(address)
+        })?
+    {
+        core.set_reservation(mem_addr, 4);
+        core.write_register(operands.rd, val as i32 as GprSigned as GprUnsigned)?;
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_lr("LR.W", &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+pub(crate) fn execute_sc_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing SC.W with operands: {:?}", operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 4 != 0 {
+        core.set_trap(
+            Trap::Exception(Exception::StoreAmoAddressMisaligned),
+            mem_addr,
+        )?;
+    } else if core.take_reservation(mem_addr, 4) {
+        let rs2 = core.read_register(operands.rs2)?;
+        if core
+            .access_memory(mem_addr, 4, AccessType::Store, bus, |bus, address| {
+                bus.write_word(address, rs2 as u32)
+            })?
+            .is_some()
+        {
+            core.write_register(operands.rd, 0)?;
+        }
+    } else {
+        // the reservation was lost, nothing is written
+        core.write_register(operands.rd, 1)?;
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_amo("SC.W", &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Common function for the read-modify-write AMO instructions.
+/// `operation` gets the loaded word and rs2, and returns the word to store.
+fn execute_amo_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+    mnemonic: &'static str,
+    operation: fn(u32, u32) -> u32,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_amo_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+    let mem_addr = core.read_register(operands.rs1)?;
+
+    if mem_addr % 4 != 0 {
+        core.set_trap(
+            Trap::Exception(Exception::StoreAmoAddressMisaligned),
+            mem_addr,
+        )?;
+    } else {
+        let rs2 = core.read_register(operands.rs2)?;
+        let amo = |bus: &mut Bus, address| {
+            let val = bus.read_word(address)?;
+            bus.write_word(address, operation(val, rs2 as u32))?;
+            Ok(val)
+        };
+        if let Some(val) = core.access_memory(mem_addr, 4, AccessType::Store, bus, amo)? {
+            core.invalidate_reservation(mem_addr, 4);
+            core.write_register(operands.rd, val as i32 as GprSigned as GprUnsigned)?;
+        }
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_amo(mnemonic, &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
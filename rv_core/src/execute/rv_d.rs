@@ -0,0 +1,328 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::execute::rv_f::{
+    check_fp_enabled, execute_fp_classify, execute_fp_compare, execute_fp_from_int,
+    execute_fp_fused, execute_fp_r, execute_fp_rounded_r, execute_fp_sqrt, execute_fp_to_int,
+    fp_return_data, get_rounding_mode, read_fp, write_fp,
+};
+use crate::softfloat::{self, FloatFormat, F32, F64};
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{self, disassemble::FpOperand, ExecutionReturnData};
+
+// The D instructions share the F helpers, they additionally need misa.D.
+
+pub(crate) fn execute_fadd_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FADD.D", &F64, softfloat::add)
+}
+
+pub(crate) fn execute_fclass_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_classify(raw, core, disasm, "FCLASS.D", &F64)
+}
+
+pub(crate) fn execute_fcvt_d_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_convert(raw, core, disasm, "FCVT.D.S", &F32, &F64)
+}
+
+pub(crate) fn execute_fcvt_d_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.D.W", &F64, |fmt, rs1, rm| {
+        softfloat::from_i64(fmt, rs1 as i32 as i64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_d_wu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.D.WU", &F64, |fmt, rs1, rm| {
+        softfloat::from_u64(fmt, rs1 as u32 as u64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_s_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_convert(raw, core, disasm, "FCVT.S.D", &F64, &F32)
+}
+
+pub(crate) fn execute_fcvt_w_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.W.D", &F64, true, 32)
+}
+
+pub(crate) fn execute_fcvt_wu_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.WU.D", &F64, false, 32)
+}
+
+pub(crate) fn execute_fdiv_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FDIV.D", &F64, softfloat::div)
+}
+
+pub(crate) fn execute_feq_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FEQ.D", &F64, softfloat::eq)
+}
+
+pub(crate) fn execute_fld(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_i_type(raw);
+    trace!("Executing FLD with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F64)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
+        if let Some(val) = core.load(mem_addr, 8, bus)? {
+            write_fp(core, &F64, operands.rd, val)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        "FLD",
+        &[
+            FpOperand::F(operands.rd),
+            FpOperand::X(operands.rs1),
+            FpOperand::Imm(operands.imm),
+        ],
+    )
+}
+
+pub(crate) fn execute_fle_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FLE.D", &F64, softfloat::le)
+}
+
+pub(crate) fn execute_flt_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FLT.D", &F64, softfloat::lt)
+}
+
+pub(crate) fn execute_fmadd_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FMADD.D", &F64, false, false)
+}
+
+pub(crate) fn execute_fmax_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FMAX.D", &F64, softfloat::max)
+}
+
+pub(crate) fn execute_fmin_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FMIN.D", &F64, softfloat::min)
+}
+
+pub(crate) fn execute_fmsub_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FMSUB.D", &F64, false, true)
+}
+
+pub(crate) fn execute_fmul_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FMUL.D", &F64, softfloat::mul)
+}
+
+pub(crate) fn execute_fnmadd_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FNMADD.D", &F64, true, true)
+}
+
+pub(crate) fn execute_fnmsub_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FNMSUB.D", &F64, true, false)
+}
+
+pub(crate) fn execute_fsd(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_s_type(raw);
+    trace!("Executing FSD with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F64)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
+        let rs2 = core.read_fregister(operands.rs2)?;
+        core.store(mem_addr, 8, rs2, bus)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FSD",
+        &[
+            FpOperand::X(operands.rs1),
+            FpOperand::F(operands.rs2),
+            FpOperand::Imm(operands.imm),
+        ],
+    )
+}
+
+pub(crate) fn execute_fsgnjn_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJN.D", &F64, |fmt, rs1, rs2| {
+        ((rs1 & !fmt.sign_bit()) | (!rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsgnj_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJ.D", &F64, |fmt, rs1, rs2| {
+        ((rs1 & !fmt.sign_bit()) | (rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsgnjx_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJX.D", &F64, |fmt, rs1, rs2| {
+        (rs1 ^ (rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsqrt_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_sqrt(raw, core, disasm, "FSQRT.D", &F64)
+}
+
+pub(crate) fn execute_fsub_d(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FSUB.D", &F64, softfloat::sub)
+}
+
+/// Common function for FCVT.S.D and FCVT.D.S.
+fn execute_fp_convert(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    from: &FloatFormat,
+    to: &FloatFormat,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, &F64)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let rs1 = read_fp(core, from, operands.rs1)?;
+            let (result, flags) = softfloat::convert(from, to, rs1, rm);
+            write_fp(core, to, operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[FpOperand::F(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
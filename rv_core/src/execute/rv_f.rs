@@ -0,0 +1,676 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::inst_csr_reg::CSR_FRM;
+use crate::softfloat::{self, ExceptionFlags, FloatFormat, RoundingMode, F32, F64};
+use crate::{
+    core::Core, FprUnsigned, GprSigned, GprUnsigned, MachineInstruction, RegisterIndex, RvCoreError,
+};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{
+    self,
+    disassemble::{disasm_format_fp, FpOperand},
+    ExecutionReturnData,
+};
+
+// All F instructions raise an illegal instruction exception while mstatus.FS
+// is Off. Writing an f register or raising a flag sets mstatus.FS to Dirty.
+//
+// The f registers are FLEN (64) bits wide, single-precision values are NaN-boxed
+// in them. The helpers below take the format, so that they are shared with rv_d.
+
+// rm value selecting the rounding mode held in frm
+const RM_DYNAMIC: u32 = 0b111;
+
+pub(crate) fn execute_fadd_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FADD.S", &F32, softfloat::add)
+}
+
+pub(crate) fn execute_fclass_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_classify(raw, core, disasm, "FCLASS.S", &F32)
+}
+
+pub(crate) fn execute_fcvt_s_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.S.W", &F32, |fmt, rs1, rm| {
+        softfloat::from_i64(fmt, rs1 as i32 as i64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_s_wu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_from_int(raw, core, disasm, "FCVT.S.WU", &F32, |fmt, rs1, rm| {
+        softfloat::from_u64(fmt, rs1 as u32 as u64, rm)
+    })
+}
+
+pub(crate) fn execute_fcvt_w_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.W.S", &F32, true, 32)
+}
+
+pub(crate) fn execute_fcvt_wu_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_to_int(raw, core, disasm, "FCVT.WU.S", &F32, false, 32)
+}
+
+pub(crate) fn execute_fdiv_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FDIV.S", &F32, softfloat::div)
+}
+
+pub(crate) fn execute_feq_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FEQ.S", &F32, softfloat::eq)
+}
+
+pub(crate) fn execute_fle_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FLE.S", &F32, softfloat::le)
+}
+
+pub(crate) fn execute_flt_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_compare(raw, core, disasm, "FLT.S", &F32, softfloat::lt)
+}
+
+pub(crate) fn execute_flw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_i_type(raw);
+    trace!("Executing FLW with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F32)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
+        if let Some(val) = core.load(mem_addr, 4, bus)? {
+            write_fp(core, &F32, operands.rd, val)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        "FLW",
+        &[
+            FpOperand::F(operands.rd),
+            FpOperand::X(operands.rs1),
+            FpOperand::Imm(operands.imm),
+        ],
+    )
+}
+
+pub(crate) fn execute_fmadd_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FMADD.S", &F32, false, false)
+}
+
+pub(crate) fn execute_fmax_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FMAX.S", &F32, softfloat::max)
+}
+
+pub(crate) fn execute_fmin_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FMIN.S", &F32, softfloat::min)
+}
+
+pub(crate) fn execute_fmsub_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FMSUB.S", &F32, false, true)
+}
+
+pub(crate) fn execute_fmul_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FMUL.S", &F32, softfloat::mul)
+}
+
+pub(crate) fn execute_fmv_w_x(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing FMV.W.X with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F32)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        write_fp(core, &F32, operands.rd, rs1 as u32 as u64)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FMV.W.X",
+        &[FpOperand::F(operands.rd), FpOperand::X(operands.rs1)],
+    )
+}
+
+pub(crate) fn execute_fmv_x_w(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing FMV.X.W with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F32)? {
+        // the bits are moved as they are, without checking the NaN-boxing,
+        // and sign-extended on RV64
+        let rs1 = core.read_fregister(operands.rs1)?;
+        core.write_register(operands.rd, rs1 as i32 as GprSigned as GprUnsigned)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FMV.X.W",
+        &[FpOperand::X(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
+
+pub(crate) fn execute_fnmadd_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FNMADD.S", &F32, true, true)
+}
+
+pub(crate) fn execute_fnmsub_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_fused(raw, core, disasm, "FNMSUB.S", &F32, true, false)
+}
+
+pub(crate) fn execute_fsgnjn_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJN.S", &F32, |fmt, rs1, rs2| {
+        ((rs1 & !fmt.sign_bit()) | (!rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsgnj_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJ.S", &F32, |fmt, rs1, rs2| {
+        ((rs1 & !fmt.sign_bit()) | (rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsgnjx_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_r(raw, core, disasm, "FSGNJX.S", &F32, |fmt, rs1, rs2| {
+        (rs1 ^ (rs2 & fmt.sign_bit()), 0)
+    })
+}
+
+pub(crate) fn execute_fsqrt_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_sqrt(raw, core, disasm, "FSQRT.S", &F32)
+}
+
+pub(crate) fn execute_fsub_s(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_fp_rounded_r(raw, core, disasm, "FSUB.S", &F32, softfloat::sub)
+}
+
+pub(crate) fn execute_fsw(
+    raw: MachineInstruction,
+    core: &mut Core,
+    bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_s_type(raw);
+    trace!("Executing FSW with operands: {:?}", operands);
+
+    if check_fp_enabled(core, &F32)? {
+        let rs1 = core.read_register(operands.rs1)?;
+        let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
+        // the low bits are stored as they are, without checking the NaN-boxing
+        let rs2 = core.read_fregister(operands.rs2)?;
+        core.store(mem_addr, 4, rs2, bus)?;
+    }
+
+    fp_return_data(
+        disasm,
+        "FSW",
+        &[
+            FpOperand::X(operands.rs1),
+            FpOperand::F(operands.rs2),
+            FpOperand::Imm(operands.imm),
+        ],
+    )
+}
+
+/// Returns false, with an illegal instruction trap set, while mstatus.FS is Off
+/// or the extension for `fmt` is disabled in misa. D instructions need misa.D.
+pub(crate) fn check_fp_enabled(core: &mut Core, fmt: &FloatFormat) -> Result<bool, RvCoreError> {
+    let mut enabled = core.is_fp_enabled()?;
+    if fmt.bits() == F64.bits() {
+        enabled &= core.is_extension_enabled('D')?;
+    }
+
+    if enabled {
+        Ok(true)
+    } else {
+        core.raise_illegal_instruction()?;
+        Ok(false)
+    }
+}
+
+/// Returns the rounding mode selected by the rm field, which is frm for the
+/// dynamic one. An illegal instruction trap is set if the mode is reserved.
+pub(crate) fn get_rounding_mode(
+    raw: MachineInstruction,
+    core: &mut Core,
+) -> Result<Option<RoundingMode>, RvCoreError> {
+    let rm = match decode::parse_rm(raw) {
+        RM_DYNAMIC => core.read_csr(CSR_FRM)? as u32,
+        rm => rm,
+    };
+
+    let mode = RoundingMode::from_bits(rm);
+    if mode.is_none() {
+        core.raise_illegal_instruction()?;
+    }
+    Ok(mode)
+}
+
+/// Reads an f register as a value of `fmt`. Narrower values are NaN-boxed,
+/// the canonical NaN is read instead if the upper bits are not all ones.
+pub(crate) fn read_fp(
+    core: &Core,
+    fmt: &FloatFormat,
+    index: RegisterIndex,
+) -> Result<u64, RvCoreError> {
+    let value = core.read_fregister(index)?;
+    let bits = fmt.bits();
+    if bits == FprUnsigned::BITS {
+        return Ok(value);
+    }
+
+    let box_mask = FprUnsigned::MAX << bits;
+    if value & box_mask == box_mask {
+        Ok(value & !box_mask)
+    } else {
+        Ok(fmt.canonical_nan())
+    }
+}
+
+/// Writes a value of `fmt` to an f register, NaN-boxing narrower values
+pub(crate) fn write_fp(
+    core: &mut Core,
+    fmt: &FloatFormat,
+    index: RegisterIndex,
+    value: u64,
+) -> Result<(), RvCoreError> {
+    let bits = fmt.bits();
+    let boxed = if bits == FprUnsigned::BITS {
+        value
+    } else {
+        (FprUnsigned::MAX << bits) | value
+    };
+    core.write_fregister(index, boxed)?;
+    core.set_fp_dirty()
+}
+
+pub(crate) fn fp_return_data(
+    disasm: bool,
+    mnemonic: &'static str,
+    operands: &[FpOperand],
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_fp(mnemonic, operands)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Common function for the rounded operations on two f registers.
+pub(crate) fn execute_fp_rounded_r(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    operation: fn(&FloatFormat, u64, u64, RoundingMode) -> (u64, ExceptionFlags),
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let rs1 = read_fp(core, fmt, operands.rs1)?;
+            let rs2 = read_fp(core, fmt, operands.rs2)?;
+            let (result, flags) = operation(fmt, rs1, rs2, rm);
+            write_fp(core, fmt, operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[
+            FpOperand::F(operands.rd),
+            FpOperand::F(operands.rs1),
+            FpOperand::F(operands.rs2),
+        ],
+    )
+}
+
+/// Common function for the operations on two f registers which do not round,
+/// the funct3 field selects the operation instead of a rounding mode.
+pub(crate) fn execute_fp_r(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    operation: fn(&FloatFormat, u64, u64) -> (u64, ExceptionFlags),
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        let rs1 = read_fp(core, fmt, operands.rs1)?;
+        let rs2 = read_fp(core, fmt, operands.rs2)?;
+        let (result, flags) = operation(fmt, rs1, rs2);
+        write_fp(core, fmt, operands.rd, result)?;
+        core.accrue_fflags(flags)?;
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[
+            FpOperand::F(operands.rd),
+            FpOperand::F(operands.rs1),
+            FpOperand::F(operands.rs2),
+        ],
+    )
+}
+
+/// Common function for FEQ, FLT and FLE, which write 1 or 0 to an x register.
+pub(crate) fn execute_fp_compare(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    operation: fn(&FloatFormat, u64, u64) -> (bool, ExceptionFlags),
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        let rs1 = read_fp(core, fmt, operands.rs1)?;
+        let rs2 = read_fp(core, fmt, operands.rs2)?;
+        let (result, flags) = operation(fmt, rs1, rs2);
+        core.write_register(operands.rd, result as GprUnsigned)?;
+        core.accrue_fflags(flags)?;
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[
+            FpOperand::X(operands.rd),
+            FpOperand::F(operands.rs1),
+            FpOperand::F(operands.rs2),
+        ],
+    )
+}
+
+/// Common function for the fused multiply-add instructions, which compute
+/// (+/-)(rs1 * rs2) (+/-) rs3 with a single rounding.
+pub(crate) fn execute_fp_fused(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    negate_product: bool,
+    negate_addend: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r4_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let mut rs1 = read_fp(core, fmt, operands.rs1)?;
+            let rs2 = read_fp(core, fmt, operands.rs2)?;
+            let mut rs3 = read_fp(core, fmt, operands.rs3)?;
+            if negate_product {
+                rs1 ^= fmt.sign_bit();
+            }
+            if negate_addend {
+                rs3 ^= fmt.sign_bit();
+            }
+            let (result, flags) = softfloat::mul_add(fmt, rs1, rs2, rs3, rm);
+            write_fp(core, fmt, operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[
+            FpOperand::F(operands.rd),
+            FpOperand::F(operands.rs1),
+            FpOperand::F(operands.rs2),
+            FpOperand::F(operands.rs3),
+        ],
+    )
+}
+
+/// Common function for the conversions from an f register to an x register.
+/// Out of range values saturate and raise the invalid flag. 32-bit results,
+/// even the unsigned ones, are sign-extended on RV64.
+pub(crate) fn execute_fp_to_int(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    signed: bool,
+    width: u32,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let rs1 = read_fp(core, fmt, operands.rs1)?;
+            let (result, flags) = softfloat::to_int(fmt, rs1, rm, signed, width);
+            let result = if width == 32 {
+                result as i32 as GprSigned as GprUnsigned
+            } else {
+                result
+            };
+            core.write_register(operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[FpOperand::X(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
+
+/// Common function for the conversions from an x register to an f register.
+pub(crate) fn execute_fp_from_int(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+    operation: fn(&FloatFormat, GprUnsigned, RoundingMode) -> (u64, ExceptionFlags),
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let rs1 = core.read_register(operands.rs1)?;
+            let (result, flags) = operation(fmt, rs1, rm);
+            write_fp(core, fmt, operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[FpOperand::F(operands.rd), FpOperand::X(operands.rs1)],
+    )
+}
+
+/// Common function for FSQRT.
+pub(crate) fn execute_fp_sqrt(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        if let Some(rm) = get_rounding_mode(raw, core)? {
+            let rs1 = read_fp(core, fmt, operands.rs1)?;
+            let (result, flags) = softfloat::sqrt(fmt, rs1, rm);
+            write_fp(core, fmt, operands.rd, result)?;
+            core.accrue_fflags(flags)?;
+        }
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[FpOperand::F(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
+
+/// Common function for FCLASS, which writes the class mask to an x register.
+pub(crate) fn execute_fp_classify(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    mnemonic: &'static str,
+    fmt: &FloatFormat,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+
+    if check_fp_enabled(core, fmt)? {
+        let rs1 = read_fp(core, fmt, operands.rs1)?;
+        core.write_register(operands.rd, softfloat::classify(fmt, rs1) as GprUnsigned)?;
+    }
+
+    fp_return_data(
+        disasm,
+        mnemonic,
+        &[FpOperand::X(operands.rd), FpOperand::F(operands.rs1)],
+    )
+}
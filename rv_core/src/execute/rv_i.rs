@@ -6,9 +6,12 @@
 use tracing::trace;
 
 use crate::trap::{Exception, Trap};
-use crate::GprSigned;
-use crate::{core::Core, GprUnsigned, MachineInstruction, ProgramCounter, RvCoreError};
-use cpu_peripherals::{bus::Bus, CpuPeripheralsError, DeviceAddress};
+use crate::{
+    core::{Core, PrivilegeMode},
+    GprUnsigned, MachineInstruction, ProgramCounter, RvCoreError,
+};
+use crate::{GprSigned, Xlen};
+use cpu_peripherals::bus::Bus;
 
 use crate::decode::{
     self,
@@ -29,7 +32,7 @@ pub(crate) fn execute_add(
         raw,
         core,
         disasm,
-        |rs1, rs2| rs1.wrapping_add(rs2) as GprUnsigned,
+        |rs1, rs2, _| rs1.wrapping_add(rs2) as GprUnsigned,
         "ADD",
     )
 }
@@ -59,7 +62,7 @@ pub(crate) fn execute_and(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 & rs2) as GprUnsigned,
+        |rs1, rs2, _| (rs1 & rs2) as GprUnsigned,
         "AND",
     )
 }
@@ -85,7 +88,7 @@ pub(crate) fn execute_auipc(
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_u_type(raw);
     trace!("Executing AUIPC with operands: {:?}", operands);
-    let rd_val = core.get_pc() + operands.imm as ProgramCounter;
+    let rd_val = core.get_pc().wrapping_add(operands.imm as ProgramCounter);
     core.write_register(operands.rd, rd_val as GprUnsigned)?;
 
     if disasm {
@@ -160,7 +163,7 @@ pub(crate) fn execute_ebreak(
     trace!("Executing EBREAK");
 
     // trigger trap
-    core.set_trap(Trap::Exception(Exception::Breakpoint), raw)?;
+    core.set_trap(Trap::Exception(Exception::Breakpoint), raw as GprUnsigned)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -180,8 +183,13 @@ pub(crate) fn execute_ecall(
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     trace!("Executing ECALL");
 
-    // trigger trap
-    core.set_trap(Trap::Exception(Exception::ECallFromMMode), raw)?;
+    // trigger trap, the cause tells the privilege mode it was called from
+    let exception = match core.get_privilege_mode() {
+        PrivilegeMode::User => Exception::ECallFromUMode,
+        PrivilegeMode::Supervisor => Exception::ECallFromSMode,
+        _ => Exception::ECallFromMMode,
+    };
+    core.set_trap(Trap::Exception(exception), raw as GprUnsigned)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -193,14 +201,49 @@ pub(crate) fn execute_ecall(
     }
 }
 
+// The hart sees its own loads and stores in program order and every access
+// reaches the bus before the next instruction, FENCE has nothing to order.
 pub(crate) fn execute_fence(
-    _raw: MachineInstruction,
+    raw: MachineInstruction,
     _core: &mut Core,
     _bus: &mut Bus,
-    _disasm: bool,
+    disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    todo!("Not implemented")
+    trace!("Executing FENCE");
+
+    if disasm {
+        // the predecessor and successor sets, fm 0b1000 is FENCE.TSO
+        let name = if raw >> 28 == 0b1000 {
+            "FENCE.TSO"
+        } else {
+            "FENCE"
+        };
+        let pred = fence_set((raw >> 24) & 0xf);
+        let succ = fence_set((raw >> 20) & 0xf);
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(format!("{} {}, {}", name, pred, succ)),
+        }))
+    } else {
+        Ok(None)
+    }
 }
+
+// Returns the letters of the I, O, R and W bits of a FENCE set
+fn fence_set(bits: MachineInstruction) -> String {
+    let set: String = "iorw"
+        .chars()
+        .enumerate()
+        .filter(|(i, _)| bits & (0x8 >> i) != 0)
+        .map(|(_, letter)| letter)
+        .collect();
+    if set.is_empty() {
+        "unknown".to_string()
+    } else {
+        set
+    }
+}
+
 pub(crate) fn execute_jal(
     raw: MachineInstruction,
     core: &mut Core,
@@ -210,9 +253,11 @@ pub(crate) fn execute_jal(
     let operands = decode::parse_j_type(raw);
     trace!("Executing JAL with operands: {:?}", operands);
     let pc = core.get_pc();
-    let next_pc = pc.wrapping_add(4);
+    let next_pc = core.get_next_pc();
     let new_pc = pc.wrapping_add(operands.imm as GprUnsigned as ProgramCounter);
-    core.write_register(operands.rd, next_pc as GprUnsigned)?;
+    if core.check_jump_target(new_pc)? {
+        core.write_register(operands.rd, next_pc as GprUnsigned)?;
+    }
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -235,11 +280,14 @@ pub(crate) fn execute_jalr(
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_i_type(raw);
     trace!("Executing JALR with operands: {:?}", operands);
-    let next_pc = core.get_pc().wrapping_add(4);
+    let next_pc = core.get_next_pc();
 
     let rs1 = core.read_register(operands.rs1).unwrap();
-    let new_pc = rs1.wrapping_add(operands.imm as GprUnsigned);
-    core.write_register(operands.rd, next_pc as GprUnsigned)?;
+    // the lowest bit of the target is cleared
+    let new_pc = rs1.wrapping_add(operands.imm as GprUnsigned) & !1;
+    if core.check_jump_target(new_pc as ProgramCounter)? {
+        core.write_register(operands.rd, next_pc as GprUnsigned)?;
+    }
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -261,11 +309,12 @@ pub(crate) fn execute_lb(
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     execute_load_i_type(
-        |addr| bus.read_byte(addr),
-        |val| (val as GprSigned) as GprUnsigned,
+        1,
+        |val| (val as i8 as GprSigned) as GprUnsigned,
         "LB",
         raw,
         core,
+        bus,
         disasm,
     )
 }
@@ -276,14 +325,7 @@ pub(crate) fn execute_lbu(
     bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_load_i_type(
-        |addr| bus.read_byte(addr),
-        |val| val as GprUnsigned,
-        "LBU",
-        raw,
-        core,
-        disasm,
-    )
+    execute_load_i_type(1, |val| val as GprUnsigned, "LBU", raw, core, bus, disasm)
 }
 pub(crate) fn execute_lh(
     raw: MachineInstruction,
@@ -292,11 +334,12 @@ pub(crate) fn execute_lh(
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     execute_load_i_type(
-        |addr| bus.read_halfword(addr),
-        |val| (val as GprSigned) as GprUnsigned,
+        2,
+        |val| (val as i16 as GprSigned) as GprUnsigned,
         "LH",
         raw,
         core,
+        bus,
         disasm,
     )
 }
@@ -307,14 +350,7 @@ pub(crate) fn execute_lhu(
     bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_load_i_type(
-        |addr| bus.read_halfword(addr),
-        |val| val as GprUnsigned,
-        "LHU",
-        raw,
-        core,
-        disasm,
-    )
+    execute_load_i_type(2, |val| val as GprUnsigned, "LHU", raw, core, bus, disasm)
 }
 pub(crate) fn execute_lui(
     raw: MachineInstruction,
@@ -342,11 +378,12 @@ pub(crate) fn execute_lw(
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     execute_load_i_type(
-        |addr| bus.read_word(addr),
-        |val| (val as GprSigned) as GprUnsigned,
+        4,
+        |val| (val as i32 as GprSigned) as GprUnsigned,
         "LW",
         raw,
         core,
+        bus,
         disasm,
     )
 }
@@ -361,7 +398,7 @@ pub(crate) fn execute_or(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 | rs2) as GprUnsigned,
+        |rs1, rs2, _| (rs1 | rs2) as GprUnsigned,
         "OR",
     )
 }
@@ -392,7 +429,7 @@ pub(crate) fn execute_sb(
 
     let rs2 = core.read_register(operands.rs2).unwrap();
 
-    bus.write_byte(mem_addr as DeviceAddress, rs2 as u8)?;
+    core.store(mem_addr, 1, rs2, bus)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -417,7 +454,7 @@ pub(crate) fn execute_sh(
 
     let rs2 = core.read_register(operands.rs2).unwrap();
 
-    bus.write_halfword(mem_addr as DeviceAddress, rs2 as u16)?;
+    core.store(mem_addr, 2, rs2, bus)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -439,7 +476,7 @@ pub(crate) fn execute_sll(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 << rs2) as GprUnsigned,
+        |rs1, rs2, xlen| (rs1 << xlen.shamt(rs2 as GprUnsigned)) as GprUnsigned,
         "SLL",
     )
 }
@@ -454,7 +491,7 @@ pub(crate) fn execute_slt(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 < rs2) as GprUnsigned,
+        |rs1, rs2, _| (rs1 < rs2) as GprUnsigned,
         "SLT",
     )
 }
@@ -499,7 +536,7 @@ pub(crate) fn execute_sltu(
         raw,
         core,
         disasm,
-        |rs1, rs2| ((rs1 as GprUnsigned) < (rs2 as GprUnsigned)) as GprUnsigned,
+        |rs1, rs2, _| ((rs1 as GprUnsigned) < (rs2 as GprUnsigned)) as GprUnsigned,
         "SLTU",
     )
 }
@@ -514,7 +551,7 @@ pub(crate) fn execute_sra(
         raw,
         core,
         disasm,
-        |rs1, rs2| ((rs1 as GprSigned) >> rs2) as GprUnsigned,
+        |rs1, rs2, xlen| (rs1 >> xlen.shamt(rs2 as GprUnsigned)) as GprUnsigned,
         "SRA",
     )
 }
@@ -529,7 +566,7 @@ pub(crate) fn execute_srl(
         raw,
         core,
         disasm,
-        |rs1, rs2| ((rs1 as GprUnsigned) >> rs2) as GprUnsigned,
+        |rs1, rs2, xlen| xlen.truncate(rs1 as GprUnsigned) >> xlen.shamt(rs2 as GprUnsigned),
         "SRL",
     )
 }
@@ -544,7 +581,7 @@ pub(crate) fn execute_sub(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 - rs2) as GprUnsigned,
+        |rs1, rs2, _| rs1.wrapping_sub(rs2) as GprUnsigned,
         "SUB",
     )
 }
@@ -562,7 +599,7 @@ pub(crate) fn execute_sw(
 
     let rs2 = core.read_register(operands.rs2).unwrap();
 
-    bus.write_word(mem_addr as DeviceAddress, rs2 as u32)?;
+    core.store(mem_addr, 4, rs2, bus)?;
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -584,7 +621,7 @@ pub(crate) fn execute_xor(
         raw,
         core,
         disasm,
-        |rs1, rs2| (rs1 ^ rs2) as GprUnsigned,
+        |rs1, rs2, _| (rs1 ^ rs2) as GprUnsigned,
         "XOR",
     )
 }
@@ -603,19 +640,19 @@ pub(crate) fn execute_xori(
     )
 }
 
-fn execute_shift_arithmetic_logical_compare_r(
+pub(crate) fn execute_shift_arithmetic_logical_compare_r(
     raw: MachineInstruction,
     core: &mut Core,
     disasm: bool,
-    operation: fn(GprSigned, GprSigned) -> GprUnsigned,
+    operation: fn(GprSigned, GprSigned, Xlen) -> GprUnsigned,
     mnemonic: &'static str,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_r_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
-    let rs1 = core.read_register(operands.rs1).unwrap() as GprSigned;
-    let rs2 = core.read_register(operands.rs2).unwrap() as GprSigned;
-    let value = operation(rs1, rs2);
-    let _ = core.write_register(operands.rd, value).unwrap();
+    let rs1 = core.read_register_signed(operands.rs1).unwrap();
+    let rs2 = core.read_register_signed(operands.rs2).unwrap();
+    let value = operation(rs1, rs2, core.get_xlen());
+    core.write_register(operands.rd, value).unwrap();
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -627,7 +664,7 @@ fn execute_shift_arithmetic_logical_compare_r(
     }
 }
 
-fn execute_arithmetic_logical_compare_i(
+pub(crate) fn execute_arithmetic_logical_compare_i(
     raw: MachineInstruction,
     core: &mut Core,
     disasm: bool,
@@ -636,10 +673,10 @@ fn execute_arithmetic_logical_compare_i(
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_i_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
-    let rs1 = core.read_register(operands.rs1).unwrap() as GprSigned;
+    let rs1 = core.read_register_signed(operands.rs1).unwrap();
     let imm = operands.imm;
     let value = operation(rs1, imm);
-    let _ = core.write_register(operands.rd, value).unwrap();
+    core.write_register(operands.rd, value).unwrap();
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -661,13 +698,14 @@ fn execute_branch_instruction(
     let operands = decode::parse_b_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
 
-    let rs1 = core.read_register(operands.rs1).unwrap() as GprSigned;
-    let rs2 = core.read_register(operands.rs2).unwrap() as GprSigned;
+    let rs1 = core.read_register_signed(operands.rs1).unwrap();
+    let rs2 = core.read_register_signed(operands.rs2).unwrap();
 
     let pc = if compare(rs1, rs2) {
         let new_pc = core
             .get_pc()
             .wrapping_add(operands.imm as GprUnsigned as ProgramCounter);
+        core.check_jump_target(new_pc)?;
         Some(new_pc as ProgramCounter)
     } else {
         None
@@ -686,33 +724,25 @@ fn execute_branch_instruction(
     }
 }
 
-trait BusAccessWidth {}
-
-impl BusAccessWidth for u8 {}
-impl BusAccessWidth for u16 {}
-impl BusAccessWidth for u32 {}
-impl BusAccessWidth for i8 {}
-impl BusAccessWidth for i16 {}
-impl BusAccessWidth for i32 {}
-
-fn execute_load_i_type<T>(
-    load_fn: impl Fn(DeviceAddress) -> Result<T, CpuPeripheralsError>,
-    convert_fn: impl Fn(T) -> GprUnsigned,
+/// Common function for the loads of `size` bytes, `convert_fn` extends the
+/// loaded bytes to XLEN
+pub(crate) fn execute_load_i_type(
+    size: GprUnsigned,
+    convert_fn: impl Fn(u64) -> GprUnsigned,
     mnemonic: &'static str,
     raw: MachineInstruction,
     core: &mut Core,
+    bus: &mut Bus,
     disasm: bool,
-) -> Result<Option<ExecutionReturnData>, RvCoreError>
-where
-    T: BusAccessWidth,
-{
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     let operands = decode::parse_i_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
     let rs1 = core.read_register(operands.rs1).unwrap();
     let mem_addr = rs1.wrapping_add(operands.imm as GprUnsigned);
 
-    let val = load_fn(mem_addr as DeviceAddress)?;
-    let _ = core.write_register(operands.rd, convert_fn(val)).unwrap();
+    if let Some(val) = core.load(mem_addr, size, bus)? {
+        core.write_register(operands.rd, convert_fn(val)).unwrap();
+    }
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -0,0 +1,171 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// Division by zero and signed overflow do not trap in RISC-V:
+//
+// | condition              | dividend    | divisor | DIVU       | REMU | DIV         | REM |
+// | ---------------------- | ----------- | ------- | ---------- | ---- | ----------- | --- |
+// | Division by zero       | x           | 0       | 2^XLEN - 1 | x    | -1          | x   |
+// | Overflow (signed only) | -2^(XLEN-1) | -1      | -          | -    | -2^(XLEN-1) | 0   |
+
+pub(crate) fn execute_div(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            if rs2 == 0 {
+                GprUnsigned::MAX
+            } else {
+                // -2^(XLEN-1) / -1 wraps back to -2^(XLEN-1)
+                rs1.wrapping_div(rs2) as GprUnsigned
+            }
+        },
+        "DIV",
+    )
+}
+
+pub(crate) fn execute_divu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            if rs2 == 0 {
+                GprUnsigned::MAX
+            } else {
+                xlen.truncate(rs1 as GprUnsigned) / xlen.truncate(rs2 as GprUnsigned)
+            }
+        },
+        "DIVU",
+    )
+}
+
+pub(crate) fn execute_mul(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| rs1.wrapping_mul(rs2) as GprUnsigned,
+        "MUL",
+    )
+}
+
+pub(crate) fn execute_mulh(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| ((rs1 as i128 * rs2 as i128) >> xlen.bits()) as GprUnsigned,
+        "MULH",
+    )
+}
+
+pub(crate) fn execute_mulhsu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            // rs1 is signed, rs2 is unsigned
+            let rs2 = xlen.truncate(rs2 as GprUnsigned);
+            ((rs1 as i128 * rs2 as i128) >> xlen.bits()) as GprUnsigned
+        },
+        "MULHSU",
+    )
+}
+
+pub(crate) fn execute_mulhu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            let rs1 = xlen.truncate(rs1 as GprUnsigned);
+            let rs2 = xlen.truncate(rs2 as GprUnsigned);
+            ((rs1 as u128 * rs2 as u128) >> xlen.bits()) as GprUnsigned
+        },
+        "MULHU",
+    )
+}
+
+pub(crate) fn execute_rem(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| {
+            if rs2 == 0 {
+                rs1 as GprUnsigned
+            } else {
+                // -2^(XLEN-1) % -1 wraps to 0
+                rs1.wrapping_rem(rs2) as GprUnsigned
+            }
+        },
+        "REM",
+    )
+}
+
+pub(crate) fn execute_remu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            if rs2 == 0 {
+                rs1 as GprUnsigned
+            } else {
+                xlen.truncate(rs1 as GprUnsigned) % xlen.truncate(rs2 as GprUnsigned)
+            }
+        },
+        "REMU",
+    )
+}
@@ -3,13 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use tracing::{trace, warn};
+use tracing::trace;
 
 use crate::inst_csr_reg::*;
 use crate::{
     core::{self, Core},
     csr,
-    decode::ExecutionReturnData,
+    decode::{self, ExecutionReturnData},
     MachineInstruction, ProgramCounter, RvCoreError,
 };
 use cpu_peripherals::bus::Bus;
@@ -27,6 +27,18 @@ pub(crate) fn execute_mret(
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     trace!("Executing MRET instruction");
+    // MRET is only legal in M-mode
+    if *core.get_privilege_mode() != core::PrivilegeMode::Machine {
+        core.raise_illegal_instruction()?;
+        if disasm {
+            return Ok(Some(ExecutionReturnData {
+                pc: None,
+                disasm: Some("MRET".to_string()),
+            }));
+        } else {
+            return Ok(None);
+        }
+    }
     let csr = core.get_csr_mut();
 
     let epc = csr.read(CSR_MEPC)?;
@@ -35,30 +47,86 @@ pub(crate) fn execute_mret(
     let mpie = (status & csr::MSTATUS_MPIE) >> 7; // (status >> 7) & 1;
     let mpp = (status & csr::MSTATUS_MPP) >> 11; //(status >> 11) & 0x3;
 
-    // MPRV (Modify PRiVilege) bit
-    let mprv = match core::get_privilege_mode((mpp >> 11) as u8) {
+    // MPRV (Modify PRiVilege) bit, cleared when returning below M-mode
+    let mprv = match core::get_privilege_mode(mpp as u8) {
         core::PrivilegeMode::Machine => (status & csr::MSTATUS_MPRV) >> 17,
         _ => 0,
     };
     // Override MIE[3] with MPIE[7], set MPIE[7] to 1, set MPP[12:11] to 0
-    // and override MPRV[17]
+    // (user mode) and override MPRV[17]
     let new_status = (status & !0x21888) | (mprv << 17) | (mpie << 3) | (1 << 7);
     csr.write(CSR_MSTATUS, new_status)?;
 
-    let privilege_mode = match mpp {
-        0 => core::PrivilegeMode::User,
-        1 => core::PrivilegeMode::Supervisor,
-        3 => core::PrivilegeMode::Machine,
-        _ => panic!(), // Shouldn't happen
+    core.set_privilege_mode(core::get_privilege_mode(mpp as u8));
+    core.clear_reservation();
+
+    let new_pc = epc as ProgramCounter;
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: Some(new_pc),
+            disasm: Some("MRET".to_string()),
+        }))
+    } else {
+        Ok(Some(ExecutionReturnData {
+            pc: Some(new_pc),
+            disasm: None,
+        }))
+    }
+}
+
+// Sets the pc to CSRs[sepc],
+// the privilege mode to CSRs[sstatus].SPP,
+// CSRs[sstatus].SIE to CSRs[sstatus].SPIE,
+// CSRs[sstatus].SPIE to 1 and CSRs[sstatus].SPP to 0;
+// and, as the new mode is below M, CSRs[mstatus].MPRV to 0.
+
+pub(crate) fn execute_sret(
+    _raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    trace!("Executing SRET instruction");
+    // SRET is illegal in U-mode, and in S-mode while mstatus.TSR is set
+    let status = core.get_csr_mut().read(CSR_MSTATUS)?;
+    let legal = match core.get_privilege_mode() {
+        core::PrivilegeMode::Machine => true,
+        core::PrivilegeMode::Supervisor => status & csr::MSTATUS_TSR == 0,
+        _ => false,
     };
+    if !legal {
+        core.raise_illegal_instruction()?;
+        if disasm {
+            return Ok(Some(ExecutionReturnData {
+                pc: None,
+                disasm: Some("SRET".to_string()),
+            }));
+        } else {
+            return Ok(None);
+        }
+    }
+    let csr = core.get_csr_mut();
+
+    let epc = csr.read(CSR_SEPC)?;
+    let spie = (status & csr::MSTATUS_SPIE) >> 5;
+    let spp = (status & csr::MSTATUS_SPP) >> 8;
 
-    core.set_privilege_mode(privilege_mode);
+    // Override SIE[1] with SPIE[5], set SPIE[5] to 1, set SPP[8] to 0
+    // (user mode) and clear MPRV[17]
+    let new_status = (status
+        & !(csr::MSTATUS_SIE | csr::MSTATUS_SPIE | csr::MSTATUS_SPP | csr::MSTATUS_MPRV))
+        | (spie << 1)
+        | csr::MSTATUS_SPIE;
+    csr.write(CSR_MSTATUS, new_status)?;
+
+    core.set_privilege_mode(core::get_privilege_mode(spp as u8));
+    core.clear_reservation();
 
     let new_pc = epc as ProgramCounter;
     if disasm {
         Ok(Some(ExecutionReturnData {
             pc: Some(new_pc),
-            disasm: Some("MRET".to_string()),
+            disasm: Some("SRET".to_string()),
         }))
     } else {
         Ok(Some(ExecutionReturnData {
@@ -68,15 +136,71 @@ pub(crate) fn execute_mret(
     }
 }
 
+// Orders the page table stores before it with the translations after it:
+// drops the TLB entries of the page at x[rs1], of the address space x[rs2]
+// or both, and all entries if rs1 and rs2 are x0.
+
+pub(crate) fn execute_sfence_vma(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_r_type(raw);
+    trace!("Executing SFENCE.VMA with operands: {:?}", operands);
+    // SFENCE.VMA is illegal in U-mode, and in S-mode while mstatus.TVM is set
+    let status = core.get_csr_mut().read(CSR_MSTATUS)?;
+    let legal = match core.get_privilege_mode() {
+        core::PrivilegeMode::Machine => true,
+        core::PrivilegeMode::Supervisor => status & csr::MSTATUS_TVM == 0,
+        _ => false,
+    };
+    if !legal {
+        core.raise_illegal_instruction()?;
+    } else {
+        let address = match operands.rs1 {
+            0 => None,
+            rs1 => Some(core.read_register(rs1)?),
+        };
+        let asid = match operands.rs2 {
+            0 => None,
+            rs2 => Some(core.read_register(rs2)?),
+        };
+        core.flush_tlb(address, asid);
+    }
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(format!("SFENCE.VMA x{}, x{}", operands.rs1, operands.rs2)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+// Stalls the hart until an interrupt is pending and enabled in mie, whether
+// or not it is globally enabled. It completes at once if one already is.
+
 pub(crate) fn execute_wfi(
     _raw: MachineInstruction,
-    _core: &mut Core,
+    core: &mut Core,
     _bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
     trace!("Executing WFI");
-
-    warn!("WFI not implemented");
+    // WFI is illegal in U-mode, and in S-mode while mstatus.TW is set
+    let status = core.get_csr_mut().read(CSR_MSTATUS)?;
+    let legal = match core.get_privilege_mode() {
+        core::PrivilegeMode::Machine => true,
+        core::PrivilegeMode::Supervisor => status & csr::MSTATUS_TW == 0,
+        _ => false,
+    };
+    if !legal {
+        core.raise_illegal_instruction()?;
+    } else if !core.is_interrupt_pending()? {
+        core.wait_for_interrupt();
+    }
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -0,0 +1,55 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+pub(crate) fn execute_sh1add(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as GprUnsigned) << 1),
+        "SH1ADD",
+    )
+}
+
+pub(crate) fn execute_sh2add(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as GprUnsigned) << 2),
+        "SH2ADD",
+    )
+}
+
+pub(crate) fn execute_sh3add(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs2 as GprUnsigned).wrapping_add((rs1 as GprUnsigned) << 3),
+        "SH3ADD",
+    )
+}
@@ -0,0 +1,335 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::execute::rv32_i::execute_shift_instruction;
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprSigned, GprUnsigned, MachineInstruction, RvCoreError, Xlen};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::{self, disassemble::disasm_format_unary, ExecutionReturnData};
+
+/// Rotates the low XLEN bits of `value` right by `amount` modulo XLEN
+fn rotate_right(value: GprSigned, amount: u32, xlen: Xlen) -> GprUnsigned {
+    match xlen {
+        Xlen::Rv32 => (value as u32).rotate_right(amount) as GprUnsigned,
+        Xlen::Rv64 => (value as GprUnsigned).rotate_right(amount),
+    }
+}
+
+pub(crate) fn execute_andn(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs1 & !rs2) as GprUnsigned,
+        "ANDN",
+    )
+}
+
+pub(crate) fn execute_clz(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, xlen| match xlen {
+            Xlen::Rv32 => (rs1 as u32).leading_zeros() as GprUnsigned,
+            Xlen::Rv64 => (rs1 as GprUnsigned).leading_zeros() as GprUnsigned,
+        },
+        "CLZ",
+    )
+}
+
+pub(crate) fn execute_cpop(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, xlen| xlen.truncate(rs1 as GprUnsigned).count_ones() as GprUnsigned,
+        "CPOP",
+    )
+}
+
+pub(crate) fn execute_ctz(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, xlen| match xlen {
+            Xlen::Rv32 => (rs1 as u32).trailing_zeros() as GprUnsigned,
+            Xlen::Rv64 => (rs1 as GprUnsigned).trailing_zeros() as GprUnsigned,
+        },
+        "CTZ",
+    )
+}
+
+pub(crate) fn execute_max(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| rs1.max(rs2) as GprUnsigned,
+        "MAX",
+    )
+}
+
+pub(crate) fn execute_maxu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            xlen.truncate(rs1 as GprUnsigned)
+                .max(xlen.truncate(rs2 as GprUnsigned))
+        },
+        "MAXU",
+    )
+}
+
+pub(crate) fn execute_min(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| rs1.min(rs2) as GprUnsigned,
+        "MIN",
+    )
+}
+
+pub(crate) fn execute_minu(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            xlen.truncate(rs1 as GprUnsigned)
+                .min(xlen.truncate(rs2 as GprUnsigned))
+        },
+        "MINU",
+    )
+}
+
+pub(crate) fn execute_orc_b(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| {
+            // each byte becomes 0xff if any of its bits is set, 0x00 otherwise
+            (0..8).fold(0, |value, byte| {
+                if (rs1 >> (byte * 8)) & 0xff != 0 {
+                    value | (0xff << (byte * 8))
+                } else {
+                    value
+                }
+            })
+        },
+        "ORC.B",
+    )
+}
+
+pub(crate) fn execute_orn(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| (rs1 | !rs2) as GprUnsigned,
+        "ORN",
+    )
+}
+
+pub(crate) fn execute_rev8(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, xlen| match xlen {
+            Xlen::Rv32 => (rs1 as u32).swap_bytes() as GprUnsigned,
+            Xlen::Rv64 => (rs1 as GprUnsigned).swap_bytes(),
+        },
+        "REV8",
+    )
+}
+
+pub(crate) fn execute_rol(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| rotate_right(rs1, xlen.bits() - xlen.shamt(rs2 as GprUnsigned), xlen),
+        "ROL",
+    )
+}
+
+pub(crate) fn execute_ror(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| rotate_right(rs1, xlen.shamt(rs2 as GprUnsigned), xlen),
+        "ROR",
+    )
+}
+
+pub(crate) fn execute_rori(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "RORI", rotate_right)
+}
+
+pub(crate) fn execute_sext_b(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| rs1 as i8 as GprSigned as GprUnsigned,
+        "SEXT.B",
+    )
+}
+
+pub(crate) fn execute_sext_h(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| rs1 as i16 as GprSigned as GprUnsigned,
+        "SEXT.H",
+    )
+}
+
+pub(crate) fn execute_xnor(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, _| !(rs1 ^ rs2) as GprUnsigned,
+        "XNOR",
+    )
+}
+
+pub(crate) fn execute_zext_h(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_unary_instruction(
+        raw,
+        core,
+        disasm,
+        |rs1, _| rs1 as u16 as GprUnsigned,
+        "ZEXT.H",
+    )
+}
+
+/// Common function for the instructions with a single source register,
+/// whose rs2 field is part of the opcode. `operation` gets rs1 sign-extended
+/// from XLEN bits.
+pub(crate) fn execute_unary_instruction(
+    raw: MachineInstruction,
+    core: &mut Core,
+    disasm: bool,
+    operation: fn(GprSigned, Xlen) -> GprUnsigned,
+    mnemonic: &'static str,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    let operands = decode::parse_i_type(raw);
+    trace!("Executing {} with operands: {:?}", mnemonic, operands);
+    let rs1 = core.read_register_signed(operands.rs1)?;
+    let value = operation(rs1, core.get_xlen());
+    core.write_register(operands.rd, value)?;
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some(disasm_format_unary(mnemonic, &operands, core, false)),
+        }))
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError, Xlen};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+/// Carry-less product of the XLEN-bit operands, the high XLEN bits are in
+/// the upper half of the result
+fn carry_less_multiply(rs1: GprUnsigned, rs2: GprUnsigned, xlen: Xlen) -> u128 {
+    let rs1 = xlen.truncate(rs1) as u128;
+    let rs2 = xlen.truncate(rs2);
+    (0..xlen.bits())
+        .filter(|i| (rs2 >> i) & 1 == 1)
+        .fold(0, |product, i| product ^ (rs1 << i))
+}
+
+pub(crate) fn execute_clmul(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            carry_less_multiply(rs1 as GprUnsigned, rs2 as GprUnsigned, xlen) as GprUnsigned
+        },
+        "CLMUL",
+    )
+}
+
+pub(crate) fn execute_clmulh(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            (carry_less_multiply(rs1 as GprUnsigned, rs2 as GprUnsigned, xlen) >> xlen.bits())
+                as GprUnsigned
+        },
+        "CLMULH",
+    )
+}
+
+pub(crate) fn execute_clmulr(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| {
+            (carry_less_multiply(rs1 as GprUnsigned, rs2 as GprUnsigned, xlen) >> (xlen.bits() - 1))
+                as GprUnsigned
+        },
+        "CLMULR",
+    )
+}
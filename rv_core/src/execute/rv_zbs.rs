@@ -0,0 +1,118 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use crate::execute::rv32_i::execute_shift_instruction;
+use crate::execute::rv_i::execute_shift_arithmetic_logical_compare_r;
+use crate::{core::Core, GprUnsigned, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+use crate::decode::ExecutionReturnData;
+
+// The bit index is rs2 or shamt modulo XLEN, the immediate forms share the
+// encoding and the shamt check of the shifts
+
+pub(crate) fn execute_bclr(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| (rs1 as GprUnsigned) & !(1 << xlen.shamt(rs2 as GprUnsigned)),
+        "BCLR",
+    )
+}
+
+pub(crate) fn execute_bclri(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "BCLRI", |rs1, shamt, _| {
+        (rs1 as GprUnsigned) & !(1 << shamt)
+    })
+}
+
+pub(crate) fn execute_bext(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| (rs1 as GprUnsigned >> xlen.shamt(rs2 as GprUnsigned)) & 1,
+        "BEXT",
+    )
+}
+
+pub(crate) fn execute_bexti(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "BEXTI", |rs1, shamt, _| {
+        (rs1 as GprUnsigned >> shamt) & 1
+    })
+}
+
+pub(crate) fn execute_binv(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| (rs1 as GprUnsigned) ^ (1 << xlen.shamt(rs2 as GprUnsigned)),
+        "BINV",
+    )
+}
+
+pub(crate) fn execute_binvi(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "BINVI", |rs1, shamt, _| {
+        (rs1 as GprUnsigned) ^ (1 << shamt)
+    })
+}
+
+pub(crate) fn execute_bset(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_arithmetic_logical_compare_r(
+        raw,
+        core,
+        disasm,
+        |rs1, rs2, xlen| (rs1 as GprUnsigned) | (1 << xlen.shamt(rs2 as GprUnsigned)),
+        "BSET",
+    )
+}
+
+pub(crate) fn execute_bseti(
+    raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    execute_shift_instruction(raw, core, disasm, "BSETI", |rs1, shamt, _| {
+        (rs1 as GprUnsigned) | (1 << shamt)
+    })
+}
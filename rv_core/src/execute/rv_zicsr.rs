@@ -5,9 +5,10 @@
 
 use tracing::trace;
 
+use crate::inst_csr_reg::{CSR_FCSR, CSR_FFLAGS, CSR_TIME, CSR_TIMEH};
 use crate::{
     core::Core,
-    csr::{CsrAddrType, CsrError},
+    csr::{Csr, CsrAddrType, CsrError},
     GprUnsigned, MachineInstruction, RvCoreError,
 };
 use cpu_peripherals::bus::Bus;
@@ -17,92 +18,197 @@ use crate::decode::{self, disassemble::disasm_format_i, ExecutionReturnData};
 pub(crate) fn execute_csrrc(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRC", |core, address, value| {
-        core.get_csr_mut().csrrc(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: false,
+        always_writes: false,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRC",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrc(address, value),
+    )
 }
 
 pub(crate) fn execute_csrrci(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRCI", |core, address, value| {
-        core.get_csr_mut().csrrc(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: true,
+        always_writes: false,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRCI",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrc(address, value),
+    )
 }
 
 pub(crate) fn execute_csrrs(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRS", |core, address, value| {
-        core.get_csr_mut().csrrs(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: false,
+        always_writes: false,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRS",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrs(address, value),
+    )
 }
 
 pub(crate) fn execute_csrrsi(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRSI", |core, address, value| {
-        core.get_csr_mut().csrrs(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: true,
+        always_writes: false,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRSI",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrs(address, value),
+    )
 }
 
 pub(crate) fn execute_csrrw(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRW", |core, address, value| {
-        core.get_csr_mut().csrrw(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: false,
+        always_writes: true,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRW",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrw(address, value),
+    )
 }
 
 pub(crate) fn execute_csrrwi(
     raw: MachineInstruction,
     core: &mut Core,
-    _bus: &mut Bus,
+    bus: &mut Bus,
     disasm: bool,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-    execute_csr_instruction(raw, core, disasm, "CSRRWI", |core, address, value| {
-        core.get_csr_mut().csrrw(address, value as u32)
-    })
+    let kind = CsrInstructionKind {
+        is_immediate: true,
+        always_writes: true,
+    };
+    execute_csr_instruction(
+        raw,
+        core,
+        bus,
+        disasm,
+        "CSRRWI",
+        kind,
+        |core, address, value| core.get_csr_mut().csrrw(address, value),
+    )
+}
+
+/// The operand and write behaviour of a CSR instruction
+struct CsrInstructionKind {
+    // the rs1 field is a 5-bit zero-extended uimm rather than a register
+    is_immediate: bool,
+    // CSRRW(I) write the CSR even with rs1=x0 or uimm=0, CSRRS(I) and
+    // CSRRC(I) then only read it
+    always_writes: bool,
 }
 
 /// Common function for executing CSR instructions.
 fn execute_csr_instruction<F>(
     raw: MachineInstruction,
     core: &mut Core,
+    bus: &Bus,
     disasm: bool,
     mnemonic: &'static str,
+    kind: CsrInstructionKind,
     operation: F,
 ) -> Result<Option<ExecutionReturnData>, RvCoreError>
 where
-    F: FnOnce(&mut Core, CsrAddrType, GprUnsigned) -> Result<u32, CsrError>,
+    F: FnOnce(&mut Core, CsrAddrType, GprUnsigned) -> Result<GprUnsigned, CsrError>,
 {
     let operands = decode::parse_i_type(raw);
     trace!("Executing {} with operands: {:?}", mnemonic, operands);
 
     let address = (operands.imm as GprUnsigned & 0xfff) as CsrAddrType;
-    let value = if operands.rs1 == 0 {
+    let value = if kind.is_immediate {
         operands.rs1 as GprUnsigned
     } else {
         core.read_register(operands.rs1)?
     };
+    let writes = kind.always_writes || operands.rs1 != 0;
+
+    // fflags, frm and fcsr are only accessible while mstatus.FS is not Off,
+    // the CSRs of a more privileged mode are not accessible at all and the
+    // read-only ones can not be written
+    let is_fp_csr = (CSR_FFLAGS..=CSR_FCSR).contains(&address);
+    let accessible = (!is_fp_csr || core.is_fp_enabled()?)
+        && core.has_csr_privilege(address)
+        && core.is_counter_accessible(address)?
+        && !core.is_trapped_by_tvm(address)?
+        && !(writes && Csr::is_read_only(address));
 
-    let old_val = operation(core, address, value)?;
-    core.write_register(operands.rd, old_val)?;
+    let result = if !accessible {
+        None
+    } else {
+        if address == CSR_TIME || address == CSR_TIMEH {
+            if let Some(mtime) = bus.mtime() {
+                core.set_time(mtime);
+            }
+        }
+        let result = if writes {
+            operation(core, address, value)
+        } else {
+            core.read_csr(address)
+        };
+        // the CSRs which are not implemented do not exist
+        result.ok()
+    };
+
+    if let Some(old_val) = result {
+        core.write_register(operands.rd, old_val)?;
+        if is_fp_csr && writes {
+            core.set_fp_dirty()?;
+        }
+    } else {
+        core.raise_illegal_instruction()?;
+    }
 
     if disasm {
         Ok(Some(ExecutionReturnData {
@@ -0,0 +1,32 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use tracing::trace;
+
+use crate::{core::Core, decode::ExecutionReturnData, MachineInstruction, RvCoreError};
+use cpu_peripherals::bus::Bus;
+
+// Stores always reach the bus before the next fetch, so the only thing that can
+// be stale is what the simulator cached from earlier fetches. FENCE.I tells it
+// to drop that, the rd, rs1 and imm fields are reserved and ignored.
+
+pub(crate) fn execute_fence_i(
+    _raw: MachineInstruction,
+    core: &mut Core,
+    _bus: &mut Bus,
+    disasm: bool,
+) -> Result<Option<ExecutionReturnData>, RvCoreError> {
+    trace!("Executing FENCE.I");
+    core.request_fence_i();
+
+    if disasm {
+        Ok(Some(ExecutionReturnData {
+            pc: None,
+            disasm: Some("FENCE.I".to_string()),
+        }))
+    } else {
+        Ok(None)
+    }
+}
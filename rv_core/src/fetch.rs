@@ -7,19 +7,57 @@
 
 use tracing::trace;
 
+use crate::core::Core;
+use crate::decode::{instruction_length, COMPRESSED_INSTRUCTION_LENGTH};
+use crate::mmu::AccessType;
 use crate::{MachineInstruction, ProgramCounter, RvCoreError};
-use cpu_peripherals::Device;
+use cpu_peripherals::bus::Bus;
 
 pub struct Fetcher;
 
 impl Fetcher {
+    /// Fetches the instruction at the virtual address `pc`. Returns None if
+    /// the fetch raised a page fault or an access fault, which is then the pending trap.
+    /// A halfword the bus cannot read, such as an unmapped one, raises an
+    /// instruction access fault.
     pub fn fetch(
         pc: ProgramCounter,
-        mem: &Box<dyn Device>,
-    ) -> Result<MachineInstruction, RvCoreError> {
+        core: &mut Core,
+        bus: &mut Bus,
+    ) -> Result<Option<MachineInstruction>, RvCoreError> {
         // Fetch the instruction from the bus
         trace!("Fetching instruction at PC: {:#010x}", pc);
-        let instr = mem.read_word(pc.try_into().unwrap()).expect("fetch failed");
-        Ok(instr)
+
+        // The instruction is fetched one halfword at a time: with the C extension
+        // the PC is only 2-byte aligned, so a 32-bit instruction may cross the
+        // boundary between two devices or two pages.
+        let read_halfword = |bus: &mut Bus, address| bus.read_halfword(address);
+        let Some(low) = core.access_memory(
+            pc,
+            COMPRESSED_INSTRUCTION_LENGTH,
+            AccessType::Fetch,
+            bus,
+            read_halfword,
+        )?
+        else {
+            return Ok(None);
+        };
+        let low = low as MachineInstruction;
+        if instruction_length(low) == COMPRESSED_INSTRUCTION_LENGTH {
+            return Ok(Some(low));
+        }
+
+        let Some(high) = core.access_memory(
+            pc.wrapping_add(2),
+            COMPRESSED_INSTRUCTION_LENGTH,
+            AccessType::Fetch,
+            bus,
+            read_halfword,
+        )?
+        else {
+            return Ok(None);
+        };
+        let high = high as MachineInstruction;
+        Ok(Some((high << 16) | low))
     }
 }
@@ -3,9 +3,9 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-// pub const CSR_FFLAGS: u16 = 0x1;
-// pub const CSR_FRM: u16 = 0x2;
-// pub const CSR_FCSR: u16 = 0x3;
+pub const CSR_FFLAGS: u16 = 0x1;
+pub const CSR_FRM: u16 = 0x2;
+pub const CSR_FCSR: u16 = 0x3;
 // pub const CSR_VSTART: u16 = 0x8;
 // pub const CSR_VXSAT: u16 = 0x9;
 // pub const CSR_VXRM: u16 = 0xa;
@@ -13,10 +13,10 @@
 // pub const CSR_SSP: u16 = 0x11;
 // pub const CSR_SEED: u16 = 0x15;
 // pub const CSR_JVT: u16 = 0x17;
-// pub const CSR_CYCLE: u16 = 0xc00;
-// pub const CSR_TIME: u16 = 0xc01;
-// pub const CSR_INSTRET: u16 = 0xc02;
-// pub const CSR_HPMCOUNTER3: u16 = 0xc03;
+pub const CSR_CYCLE: u16 = 0xc00;
+pub const CSR_TIME: u16 = 0xc01;
+pub const CSR_INSTRET: u16 = 0xc02;
+pub const CSR_HPMCOUNTER3: u16 = 0xc03;
 // pub const CSR_HPMCOUNTER4: u16 = 0xc04;
 // pub const CSR_HPMCOUNTER5: u16 = 0xc05;
 // pub const CSR_HPMCOUNTER6: u16 = 0xc06;
@@ -44,27 +44,27 @@
 // pub const CSR_HPMCOUNTER28: u16 = 0xc1c;
 // pub const CSR_HPMCOUNTER29: u16 = 0xc1d;
 // pub const CSR_HPMCOUNTER30: u16 = 0xc1e;
-// pub const CSR_HPMCOUNTER31: u16 = 0xc1f;
+pub const CSR_HPMCOUNTER31: u16 = 0xc1f;
 // pub const CSR_VL: u16 = 0xc20;
 // pub const CSR_VTYPE: u16 = 0xc21;
 // pub const CSR_VLENB: u16 = 0xc22;
-// pub const CSR_SSTATUS: u16 = 0x100;
+pub const CSR_SSTATUS: u16 = 0x100;
 // pub const CSR_SEDELEG: u16 = 0x102;
 // pub const CSR_SIDELEG: u16 = 0x103;
-// pub const CSR_SIE: u16 = 0x104;
-// pub const CSR_STVEC: u16 = 0x105;
-// pub const CSR_SCOUNTEREN: u16 = 0x106;
+pub const CSR_SIE: u16 = 0x104;
+pub const CSR_STVEC: u16 = 0x105;
+pub const CSR_SCOUNTEREN: u16 = 0x106;
 // pub const CSR_SENVCFG: u16 = 0x10a;
 // pub const CSR_SSTATEEN0: u16 = 0x10c;
 // pub const CSR_SSTATEEN1: u16 = 0x10d;
 // pub const CSR_SSTATEEN2: u16 = 0x10e;
 // pub const CSR_SSTATEEN3: u16 = 0x10f;
 // pub const CSR_SCOUNTINHIBIT: u16 = 0x120;
-// pub const CSR_SSCRATCH: u16 = 0x140;
-// pub const CSR_SEPC: u16 = 0x141;
-// pub const CSR_SCAUSE: u16 = 0x142;
-// pub const CSR_STVAL: u16 = 0x143;
-// pub const CSR_SIP: u16 = 0x144;
+pub const CSR_SSCRATCH: u16 = 0x140;
+pub const CSR_SEPC: u16 = 0x141;
+pub const CSR_SCAUSE: u16 = 0x142;
+pub const CSR_STVAL: u16 = 0x143;
+pub const CSR_SIP: u16 = 0x144;
 // pub const CSR_STIMECMP: u16 = 0x14d;
 // pub const CSR_SCTRCTL: u16 = 0x14e;
 // pub const CSR_SCTRSTATUS: u16 = 0x14f;
@@ -77,7 +77,7 @@
 // pub const CSR_SIREG6: u16 = 0x157;
 // pub const CSR_STOPEI: u16 = 0x15c;
 // pub const CSR_SCTRDEPTH: u16 = 0x15f;
-// pub const CSR_SATP: u16 = 0x180;
+pub const CSR_SATP: u16 = 0x180;
 // pub const CSR_SRMCFG: u16 = 0x181;
 // pub const CSR_SCONTEXT: u16 = 0x5a8;
 // pub const CSR_VSSTATUS: u16 = 0x200;
@@ -142,19 +142,19 @@
 // pub const CSR_MSCRATCHCSWL: u16 = 0x349;
 pub const CSR_MSTATUS: u16 = 0x300;
 pub const CSR_MISA: u16 = 0x301;
-// pub const CSR_MEDELEG: u16 = 0x302;
-// pub const CSR_MIDELEG: u16 = 0x303;
+pub const CSR_MEDELEG: u16 = 0x302;
+pub const CSR_MIDELEG: u16 = 0x303;
 pub const CSR_MIE: u16 = 0x304;
 pub const CSR_MTVEC: u16 = 0x305;
-// pub const CSR_MCOUNTEREN: u16 = 0x306;
+pub const CSR_MCOUNTEREN: u16 = 0x306;
 // pub const CSR_MVIEN: u16 = 0x308;
 // pub const CSR_MVIP: u16 = 0x309;
-// pub const CSR_MENVCFG: u16 = 0x30a;
+pub const CSR_MENVCFG: u16 = 0x30a;
 // pub const CSR_MSTATEEN0: u16 = 0x30c;
 // pub const CSR_MSTATEEN1: u16 = 0x30d;
 // pub const CSR_MSTATEEN2: u16 = 0x30e;
 // pub const CSR_MSTATEEN3: u16 = 0x30f;
-// pub const CSR_MCOUNTINHIBIT: u16 = 0x320;
+pub const CSR_MCOUNTINHIBIT: u16 = 0x320;
 pub const CSR_MSCRATCH: u16 = 0x340;
 pub const CSR_MEPC: u16 = 0x341;
 pub const CSR_MCAUSE: u16 = 0x342;
@@ -171,10 +171,10 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_MIREG5: u16 = 0x356;
 // pub const CSR_MIREG6: u16 = 0x357;
 // pub const CSR_MTOPEI: u16 = 0x35c;
-// pub const CSR_PMPCFG0: u16 = 0x3a0;
-// pub const CSR_PMPCFG1: u16 = 0x3a1;
-// pub const CSR_PMPCFG2: u16 = 0x3a2;
-// pub const CSR_PMPCFG3: u16 = 0x3a3;
+pub const CSR_PMPCFG0: u16 = 0x3a0;
+pub const CSR_PMPCFG1: u16 = 0x3a1;
+pub const CSR_PMPCFG2: u16 = 0x3a2;
+pub const CSR_PMPCFG3: u16 = 0x3a3;
 // pub const CSR_PMPCFG4: u16 = 0x3a4;
 // pub const CSR_PMPCFG5: u16 = 0x3a5;
 // pub const CSR_PMPCFG6: u16 = 0x3a6;
@@ -187,22 +187,22 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_PMPCFG13: u16 = 0x3ad;
 // pub const CSR_PMPCFG14: u16 = 0x3ae;
 // pub const CSR_PMPCFG15: u16 = 0x3af;
-// pub const CSR_PMPADDR0: u16 = 0x3b0;
-// pub const CSR_PMPADDR1: u16 = 0x3b1;
-// pub const CSR_PMPADDR2: u16 = 0x3b2;
-// pub const CSR_PMPADDR3: u16 = 0x3b3;
-// pub const CSR_PMPADDR4: u16 = 0x3b4;
-// pub const CSR_PMPADDR5: u16 = 0x3b5;
-// pub const CSR_PMPADDR6: u16 = 0x3b6;
-// pub const CSR_PMPADDR7: u16 = 0x3b7;
-// pub const CSR_PMPADDR8: u16 = 0x3b8;
-// pub const CSR_PMPADDR9: u16 = 0x3b9;
-// pub const CSR_PMPADDR10: u16 = 0x3ba;
-// pub const CSR_PMPADDR11: u16 = 0x3bb;
-// pub const CSR_PMPADDR12: u16 = 0x3bc;
-// pub const CSR_PMPADDR13: u16 = 0x3bd;
-// pub const CSR_PMPADDR14: u16 = 0x3be;
-// pub const CSR_PMPADDR15: u16 = 0x3bf;
+pub const CSR_PMPADDR0: u16 = 0x3b0;
+pub const CSR_PMPADDR1: u16 = 0x3b1;
+pub const CSR_PMPADDR2: u16 = 0x3b2;
+pub const CSR_PMPADDR3: u16 = 0x3b3;
+pub const CSR_PMPADDR4: u16 = 0x3b4;
+pub const CSR_PMPADDR5: u16 = 0x3b5;
+pub const CSR_PMPADDR6: u16 = 0x3b6;
+pub const CSR_PMPADDR7: u16 = 0x3b7;
+pub const CSR_PMPADDR8: u16 = 0x3b8;
+pub const CSR_PMPADDR9: u16 = 0x3b9;
+pub const CSR_PMPADDR10: u16 = 0x3ba;
+pub const CSR_PMPADDR11: u16 = 0x3bb;
+pub const CSR_PMPADDR12: u16 = 0x3bc;
+pub const CSR_PMPADDR13: u16 = 0x3bd;
+pub const CSR_PMPADDR14: u16 = 0x3be;
+pub const CSR_PMPADDR15: u16 = 0x3bf;
 // pub const CSR_PMPADDR16: u16 = 0x3c0;
 // pub const CSR_PMPADDR17: u16 = 0x3c1;
 // pub const CSR_PMPADDR18: u16 = 0x3c2;
@@ -264,9 +264,9 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_DPC: u16 = 0x7b1;
 // pub const CSR_DSCRATCH0: u16 = 0x7b2;
 // pub const CSR_DSCRATCH1: u16 = 0x7b3;
-// pub const CSR_MCYCLE: u16 = 0xb00;
-// pub const CSR_MINSTRET: u16 = 0xb02;
-// pub const CSR_MHPMCOUNTER3: u16 = 0xb03;
+pub const CSR_MCYCLE: u16 = 0xb00;
+pub const CSR_MINSTRET: u16 = 0xb02;
+pub const CSR_MHPMCOUNTER3: u16 = 0xb03;
 // pub const CSR_MHPMCOUNTER4: u16 = 0xb04;
 // pub const CSR_MHPMCOUNTER5: u16 = 0xb05;
 // pub const CSR_MHPMCOUNTER6: u16 = 0xb06;
@@ -294,10 +294,10 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_MHPMCOUNTER28: u16 = 0xb1c;
 // pub const CSR_MHPMCOUNTER29: u16 = 0xb1d;
 // pub const CSR_MHPMCOUNTER30: u16 = 0xb1e;
-// pub const CSR_MHPMCOUNTER31: u16 = 0xb1f;
+pub const CSR_MHPMCOUNTER31: u16 = 0xb1f;
 // pub const CSR_MCYCLECFG: u16 = 0x321;
 // pub const CSR_MINSTRETCFG: u16 = 0x322;
-// pub const CSR_MHPMEVENT3: u16 = 0x323;
+pub const CSR_MHPMEVENT3: u16 = 0x323;
 // pub const CSR_MHPMEVENT4: u16 = 0x324;
 // pub const CSR_MHPMEVENT5: u16 = 0x325;
 // pub const CSR_MHPMEVENT6: u16 = 0x326;
@@ -325,12 +325,12 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_MHPMEVENT28: u16 = 0x33c;
 // pub const CSR_MHPMEVENT29: u16 = 0x33d;
 // pub const CSR_MHPMEVENT30: u16 = 0x33e;
-// pub const CSR_MHPMEVENT31: u16 = 0x33f;
-// pub const CSR_MVENDORID: u16 = 0xf11;
-// pub const CSR_MARCHID: u16 = 0xf12;
-// pub const CSR_MIMPID: u16 = 0xf13;
-// pub const CSR_MHARTID: u16 = 0xf14;
-// pub const CSR_MCONFIGPTR: u16 = 0xf15;
+pub const CSR_MHPMEVENT31: u16 = 0x33f;
+pub const CSR_MVENDORID: u16 = 0xf11;
+pub const CSR_MARCHID: u16 = 0xf12;
+pub const CSR_MIMPID: u16 = 0xf13;
+pub const CSR_MHARTID: u16 = 0xf14;
+pub const CSR_MCONFIGPTR: u16 = 0xf15;
 // pub const CSR_MTOPI: u16 = 0xfb0;
 // pub const CSR_SIEH: u16 = 0x114;
 // pub const CSR_SIPH: u16 = 0x154;
@@ -349,10 +349,10 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_HSTATEEN1H: u16 = 0x61d;
 // pub const CSR_HSTATEEN2H: u16 = 0x61e;
 // pub const CSR_HSTATEEN3H: u16 = 0x61f;
-// pub const CSR_CYCLEH: u16 = 0xc80;
-// pub const CSR_TIMEH: u16 = 0xc81;
-// pub const CSR_INSTRETH: u16 = 0xc82;
-// pub const CSR_HPMCOUNTER3H: u16 = 0xc83;
+pub const CSR_CYCLEH: u16 = 0xc80;
+pub const CSR_TIMEH: u16 = 0xc81;
+pub const CSR_INSTRETH: u16 = 0xc82;
+pub const CSR_HPMCOUNTER3H: u16 = 0xc83;
 // pub const CSR_HPMCOUNTER4H: u16 = 0xc84;
 // pub const CSR_HPMCOUNTER5H: u16 = 0xc85;
 // pub const CSR_HPMCOUNTER6H: u16 = 0xc86;
@@ -380,13 +380,13 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_HPMCOUNTER28H: u16 = 0xc9c;
 // pub const CSR_HPMCOUNTER29H: u16 = 0xc9d;
 // pub const CSR_HPMCOUNTER30H: u16 = 0xc9e;
-// pub const CSR_HPMCOUNTER31H: u16 = 0xc9f;
-// pub const CSR_MSTATUSH: u16 = 0x310;
+pub const CSR_HPMCOUNTER31H: u16 = 0xc9f;
+pub const CSR_MSTATUSH: u16 = 0x310;
 // pub const CSR_MIDELEGH: u16 = 0x313;
 // pub const CSR_MIEH: u16 = 0x314;
 // pub const CSR_MVIENH: u16 = 0x318;
 // pub const CSR_MVIPH: u16 = 0x319;
-// pub const CSR_MENVCFGH: u16 = 0x31a;
+pub const CSR_MENVCFGH: u16 = 0x31a;
 // pub const CSR_MSTATEEN0H: u16 = 0x31c;
 // pub const CSR_MSTATEEN1H: u16 = 0x31d;
 // pub const CSR_MSTATEEN2H: u16 = 0x31e;
@@ -428,9 +428,9 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_MNCAUSE: u16 = 0x742;
 // pub const CSR_MNSTATUS: u16 = 0x744;
 // pub const CSR_MSECCFGH: u16 = 0x757;
-// pub const CSR_MCYCLEH: u16 = 0xb80;
-// pub const CSR_MINSTRETH: u16 = 0xb82;
-// pub const CSR_MHPMCOUNTER3H: u16 = 0xb83;
+pub const CSR_MCYCLEH: u16 = 0xb80;
+pub const CSR_MINSTRETH: u16 = 0xb82;
+pub const CSR_MHPMCOUNTER3H: u16 = 0xb83;
 // pub const CSR_MHPMCOUNTER4H: u16 = 0xb84;
 // pub const CSR_MHPMCOUNTER5H: u16 = 0xb85;
 // pub const CSR_MHPMCOUNTER6H: u16 = 0xb86;
@@ -458,4 +458,4 @@ pub const CSR_MIP: u16 = 0x344;
 // pub const CSR_MHPMCOUNTER28H: u16 = 0xb9c;
 // pub const CSR_MHPMCOUNTER29H: u16 = 0xb9d;
 // pub const CSR_MHPMCOUNTER30H: u16 = 0xb9e;
-// pub const CSR_MHPMCOUNTER31H: u16 = 0xb9f;
+pub const CSR_MHPMCOUNTER31H: u16 = 0xb9f;
@@ -0,0 +1,281 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// rv_core/src/isa.rs
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{GprUnsigned, RvCoreError, Xlen};
+
+/// The extensions a hart can be configured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Extension {
+    /// Base integer instruction set
+    I,
+    /// Integer multiplication and division
+    M,
+    /// Atomic instructions
+    A,
+    /// Single-precision floating-point
+    F,
+    /// Double-precision floating-point
+    D,
+    /// Compressed instructions
+    C,
+    /// Control and status register instructions
+    Zicsr,
+    /// Instruction-fetch fence
+    Zifencei,
+    /// Address generation
+    Zba,
+    /// Basic bit-manipulation
+    Zbb,
+    /// Carry-less multiplication
+    Zbc,
+    /// Single-bit instructions
+    Zbs,
+}
+
+impl Extension {
+    /// All extensions in the canonical order of an ISA string
+    pub const ALL: [Extension; 12] = [
+        Extension::I,
+        Extension::M,
+        Extension::A,
+        Extension::F,
+        Extension::D,
+        Extension::C,
+        Extension::Zicsr,
+        Extension::Zifencei,
+        Extension::Zba,
+        Extension::Zbb,
+        Extension::Zbc,
+        Extension::Zbs,
+    ];
+
+    /// Returns the name used in ISA strings, e.g. "m" or "zicsr"
+    pub fn name(self) -> &'static str {
+        match self {
+            Extension::I => "i",
+            Extension::M => "m",
+            Extension::A => "a",
+            Extension::F => "f",
+            Extension::D => "d",
+            Extension::C => "c",
+            Extension::Zicsr => "zicsr",
+            Extension::Zifencei => "zifencei",
+            Extension::Zba => "zba",
+            Extension::Zbb => "zbb",
+            Extension::Zbc => "zbc",
+            Extension::Zbs => "zbs",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Extension> {
+        Extension::ALL.into_iter().find(|ext| ext.name() == name)
+    }
+
+    /// Returns the bit of the extension in misa, 0 for the multi-letter ones
+    pub fn misa_bit(self) -> GprUnsigned {
+        match self.name().as_bytes() {
+            [letter] => 1 << (letter - b'a'),
+            _ => 0,
+        }
+    }
+
+    // The extensions an enabled extension depends on. The hart always
+    // implements the machine-level CSRs, which are only reachable with Zicsr.
+    fn implied(self) -> &'static [Extension] {
+        match self {
+            Extension::I | Extension::F => &[Extension::Zicsr],
+            Extension::D => &[Extension::F],
+            _ => &[],
+        }
+    }
+}
+
+/// XLEN and extensions of a hart, parsed from an ISA string such as
+/// `rv32imac_zicsr_zifencei_zba`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Isa {
+    xlen: Xlen,
+    extensions: HashSet<Extension>,
+}
+
+/// ISA of a hart when none is configured
+pub const DEFAULT_ISA: &str = "rv32imafdc_zicsr_zifencei_zba_zbb_zbc_zbs";
+
+impl Default for Isa {
+    fn default() -> Self {
+        DEFAULT_ISA.parse().unwrap()
+    }
+}
+
+impl Isa {
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        self.xlen = xlen;
+    }
+
+    pub fn is_extension_enabled(&self, extension: Extension) -> bool {
+        self.extensions.contains(&extension)
+    }
+
+    /// Enables or disables `extension`, enabling it also enables the
+    /// extensions it depends on
+    pub fn set_extension_enabled(&mut self, extension: Extension, enabled: bool) {
+        if enabled {
+            if self.extensions.insert(extension) {
+                for implied in extension.implied() {
+                    self.set_extension_enabled(*implied, true);
+                }
+            }
+        } else {
+            self.extensions.remove(&extension);
+        }
+    }
+
+    /// The enabled extensions in canonical order
+    pub fn extensions(&self) -> impl Iterator<Item = Extension> + '_ {
+        Extension::ALL
+            .into_iter()
+            .filter(|ext| self.is_extension_enabled(*ext))
+    }
+
+    /// Returns misa.Extensions, the bits of the enabled single-letter extensions
+    pub fn misa_extensions(&self) -> GprUnsigned {
+        self.extensions().fold(0, |bits, ext| bits | ext.misa_bit())
+    }
+}
+
+impl FromStr for Isa {
+    type Err = RvCoreError;
+
+    /// Parses `rv32`/`rv64`, the single-letter extensions starting with `i`
+    /// or `g`, then `_`-separated multi-letter extensions. Version numbers
+    /// are not supported.
+    fn from_str(isa: &str) -> Result<Self, Self::Err> {
+        let invalid = || RvCoreError::InvalidIsaString(isa.to_string());
+        let lower = isa.to_ascii_lowercase();
+        let (xlen, rest) = if let Some(rest) = lower.strip_prefix("rv32") {
+            (Xlen::Rv32, rest)
+        } else if let Some(rest) = lower.strip_prefix("rv64") {
+            (Xlen::Rv64, rest)
+        } else {
+            return Err(invalid());
+        };
+
+        let mut parts = rest.split('_');
+        let letters = parts.next().unwrap_or_default();
+        if !letters.starts_with(['i', 'g']) {
+            return Err(invalid());
+        }
+
+        let mut result = Isa {
+            xlen,
+            extensions: HashSet::new(),
+        };
+        for letter in letters.chars() {
+            let extensions: &[Extension] = match letter {
+                'g' => &[
+                    Extension::I,
+                    Extension::M,
+                    Extension::A,
+                    Extension::F,
+                    Extension::D,
+                    Extension::Zicsr,
+                    Extension::Zifencei,
+                ],
+                'b' => &[Extension::Zba, Extension::Zbb, Extension::Zbs],
+                _ => match Extension::from_name(letter.encode_utf8(&mut [0; 4])) {
+                    Some(extension) if extension.misa_bit() != 0 => &[extension],
+                    _ => return Err(invalid()),
+                },
+            };
+            for extension in extensions {
+                result.set_extension_enabled(*extension, true);
+            }
+        }
+        for name in parts {
+            match Extension::from_name(name) {
+                Some(extension) if extension.misa_bit() == 0 => {
+                    result.set_extension_enabled(extension, true)
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Isa {
+    /// Writes the canonical ISA string, e.g. `rv32imac_zicsr`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rv{}", self.xlen.bits())?;
+        for extension in self.extensions() {
+            if extension.misa_bit() == 0 {
+                write!(f, "_")?;
+            }
+            write!(f, "{}", extension.name())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_isa_string() {
+        let isa: Isa = "rv32imac_zicsr_zifencei_zba".parse().unwrap();
+        assert_eq!(isa.xlen(), Xlen::Rv32);
+        assert!(isa.is_extension_enabled(Extension::C));
+        assert!(isa.is_extension_enabled(Extension::Zba));
+        assert!(!isa.is_extension_enabled(Extension::F));
+        assert!(!isa.is_extension_enabled(Extension::Zbb));
+        // I, M, A and C
+        assert_eq!(isa.misa_extensions(), 0x1105);
+        assert_eq!(isa.to_string(), "rv32imac_zicsr_zifencei_zba");
+
+        // G expands, D implies F and F implies Zicsr
+        let isa: Isa = "RV64GC".parse().unwrap();
+        assert_eq!(isa.xlen(), Xlen::Rv64);
+        assert_eq!(isa.to_string(), "rv64imafdc_zicsr_zifencei");
+        let isa: Isa = "rv32id".parse().unwrap();
+        assert_eq!(isa.to_string(), "rv32ifd_zicsr");
+        // I implies Zicsr, the M-mode CSRs are always there
+        let isa: Isa = "rv32imac".parse().unwrap();
+        assert_eq!(isa.to_string(), "rv32imac_zicsr");
+
+        assert_eq!(Isa::default().misa_extensions(), 0x112d);
+    }
+
+    #[test]
+    fn test_invalid_isa_string() {
+        for isa in [
+            "",
+            "rv32",
+            "rv128i",
+            "rv32mi",
+            "rv32ix",
+            "rv32i_zfoo",
+            "rv32i_m",
+            "rv32i2p1",
+        ] {
+            assert_eq!(
+                isa.parse::<Isa>(),
+                Err(RvCoreError::InvalidIsaString(isa.to_string())),
+                "{}",
+                isa
+            );
+        }
+    }
+}
@@ -10,18 +10,69 @@ mod csr;
 pub mod decode;
 mod execute;
 pub mod fetch;
+pub mod isa;
+pub mod mmu;
+mod pmp;
+mod softfloat;
 pub mod trap;
 
 pub mod inst_cause;
 pub mod inst_csr_reg;
 
-pub type ProgramCounter = u32;
+pub type ProgramCounter = u64;
 pub type MachineInstruction = u32;
-pub type GprUnsigned = u32;
-pub type GprSigned = i32;
+pub type GprUnsigned = u64;
+pub type GprSigned = i64;
+pub type FprUnsigned = u64;
 
 pub type RegisterIndex = usize;
 
+/// Width of the x registers, the pc and most CSRs.
+/// Values are kept truncated to XLEN bits, the upper bits are zero on RV32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Xlen {
+    Rv32,
+    Rv64,
+}
+
+impl Xlen {
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::Rv32 => 32,
+            Xlen::Rv64 => 64,
+        }
+    }
+
+    /// Returns the low XLEN bits of `value`
+    pub fn truncate(self, value: GprUnsigned) -> GprUnsigned {
+        value & (GprUnsigned::MAX >> (GprUnsigned::BITS - self.bits()))
+    }
+
+    /// Returns the low XLEN bits of `value` as a signed number
+    pub fn sign_extend(self, value: GprUnsigned) -> GprSigned {
+        let shift = GprUnsigned::BITS - self.bits();
+        ((value << shift) as GprSigned) >> shift
+    }
+
+    /// Returns the shift amount held in the low log2(XLEN) bits of `value`
+    pub fn shamt(self, value: GprUnsigned) -> u32 {
+        (value as u32) & (self.bits() - 1)
+    }
+
+    /// Returns the most significant bit, e.g. the interrupt bit of mcause
+    pub fn msb(self) -> GprUnsigned {
+        1 << (self.bits() - 1)
+    }
+
+    /// Returns the encoding of misa.MXL
+    pub fn mxl(self) -> GprUnsigned {
+        match self {
+            Xlen::Rv32 => 1,
+            Xlen::Rv64 => 2,
+        }
+    }
+}
+
 use thiserror::Error;
 
 /// Define error types for the rv_core crate.
@@ -59,7 +110,10 @@ pub enum RvCoreError {
     UnimplementedInstruction(u32),
 
     #[error("Shamt is invalid: {0:#x}")]
-    ShamtIsInvalid(u32),
+    ShamtIsInvalid(GprUnsigned),
+
+    #[error("Invalid ISA string: {0}")]
+    InvalidIsaString(String),
 
     #[error("Invalid Trap mode: {0}")]
     InvalidTrapMode(u32),
@@ -0,0 +1,604 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// rv_core/src/mmu.rs
+
+use std::collections::HashMap;
+
+use tracing::trace;
+
+use crate::core::PrivilegeMode;
+use crate::csr::{self, Csr};
+use crate::inst_csr_reg::{CSR_MSTATUS, CSR_SATP};
+use crate::pmp;
+use crate::trap::Exception;
+use crate::{GprUnsigned, Xlen};
+use cpu_peripherals::{bus::Bus, DeviceAddress};
+
+/// The kind of memory access being translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessType {
+    Fetch,
+    Load,
+    /// Stores and AMOs
+    Store,
+}
+
+impl AccessType {
+    fn page_fault(self) -> Exception {
+        match self {
+            AccessType::Fetch => Exception::InstructionPageFault,
+            AccessType::Load => Exception::LoadPageFault,
+            AccessType::Store => Exception::StoreAmoPageFault,
+        }
+    }
+
+    pub(crate) fn misaligned(self) -> Exception {
+        match self {
+            AccessType::Fetch => Exception::InstructionAddressMisaligned,
+            AccessType::Load => Exception::LoadAddressMisaligned,
+            AccessType::Store => Exception::StoreAmoAddressMisaligned,
+        }
+    }
+
+    pub(crate) fn access_fault(self) -> Exception {
+        match self {
+            AccessType::Fetch => Exception::InstructionAccessFault,
+            AccessType::Load => Exception::LoadAccessFault,
+            AccessType::Store => Exception::StoreAmoAccessFault,
+        }
+    }
+}
+
+// Sv32 page table entry bits
+const PTE_V: u32 = 0x001;
+const PTE_R: u32 = 0x002;
+const PTE_W: u32 = 0x004;
+const PTE_X: u32 = 0x008;
+const PTE_U: u32 = 0x010;
+const PTE_A: u32 = 0x040;
+const PTE_D: u32 = 0x080;
+const PTE_PPN_SHIFT: u32 = 10;
+
+// satp fields on RV32
+const SATP32_MODE_SV32: GprUnsigned = 0x8000_0000;
+const SATP32_ASID_SHIFT: u32 = 22;
+const SATP32_ASID: GprUnsigned = 0x1ff;
+const SATP32_PPN: GprUnsigned = 0x003f_ffff;
+
+const PAGE_SHIFT: u32 = 12;
+/// Size of a page, 4 KiB
+pub const PAGE_SIZE: GprUnsigned = 1 << PAGE_SHIFT;
+// Sv32 has two levels of tables, indexed by 10 bits of the virtual page number
+const LEVELS: usize = 2;
+const VPN_BITS: u32 = 10;
+const VPN_MASK: GprUnsigned = 0x3ff;
+const PTE_SIZE: GprUnsigned = 4;
+
+// Entries kept in the TLB before it is emptied to make room
+const TLB_ENTRIES: usize = 64;
+
+/// A leaf PTE cached by the TLB
+#[derive(Debug, Clone, Copy)]
+struct TlbEntry {
+    pte: u32,
+    // 1 for a 4 MiB megapage, 0 for a 4 KiB page
+    level: usize,
+}
+
+/// Sv32 address translation, the leaf PTEs of the last walks are cached by
+/// virtual page number and ASID until SFENCE.VMA
+pub(crate) struct Mmu {
+    tlb: HashMap<(GprUnsigned, GprUnsigned), TlbEntry>,
+}
+
+impl Mmu {
+    pub fn new() -> Self {
+        Self {
+            tlb: HashMap::new(),
+        }
+    }
+
+    /// Drops the TLB entries of the page holding `address` and of the
+    /// address space `asid`, all of them if both are None (SFENCE.VMA)
+    pub fn flush(&mut self, address: Option<GprUnsigned>, asid: Option<GprUnsigned>) {
+        let vpn = address.map(|address| address >> PAGE_SHIFT);
+        self.tlb.retain(|&(entry_asid, entry_vpn), _| {
+            let page_matches = vpn.is_none_or(|vpn| vpn == entry_vpn);
+            let asid_matches = asid.is_none_or(|asid| asid & SATP32_ASID == entry_asid);
+            !(page_matches && asid_matches)
+        });
+    }
+
+    /// Translates the virtual `address` of an access made in the privilege
+    /// mode `privilege`. M-mode and the Bare mode of satp use physical
+    /// addresses. Returns the page fault or access fault to raise on failure.
+    pub fn translate(
+        &mut self,
+        address: GprUnsigned,
+        access: AccessType,
+        privilege: PrivilegeMode,
+        csr: &Csr,
+        bus: &mut Bus,
+    ) -> Result<GprUnsigned, Exception> {
+        let satp = csr.read(CSR_SATP).unwrap_or(0);
+        let mstatus = csr.read(CSR_MSTATUS).unwrap_or(0);
+        // Sv32 is the only translation mode, RV64 is always Bare
+        if privilege == PrivilegeMode::Machine
+            || csr.xlen() != Xlen::Rv32
+            || satp & SATP32_MODE_SV32 == 0
+        {
+            return Ok(address);
+        }
+
+        let asid = (satp >> SATP32_ASID_SHIFT) & SATP32_ASID;
+        let key = (asid, address >> PAGE_SHIFT);
+        if let Some(entry) = self.tlb.get(&key) {
+            // an entry whose A/D bits need an update is walked again
+            if Self::is_allowed(entry.pte, access, privilege, mstatus)
+                && entry.pte & Self::accessed_dirty(access) == Self::accessed_dirty(access)
+            {
+                return Ok(Self::physical_address(address, entry));
+            }
+        }
+
+        let entry = self.walk(address, access, privilege, csr, bus)?;
+        if self.tlb.len() >= TLB_ENTRIES {
+            self.tlb.clear();
+        }
+        self.tlb.insert(key, entry);
+        Ok(Self::physical_address(address, &entry))
+    }
+
+    /// Walks the two-level page table from satp.PPN and returns the leaf
+    /// PTE, with its A bit, and D bit for stores, set in memory. The PTE
+    /// accesses are checked by PMP as S-mode accesses.
+    fn walk(
+        &self,
+        address: GprUnsigned,
+        access: AccessType,
+        privilege: PrivilegeMode,
+        csr: &Csr,
+        bus: &mut Bus,
+    ) -> Result<TlbEntry, Exception> {
+        trace!("Walking the page table for {:#010x}", address);
+        let satp = csr.read(CSR_SATP).unwrap_or(0);
+        let mstatus = csr.read(CSR_MSTATUS).unwrap_or(0);
+        let pte_allowed = |pte_address: DeviceAddress, pte_access| {
+            let supervisor = PrivilegeMode::Supervisor;
+            pmp::is_allowed(
+                csr,
+                pte_address as GprUnsigned,
+                PTE_SIZE,
+                pte_access,
+                supervisor,
+            )
+        };
+        let mut table = (satp & SATP32_PPN) << PAGE_SHIFT;
+        let mut level = LEVELS - 1;
+        let (pte, pte_address) = loop {
+            let vpn = (address >> (PAGE_SHIFT + VPN_BITS * level as u32)) & VPN_MASK;
+            let pte_address = (table + vpn * PTE_SIZE) as DeviceAddress;
+            if !pte_allowed(pte_address, AccessType::Load) {
+                return Err(access.access_fault());
+            }
+            let pte = bus
+                .read_word(pte_address)
+                .map_err(|_| access.access_fault())?;
+
+            if pte & PTE_V == 0 || (pte & PTE_R == 0 && pte & PTE_W != 0) {
+                return Err(access.page_fault());
+            }
+            if pte & (PTE_R | PTE_X) != 0 {
+                break (pte, pte_address);
+            }
+            // a pointer to the next level, there is none after level 0
+            if level == 0 {
+                return Err(access.page_fault());
+            }
+            level -= 1;
+            table = ((pte >> PTE_PPN_SHIFT) as GprUnsigned) << PAGE_SHIFT;
+        };
+
+        if !Self::is_allowed(pte, access, privilege, mstatus) {
+            return Err(access.page_fault());
+        }
+        // a megapage must be aligned to 4 MiB
+        if level == 1 && (pte >> PTE_PPN_SHIFT) as GprUnsigned & VPN_MASK != 0 {
+            return Err(access.page_fault());
+        }
+
+        let updated = pte | Self::accessed_dirty(access);
+        if updated != pte {
+            if !pte_allowed(pte_address, AccessType::Store) {
+                return Err(access.access_fault());
+            }
+            bus.write_word(pte_address, updated)
+                .map_err(|_| access.access_fault())?;
+        }
+        Ok(TlbEntry {
+            pte: updated,
+            level,
+        })
+    }
+
+    /// The PTE bits an access sets: A, and D for stores
+    fn accessed_dirty(access: AccessType) -> u32 {
+        match access {
+            AccessType::Store => PTE_A | PTE_D,
+            _ => PTE_A,
+        }
+    }
+
+    /// Checks the R/W/X and U bits of a leaf PTE. S-mode only reaches U pages
+    /// with loads and stores while mstatus.SUM is set, loads also read
+    /// executable pages while mstatus.MXR is set.
+    fn is_allowed(
+        pte: u32,
+        access: AccessType,
+        privilege: PrivilegeMode,
+        mstatus: GprUnsigned,
+    ) -> bool {
+        let user_page = pte & PTE_U != 0;
+        let privilege_allowed = match privilege {
+            PrivilegeMode::User => user_page,
+            PrivilegeMode::Supervisor => {
+                !user_page || (access != AccessType::Fetch && mstatus & csr::MSTATUS_SUM != 0)
+            }
+            _ => true,
+        };
+        let mxr = mstatus & csr::MSTATUS_MXR != 0;
+        privilege_allowed
+            && match access {
+                AccessType::Fetch => pte & PTE_X != 0,
+                AccessType::Load => pte & PTE_R != 0 || (mxr && pte & PTE_X != 0),
+                AccessType::Store => pte & PTE_W != 0,
+            }
+    }
+
+    fn physical_address(address: GprUnsigned, entry: &TlbEntry) -> GprUnsigned {
+        let offset_bits = PAGE_SHIFT + VPN_BITS * entry.level as u32;
+        let ppn = (entry.pte >> PTE_PPN_SHIFT) as GprUnsigned;
+        let offset = address & ((1 << offset_bits) - 1);
+        ((ppn << PAGE_SHIFT) & !((1 << offset_bits) - 1)) | offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inst_csr_reg::{CSR_PMPADDR0, CSR_PMPCFG0};
+    use cpu_peripherals::{bus::DevicePointer, mem::Mem};
+
+    const ROOT_TABLE: GprUnsigned = 0x1000;
+    const LEAF_TABLE: GprUnsigned = 0x2000;
+    const SATP: GprUnsigned = SATP32_MODE_SV32 | (ROOT_TABLE >> PAGE_SHIFT);
+
+    fn pte(physical: GprUnsigned, flags: u32) -> u32 {
+        ((physical >> PAGE_SHIFT) as u32) << PTE_PPN_SHIFT | flags
+    }
+
+    /// Maps the page 0x0040_1000 to 0x5000 through a leaf table and the
+    /// megapage 0x0080_0000 to 0x0040_0000
+    fn create_bus(leaf_flags: u32) -> Bus {
+        let mut bus = Bus::new();
+        bus.add_device(0, 0x10_0000, DevicePointer::new(Mem::new(0x10_0000)))
+            .unwrap();
+        bus.write_word(ROOT_TABLE as DeviceAddress + 4, pte(LEAF_TABLE, PTE_V))
+            .unwrap();
+        bus.write_word(LEAF_TABLE as DeviceAddress + 4, pte(0x5000, leaf_flags))
+            .unwrap();
+        bus.write_word(
+            ROOT_TABLE as DeviceAddress + 8,
+            pte(0x40_0000, PTE_V | PTE_R | PTE_W),
+        )
+        .unwrap();
+        bus
+    }
+
+    fn create_csr(satp: GprUnsigned, mstatus: GprUnsigned) -> Csr {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_SATP, satp).unwrap();
+        csr.write(CSR_MSTATUS, mstatus).unwrap();
+        csr
+    }
+
+    fn translate(
+        mmu: &mut Mmu,
+        bus: &mut Bus,
+        address: GprUnsigned,
+        access: AccessType,
+        privilege: PrivilegeMode,
+        mstatus: GprUnsigned,
+    ) -> Result<GprUnsigned, Exception> {
+        mmu.translate(address, access, privilege, &create_csr(SATP, mstatus), bus)
+    }
+
+    #[test]
+    fn test_translate() {
+        let mut mmu = Mmu::new();
+        let mut bus = create_bus(PTE_V | PTE_R | PTE_X);
+        let supervisor = PrivilegeMode::Supervisor;
+
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1234,
+                AccessType::Load,
+                supervisor,
+                0
+            ),
+            Ok(0x5234)
+        );
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1234,
+                AccessType::Fetch,
+                supervisor,
+                0
+            ),
+            Ok(0x5234)
+        );
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1234,
+                AccessType::Store,
+                supervisor,
+                0
+            ),
+            Err(Exception::StoreAmoPageFault)
+        );
+        // the megapage keeps 22 bits of the virtual address
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x8a_bcde,
+                AccessType::Store,
+                supervisor,
+                0
+            ),
+            Ok(0x4a_bcde)
+        );
+        // nothing is mapped at 0
+        assert_eq!(
+            translate(&mut mmu, &mut bus, 0x0, AccessType::Fetch, supervisor, 0),
+            Err(Exception::InstructionPageFault)
+        );
+
+        // M-mode does not translate
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x0,
+                AccessType::Load,
+                PrivilegeMode::Machine,
+                0
+            ),
+            Ok(0)
+        );
+        // neither does Bare
+        assert_eq!(
+            mmu.translate(
+                0x123,
+                AccessType::Load,
+                supervisor,
+                &create_csr(0, 0),
+                &mut bus
+            ),
+            Ok(0x123)
+        );
+    }
+
+    #[test]
+    fn test_accessed_dirty() {
+        let mut mmu = Mmu::new();
+        let mut bus = create_bus(PTE_V | PTE_R | PTE_W);
+        let leaf = LEAF_TABLE as DeviceAddress + 4;
+
+        translate(
+            &mut mmu,
+            &mut bus,
+            0x40_1000,
+            AccessType::Load,
+            PrivilegeMode::Supervisor,
+            0,
+        )
+        .unwrap();
+        assert_eq!(bus.read_word(leaf).unwrap() & (PTE_A | PTE_D), PTE_A);
+        // the TLB entry is not dirty, the store walks again
+        translate(
+            &mut mmu,
+            &mut bus,
+            0x40_1000,
+            AccessType::Store,
+            PrivilegeMode::Supervisor,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            bus.read_word(leaf).unwrap() & (PTE_A | PTE_D),
+            PTE_A | PTE_D
+        );
+    }
+
+    #[test]
+    fn test_user_pages() {
+        let mut mmu = Mmu::new();
+        let mut bus = create_bus(PTE_V | PTE_X | PTE_U);
+
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Fetch,
+                PrivilegeMode::User,
+                0
+            ),
+            Ok(0x5000)
+        );
+        // the megapage is not a U page
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x80_0000,
+                AccessType::Load,
+                PrivilegeMode::User,
+                0
+            ),
+            Err(Exception::LoadPageFault)
+        );
+
+        // S-mode never executes U pages and only reads them with SUM
+        let supervisor = PrivilegeMode::Supervisor;
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Fetch,
+                supervisor,
+                0
+            ),
+            Err(Exception::InstructionPageFault)
+        );
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                0
+            ),
+            Err(Exception::LoadPageFault)
+        );
+        let sum = csr::MSTATUS_SUM;
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                sum
+            ),
+            Err(Exception::LoadPageFault)
+        );
+        // the page is execute-only, MXR makes it readable
+        let mxr = csr::MSTATUS_SUM | csr::MSTATUS_MXR;
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                mxr
+            ),
+            Ok(0x5000)
+        );
+    }
+
+    #[test]
+    fn test_tlb_flush() {
+        let mut mmu = Mmu::new();
+        let mut bus = create_bus(PTE_V | PTE_R);
+        let supervisor = PrivilegeMode::Supervisor;
+        translate(
+            &mut mmu,
+            &mut bus,
+            0x40_1000,
+            AccessType::Load,
+            supervisor,
+            0,
+        )
+        .unwrap();
+
+        // the TLB keeps the old mapping until SFENCE.VMA
+        bus.write_word(
+            LEAF_TABLE as DeviceAddress + 4,
+            pte(0x6000, PTE_V | PTE_R | PTE_A),
+        )
+        .unwrap();
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                0
+            ),
+            Ok(0x5000)
+        );
+        mmu.flush(Some(0x40_2000), None);
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                0
+            ),
+            Ok(0x5000)
+        );
+        mmu.flush(Some(0x40_1fff), Some(0));
+        assert_eq!(
+            translate(
+                &mut mmu,
+                &mut bus,
+                0x40_1000,
+                AccessType::Load,
+                supervisor,
+                0
+            ),
+            Ok(0x6000)
+        );
+
+        // a PTE read outside of memory is an access fault
+        mmu.flush(None, None);
+        let satp = SATP32_MODE_SV32 | 0x10_0000;
+        assert_eq!(
+            mmu.translate(
+                0,
+                AccessType::Store,
+                supervisor,
+                &create_csr(satp, 0),
+                &mut bus
+            ),
+            Err(Exception::StoreAmoAccessFault)
+        );
+    }
+
+    #[test]
+    fn test_pmp_checks_page_table_accesses() {
+        let mut mmu = Mmu::new();
+        let mut bus = create_bus(PTE_V | PTE_R);
+        let mut csr = create_csr(SATP, 0);
+        // only the 16 KiB from 0x4000 are accessible below M-mode
+        csr.write(CSR_PMPADDR0, (0x4000 >> 2) | 0x7ff).unwrap();
+        csr.write(CSR_PMPCFG0, 0x1f).unwrap();
+        assert_eq!(
+            mmu.translate(
+                0x40_1000,
+                AccessType::Load,
+                PrivilegeMode::Supervisor,
+                &csr,
+                &mut bus
+            ),
+            Err(Exception::LoadAccessFault)
+        );
+    }
+}
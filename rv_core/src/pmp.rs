@@ -0,0 +1,203 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// rv_core/src/pmp.rs
+
+use crate::core::PrivilegeMode;
+use crate::csr::Csr;
+use crate::mmu::AccessType;
+use crate::GprUnsigned;
+
+/// Number of PMP entries, configured by pmpcfg0-3 and pmpaddr0-15
+pub const PMP_ENTRIES: usize = 16;
+
+// Bits of the 8-bit configuration of an entry
+pub(crate) const PMP_R: u8 = 0x01;
+pub(crate) const PMP_W: u8 = 0x02;
+pub(crate) const PMP_X: u8 = 0x04;
+pub(crate) const PMP_A: u8 = 0x18;
+pub(crate) const PMP_L: u8 = 0x80;
+// Bits 5 and 6 are reserved
+pub(crate) const PMP_CFG_WRITABLE: u8 = PMP_R | PMP_W | PMP_X | PMP_A | PMP_L;
+
+// Address-matching modes of the A field, the entry is off while it is 0
+pub(crate) const PMP_A_TOR: u8 = 0x08;
+const PMP_A_NA4: u8 = 0x10;
+const PMP_A_NAPOT: u8 = 0x18;
+
+// pmpaddr holds the address bits 33:2 on RV32 and 55:2 on RV64
+const PMP_SHIFT: u32 = 2;
+
+/// Returns the address range [start, end) matched by the entry `index`, None
+/// while the entry is off
+fn range(csr: &Csr, index: usize) -> Option<(u64, u64)> {
+    let (cfg, pmpaddr) = csr.pmp_entry(index);
+    match cfg & PMP_A {
+        PMP_A_TOR => {
+            // the entry matches from the address of the previous one, or from 0
+            let start = match index {
+                0 => 0,
+                _ => csr.pmp_entry(index - 1).1,
+            };
+            Some((start << PMP_SHIFT, pmpaddr << PMP_SHIFT))
+        }
+        PMP_A_NA4 => Some((pmpaddr << PMP_SHIFT, (pmpaddr << PMP_SHIFT) + 4)),
+        PMP_A_NAPOT => {
+            // the trailing ones of pmpaddr encode a region of 8 << ones bytes
+            let ones = pmpaddr.trailing_ones();
+            let start = (pmpaddr & !((1 << ones) - 1)) << PMP_SHIFT;
+            Some((start, start + (8 << ones)))
+        }
+        _ => None,
+    }
+}
+
+/// Checks an access of `size` bytes at the physical `address` made in the
+/// privilege mode `privilege`. The lowest-numbered entry matching any byte
+/// of the access decides, it must match all of them. Its permissions apply
+/// to S and U-mode, and to M-mode while it is locked. Without a match M-mode
+/// is allowed and the other modes are not, unless no entry is configured at
+/// all: PMP is then not in use and nothing is checked.
+pub(crate) fn is_allowed(
+    csr: &Csr,
+    address: GprUnsigned,
+    size: GprUnsigned,
+    access: AccessType,
+    privilege: PrivilegeMode,
+) -> bool {
+    let end = address.saturating_add(size);
+    let mut configured = false;
+    for index in 0..PMP_ENTRIES {
+        let Some((start, region_end)) = range(csr, index) else {
+            continue;
+        };
+        configured = true;
+        if address >= region_end || end <= start {
+            continue;
+        }
+        if address < start || end > region_end {
+            // the access is only partly in the region
+            return false;
+        }
+
+        let cfg = csr.pmp_entry(index).0;
+        if privilege == PrivilegeMode::Machine && cfg & PMP_L == 0 {
+            return true;
+        }
+        let permission = match access {
+            AccessType::Fetch => PMP_X,
+            AccessType::Load => PMP_R,
+            AccessType::Store => PMP_W,
+        };
+        return cfg & permission != 0;
+    }
+    !configured || privilege == PrivilegeMode::Machine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inst_csr_reg::*;
+    use crate::Xlen;
+
+    const USER: PrivilegeMode = PrivilegeMode::User;
+    const MACHINE: PrivilegeMode = PrivilegeMode::Machine;
+
+    fn allowed(csr: &Csr, address: GprUnsigned, access: AccessType, mode: PrivilegeMode) -> bool {
+        is_allowed(csr, address, 4, access, mode)
+    }
+
+    #[test]
+    fn test_address_matching() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        // nothing configured, nothing checked
+        assert!(allowed(&csr, 0x1000, AccessType::Store, USER));
+
+        // entry 0: NA4 at 0x1000, read-only
+        csr.write(CSR_PMPADDR0, 0x1000 >> 2).unwrap();
+        // entry 1: TOR from 0x1000 to 0x2000, read-write
+        csr.write(CSR_PMPADDR1, 0x2000 >> 2).unwrap();
+        // entry 2: NAPOT of 0x1000 bytes at 0x8000, executable
+        csr.write(CSR_PMPADDR2, (0x8000 >> 2) | 0x1ff).unwrap();
+        let cfg = (PMP_A_NA4 | PMP_R) as GprUnsigned
+            | ((PMP_A_TOR | PMP_R | PMP_W) as GprUnsigned) << 8
+            | ((PMP_A_NAPOT | PMP_X) as GprUnsigned) << 16;
+        csr.write(CSR_PMPCFG0, cfg).unwrap();
+
+        // entry 0 has priority over entry 1
+        assert!(allowed(&csr, 0x1000, AccessType::Load, USER));
+        assert!(!allowed(&csr, 0x1000, AccessType::Store, USER));
+        assert!(allowed(&csr, 0x1004, AccessType::Store, USER));
+        assert!(allowed(&csr, 0x1ffc, AccessType::Store, USER));
+        // an access must be in a single region
+        assert!(!is_allowed(&csr, 0x1ffe, 4, AccessType::Load, USER));
+        assert!(!is_allowed(&csr, 0x1002, 4, AccessType::Load, USER));
+
+        assert!(allowed(&csr, 0x8000, AccessType::Fetch, USER));
+        assert!(allowed(&csr, 0x8ffc, AccessType::Fetch, USER));
+        assert!(!allowed(&csr, 0x8ffc, AccessType::Load, USER));
+        assert!(!allowed(&csr, 0x9000, AccessType::Fetch, USER));
+
+        // no match: only M-mode is allowed
+        assert!(!allowed(&csr, 0x4000, AccessType::Load, USER));
+        assert!(allowed(&csr, 0x4000, AccessType::Load, MACHINE));
+        // the unlocked entries do not apply to M-mode
+        assert!(allowed(&csr, 0x1000, AccessType::Store, MACHINE));
+    }
+
+    #[test]
+    fn test_napot_all_memory() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_PMPADDR0, 0xffff_ffff).unwrap();
+        csr.write(CSR_PMPCFG0, (PMP_A_NAPOT | PMP_R | PMP_X) as GprUnsigned)
+            .unwrap();
+        assert!(allowed(&csr, 0, AccessType::Fetch, USER));
+        assert!(allowed(&csr, 0xffff_fffc, AccessType::Load, USER));
+        assert!(!allowed(&csr, 0x8000_0000, AccessType::Store, USER));
+    }
+
+    #[test]
+    fn test_locked_entries() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        csr.write(CSR_PMPADDR0, 0x1000 >> 2).unwrap();
+        csr.write(CSR_PMPADDR1, 0x2000 >> 2).unwrap();
+        let cfg = ((PMP_A_TOR | PMP_R | PMP_L) as GprUnsigned) << 8;
+        csr.write(CSR_PMPCFG0, cfg).unwrap();
+
+        // a locked entry also applies to M-mode
+        assert!(allowed(&csr, 0x1000, AccessType::Load, MACHINE));
+        assert!(!allowed(&csr, 0x1000, AccessType::Store, MACHINE));
+
+        // it can no longer be written, nor the address below a locked TOR entry
+        csr.write(CSR_PMPCFG0, 0).unwrap();
+        assert_eq!(csr.read(CSR_PMPCFG0), Ok(cfg));
+        csr.write(CSR_PMPADDR1, 0).unwrap();
+        csr.write(CSR_PMPADDR0, 0).unwrap();
+        assert_eq!(csr.read(CSR_PMPADDR1), Ok(0x2000 >> 2));
+        assert_eq!(csr.read(CSR_PMPADDR0), Ok(0x1000 >> 2));
+        // reset unlocks it
+        csr.reset();
+        assert_eq!(csr.read(CSR_PMPCFG0), Ok(0));
+    }
+
+    #[test]
+    fn test_pmp_csrs() {
+        let mut csr = Csr::new(Xlen::Rv32);
+        // the reserved bits read as zero and W needs R
+        csr.write(CSR_PMPCFG3, 0x6262_6363).unwrap();
+        assert_eq!(csr.read(CSR_PMPCFG3), Ok(0x0000_0303));
+        assert_eq!(csr.pmp_entry(12), (0x03, 0));
+        assert_eq!(csr.pmp_entry(14), (0x00, 0));
+
+        // RV64 only has the even pmpcfg CSRs, with 8 entries each
+        let mut csr = Csr::new(Xlen::Rv64);
+        assert!(csr.read(CSR_PMPCFG1).is_err());
+        assert!(csr.write(CSR_PMPCFG3, 0).is_err());
+        csr.write(CSR_PMPCFG2, 0x0100_0000_0000_0000).unwrap();
+        assert_eq!(csr.pmp_entry(15), (PMP_R, 0));
+        csr.write(CSR_PMPADDR15, GprUnsigned::MAX).unwrap();
+        assert_eq!(csr.read(CSR_PMPADDR15), Ok(0x003f_ffff_ffff_ffff));
+    }
+}
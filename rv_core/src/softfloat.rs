@@ -0,0 +1,1080 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// rv_core/src/softfloat.rs
+
+// IEEE 754 binary floating-point arithmetic done with integers, so that the
+// rounding and the exception flags follow the RISC-V spec exactly instead of
+// depending on the host FPU:
+//
+// - every rounding mode is honoured, including round to nearest, ties to max magnitude
+// - tininess is detected after rounding
+// - every NaN result is the canonical NaN
+//
+// Values are passed around as raw bits in a u64, `FloatFormat` tells how they
+// are laid out.
+
+use std::cmp::Ordering;
+
+/// Exception flags, laid out as in fflags
+pub(crate) type ExceptionFlags = u32;
+
+pub(crate) const FLAG_INEXACT: ExceptionFlags = 0x01;
+pub(crate) const FLAG_UNDERFLOW: ExceptionFlags = 0x02;
+pub(crate) const FLAG_OVERFLOW: ExceptionFlags = 0x04;
+pub(crate) const FLAG_DIVIDE_BY_ZERO: ExceptionFlags = 0x08;
+pub(crate) const FLAG_INVALID: ExceptionFlags = 0x10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RoundingMode {
+    /// RNE, round to nearest, ties to even
+    NearestEven,
+    /// RTZ, round towards zero
+    TowardZero,
+    /// RDN, round down (towards -infinity)
+    Down,
+    /// RUP, round up (towards +infinity)
+    Up,
+    /// RMM, round to nearest, ties to max magnitude
+    NearestMaxMagnitude,
+}
+
+impl RoundingMode {
+    /// Decodes the rm field of an instruction or frm, 0b101-0b111 are not rounding modes
+    pub(crate) fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0b000 => Some(RoundingMode::NearestEven),
+            0b001 => Some(RoundingMode::TowardZero),
+            0b010 => Some(RoundingMode::Down),
+            0b011 => Some(RoundingMode::Up),
+            0b100 => Some(RoundingMode::NearestMaxMagnitude),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct FloatFormat {
+    exp_bits: u32,
+    frac_bits: u32,
+}
+
+/// IEEE 754 binary32
+pub(crate) const F32: FloatFormat = FloatFormat {
+    exp_bits: 8,
+    frac_bits: 23,
+};
+
+/// IEEE 754 binary64
+pub(crate) const F64: FloatFormat = FloatFormat {
+    exp_bits: 11,
+    frac_bits: 52,
+};
+
+impl FloatFormat {
+    /// Width of the format in bits
+    pub(crate) fn bits(&self) -> u32 {
+        1 + self.exp_bits + self.frac_bits
+    }
+
+    fn bias(&self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    pub(crate) fn sign_bit(&self) -> u64 {
+        1 << (self.exp_bits + self.frac_bits)
+    }
+
+    fn all_bits(&self) -> u64 {
+        (self.sign_bit() << 1).wrapping_sub(1)
+    }
+
+    fn frac_mask(&self) -> u64 {
+        (1 << self.frac_bits) - 1
+    }
+
+    fn exp_max(&self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    fn quiet_bit(&self) -> u64 {
+        1 << (self.frac_bits - 1)
+    }
+
+    fn with_sign(&self, sign: bool, magnitude: u64) -> u64 {
+        if sign {
+            self.sign_bit() | magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    fn zero(&self, sign: bool) -> u64 {
+        self.with_sign(sign, 0)
+    }
+
+    fn infinity(&self, sign: bool) -> u64 {
+        self.with_sign(sign, self.exp_max() << self.frac_bits)
+    }
+
+    fn max_finite(&self, sign: bool) -> u64 {
+        self.with_sign(
+            sign,
+            ((self.exp_max() - 1) << self.frac_bits) | self.frac_mask(),
+        )
+    }
+
+    /// The canonical NaN: positive, quiet, with an all-zero payload
+    pub(crate) fn canonical_nan(&self) -> u64 {
+        (self.exp_max() << self.frac_bits) | self.quiet_bit()
+    }
+
+    pub(crate) fn is_nan(&self, bits: u64) -> bool {
+        (bits >> self.frac_bits) & self.exp_max() == self.exp_max() && bits & self.frac_mask() != 0
+    }
+
+    pub(crate) fn is_signaling_nan(&self, bits: u64) -> bool {
+        self.is_nan(bits) && bits & self.quiet_bit() == 0
+    }
+
+    /// Maps the bits to an unsigned key with the same order as the values, -0 sorts before +0
+    fn order_key(&self, bits: u64) -> u64 {
+        if bits & self.sign_bit() != 0 {
+            !bits & self.all_bits()
+        } else {
+            bits | self.sign_bit()
+        }
+    }
+}
+
+/// A finite non-zero value: (-1)^sign * sig * 2^exp
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    sign: bool,
+    exp: i32,
+    sig: u128,
+}
+
+impl Value {
+    /// Shifts the significand so that its most significant bit is at bit `msb`
+    fn normalize(self, msb: u32) -> Self {
+        let shift = msb as i32 - (127 - self.sig.leading_zeros() as i32);
+        let sig = if shift >= 0 {
+            self.sig << shift
+        } else {
+            self.sig >> -shift
+        };
+        Value {
+            sign: self.sign,
+            exp: self.exp - shift,
+            sig,
+        }
+    }
+}
+
+enum Class {
+    Zero(bool),
+    Infinity(bool),
+    Nan,
+    Finite(Value),
+}
+
+fn unpack(fmt: &FloatFormat, bits: u64) -> Class {
+    let sign = bits & fmt.sign_bit() != 0;
+    let exp = (bits >> fmt.frac_bits) & fmt.exp_max();
+    let frac = bits & fmt.frac_mask();
+    let emin = 1 - fmt.bias();
+
+    if exp == fmt.exp_max() {
+        if frac == 0 {
+            Class::Infinity(sign)
+        } else {
+            Class::Nan
+        }
+    } else if exp == 0 {
+        if frac == 0 {
+            Class::Zero(sign)
+        } else {
+            // subnormal
+            Class::Finite(Value {
+                sign,
+                exp: emin - fmt.frac_bits as i32,
+                sig: frac as u128,
+            })
+        }
+    } else {
+        Class::Finite(Value {
+            sign,
+            exp: exp as i32 - fmt.bias() - fmt.frac_bits as i32,
+            sig: (frac | (1 << fmt.frac_bits)) as u128,
+        })
+    }
+}
+
+/// Returns the canonical NaN if any operand is a NaN, raising invalid for a signaling one
+fn propagate_nan(fmt: &FloatFormat, operands: &[u64]) -> Option<(u64, ExceptionFlags)> {
+    if !operands.iter().any(|&bits| fmt.is_nan(bits)) {
+        return None;
+    }
+    let flags = if operands.iter().any(|&bits| fmt.is_signaling_nan(bits)) {
+        FLAG_INVALID
+    } else {
+        0
+    };
+    Some((fmt.canonical_nan(), flags))
+}
+
+fn invalid(fmt: &FloatFormat) -> (u64, ExceptionFlags) {
+    (fmt.canonical_nan(), FLAG_INVALID)
+}
+
+/// Shifts `sig` right by `shift` bits, rounding the result according to `rm`.
+/// Returns the rounded value, and whether any non-zero bit was shifted out.
+fn shift_right_round(sig: u128, shift: u32, sign: bool, rm: RoundingMode) -> (u128, bool) {
+    if shift == 0 || sig == 0 {
+        return (sig, false);
+    }
+
+    let msb = 127 - sig.leading_zeros();
+    let (keep, rem, half) = if shift > msb + 1 {
+        // the whole value is below half of the last kept bit
+        (0, 1, 2)
+    } else if shift == u128::BITS {
+        (0, sig, 1 << 127)
+    } else {
+        (sig >> shift, sig & ((1 << shift) - 1), 1 << (shift - 1))
+    };
+
+    let inexact = rem != 0;
+    let round_up = match rm {
+        RoundingMode::NearestEven => rem > half || (rem == half && keep & 1 == 1),
+        RoundingMode::NearestMaxMagnitude => rem >= half,
+        RoundingMode::TowardZero => false,
+        RoundingMode::Down => sign && inexact,
+        RoundingMode::Up => !sign && inexact,
+    };
+    (keep + round_up as u128, inexact)
+}
+
+/// Shifts right, keeping a sticky bit for anything shifted out
+fn shift_right_jam(sig: u128, shift: u32) -> u128 {
+    if shift == 0 {
+        sig
+    } else if shift >= u128::BITS {
+        (sig != 0) as u128
+    } else {
+        (sig >> shift) | ((sig & ((1 << shift) - 1)) != 0) as u128
+    }
+}
+
+/// Rounds a finite non-zero value to the format
+fn round_pack(fmt: &FloatFormat, value: Value, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    let frac_bits = fmt.frac_bits as i32;
+    let precision = fmt.frac_bits + 1;
+    let msb = 127 - value.sig.leading_zeros() as i32;
+    // exponent of the leading bit
+    let e = value.exp + msb;
+    let emin = 1 - fmt.bias();
+
+    // weight of the last significand bit, subnormals have fewer bits
+    let mut lsb_exp = e.max(emin) - frac_bits;
+    let (mut sig, inexact) = if lsb_exp <= value.exp {
+        (value.sig << (value.exp - lsb_exp), false)
+    } else {
+        shift_right_round(value.sig, (lsb_exp - value.exp) as u32, value.sign, rm)
+    };
+    if sig >> precision != 0 {
+        // rounding carried into a new bit
+        sig >>= 1;
+        lsb_exp += 1;
+    }
+
+    let mut flags = if inexact { FLAG_INEXACT } else { 0 };
+
+    // Tininess is detected after rounding: the value is tiny if it is still
+    // below 2^emin when rounded with an unbounded exponent range.
+    if inexact && e < emin {
+        let tiny = if e == emin - 1 {
+            let shift = msb - frac_bits;
+            shift <= 0
+                || shift_right_round(value.sig, shift as u32, value.sign, rm).0 >> precision == 0
+        } else {
+            true
+        };
+        if tiny {
+            flags |= FLAG_UNDERFLOW;
+        }
+    }
+
+    let biased_exp = if sig >> fmt.frac_bits != 0 {
+        lsb_exp + frac_bits + fmt.bias()
+    } else {
+        // subnormal or zero
+        0
+    };
+
+    if biased_exp >= fmt.exp_max() as i32 {
+        let bits = match rm {
+            RoundingMode::NearestEven | RoundingMode::NearestMaxMagnitude => {
+                fmt.infinity(value.sign)
+            }
+            RoundingMode::TowardZero => fmt.max_finite(value.sign),
+            RoundingMode::Down if value.sign => fmt.infinity(true),
+            RoundingMode::Down => fmt.max_finite(false),
+            RoundingMode::Up if value.sign => fmt.max_finite(true),
+            RoundingMode::Up => fmt.infinity(false),
+        };
+        return (bits, FLAG_OVERFLOW | FLAG_INEXACT);
+    }
+
+    let bits = fmt.with_sign(
+        value.sign,
+        ((biased_exp as u64) << fmt.frac_bits) | (sig as u64 & fmt.frac_mask()),
+    );
+    (bits, flags)
+}
+
+/// Exact sum of two finite values, None if it is zero
+fn add_values(a: Value, b: Value) -> Option<Value> {
+    // Both significands get their msb at bit 125, the sum still fits in 127 bits and
+    // keeps more than 64 bits below the rounding position of the widest format.
+    let a = a.normalize(125);
+    let b = b.normalize(125);
+    let (big, small) = if a.exp >= b.exp { (a, b) } else { (b, a) };
+    let small_sig = shift_right_jam(small.sig, (big.exp - small.exp) as u32);
+
+    let (sign, sig) = if big.sign == small.sign {
+        (big.sign, big.sig + small_sig)
+    } else {
+        match big.sig.cmp(&small_sig) {
+            Ordering::Greater => (big.sign, big.sig - small_sig),
+            Ordering::Less => (small.sign, small_sig - big.sig),
+            Ordering::Equal => return None,
+        }
+    };
+
+    Some(Value {
+        sign,
+        exp: big.exp,
+        sig,
+    })
+}
+
+/// Sign of an exact zero sum of operands with opposite signs
+fn zero_sum_sign(rm: RoundingMode) -> bool {
+    rm == RoundingMode::Down
+}
+
+pub(crate) fn add(fmt: &FloatFormat, a: u64, b: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    if let Some(nan) = propagate_nan(fmt, &[a, b]) {
+        return nan;
+    }
+
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Class::Infinity(sa), Class::Infinity(sb)) if sa != sb => invalid(fmt),
+        (Class::Infinity(sign), _) | (_, Class::Infinity(sign)) => (fmt.infinity(sign), 0),
+        (Class::Zero(sa), Class::Zero(sb)) => {
+            let sign = if sa == sb { sa } else { zero_sum_sign(rm) };
+            (fmt.zero(sign), 0)
+        }
+        (Class::Zero(_), _) => (b, 0),
+        (_, Class::Zero(_)) => (a, 0),
+        (Class::Finite(va), Class::Finite(vb)) => match add_values(va, vb) {
+            Some(sum) => round_pack(fmt, sum, rm),
+            None => (fmt.zero(zero_sum_sign(rm)), 0),
+        },
+        _ => unreachable!("NaN operands are handled above"),
+    }
+}
+
+pub(crate) fn sub(fmt: &FloatFormat, a: u64, b: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    add(fmt, a, b ^ fmt.sign_bit(), rm)
+}
+
+pub(crate) fn mul(fmt: &FloatFormat, a: u64, b: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    if let Some(nan) = propagate_nan(fmt, &[a, b]) {
+        return nan;
+    }
+
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Class::Infinity(_), Class::Zero(_)) | (Class::Zero(_), Class::Infinity(_)) => invalid(fmt),
+        (Class::Infinity(sa), Class::Infinity(sb))
+        | (Class::Infinity(sa), Class::Finite(Value { sign: sb, .. }))
+        | (Class::Finite(Value { sign: sa, .. }), Class::Infinity(sb)) => {
+            (fmt.infinity(sa != sb), 0)
+        }
+        (Class::Zero(sa), Class::Zero(sb))
+        | (Class::Zero(sa), Class::Finite(Value { sign: sb, .. }))
+        | (Class::Finite(Value { sign: sa, .. }), Class::Zero(sb)) => (fmt.zero(sa != sb), 0),
+        (Class::Finite(va), Class::Finite(vb)) => round_pack(fmt, mul_values(va, vb), rm),
+        _ => unreachable!("NaN operands are handled above"),
+    }
+}
+
+/// Exact product of two finite values
+fn mul_values(a: Value, b: Value) -> Value {
+    Value {
+        sign: a.sign != b.sign,
+        exp: a.exp + b.exp,
+        sig: a.sig * b.sig,
+    }
+}
+
+pub(crate) fn div(fmt: &FloatFormat, a: u64, b: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    if let Some(nan) = propagate_nan(fmt, &[a, b]) {
+        return nan;
+    }
+
+    let sign = (a ^ b) & fmt.sign_bit() != 0;
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Class::Infinity(_), Class::Infinity(_)) | (Class::Zero(_), Class::Zero(_)) => invalid(fmt),
+        (Class::Infinity(_), _) => (fmt.infinity(sign), 0),
+        (_, Class::Infinity(_)) | (Class::Zero(_), _) => (fmt.zero(sign), 0),
+        (_, Class::Zero(_)) => (fmt.infinity(sign), FLAG_DIVIDE_BY_ZERO),
+        (Class::Finite(va), Class::Finite(vb)) => {
+            // a quotient of at least 63 bits, plus a sticky bit for the remainder
+            let va = va.normalize(126);
+            let vb = vb.normalize(63);
+            let quotient = va.sig / vb.sig;
+            let sticky = (va.sig % vb.sig != 0) as u128;
+            let value = Value {
+                sign,
+                exp: va.exp - vb.exp,
+                sig: quotient | sticky,
+            };
+            round_pack(fmt, value, rm)
+        }
+        _ => unreachable!("NaN operands are handled above"),
+    }
+}
+
+/// Integer square root, with whether a remainder is left
+fn isqrt(n: u128) -> (u128, bool) {
+    let mut rem = n;
+    let mut root = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > rem {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    (root, rem != 0)
+}
+
+pub(crate) fn sqrt(fmt: &FloatFormat, a: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    if let Some(nan) = propagate_nan(fmt, &[a]) {
+        return nan;
+    }
+
+    match unpack(fmt, a) {
+        // sqrt(-0) is -0
+        Class::Zero(_) => (a, 0),
+        Class::Infinity(false) => (a, 0),
+        Class::Infinity(true) | Class::Finite(Value { sign: true, .. }) => invalid(fmt),
+        Class::Finite(value) => {
+            // an even exponent, and a root of at least 62 bits
+            let mut value = value.normalize(125);
+            if value.exp % 2 != 0 {
+                value = value.normalize(124);
+            }
+            let (root, remainder) = isqrt(value.sig);
+            let value = Value {
+                sign: false,
+                exp: value.exp / 2,
+                sig: root | remainder as u128,
+            };
+            round_pack(fmt, value, rm)
+        }
+        Class::Nan => unreachable!("NaN operands are handled above"),
+    }
+}
+
+/// Fused a * b + c with a single rounding
+pub(crate) fn mul_add(
+    fmt: &FloatFormat,
+    a: u64,
+    b: u64,
+    c: u64,
+    rm: RoundingMode,
+) -> (u64, ExceptionFlags) {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return propagate_nan(fmt, &[a, b, c]).unwrap();
+    }
+
+    let (ua, ub) = (unpack(fmt, a), unpack(fmt, b));
+    let product_sign = (a ^ b) & fmt.sign_bit() != 0;
+    match (&ua, &ub) {
+        // 0 * inf is invalid, even when c is a quiet NaN
+        (Class::Infinity(_), Class::Zero(_)) | (Class::Zero(_), Class::Infinity(_)) => {
+            return invalid(fmt);
+        }
+        _ => {}
+    }
+
+    if let Some(nan) = propagate_nan(fmt, &[c]) {
+        return nan;
+    }
+
+    let uc = unpack(fmt, c);
+    match (ua, ub, uc) {
+        (Class::Infinity(_), _, Class::Infinity(sc))
+        | (_, Class::Infinity(_), Class::Infinity(sc))
+            if sc != product_sign =>
+        {
+            invalid(fmt)
+        }
+        (Class::Infinity(_), _, _) | (_, Class::Infinity(_), _) => (fmt.infinity(product_sign), 0),
+        (_, _, Class::Infinity(sc)) => (fmt.infinity(sc), 0),
+        (Class::Zero(_), _, Class::Zero(sc)) | (_, Class::Zero(_), Class::Zero(sc)) => {
+            let sign = if sc == product_sign {
+                sc
+            } else {
+                zero_sum_sign(rm)
+            };
+            (fmt.zero(sign), 0)
+        }
+        (Class::Zero(_), _, _) | (_, Class::Zero(_), _) => (c, 0),
+        (Class::Finite(va), Class::Finite(vb), Class::Zero(_)) => {
+            round_pack(fmt, mul_values(va, vb), rm)
+        }
+        (Class::Finite(va), Class::Finite(vb), Class::Finite(vc)) => {
+            match add_values(mul_values(va, vb), vc) {
+                Some(sum) => round_pack(fmt, sum, rm),
+                None => (fmt.zero(zero_sum_sign(rm)), 0),
+            }
+        }
+        _ => unreachable!("NaN operands are handled above"),
+    }
+}
+
+/// minimumNumber: a NaN operand is ignored, and -0 is smaller than +0
+pub(crate) fn min(fmt: &FloatFormat, a: u64, b: u64) -> (u64, ExceptionFlags) {
+    min_max(fmt, a, b, Ordering::Less)
+}
+
+/// maximumNumber: a NaN operand is ignored, and +0 is larger than -0
+pub(crate) fn max(fmt: &FloatFormat, a: u64, b: u64) -> (u64, ExceptionFlags) {
+    min_max(fmt, a, b, Ordering::Greater)
+}
+
+fn min_max(fmt: &FloatFormat, a: u64, b: u64, pick: Ordering) -> (u64, ExceptionFlags) {
+    let flags = if fmt.is_signaling_nan(a) || fmt.is_signaling_nan(b) {
+        FLAG_INVALID
+    } else {
+        0
+    };
+
+    let result = match (fmt.is_nan(a), fmt.is_nan(b)) {
+        (true, true) => fmt.canonical_nan(),
+        (true, false) => b,
+        (false, true) => a,
+        (false, false) => {
+            if fmt.order_key(a).cmp(&fmt.order_key(b)) == pick {
+                a
+            } else {
+                b
+            }
+        }
+    };
+    (result, flags)
+}
+
+/// Quiet comparison, only signaling NaNs raise invalid
+pub(crate) fn eq(fmt: &FloatFormat, a: u64, b: u64) -> (bool, ExceptionFlags) {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        let flags = if fmt.is_signaling_nan(a) || fmt.is_signaling_nan(b) {
+            FLAG_INVALID
+        } else {
+            0
+        };
+        return (false, flags);
+    }
+    let both_zero = (a | b) & !fmt.sign_bit() == 0;
+    (a == b || both_zero, 0)
+}
+
+/// Signaling comparison, any NaN raises invalid
+pub(crate) fn lt(fmt: &FloatFormat, a: u64, b: u64) -> (bool, ExceptionFlags) {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return (false, FLAG_INVALID);
+    }
+    let both_zero = (a | b) & !fmt.sign_bit() == 0;
+    (!both_zero && fmt.order_key(a) < fmt.order_key(b), 0)
+}
+
+/// Signaling comparison, any NaN raises invalid
+pub(crate) fn le(fmt: &FloatFormat, a: u64, b: u64) -> (bool, ExceptionFlags) {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return (false, FLAG_INVALID);
+    }
+    let both_zero = (a | b) & !fmt.sign_bit() == 0;
+    (both_zero || fmt.order_key(a) <= fmt.order_key(b), 0)
+}
+
+/// The 10-bit mask written by FCLASS
+pub(crate) fn classify(fmt: &FloatFormat, a: u64) -> u32 {
+    let sign = a & fmt.sign_bit() != 0;
+    let exp = (a >> fmt.frac_bits) & fmt.exp_max();
+    let bit = match unpack(fmt, a) {
+        Class::Infinity(true) => 0,
+        Class::Finite(_) if sign && exp != 0 => 1,
+        Class::Finite(_) if sign => 2,
+        Class::Zero(true) => 3,
+        Class::Zero(false) => 4,
+        Class::Finite(_) if exp == 0 => 5,
+        Class::Finite(_) => 6,
+        Class::Infinity(false) => 7,
+        Class::Nan if fmt.is_signaling_nan(a) => 8,
+        Class::Nan => 9,
+    };
+    1 << bit
+}
+
+/// Converts to a `width`-bit integer, saturating and raising invalid when out of range.
+/// The result is returned as a two's complement value in 64 bits.
+pub(crate) fn to_int(
+    fmt: &FloatFormat,
+    a: u64,
+    rm: RoundingMode,
+    signed: bool,
+    width: u32,
+) -> (u64, ExceptionFlags) {
+    let max_positive: u128 = if signed {
+        (1 << (width - 1)) - 1
+    } else {
+        (1 << width) - 1
+    };
+    // magnitude of the most negative result
+    let max_negative: u128 = if signed { 1 << (width - 1) } else { 0 };
+    let saturate = |sign: bool| -> (u64, ExceptionFlags) {
+        let value = if sign {
+            (max_negative as i128).wrapping_neg() as u64
+        } else {
+            max_positive as u64
+        };
+        (value, FLAG_INVALID)
+    };
+
+    match unpack(fmt, a) {
+        Class::Nan => saturate(false),
+        Class::Infinity(sign) => saturate(sign),
+        Class::Zero(_) => (0, 0),
+        Class::Finite(value) => {
+            let (magnitude, inexact) = if value.exp >= 0 {
+                if value.exp > 64 {
+                    return saturate(value.sign);
+                }
+                (value.sig << value.exp, false)
+            } else {
+                shift_right_round(value.sig, (-value.exp) as u32, value.sign, rm)
+            };
+
+            let limit = if value.sign {
+                max_negative
+            } else {
+                max_positive
+            };
+            if magnitude > limit {
+                return saturate(value.sign);
+            }
+
+            let result = if value.sign {
+                (magnitude as i128).wrapping_neg() as u64
+            } else {
+                magnitude as u64
+            };
+            (result, if inexact { FLAG_INEXACT } else { 0 })
+        }
+    }
+}
+
+/// Converts between two formats, rounding when narrowing
+pub(crate) fn convert(
+    from: &FloatFormat,
+    to: &FloatFormat,
+    a: u64,
+    rm: RoundingMode,
+) -> (u64, ExceptionFlags) {
+    if let Some((_, flags)) = propagate_nan(from, &[a]) {
+        return (to.canonical_nan(), flags);
+    }
+
+    match unpack(from, a) {
+        Class::Zero(sign) => (to.zero(sign), 0),
+        Class::Infinity(sign) => (to.infinity(sign), 0),
+        Class::Finite(value) => round_pack(to, value, rm),
+        Class::Nan => unreachable!("NaN operands are handled above"),
+    }
+}
+
+/// Converts a signed integer
+pub(crate) fn from_i64(fmt: &FloatFormat, a: i64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    from_magnitude(fmt, a < 0, a.unsigned_abs(), rm)
+}
+
+/// Converts an unsigned integer
+pub(crate) fn from_u64(fmt: &FloatFormat, a: u64, rm: RoundingMode) -> (u64, ExceptionFlags) {
+    from_magnitude(fmt, false, a, rm)
+}
+
+fn from_magnitude(
+    fmt: &FloatFormat,
+    sign: bool,
+    magnitude: u64,
+    rm: RoundingMode,
+) -> (u64, ExceptionFlags) {
+    if magnitude == 0 {
+        return (fmt.zero(false), 0);
+    }
+    let value = Value {
+        sign,
+        exp: 0,
+        sig: magnitude as u128,
+    };
+    round_pack(fmt, value, rm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RNE: RoundingMode = RoundingMode::NearestEven;
+
+    type BinaryOp = fn(&FloatFormat, u64, u64, RoundingMode) -> (u64, ExceptionFlags);
+
+    fn f(value: f32) -> u64 {
+        value.to_bits() as u64
+    }
+
+    // A deterministic mix of bit patterns: random ones plus zeros, subnormals,
+    // infinities, NaNs and values around 1.0.
+    fn test_values() -> Vec<u32> {
+        let mut values = vec![
+            0x0000_0000,
+            0x8000_0000,
+            0x0000_0001,
+            0x807f_ffff,
+            0x0080_0000,
+            0x7f7f_ffff,
+            0xff80_0000,
+            0x7f80_0000,
+            0x7fc0_0000,
+            0x7f80_0001,
+            0x3f80_0000,
+            0x3f80_0001,
+            0xbf7f_ffff,
+            0x4040_0000,
+        ];
+        let mut state = 0x1234_5678_9abc_def0u64;
+        for _ in 0..300 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            values.push((state >> 32) as u32);
+        }
+        values
+    }
+
+    // The host FPU rounds to nearest even, so its results must match bit for bit.
+    fn check_against_host(op: BinaryOp, host: fn(f32, f32) -> f32) {
+        let values = test_values();
+        for &a in &values {
+            for &b in &values {
+                let expected = host(f32::from_bits(a), f32::from_bits(b));
+                let (result, _) = op(&F32, a as u64, b as u64, RNE);
+                if expected.is_nan() {
+                    assert_eq!(result, F32.canonical_nan(), "{:#x} {:#x}", a, b);
+                } else {
+                    assert_eq!(result, f(expected), "{:#x} {:#x}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_matches_host() {
+        check_against_host(add, |a, b| a + b);
+        check_against_host(sub, |a, b| a - b);
+        check_against_host(mul, |a, b| a * b);
+        check_against_host(div, |a, b| a / b);
+    }
+
+    #[test]
+    fn test_f64_arithmetic_matches_host() {
+        let mut values = vec![
+            0,
+            1,
+            0x0010_0000_0000_0000,
+            0x7ff0_0000_0000_0000,
+            0x7ff0_0000_0000_0001,
+        ];
+        let mut state = 0x0fed_cba9_8765_4321u64;
+        for _ in 0..200 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // keep the exponents close enough for the results to be interesting
+            values.push(state & 0xc0ff_ffff_ffff_ffff | 0x3c00_0000_0000_0000);
+        }
+
+        let check = |op: BinaryOp, host: fn(f64, f64) -> f64| {
+            for &a in &values {
+                for &b in &values {
+                    let expected = host(f64::from_bits(a), f64::from_bits(b));
+                    let (result, _) = op(&F64, a, b, RNE);
+                    if expected.is_nan() {
+                        assert_eq!(result, F64.canonical_nan(), "{:#x} {:#x}", a, b);
+                    } else {
+                        assert_eq!(result, expected.to_bits(), "{:#x} {:#x}", a, b);
+                    }
+                }
+            }
+        };
+        check(add, |a, b| a + b);
+        check(sub, |a, b| a - b);
+        check(mul, |a, b| a * b);
+        check(div, |a, b| a / b);
+
+        for &a in &values {
+            let expected = f64::from_bits(a).sqrt();
+            let (result, _) = sqrt(&F64, a, RNE);
+            if expected.is_nan() {
+                assert_eq!(result, F64.canonical_nan(), "{:#x}", a);
+            } else {
+                assert_eq!(result, expected.to_bits(), "{:#x}", a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_conversions() {
+        let d = |value: f64| value.to_bits();
+        assert_eq!(convert(&F32, &F64, f(1.5), RNE), (d(1.5), 0));
+        assert_eq!(
+            convert(&F32, &F64, 0x0000_0001, RNE),
+            (d(f32::from_bits(1) as f64), 0)
+        );
+        assert_eq!(convert(&F64, &F32, d(1.5), RNE), (f(1.5), 0));
+        assert_eq!(convert(&F64, &F32, d(0.1), RNE), (f(0.1), FLAG_INEXACT));
+        assert_eq!(
+            convert(&F64, &F32, d(1e300), RNE),
+            (f(f32::INFINITY), FLAG_OVERFLOW | FLAG_INEXACT)
+        );
+        assert_eq!(
+            convert(&F64, &F32, d(1e-300), RoundingMode::Up),
+            (0x0000_0001, FLAG_UNDERFLOW | FLAG_INEXACT)
+        );
+        // a signaling NaN becomes the canonical NaN of the target
+        assert_eq!(convert(&F32, &F64, 0x7f80_0001, RNE), invalid(&F64));
+        assert_eq!(
+            convert(&F64, &F32, d(f64::NAN), RNE),
+            (F32.canonical_nan(), 0)
+        );
+        assert_eq!(from_i64(&F64, i64::MIN, RNE), (d(-(2f64.powi(63))), 0));
+        assert_eq!(
+            to_int(&F64, d(-2.5), RNE, true, 64),
+            (-2i64 as u64, FLAG_INEXACT)
+        );
+        assert_eq!(
+            to_int(&F64, d(1.8446744073709552e19), RNE, false, 64),
+            (u64::MAX, FLAG_INVALID)
+        );
+    }
+
+    #[test]
+    fn test_sqrt_and_mul_add_match_host() {
+        let values = test_values();
+        for &a in &values {
+            let expected = f32::from_bits(a).sqrt();
+            let (result, _) = sqrt(&F32, a as u64, RNE);
+            if expected.is_nan() {
+                assert_eq!(result, F32.canonical_nan(), "{:#x}", a);
+            } else {
+                assert_eq!(result, f(expected), "{:#x}", a);
+            }
+        }
+        for (i, &a) in values.iter().enumerate() {
+            for &b in &values {
+                let c = values[(i * 7 + b as usize) % values.len()];
+                let expected = f32::from_bits(a).mul_add(f32::from_bits(b), f32::from_bits(c));
+                let (result, _) = mul_add(&F32, a as u64, b as u64, c as u64, RNE);
+                if expected.is_nan() {
+                    assert_eq!(result, F32.canonical_nan(), "{:#x} {:#x} {:#x}", a, b, c);
+                } else {
+                    assert_eq!(result, f(expected), "{:#x} {:#x} {:#x}", a, b, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        // 1 + 2^-24 is exactly halfway between 1 and the next float
+        let a = f(1.0);
+        let b = f(f32::EPSILON / 2.0);
+        let next = f(1.0) + 1;
+        assert_eq!(add(&F32, a, b, RNE), (a, FLAG_INEXACT));
+        assert_eq!(
+            add(&F32, a, b, RoundingMode::NearestMaxMagnitude),
+            (next, FLAG_INEXACT)
+        );
+        assert_eq!(add(&F32, a, b, RoundingMode::TowardZero), (a, FLAG_INEXACT));
+        assert_eq!(add(&F32, a, b, RoundingMode::Up), (next, FLAG_INEXACT));
+        assert_eq!(add(&F32, a, b, RoundingMode::Down), (a, FLAG_INEXACT));
+        // towards -infinity for a negative value
+        let (neg, _) = sub(&F32, f(-1.0), b, RoundingMode::Down);
+        assert_eq!(neg, f(-1.0) + 1);
+        // x - x is -0 only when rounding down
+        assert_eq!(sub(&F32, a, a, RNE), (f(0.0), 0));
+        assert_eq!(sub(&F32, a, a, RoundingMode::Down), (f(-0.0), 0));
+    }
+
+    #[test]
+    fn test_exception_flags() {
+        let max = f(f32::MAX);
+        assert_eq!(
+            mul(&F32, max, f(2.0), RNE),
+            (f(f32::INFINITY), FLAG_OVERFLOW | FLAG_INEXACT)
+        );
+        assert_eq!(
+            mul(&F32, max, f(2.0), RoundingMode::TowardZero),
+            (max, FLAG_OVERFLOW | FLAG_INEXACT)
+        );
+        assert_eq!(
+            div(&F32, f(1.0), f(0.0), RNE),
+            (f(f32::INFINITY), FLAG_DIVIDE_BY_ZERO)
+        );
+        assert_eq!(div(&F32, f(0.0), f(0.0), RNE), invalid(&F32));
+        assert_eq!(sqrt(&F32, f(-1.0), RNE), invalid(&F32));
+        assert_eq!(
+            add(&F32, f(f32::INFINITY), f(f32::NEG_INFINITY), RNE),
+            invalid(&F32)
+        );
+        assert_eq!(div(&F32, f(1.0), f(3.0), RNE).1, FLAG_INEXACT);
+        // signaling NaN input
+        assert_eq!(add(&F32, 0x7f80_0001, f(1.0), RNE), invalid(&F32));
+        assert_eq!(
+            add(&F32, 0x7fc0_0001, f(1.0), RNE),
+            (F32.canonical_nan(), 0)
+        );
+        // 0 * inf + qNaN is invalid
+        assert_eq!(
+            mul_add(&F32, f(0.0), f(f32::INFINITY), 0x7fc0_0000, RNE),
+            invalid(&F32)
+        );
+    }
+
+    #[test]
+    fn test_underflow_after_rounding() {
+        let min_normal = f(f32::MIN_POSITIVE);
+        // an exact subnormal result does not underflow
+        assert_eq!(mul(&F32, min_normal, f(0.5), RNE), (min_normal >> 1, 0));
+        // an inexact subnormal result does
+        assert_eq!(
+            mul(&F32, 0x0000_0003, f(0.5), RNE),
+            (0x0000_0002, FLAG_UNDERFLOW | FLAG_INEXACT)
+        );
+        // 2^-126 - 2^-150 is exact with an unbounded exponent, so it is tiny
+        // even though it rounds up to 2^-126
+        assert_eq!(
+            mul(&F32, min_normal, f(1.0) - 1, RNE),
+            (min_normal, FLAG_UNDERFLOW | FLAG_INEXACT)
+        );
+        // 2^-126 - 2^-152 rounds to 2^-126 with an unbounded exponent, so it is not tiny
+        let two_pow_minus_76 = 0x1980_0000;
+        assert_eq!(
+            mul_add(
+                &F32,
+                two_pow_minus_76 | F32.sign_bit(),
+                two_pow_minus_76,
+                min_normal,
+                RNE
+            ),
+            (min_normal, FLAG_INEXACT)
+        );
+    }
+
+    #[test]
+    fn test_min_max_compare_classify() {
+        assert_eq!(min(&F32, f(-0.0), f(0.0)), (f(-0.0), 0));
+        assert_eq!(max(&F32, f(-0.0), f(0.0)), (f(0.0), 0));
+        assert_eq!(min(&F32, 0x7fc0_0000, f(1.0)), (f(1.0), 0));
+        assert_eq!(max(&F32, 0x7f80_0001, f(1.0)), (f(1.0), FLAG_INVALID));
+        assert_eq!(
+            min(&F32, 0x7fc0_0001, 0xffc0_0000),
+            (F32.canonical_nan(), 0)
+        );
+
+        assert_eq!(eq(&F32, f(-0.0), f(0.0)), (true, 0));
+        assert_eq!(eq(&F32, 0x7fc0_0000, f(0.0)), (false, 0));
+        assert_eq!(eq(&F32, 0x7f80_0001, f(0.0)), (false, FLAG_INVALID));
+        assert_eq!(lt(&F32, 0x7fc0_0000, f(0.0)), (false, FLAG_INVALID));
+        assert_eq!(lt(&F32, f(-0.0), f(0.0)), (false, 0));
+        assert_eq!(le(&F32, f(-0.0), f(0.0)), (true, 0));
+        assert_eq!(lt(&F32, f(-2.0), f(-1.0)), (true, 0));
+
+        assert_eq!(classify(&F32, f(f32::NEG_INFINITY)), 1 << 0);
+        assert_eq!(classify(&F32, f(-1.0)), 1 << 1);
+        assert_eq!(classify(&F32, 0x8000_0001), 1 << 2);
+        assert_eq!(classify(&F32, f(-0.0)), 1 << 3);
+        assert_eq!(classify(&F32, f(0.0)), 1 << 4);
+        assert_eq!(classify(&F32, 0x0000_0001), 1 << 5);
+        assert_eq!(classify(&F32, f(1.0)), 1 << 6);
+        assert_eq!(classify(&F32, f(f32::INFINITY)), 1 << 7);
+        assert_eq!(classify(&F32, 0x7f80_0001), 1 << 8);
+        assert_eq!(classify(&F32, 0x7fc0_0000), 1 << 9);
+    }
+
+    #[test]
+    fn test_integer_conversions() {
+        assert_eq!(to_int(&F32, f(2.5), RNE, true, 32), (2, FLAG_INEXACT));
+        assert_eq!(
+            to_int(&F32, f(2.5), RoundingMode::NearestMaxMagnitude, true, 32),
+            (3, FLAG_INEXACT)
+        );
+        assert_eq!(
+            to_int(&F32, f(-2.5), RoundingMode::Down, true, 32),
+            (-3i64 as u64, FLAG_INEXACT)
+        );
+        assert_eq!(
+            to_int(&F32, f(3e9), RNE, true, 32),
+            (0x7fff_ffff, FLAG_INVALID)
+        );
+        assert_eq!(
+            to_int(&F32, f(-3e9), RNE, true, 32),
+            (i32::MIN as i64 as u64, FLAG_INVALID)
+        );
+        assert_eq!(
+            to_int(&F32, 0x7fc0_0000, RNE, true, 32),
+            (0x7fff_ffff, FLAG_INVALID)
+        );
+        assert_eq!(to_int(&F32, f(3e9), RNE, false, 32), (3_000_000_000, 0));
+        // rounds to -0, which is in range
+        assert_eq!(to_int(&F32, f(-0.25), RNE, false, 32), (0, FLAG_INEXACT));
+        assert_eq!(to_int(&F32, f(-1.0), RNE, false, 32), (0, FLAG_INVALID));
+        assert_eq!(
+            to_int(&F32, f(f32::INFINITY), RNE, false, 32),
+            (0xffff_ffff, FLAG_INVALID)
+        );
+
+        assert_eq!(from_i64(&F32, -7, RNE), (f(-7.0), 0));
+        assert_eq!(from_u64(&F32, 0, RNE), (f(0.0), 0));
+        // 2^24 + 1 is not representable
+        assert_eq!(
+            from_i64(&F32, 16_777_217, RNE),
+            (f(16_777_216.0), FLAG_INEXACT)
+        );
+        assert_eq!(
+            from_u64(&F32, 0xffff_ffff, RoundingMode::TowardZero),
+            (f(4_294_967_040.0), FLAG_INEXACT)
+        );
+    }
+}
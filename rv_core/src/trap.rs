@@ -7,10 +7,10 @@
 
 use thiserror::Error;
 
-use crate::csr::Csr;
+use crate::csr::{Csr, CsrAddrType};
 use crate::decode::ExecutionReturnData;
 use crate::inst_csr_reg::*;
-use crate::{ProgramCounter, RvCoreError};
+use crate::{GprUnsigned, ProgramCounter, RvCoreError};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum Exception {
@@ -20,7 +20,7 @@ pub enum Exception {
     #[error("instruction access fault")]
     InstructionAccessFault,
 
-    #[error("illegal instruction: {0}")]
+    #[error("illegal instruction: {0:#x}")]
     IllegalInstruction(u32),
 
     #[error("breakpoint")]
@@ -78,45 +78,70 @@ pub enum Interrupt {
     MachineExternalInterrupt,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Error, Debug, PartialEq)]
 pub enum Trap {
+    #[error(transparent)]
     Exception(Exception),
+    #[error(transparent)]
     Interrupt(Interrupt),
 }
 
-// mtval
-// mstatus
-// mepc, mcause
-// mtvec
-// mie, mip
+/// The CSRs a trap is taken into, the M-mode ones unless it is delegated to S-mode
+struct TrapCsrs {
+    tvec: CsrAddrType,
+    epc: CsrAddrType,
+    cause: CsrAddrType,
+    tval: CsrAddrType,
+}
+
+const M_MODE_TRAP_CSRS: TrapCsrs = TrapCsrs {
+    tvec: CSR_MTVEC,
+    epc: CSR_MEPC,
+    cause: CSR_MCAUSE,
+    tval: CSR_MTVAL,
+};
+
+const S_MODE_TRAP_CSRS: TrapCsrs = TrapCsrs {
+    tvec: CSR_STVEC,
+    epc: CSR_SEPC,
+    cause: CSR_SCAUSE,
+    tval: CSR_STVAL,
+};
+
+// xtval
+// xepc, xcause
+// xtvec
+// the status fields are updated by the core
 impl Trap {
     pub(crate) fn handle_trap(
         &self,
         csr: &mut Csr,
         current_pc: ProgramCounter,
         new_pc: ProgramCounter,
-        // core: &mut Core,
+        tval: GprUnsigned,
+        delegated: bool,
     ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
-        let mut tvec = csr.read(CSR_MTVEC)?;
+        let csrs = if delegated {
+            &S_MODE_TRAP_CSRS
+        } else {
+            &M_MODE_TRAP_CSRS
+        };
+        let mut tvec = csr.read(csrs.tvec)?;
         let tvec_mode = tvec & 0b11;
         tvec &= !0b11;
 
         match self {
-            Trap::Exception(exception) => {
-                csr.write(CSR_MEPC, current_pc as u32)?;
-                self.set_mcause_for_exception(csr, exception)?;
-
-                // TODO:
-                // csr.write(CSR_MSTATUS, mstatus)?;
-            }
-            Trap::Interrupt(interrupt) => {
-                csr.write(CSR_MEPC, new_pc as u32)?;
-                self.set_mcause_for_insterrupt(csr, interrupt)?;
-
-                // TODO
-                // csr.write(CSR_MSTATUS, mstatus)?;
-            }
+            // an exception returns to the instruction which raised it
+            Trap::Exception(_) => csr.write(csrs.epc, current_pc)?,
+            Trap::Interrupt(_) => csr.write(csrs.epc, new_pc)?,
         }
+        let cause = if self.is_interrupt() {
+            self.code() | csr.xlen().msb()
+        } else {
+            self.code()
+        };
+        csr.write(csrs.cause, cause)?;
+        csr.write(csrs.tval, tval)?;
 
         let new_pc = match tvec_mode {
             0 => {
@@ -124,64 +149,74 @@ impl Trap {
                 tvec
             }
             1 => {
-                // vectored mode
-                let cause_no = csr.read(CSR_MCAUSE)? & !(1 << 31);
-                tvec + (cause_no as u32) * 4
+                // vectored mode, only interrupts are vectored
+                if self.is_interrupt() {
+                    tvec + self.code() * 4
+                } else {
+                    tvec
+                }
             }
             _ => {
-                return Err(RvCoreError::InvalidTrapMode(tvec_mode));
+                return Err(RvCoreError::InvalidTrapMode(tvec_mode as u32));
             }
         };
 
-        // self.set_mstatus(csr, current_mode)?;
         Ok(Some(ExecutionReturnData {
             pc: Some(new_pc as ProgramCounter),
             disasm: None,
         }))
     }
 
-    fn set_mcause_for_exception(
-        &self,
-        csr: &mut Csr,
-        exception: &Exception,
-    ) -> Result<(), RvCoreError> {
-        let value = match exception {
-            Exception::InstructionAddressMisaligned => 0,
-            Exception::InstructionAccessFault => 1,
-            Exception::IllegalInstruction(_instruction) => 2,
-            Exception::Breakpoint => 3,
-            Exception::LoadAddressMisaligned => 4,
-            Exception::LoadAccessFault => 5,
-            Exception::StoreAmoAddressMisaligned => 6,
-            Exception::StoreAmoAccessFault => 7,
-            Exception::ECallFromUMode => 8,
-            Exception::ECallFromSMode => 9,
-            Exception::ECallFromMMode => 11,
-            Exception::InstructionPageFault => 12,
-            Exception::LoadPageFault => 13,
-            Exception::StoreAmoPageFault => 15,
-        };
-
-        csr.write(CSR_MCAUSE, value)?; // cause
+    pub fn is_interrupt(&self) -> bool {
+        matches!(self, Trap::Interrupt(_))
+    }
 
-        Ok(())
+    /// Returns the exception code of xcause, which is also the bit of the
+    /// trap in medeleg or mideleg
+    pub fn code(&self) -> GprUnsigned {
+        match self {
+            Trap::Exception(exception) => match exception {
+                Exception::InstructionAddressMisaligned => 0,
+                Exception::InstructionAccessFault => 1,
+                Exception::IllegalInstruction(_instruction) => 2,
+                Exception::Breakpoint => 3,
+                Exception::LoadAddressMisaligned => 4,
+                Exception::LoadAccessFault => 5,
+                Exception::StoreAmoAddressMisaligned => 6,
+                Exception::StoreAmoAccessFault => 7,
+                Exception::ECallFromUMode => 8,
+                Exception::ECallFromSMode => 9,
+                Exception::ECallFromMMode => 11,
+                Exception::InstructionPageFault => 12,
+                Exception::LoadPageFault => 13,
+                Exception::StoreAmoPageFault => 15,
+            },
+            Trap::Interrupt(interrupt) => interrupt.code(),
+        }
     }
+}
 
-    fn set_mcause_for_insterrupt(
-        &self,
-        csr: &mut Csr,
-        interrupt: &Interrupt,
-    ) -> Result<(), RvCoreError> {
-        let value = match interrupt {
+impl Interrupt {
+    /// Interrupts in the order they are taken when several are pending
+    pub const PRIORITY: [Interrupt; 6] = [
+        Interrupt::MachineExternalInterrupt,
+        Interrupt::MachineSoftwareInterrupt,
+        Interrupt::MachineTimerInterrupt,
+        Interrupt::SupervisorExternalInterrupt,
+        Interrupt::SupervisorSoftwareInterrupt,
+        Interrupt::SupervisorTimerInterrupt,
+    ];
+
+    /// Returns the exception code of xcause, which is also the bit of the
+    /// interrupt in mip, mie and mideleg
+    pub fn code(&self) -> GprUnsigned {
+        match self {
             Interrupt::SupervisorSoftwareInterrupt => 1,
             Interrupt::MachineSoftwareInterrupt => 3,
             Interrupt::SupervisorTimerInterrupt => 5,
             Interrupt::MachineTimerInterrupt => 7,
             Interrupt::SupervisorExternalInterrupt => 9,
             Interrupt::MachineExternalInterrupt => 11,
-        } | (1 << 31);
-
-        csr.write(CSR_MCAUSE, value)?; // cause
-        Ok(())
+        }
     }
 }
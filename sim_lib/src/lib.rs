@@ -13,6 +13,8 @@ use thiserror::Error;
 use cpu_peripherals::CpuPeripheralsError;
 use rv_core::RvCoreError;
 
+pub use rv_core::core::{MachineInfo, MisalignedAccess};
+pub use rv_core::isa::Isa;
 pub use rv_core::ProgramCounter;
 
 /// Define error types for the simulator crate.
@@ -14,12 +14,14 @@ use goblin::elf::Elf;
 use tracing::{info, trace};
 
 use cpu_peripherals::{bus::Bus, DeviceAddress};
+use rv_core::Xlen;
 
 use crate::SimulatorError;
 
 pub struct Loader {
     entry_point: u64,
     program_headers: Vec<goblin::elf::ProgramHeader>,
+    xlen: Xlen,
 }
 
 impl Loader {
@@ -63,6 +65,9 @@ impl Loader {
 
         let entry_point = elf.entry;
         let program_headers = elf.program_headers;
+        // ELFCLASS64 objects are built for RV64
+        let xlen = if elf.is_64 { Xlen::Rv64 } else { Xlen::Rv32 };
+        info!("ELF class selects {:?}", xlen);
 
         for ph in &program_headers {
             trace!("Loading program header: {:?}", ph);
@@ -89,6 +94,7 @@ impl Loader {
         Ok(Some(Loader {
             entry_point,
             program_headers,
+            xlen,
         }))
     }
 
@@ -99,4 +105,8 @@ impl Loader {
     pub fn program_headers(&self) -> &[goblin::elf::ProgramHeader] {
         &self.program_headers
     }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
 }
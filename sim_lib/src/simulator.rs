@@ -5,20 +5,42 @@
 
 // sim_lib/src/simulator.rs
 
-use std::{fs::File, io::Write, path::Path};
-use tracing::{error, info, trace};
+use std::{collections::HashMap, fs::File, io::Write, path::Path};
+use tracing::{debug, error, info, trace};
 
 use cpu_peripherals::{bus::Bus, DeviceAddress};
 use rv_core::{
-    core::Core,
-    decode::{decoder::Decoder, DecodedInstruction, ExecutionReturnData},
+    core::{Core, MachineInfo, MisalignedAccess},
+    decode::{
+        decoder::Decoder, DecodedInstruction, ExecutionReturnData, COMPRESSED_INSTRUCTION_LENGTH,
+    },
     fetch::Fetcher,
-    GprSigned, MachineInstruction, ProgramCounter, RvCoreError,
+    isa::{Extension, Isa},
+    mmu::{AccessType, PAGE_SIZE},
+    trap::Trap,
+    GprSigned, MachineInstruction, ProgramCounter, RvCoreError, Xlen,
 };
 
 use crate::loader::Loader;
 use crate::SimulatorError;
 
+// An instruction and its decoding, or its bits if it belongs to a disabled extension
+type FetchedInstruction = (
+    MachineInstruction,
+    Result<DecodedInstruction, MachineInstruction>,
+);
+
+/// What the simulator does with a trap the guest has no handler for, the
+/// trap vector of the mode taking it being 0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnhandledTrap {
+    /// Take it as a hart does, the execution continues at the trap vector
+    #[default]
+    Enter,
+    /// Stop the run with `SimulatorError::UnhandledTrap`
+    Stop,
+}
+
 pub struct Simulator {
     core: Core,
     decoder: Decoder,
@@ -26,6 +48,10 @@ pub struct Simulator {
     exit_code: GprSigned,
     log_file: Option<File>,
     run_instrctions: u64,
+    unhandled_trap: UnhandledTrap,
+    // Fetched and decoded instructions by physical address. Stores do not touch
+    // it, like a hart's instruction cache it is only made coherent by FENCE.I.
+    predecode_cache: HashMap<DeviceAddress, (MachineInstruction, DecodedInstruction)>,
 }
 
 impl Simulator {
@@ -38,6 +64,8 @@ impl Simulator {
             exit_code: 0,
             log_file: None,
             run_instrctions: 0,
+            unhandled_trap: UnhandledTrap::default(),
+            predecode_cache: HashMap::new(),
         }
     }
 
@@ -49,7 +77,12 @@ impl Simulator {
     }
 
     pub fn load_elf_file(&mut self, elf_file: &Path) -> Result<(), SimulatorError> {
-        let _ = Loader::load_elf_file(elf_file, &mut self.bus);
+        self.flush_predecode_cache();
+        if let Some(loader) = Loader::load_elf_file(elf_file, &mut self.bus)? {
+            if loader.xlen() != self.core.get_xlen() {
+                self.set_xlen(loader.xlen());
+            }
+        }
         Ok(())
     }
 
@@ -58,6 +91,7 @@ impl Simulator {
         bin_file: &Path,
         base_addr: DeviceAddress,
     ) -> Result<(), SimulatorError> {
+        self.flush_predecode_cache();
         let _ = Loader::load_bin_file(bin_file, &mut self.bus, base_addr);
         Ok(())
     }
@@ -67,39 +101,93 @@ impl Simulator {
         bin_program: &[u8],
         base_addr: DeviceAddress,
     ) -> Result<(), SimulatorError> {
+        self.flush_predecode_cache();
         let _ = Loader::load_bin_program(bin_program, &mut self.bus, base_addr);
         Ok(())
     }
 
+    /// Switches the core and the decoder to `xlen`, this resets the core
+    pub fn set_xlen(&mut self, xlen: Xlen) {
+        self.core.set_xlen(xlen);
+        let mut isa = self.get_isa().clone();
+        isa.set_xlen(xlen);
+        self.set_isa(isa);
+    }
+
+    pub fn get_isa(&self) -> &Isa {
+        self.decoder.get_isa()
+    }
+
+    /// Configures the core and the decoder for `isa`, the core is reset if
+    /// XLEN changes
+    pub fn set_isa(&mut self, isa: Isa) {
+        info!("Configuring the ISA {}", isa);
+        self.core.set_isa(&isa);
+        self.decoder.set_isa(isa);
+        self.flush_predecode_cache();
+    }
+
+    /// Enables or disables an extension, see `set_isa`
+    pub fn set_extension_enabled(&mut self, extension: Extension, enabled: bool) {
+        let mut isa = self.get_isa().clone();
+        isa.set_extension_enabled(extension, enabled);
+        self.set_isa(isa);
+    }
+
+    /// Sets how the hart handles misaligned loads and stores, a choice of the
+    /// platform
+    pub fn set_misaligned_access(&mut self, policy: MisalignedAccess) {
+        self.core.set_misaligned_access(policy);
+    }
+
+    /// Sets the identification of the hart read through mvendorid, marchid,
+    /// mimpid, mhartid and mconfigptr
+    pub fn set_machine_info(&mut self, info: MachineInfo) {
+        self.core.set_machine_info(info);
+    }
+
+    /// Sets what happens to a trap the guest has no handler for
+    pub fn set_unhandled_trap(&mut self, policy: UnhandledTrap) {
+        self.unhandled_trap = policy;
+    }
+
+    /// Drops the predecoded instructions. Code written to memory by the
+    /// target is picked up after FENCE.I, code written by the host through
+    /// `get_bus_mut` after this call.
+    pub fn flush_predecode_cache(&mut self) {
+        trace!("Flushing the predecode cache");
+        self.predecode_cache.clear();
+    }
+
     pub fn set_reset_vector(&mut self, pc: ProgramCounter) {
         self.core.set_pc(pc);
     }
     pub fn run(&mut self, steps: Option<usize>) -> Result<(), SimulatorError> {
-        if steps.is_none() {
-            loop {
+        if let Some(steps) = steps {
+            for _ in 0..steps {
                 if self.exit_code != 0 {
                     info!(
                         "Target APP exit with code: {}({:#x})",
                         self.get_exit_code(),
                         self.get_exit_code()
                     );
-                    break Ok(());
+                    break;
                 }
                 self.step()?
             }
+            Ok(())
         } else {
-            for _ in 0..steps.unwrap() {
+            loop {
                 if self.exit_code != 0 {
                     info!(
                         "Target APP exit with code: {}({:#x})",
                         self.get_exit_code(),
                         self.get_exit_code()
                     );
-                    break;
+                    break Ok(());
                 }
                 self.step()?
             }
-            Ok(())
         }
     }
 
@@ -112,7 +200,7 @@ impl Simulator {
         &mut self.core
     }
 
-    pub fn get_bus(&self) -> &Box<Bus> {
+    pub fn get_bus(&self) -> &Bus {
         &self.bus
     }
 
@@ -123,49 +211,180 @@ impl Simulator {
     pub fn get_run_instrctions(&self) -> u64 {
         self.run_instrctions
     }
-    
+
     fn step(&mut self) -> Result<(), SimulatorError> {
+        if self.core.is_waiting() && !self.wait_for_interrupt()? {
+            return Ok(());
+        }
+
         let pc = self.core.get_pc();
         trace!("PC: {:#010x}", pc);
-        let mem = self.bus.find_device(pc.try_into().unwrap())?;
-
-        // step 1. Fetch instruction
-        let instruction = Fetcher::fetch(pc, mem)?;
-        trace!("Instruction: {:#010x}", instruction);
 
-        // step 2. Decode instruction
-        let decoded_instruction = self.decoder.decode(instruction)?;
+        // step 1 and 2. Fetch and decode instruction, unless done before
+        let fetched = self.fetch_and_decode(pc)?;
+        if let Some((instruction, _)) = fetched {
+            trace!("Instruction: {:#010x}", instruction);
+        }
 
-        // step 3. Execute instruction
-        let mut ret_data = self.execute(&decoded_instruction, instruction)?;
+        // step 3. Execute instruction, unknown instructions and those of disabled
+        // extensions are illegal.
+        // Nothing is executed if the fetch raised an exception.
+        let mut ret_data = match fetched {
+            Some((instruction, Ok(decoded_instruction))) => {
+                self.core.set_instruction_length(decoded_instruction.length);
+                self.core.set_instruction_bits(instruction);
+                self.execute(&decoded_instruction, instruction)?
+            }
+            Some((_, Err(bits))) => {
+                self.core.set_instruction_bits(bits);
+                self.core.raise_illegal_instruction()?;
+                None
+            }
+            None => None,
+        };
 
         self.run_instrctions += 1;
 
-        // step 4. check interrupt TODO: // mie, mip
+        if self.core.take_fence_i() {
+            self.flush_predecode_cache();
+        }
 
-        // step 5. process trap
-        ret_data = if let Some(trap) = self.core.take_trap() {
+        // step 4. count the cycle, an instruction raising an exception does
+        // not retire, and let the devices run for it
+        let exception = self.core.take_trap();
+        self.core.advance_counters(exception.is_none());
+        self.bus.tick(1);
+
+        // step 5. check interrupts, the devices update mip and a pending enabled
+        // interrupt is taken after the instruction unless it raised an exception
+        self.core.set_interrupt_lines(self.bus.interrupts());
+        let trap = match exception {
+            Some(exception) => Some(exception),
+            None => self.core.pending_interrupt()?.map(Trap::Interrupt),
+        };
+
+        // step 6. process trap
+        ret_data = if let Some(trap) = trap {
             let new_pc = self.calc_new_pc(ret_data);
 
             if Core::is_ecall(&trap) {
                 let a7 = self.core.read_reg_by_name("a7")?;
                 if a7 == 93 {
                     let a0 = self.core.read_reg_by_name("a0")?;
-                    self.set_exit_code(a0 as GprSigned);
+                    self.set_exit_code(a0 as i32 as GprSigned);
+                    // the output still queued in the devices is sent before exiting
+                    self.bus.flush();
                 }
             }
 
+            if self.exit_code == 0
+                && self.unhandled_trap == UnhandledTrap::Stop
+                && !self.core.has_trap_handler(&trap)?
+            {
+                return Err(SimulatorError::UnhandledTrap(format!(
+                    "{} at {:#010x}, tval {:#x}",
+                    trap,
+                    pc,
+                    self.core.get_trap_value()
+                )));
+            }
+
             self.core.handle_trap(&trap, new_pc)?
         } else {
             ret_data
         };
 
-        // step 6. update PC
+        // step 7. update PC
         self.update_pc(ret_data);
 
         Ok(())
     }
 
+    /// Runs the hart stalled by WFI until an interrupt is pending and enabled
+    /// in mie, the devices are fast-forwarded to their next event meanwhile.
+    /// Returns true once it is awake, with the interrupt taken if globally
+    /// enabled, mepc is then the instruction after WFI.
+    fn wait_for_interrupt(&mut self) -> Result<bool, SimulatorError> {
+        self.core.set_interrupt_lines(self.bus.interrupts());
+        if !self.core.wake_up()? {
+            // without a scheduled event the devices run one cycle at a time
+            let cycles = self.bus.next_event().unwrap_or(1).max(1);
+            trace!("Waiting for an interrupt for {} cycles", cycles);
+            self.core.skip_cycles(cycles);
+            self.bus.tick(cycles);
+            self.core.set_interrupt_lines(self.bus.interrupts());
+            if !self.core.wake_up()? {
+                return Ok(false);
+            }
+        }
+
+        if let Some(interrupt) = self.core.pending_interrupt()? {
+            let pc = self.core.get_pc();
+            let ret_data = self.core.handle_trap(&Trap::Interrupt(interrupt), pc)?;
+            self.update_pc(ret_data);
+        }
+        Ok(true)
+    }
+
+    /// Fetches and decodes the instruction at `pc`, the decoded instructions are
+    /// cached by physical address. Returns None if the fetch raised an exception,
+    /// and the instruction bits as error if they are not an instruction of an
+    /// enabled extension.
+    fn fetch_and_decode(
+        &mut self,
+        pc: ProgramCounter,
+    ) -> Result<Option<FetchedInstruction>, SimulatorError> {
+        let Some(address) = self.core.translate(
+            pc,
+            COMPRESSED_INSTRUCTION_LENGTH,
+            AccessType::Fetch,
+            &mut self.bus,
+        )?
+        else {
+            return Ok(None);
+        };
+        if let Some(&(instruction, decoded_instruction)) = self.predecode_cache.get(&address) {
+            // PMP regions are 4-byte aligned, only a 32-bit instruction that is
+            // not can span two of them
+            if decoded_instruction.length > COMPRESSED_INSTRUCTION_LENGTH
+                && !pc.is_multiple_of(4)
+                && self
+                    .core
+                    .translate(
+                        pc + COMPRESSED_INSTRUCTION_LENGTH,
+                        COMPRESSED_INSTRUCTION_LENGTH,
+                        AccessType::Fetch,
+                        &mut self.bus,
+                    )?
+                    .is_none()
+            {
+                return Ok(None);
+            }
+            return Ok(Some((instruction, Ok(decoded_instruction))));
+        }
+
+        let Some(instruction) = Fetcher::fetch(pc, &mut self.core, &mut self.bus)? else {
+            return Ok(None);
+        };
+        match self.decoder.decode(instruction) {
+            Ok(decoded_instruction) => {
+                // an instruction crossing into the next page is not cached, that
+                // page is translated on every fetch
+                let offset = pc % PAGE_SIZE;
+                if offset + decoded_instruction.length <= PAGE_SIZE {
+                    self.predecode_cache
+                        .insert(address, (instruction, decoded_instruction));
+                }
+                Ok(Some((instruction, Ok(decoded_instruction))))
+            }
+            Err(RvCoreError::InvalidInstruction(bits)) => {
+                debug!("Illegal instruction {:#x} at {:#010x}", bits, pc);
+                Ok(Some((instruction, Err(bits))))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     fn set_exit_code(&mut self, code: GprSigned) {
         self.exit_code = code << 1 | 1;
     }
@@ -180,7 +399,7 @@ impl Simulator {
         instruction: MachineInstruction,
     ) -> Result<Option<ExecutionReturnData>, RvCoreError> {
         let disasm = self.log_file.is_some();
-        let rdata = (instr.execute)(instruction, &mut self.core, &mut self.bus, disasm);
+        let rdata = (instr.execute)(instr.instruction, &mut self.core, &mut self.bus, disasm);
 
         if disasm {
             if let Ok(Some(rdata)) = rdata.as_ref() {
@@ -188,10 +407,12 @@ impl Simulator {
                     if let Some(log_file) = self.log_file.as_mut() {
                         log_file
                             .write_fmt(format_args!(
-                                "{:#010x} ({:#010x}) {}\n",
+                                "{:#010x} ({:#0width$x}) {}\n",
                                 self.core.get_pc(),
                                 instruction,
-                                disasm
+                                instr.format_disasm(disasm),
+                                // "0x" and two digits per byte
+                                width = 2 + 2 * instr.length as usize
                             ))
                             .unwrap_or_else(|e| {
                                 error!("Failed to write log: {}", e);
@@ -209,10 +430,10 @@ impl Simulator {
             if let Some(pc) = rdata.pc {
                 pc
             } else {
-                self.core.get_pc().wrapping_add(4)
+                self.core.get_next_pc()
             }
         } else {
-            self.core.get_pc().wrapping_add(4)
+            self.core.get_next_pc()
         }
     }
     fn update_pc(&mut self, ret_data: Option<ExecutionReturnData>) {
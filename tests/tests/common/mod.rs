@@ -3,14 +3,21 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use std::cell::Cell;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use tracing_subscriber::{self, FmtSubscriber};
 
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter, Xlen};
+
 use cpu_peripherals::{
     bus::{Bus, DevicePointer},
+    clint::Clint,
     mem::Mem,
-    DeviceAddress, DeviceSize,
+    CpuPeripheralsError, Device, DeviceAddress, DeviceSize, DeviceType, MIP_MTIP,
 };
 use sim_lib::simulator::Simulator;
 
@@ -32,6 +39,52 @@ pub(crate) fn creat_sim_for_test() -> Simulator {
     Simulator::new(bus)
 }
 
+pub(crate) const CLINT_BASE_ADDRESS: DeviceAddress = 0x0200_0000;
+const CLINT_SIZE: DeviceSize = 0x1_0000;
+
+/// The hart and devices of the simulators made by `creat_sim_with_options`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SimOptions {
+    pub(crate) xlen: Xlen,
+    /// Adds a CLINT at CLINT_BASE_ADDRESS
+    pub(crate) clint: bool,
+}
+
+impl SimOptions {
+    /// An RV32 hart with the memory only
+    pub(crate) const RV32: SimOptions = SimOptions {
+        xlen: Xlen::Rv32,
+        clint: false,
+    };
+}
+
+/// Creates a simulator whose hart starts with `program` at the start of the
+/// memory
+#[allow(dead_code)]
+pub(crate) fn creat_sim_with_program(program: &[u32]) -> Simulator {
+    creat_sim_with_options(program, SimOptions::RV32)
+}
+
+/// Creates a simulator set up as `options`, whose hart starts with `program`
+/// at the start of the memory
+#[allow(dead_code)]
+pub(crate) fn creat_sim_with_options(program: &[u32], options: SimOptions) -> Simulator {
+    let mut sim = creat_sim_for_test();
+    if options.clint {
+        let clint = DevicePointer::new(Clint::default());
+        let _ = sim
+            .get_bus_mut()
+            .add_device(CLINT_BASE_ADDRESS, CLINT_SIZE, clint);
+    }
+    // a change of XLEN resets the core
+    if options.xlen != sim.get_core().get_xlen() {
+        sim.set_xlen(options.xlen);
+    }
+    load_program(&mut sim, MEMORY_BASE_ADDRESS as GprUnsigned, program);
+    sim.set_reset_vector(MEMORY_BASE_ADDRESS as ProgramCounter);
+    sim
+}
+
 pub(crate) const FLASH_BASE_ADDRESS: DeviceAddress = 0x8000_0000;
 const FLASH_SIZE: DeviceSize = 512 * 1024;
 
@@ -68,3 +121,162 @@ pub(crate) fn load_test_bin_program(sim: &mut Simulator, bin_file_name: &str) {
     println!("bin file path: {:?}", bin_file_path);
     let _ = sim.load_bin_file(bin_file_path.as_path(), MEMORY_BASE_ADDRESS);
 }
+
+/// Writes the instructions of `program` to the memory at `address`
+#[allow(dead_code)]
+pub(crate) fn load_program(sim: &mut Simulator, address: GprUnsigned, program: &[u32]) {
+    for (i, instruction) in program.iter().enumerate() {
+        let address = (address + 4 * i as GprUnsigned) as DeviceAddress;
+        sim.get_bus_mut().write_word(address, *instruction).unwrap();
+    }
+}
+
+/// Loads `setup` followed by `code` at the start of the memory, sets
+/// `registers` and lets `configure` prepare the rest. Then runs the setup
+/// code, which ends with an MRET, and returns once the hart is at mepc.
+#[allow(dead_code)]
+pub(crate) fn run_after_mret(
+    setup: &[u32],
+    code: &[u32],
+    registers: &[(&str, GprUnsigned)],
+    configure: impl FnOnce(&mut Simulator),
+) -> Simulator {
+    let base = MEMORY_BASE_ADDRESS as GprUnsigned;
+    let mut sim = creat_sim_for_test();
+    load_program(&mut sim, base, setup);
+    load_program(&mut sim, base + 4 * setup.len() as GprUnsigned, code);
+    sim.set_reset_vector(MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    for (name, value) in registers {
+        core.write_reg_by_name(name, *value).unwrap();
+    }
+    configure(&mut sim);
+
+    sim.run(Some(setup.len())).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), core.read_csr(CSR_MEPC).unwrap());
+    sim
+}
+
+/// Runs one instruction and returns the privilege mode it trapped into with
+/// its cause, if it jumped to mtvec or stvec
+#[allow(dead_code)]
+pub(crate) fn run_trapping(sim: &mut Simulator) -> Option<(PrivilegeMode, GprUnsigned)> {
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    let pc = core.get_pc();
+    if pc == core.read_csr(CSR_MTVEC).unwrap() {
+        Some((PrivilegeMode::Machine, core.read_csr(CSR_MCAUSE).unwrap()))
+    } else if pc == core.read_csr(CSR_STVEC).unwrap() {
+        Some((
+            PrivilegeMode::Supervisor,
+            core.read_csr(CSR_SCAUSE).unwrap(),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Asserts the instruction at `epc` trapped into M-mode with `cause` and `tval`
+#[allow(dead_code)]
+pub(crate) fn assert_trapped(
+    sim: &Simulator,
+    cause: GprUnsigned,
+    epc: GprUnsigned,
+    tval: GprUnsigned,
+) {
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), core.read_csr(CSR_MTVEC).unwrap());
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Machine);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(cause));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(epc));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(tval));
+}
+
+/// A device without registers whose interrupt lines are driven by the test.
+/// It also asserts MTIP once its timer, if any, has run out.
+struct InterruptSource {
+    lines: Rc<Cell<u64>>,
+    timer: Option<u64>,
+}
+
+impl Device for InterruptSource {
+    fn get_type(&self) -> DeviceType {
+        DeviceType::Mem
+    }
+    fn set_base_addr(&mut self, _base_addr: DeviceAddress) {}
+
+    fn read_byte(&self, _address: DeviceAddress) -> Result<u8, CpuPeripheralsError> {
+        Ok(0)
+    }
+    fn write_byte(
+        &mut self,
+        _address: DeviceAddress,
+        _value: u8,
+    ) -> Result<(), CpuPeripheralsError> {
+        Ok(())
+    }
+    fn read_halfword(&self, _address: DeviceAddress) -> Result<u16, CpuPeripheralsError> {
+        Ok(0)
+    }
+    fn write_halfword(
+        &mut self,
+        _address: DeviceAddress,
+        _value: u16,
+    ) -> Result<(), CpuPeripheralsError> {
+        Ok(())
+    }
+    fn read_word(&self, _address: DeviceAddress) -> Result<u32, CpuPeripheralsError> {
+        Ok(0)
+    }
+    fn write_word(
+        &mut self,
+        _address: DeviceAddress,
+        _value: u32,
+    ) -> Result<(), CpuPeripheralsError> {
+        Ok(())
+    }
+    fn read(&self, _address: DeviceAddress, size: usize) -> Result<Vec<u8>, CpuPeripheralsError> {
+        Ok(vec![0; size])
+    }
+    fn write(&mut self, _address: DeviceAddress, _data: &[u8]) -> Result<(), CpuPeripheralsError> {
+        Ok(())
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.timer = self.timer.map(|timer| timer.saturating_sub(cycles));
+    }
+
+    fn interrupts(&self) -> u64 {
+        let timer = match self.timer {
+            Some(0) => MIP_MTIP,
+            _ => 0,
+        };
+        self.lines.get() | timer
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        self.timer.filter(|&timer| timer > 0)
+    }
+}
+
+const INTERRUPT_SOURCE_BASE: DeviceAddress = 0x10_0000;
+
+/// Adds a device asserting the interrupts `lines`, and MTIP after `timer`
+/// cycles. Returns its lines, which the test can change.
+#[allow(dead_code)]
+pub(crate) fn add_interrupt_source(
+    sim: &mut Simulator,
+    lines: u64,
+    timer: Option<u64>,
+) -> Rc<Cell<u64>> {
+    let lines = Rc::new(Cell::new(lines));
+    let source = InterruptSource {
+        lines: lines.clone(),
+        timer,
+    };
+    sim.get_bus_mut()
+        .add_device(INTERRUPT_SOURCE_BASE, 0x1000, DevicePointer::new(source))
+        .unwrap();
+    lines
+}
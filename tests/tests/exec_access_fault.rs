@@ -0,0 +1,104 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_access_fault.rs
+
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::DeviceAddress;
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// lw a0, 0(a1)
+const LW_INSTR: u32 = 0x0005a503;
+// sw a0, 0(a1)
+const SW_INSTR: u32 = 0x00a5a023;
+// amoadd.w a0, a2, (a1)
+const AMOADD_W_INSTR: u32 = 0x00c5a52f;
+// jr a5
+const JR_A5_INSTR: u32 = 0x00078067;
+// lui a0, 0x12345
+const LUI_INSTR: u32 = 0x12345537;
+
+const INSTRUCTION_ACCESS_FAULT: GprUnsigned = 1;
+const LOAD_ACCESS_FAULT: GprUnsigned = 5;
+const STORE_AMO_ACCESS_FAULT: GprUnsigned = 7;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The instruction under test follows the setup instruction
+const CODE: GprUnsigned = BASE + 4;
+const HANDLER: GprUnsigned = BASE + 0x100;
+// Nothing is mapped after the memory, which ends at 0x3_0000
+const MEMORY_END: GprUnsigned = 0x3_0000;
+const UNMAPPED: GprUnsigned = 0x10_0000;
+
+/// Runs the setup code, which sets mtvec, then `instruction` with `register`
+/// set to `value`
+fn run_instruction(instruction: u32, register: &str, value: GprUnsigned) -> Simulator {
+    let program = [CSRW_MTVEC_INSTR, instruction];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("a0", 0x55).unwrap();
+    core.write_reg_by_name("a2", 1).unwrap();
+    core.write_reg_by_name(register, value).unwrap();
+
+    sim.run(Some(2)).expect("Simulation failed");
+    sim
+}
+
+#[test]
+fn test_load_access_fault() {
+    let sim = run_instruction(LW_INSTR, "a1", UNMAPPED + 8);
+    common::assert_trapped(&sim, LOAD_ACCESS_FAULT, CODE, UNMAPPED + 8);
+    // rd is not written
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x55));
+}
+
+#[test]
+fn test_store_access_fault() {
+    let sim = run_instruction(SW_INSTR, "a1", UNMAPPED);
+    common::assert_trapped(&sim, STORE_AMO_ACCESS_FAULT, CODE, UNMAPPED);
+
+    // a misaligned word that is only partly in the memory, it is emulated and
+    // its first byte after the memory faults
+    let sim = run_instruction(SW_INSTR, "a1", MEMORY_END - 2);
+    common::assert_trapped(&sim, STORE_AMO_ACCESS_FAULT, CODE, MEMORY_END);
+}
+
+#[test]
+fn test_amo_access_fault() {
+    // an AMO reports a store fault, even though it reads first
+    let sim = run_instruction(AMOADD_W_INSTR, "a1", UNMAPPED);
+    common::assert_trapped(&sim, STORE_AMO_ACCESS_FAULT, CODE, UNMAPPED);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x55));
+}
+
+#[test]
+fn test_fetch_access_fault() {
+    let mut sim = run_instruction(JR_A5_INSTR, "a5", UNMAPPED);
+    assert_eq!(sim.get_core().get_pc(), UNMAPPED);
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(&sim, INSTRUCTION_ACCESS_FAULT, UNMAPPED, UNMAPPED);
+}
+
+#[test]
+fn test_fetch_crossing_the_end_of_memory() {
+    // the upper half of the instruction is unmapped, mtval is its address
+    let mut sim = run_instruction(JR_A5_INSTR, "a5", MEMORY_END - 2);
+    let _ = sim.load_bin_program(
+        &LUI_INSTR.to_le_bytes()[..2],
+        (MEMORY_END - 2) as DeviceAddress,
+    );
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(&sim, INSTRUCTION_ACCESS_FAULT, MEMORY_END - 2, MEMORY_END);
+}
@@ -5,7 +5,7 @@
 
 // tests/tests/exec_add_instr.rs
 
-use rv_core::{GprSigned, GprUnsigned};
+use rv_core::GprUnsigned;
 
 mod common;
 
@@ -24,8 +24,8 @@ fn test_add_instruction_execution() {
     // step 3. prepare the environment
     sim.set_reset_vector(common::MEMORY_BASE_ADDRESS.try_into().unwrap());
     let core = sim.get_core_mut();
-    let _ = core.write_register(10, 10).unwrap();
-    let _ = core.write_register(11, 11).unwrap();
+    core.write_register(10, 10).unwrap();
+    core.write_register(11, 11).unwrap();
 
     // step 4. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -40,7 +40,8 @@ fn test_add_instruction_execution() {
 // let ADD_INSTR = [0x13, 0x03, 0x13, 0x00];
 const ADDI_0_INSTR: [u8; 4] = [0x13, 0x03, 0x03, 0x00];
 
-fn test_addi_instruction_execution_x(initial_val: GprSigned, imm: GprSigned) {
+// The simulator is an RV32 one, the values are 32 bits wide
+fn test_addi_instruction_execution_x(initial_val: i32, imm: i32) {
     // common::setup_tracing();
 
     // step 1. create a simulator
@@ -48,15 +49,14 @@ fn test_addi_instruction_execution_x(initial_val: GprSigned, imm: GprSigned) {
 
     // step 2. load the program into memory
     let mut addi_instr = u32::from_le_bytes(ADDI_0_INSTR);
-    addi_instr |= (imm as GprUnsigned) << 20;
+    addi_instr |= (imm as u32) << 20;
     let addi_instr = addi_instr.to_le_bytes();
     let _ = sim.load_bin_program(&addi_instr, common::MEMORY_BASE_ADDRESS);
 
     // step 3. prepare the environment
     sim.set_reset_vector(common::MEMORY_BASE_ADDRESS.try_into().unwrap());
     let core = sim.get_core_mut();
-    let _ = core
-        .write_reg_by_name("t1", initial_val as GprUnsigned)
+    core.write_reg_by_name("t1", initial_val as u32 as GprUnsigned)
         .unwrap();
 
     // step 4. run the simulator
@@ -66,7 +66,7 @@ fn test_addi_instruction_execution_x(initial_val: GprSigned, imm: GprSigned) {
     let core = sim.get_core();
     assert_eq!(
         core.read_register(6),
-        Ok((initial_val + imm) as GprUnsigned)
+        Ok((initial_val + imm) as u32 as GprUnsigned)
     );
 }
 
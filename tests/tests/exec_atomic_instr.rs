@@ -0,0 +1,209 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_atomic_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprSigned, GprUnsigned};
+
+use cpu_peripherals::DeviceAddress;
+
+mod common;
+
+// amoadd.w  a2, a1, (a0) ---- amoadd.w rd, rs2, (rs1)
+const AMOADD_W_INSTR: u32 = 0x00b5262f;
+// amoswap.w a2, a1, (a0)
+const AMOSWAP_W_INSTR: u32 = 0x08b5262f;
+// amoxor.w  a2, a1, (a0)
+const AMOXOR_W_INSTR: u32 = 0x20b5262f;
+// amoor.w   a2, a1, (a0)
+const AMOOR_W_INSTR: u32 = 0x40b5262f;
+// amoand.w  a2, a1, (a0)
+const AMOAND_W_INSTR: u32 = 0x60b5262f;
+// amomin.w  a2, a1, (a0)
+const AMOMIN_W_INSTR: u32 = 0x80b5262f;
+// amomax.w  a2, a1, (a0)
+const AMOMAX_W_INSTR: u32 = 0xa0b5262f;
+// amominu.w a2, a1, (a0)
+const AMOMINU_W_INSTR: u32 = 0xc0b5262f;
+// amomaxu.w a2, a1, (a0)
+const AMOMAXU_W_INSTR: u32 = 0xe0b5262f;
+// lr.w      a2, (a0)
+const LR_W_INSTR: u32 = 0x1005262f;
+// sc.w      a2, a1, (a0)
+const SC_W_INSTR: u32 = 0x18b5262f;
+// sw        a3, 0(a0)
+const SW_INSTR: u32 = 0x00d52023;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// aq and rl bits
+const AQRL_BITS: u32 = 0x0600_0000;
+
+const DATA_ADDRESS: DeviceAddress = common::MEMORY_BASE_ADDRESS + 0x100;
+
+fn test_amo_instruction_execution_x(instr: u32, mem_val: u32, rs2_val: u32, expected_mem: u32) {
+    // common::setup_tracing();
+
+    // step 1. create a simulator and load the program into memory
+    let mut sim = common::creat_sim_with_program(&[instr]);
+
+    // step 2. prepare the environment
+    sim.get_bus_mut().write_word(DATA_ADDRESS, mem_val).unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", rs2_val as GprUnsigned)
+        .unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 4. check the result, rd gets the original memory value
+    assert_eq!(
+        sim.get_core().read_reg_by_name("a2"),
+        Ok(mem_val as GprUnsigned)
+    );
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(expected_mem));
+}
+
+const MINUS_ONE: u32 = -1 as GprSigned as u32;
+
+#[test]
+fn test_amoadd_w_instruction_execution() {
+    test_amo_instruction_execution_x(AMOADD_W_INSTR, 40, 2, 42);
+    test_amo_instruction_execution_x(AMOADD_W_INSTR, MINUS_ONE, 1, 0);
+}
+
+#[test]
+fn test_amoswap_w_instruction_execution() {
+    test_amo_instruction_execution_x(AMOSWAP_W_INSTR, 0x1234, 0x5678, 0x5678);
+    test_amo_instruction_execution_x(AMOSWAP_W_INSTR | AQRL_BITS, 1, 0, 0);
+}
+
+#[test]
+fn test_amoxor_or_and_w_instruction_execution() {
+    test_amo_instruction_execution_x(AMOXOR_W_INSTR, 0b1100, 0b1010, 0b0110);
+    test_amo_instruction_execution_x(AMOOR_W_INSTR, 0b1100, 0b1010, 0b1110);
+    test_amo_instruction_execution_x(AMOAND_W_INSTR, 0b1100, 0b1010, 0b1000);
+}
+
+#[test]
+fn test_amomin_max_w_instruction_execution() {
+    test_amo_instruction_execution_x(AMOMIN_W_INSTR, MINUS_ONE, 1, MINUS_ONE);
+    test_amo_instruction_execution_x(AMOMAX_W_INSTR, MINUS_ONE, 1, 1);
+    test_amo_instruction_execution_x(AMOMINU_W_INSTR, MINUS_ONE, 1, 1);
+    test_amo_instruction_execution_x(AMOMAXU_W_INSTR, MINUS_ONE, 1, MINUS_ONE);
+}
+
+#[test]
+fn test_lr_sc_w_instruction_execution() {
+    // lr.w; sc.w succeeds and writes rs2
+    let mut sim = common::creat_sim_with_program(&[LR_W_INSTR, SC_W_INSTR]);
+    sim.get_bus_mut().write_word(DATA_ADDRESS, 7).unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 42).unwrap();
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(7));
+    assert_eq!(
+        sim.get_core().get_reservation(),
+        Some(DATA_ADDRESS as GprUnsigned)
+    );
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0));
+    assert_eq!(sim.get_core().get_reservation(), None);
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(42));
+}
+
+#[test]
+fn test_sc_w_without_reservation_fails() {
+    let mut sim = common::creat_sim_with_program(&[SC_W_INSTR]);
+    sim.get_bus_mut().write_word(DATA_ADDRESS, 7).unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 42).unwrap();
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(1));
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(7));
+}
+
+#[test]
+fn test_store_breaks_reservation() {
+    // lr.w; sw to the reserved word; sc.w fails
+    let mut sim = common::creat_sim_with_program(&[LR_W_INSTR, SW_INSTR, SC_W_INSTR | AQRL_BITS]);
+    sim.get_bus_mut().write_word(DATA_ADDRESS, 7).unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 42).unwrap();
+    core.write_reg_by_name("a3", 8).unwrap();
+
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(1));
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(8));
+}
+
+#[test]
+fn test_xret_breaks_reservation() {
+    // lr.w; mret to the next instruction; sc.w fails
+    let mut sim = common::creat_sim_with_program(&[LR_W_INSTR, MRET_INSTR, SC_W_INSTR]);
+    sim.get_bus_mut().write_word(DATA_ADDRESS, 7).unwrap();
+    let core = sim.get_core_mut();
+    core.write_csr(CSR_MEPC, common::MEMORY_BASE_ADDRESS as GprUnsigned + 8)
+        .unwrap();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 42).unwrap();
+
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(1));
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(7));
+}
+
+#[test]
+fn test_misaligned_amo_raises_exception() {
+    let mut sim = common::creat_sim_with_program(&[AMOADD_W_INSTR]);
+    let misaligned_address = DATA_ADDRESS as GprUnsigned + 2;
+    sim.get_bus_mut().write_word(DATA_ADDRESS, 7).unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", misaligned_address).unwrap();
+    core.write_reg_by_name("a1", 1).unwrap();
+    core.write_reg_by_name("a2", 0x55).unwrap();
+
+    sim.run(Some(1)).expect("Simulation failed");
+
+    let core = sim.get_core();
+    // store/AMO address misaligned
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(6));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(misaligned_address));
+    assert_eq!(
+        core.read_csr(CSR_MEPC),
+        Ok(common::MEMORY_BASE_ADDRESS as GprUnsigned)
+    );
+    // neither rd nor memory are touched
+    assert_eq!(core.read_reg_by_name("a2"), Ok(0x55));
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS), Ok(7));
+}
+
+#[test]
+fn test_misaligned_lr_raises_exception() {
+    let mut sim = common::creat_sim_with_program(&[LR_W_INSTR]);
+    let misaligned_address = DATA_ADDRESS as GprUnsigned + 1;
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", misaligned_address).unwrap();
+
+    sim.run(Some(1)).expect("Simulation failed");
+
+    let core = sim.get_core();
+    // load address misaligned
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(4));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(misaligned_address));
+    assert_eq!(core.get_reservation(), None);
+}
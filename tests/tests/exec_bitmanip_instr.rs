@@ -0,0 +1,203 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_bitmanip_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::isa::Extension;
+use rv_core::{GprSigned, GprUnsigned, Xlen};
+
+mod common;
+
+// sh1add a2, a0, a1
+const SH1ADD_INSTR: u32 = 0x20b52633;
+// sh3add a2, a0, a1
+const SH3ADD_INSTR: u32 = 0x20b56633;
+// andn   a2, a0, a1
+const ANDN_INSTR: u32 = 0x40b57633;
+// xnor   a2, a0, a1
+const XNOR_INSTR: u32 = 0x40b54633;
+// clz    a2, a0
+const CLZ_INSTR: u32 = 0x60051613;
+// ctz    a2, a0
+const CTZ_INSTR: u32 = 0x60151613;
+// cpop   a2, a0
+const CPOP_INSTR: u32 = 0x60251613;
+// max    a2, a0, a1
+const MAX_INSTR: u32 = 0x0ab56633;
+// minu   a2, a0, a1
+const MINU_INSTR: u32 = 0x0ab55633;
+// sext.b a2, a0
+const SEXT_B_INSTR: u32 = 0x60451613;
+// zext.h a2, a0
+const ZEXT_H_INSTR: u32 = 0x08054633;
+// rol    a2, a0, a1
+const ROL_INSTR: u32 = 0x60b51633;
+// rori   a2, a0, 8
+const RORI_INSTR: u32 = 0x60855613;
+// orc.b  a2, a0
+const ORC_B_INSTR: u32 = 0x28755613;
+// rev8   a2, a0
+const REV8_INSTR: u32 = 0x69855613;
+// clmul  a2, a0, a1
+const CLMUL_INSTR: u32 = 0x0ab51633;
+// clmulh a2, a0, a1
+const CLMULH_INSTR: u32 = 0x0ab53633;
+// clmulr a2, a0, a1
+const CLMULR_INSTR: u32 = 0x0ab52633;
+// bset   a2, a0, a1
+const BSET_INSTR: u32 = 0x28b51633;
+// bclri  a2, a0, 31
+const BCLRI_INSTR: u32 = 0x49f51613;
+// bext   a2, a0, a1
+const BEXT_INSTR: u32 = 0x48b55633;
+// binvi  a2, a0, 0
+const BINVI_INSTR: u32 = 0x68051613;
+// add.uw a2, a0, a1
+const ADD_UW_INSTR: u32 = 0x08b5063b;
+// slli.uw a2, a0, 4
+const SLLI_UW_INSTR: u32 = 0x0845161b;
+// clzw   a2, a0
+const CLZW_INSTR: u32 = 0x6005161b;
+// rorw   a2, a0, a1
+const RORW_INSTR: u32 = 0x60b5563b;
+// rev8   a2, a0 on RV64
+const REV8_RV64_INSTR: u32 = 0x6b855613;
+// bseti  a2, a0, 40
+const BSETI_RV64_INSTR: u32 = 0x2a851613;
+
+/// Runs `a2 = a0 op a1` and returns a2
+fn run_instruction(
+    xlen: Xlen,
+    instr: u32,
+    rs1_val: GprUnsigned,
+    rs2_val: GprUnsigned,
+) -> GprUnsigned {
+    // common::setup_tracing();
+
+    // step 1. create a simulator and load the program into memory
+    let options = common::SimOptions {
+        xlen,
+        ..common::SimOptions::RV32
+    };
+    let mut sim = common::creat_sim_with_options(&[instr], options);
+
+    // step 2. prepare the environment
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", rs1_val).unwrap();
+    core.write_reg_by_name("a1", rs2_val).unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 4. return the result
+    sim.get_core().read_reg_by_name("a2").unwrap()
+}
+
+fn run_rv32(instr: u32, rs1_val: GprUnsigned, rs2_val: GprUnsigned) -> GprUnsigned {
+    run_instruction(Xlen::Rv32, instr, rs1_val, rs2_val)
+}
+
+fn run_rv64(instr: u32, rs1_val: GprUnsigned, rs2_val: GprUnsigned) -> GprUnsigned {
+    run_instruction(Xlen::Rv64, instr, rs1_val, rs2_val)
+}
+
+#[test]
+fn test_zba() {
+    assert_eq!(run_rv32(SH1ADD_INSTR, 0x10, 3), 0x23);
+    assert_eq!(run_rv32(SH3ADD_INSTR, 0x2000_0001, 1), 9);
+    // the .uw forms take the zero-extended low word of rs1
+    assert_eq!(
+        run_rv64(ADD_UW_INSTR, (-1 as GprSigned) as GprUnsigned, 1),
+        0x1_0000_0000
+    );
+    assert_eq!(
+        run_rv64(SLLI_UW_INSTR, 0xffff_ffff_8000_0000, 0),
+        0x8_0000_0000
+    );
+}
+
+#[test]
+fn test_zbb_logical_and_min_max() {
+    assert_eq!(run_rv32(ANDN_INSTR, 0xff, 0x0f), 0xf0);
+    assert_eq!(run_rv32(XNOR_INSTR, 0xffff_0000, 0xff00_ff00), 0xff00_00ff);
+    assert_eq!(run_rv32(MAX_INSTR, 0xffff_ffff, 1), 1);
+    assert_eq!(run_rv32(MINU_INSTR, 0xffff_ffff, 1), 1);
+    assert_eq!(run_rv32(SEXT_B_INSTR, 0x80, 0), 0xffff_ff80);
+    assert_eq!(run_rv32(ZEXT_H_INSTR, 0xffff_8000, 0), 0x8000);
+}
+
+#[test]
+fn test_zbb_count_and_bytes() {
+    assert_eq!(run_rv32(CLZ_INSTR, 0x0001_0000, 0), 15);
+    assert_eq!(run_rv32(CLZ_INSTR, 0, 0), 32);
+    assert_eq!(run_rv64(CLZ_INSTR, 0x0001_0000, 0), 47);
+    assert_eq!(run_rv32(CTZ_INSTR, 0, 0), 32);
+    assert_eq!(run_rv32(CTZ_INSTR, 0x8000_0000, 0), 31);
+    assert_eq!(run_rv32(CPOP_INSTR, 0xf0f0_0001, 0), 9);
+    assert_eq!(run_rv32(ORC_B_INSTR, 0x0100_2000, 0), 0xff00_ff00);
+    assert_eq!(run_rv32(REV8_INSTR, 0x1122_3344, 0), 0x4433_2211);
+    assert_eq!(
+        run_rv64(REV8_RV64_INSTR, 0x1122_3344_5566_7788, 0),
+        0x8877_6655_4433_2211
+    );
+    assert_eq!(run_rv64(CLZW_INSTR, 0xffff_0000_0000_ffff, 0), 16);
+}
+
+#[test]
+fn test_zbb_rotations() {
+    assert_eq!(run_rv32(ROL_INSTR, 0x8000_0001, 1), 3);
+    // only the low log2(XLEN) bits of rs2 are used
+    assert_eq!(run_rv32(ROL_INSTR, 0x8000_0001, 33), 3);
+    assert_eq!(run_rv32(RORI_INSTR, 0x1122_3344, 0), 0x4411_2233);
+    assert_eq!(run_rv64(RORI_INSTR, 0x1122_3344, 0), 0x4400_0000_0011_2233);
+    // rorw sign-extends the rotated word
+    assert_eq!(
+        run_rv64(RORW_INSTR, 0x1_0000_0001, 1),
+        0xffff_ffff_8000_0000
+    );
+}
+
+#[test]
+fn test_zbc() {
+    assert_eq!(run_rv32(CLMUL_INSTR, 0b101, 0b11), 0b1111);
+    assert_eq!(run_rv32(CLMUL_INSTR, 0x8000_0001, 0b11), 0x8000_0003);
+    assert_eq!(run_rv32(CLMULH_INSTR, 0x8000_0001, 0b11), 1);
+    assert_eq!(run_rv32(CLMULR_INSTR, 0x8000_0001, 0b11), 3);
+    assert_eq!(run_rv64(CLMULH_INSTR, 0x8000_0000_0000_0000, 0b10), 1);
+}
+
+#[test]
+fn test_zbs() {
+    assert_eq!(run_rv32(BSET_INSTR, 0, 31), 0x8000_0000);
+    assert_eq!(run_rv32(BSET_INSTR, 0, 32), 1);
+    assert_eq!(run_rv32(BCLRI_INSTR, 0xffff_ffff, 0), 0x7fff_ffff);
+    assert_eq!(run_rv32(BEXT_INSTR, 0x10, 4), 1);
+    assert_eq!(run_rv32(BINVI_INSTR, 1, 0), 0);
+    assert_eq!(run_rv64(BSETI_RV64_INSTR, 0, 0), 1 << 40);
+}
+
+#[test]
+fn test_disabled_extension_is_illegal() {
+    let mut sim = common::creat_sim_with_program(&[BSET_INSTR, ANDN_INSTR]);
+    sim.set_extension_enabled(Extension::Zbb, false);
+    sim.get_core_mut().write_reg_by_name("a0", 0x10).unwrap();
+
+    // Zbs is still enabled
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0x11));
+
+    // andn raises an illegal instruction exception
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MTVAL),
+        Ok(ANDN_INSTR as GprUnsigned)
+    );
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MEPC),
+        Ok(common::MEMORY_BASE_ADDRESS as GprUnsigned + 4)
+    );
+}
@@ -22,8 +22,8 @@ fn test_beq_instruction_execution_0() {
     // step 3. prepare the environment
     sim.set_reset_vector(common::MEMORY_BASE_ADDRESS.try_into().unwrap());
     let core = sim.get_core_mut();
-    let _ = core.write_register(8, 0).unwrap();
-    let _ = core.write_register(9, 0).unwrap();
+    core.write_register(8, 0).unwrap();
+    core.write_register(9, 0).unwrap();
 
     // step 4. run the simulator
     sim.run(Some(3)).expect("Simulation failed");
@@ -48,8 +48,8 @@ fn test_beq_instruction_execution_1() {
     // step 3. prepare the environment
     sim.set_reset_vector(common::MEMORY_BASE_ADDRESS.try_into().unwrap());
     let core = sim.get_core_mut();
-    let _ = core.write_register(8, 0).unwrap();
-    let _ = core.write_register(9, 1).unwrap();
+    core.write_register(8, 0).unwrap();
+    core.write_register(9, 1).unwrap();
 
     // step 4. run the simulator
     sim.run(Some(3)).expect("Simulation failed");
@@ -85,7 +85,7 @@ fn test_jal_instruction_execution() {
     let core = sim.get_core();
     assert_eq!(core.read_register(5), Ok(10));
     assert_eq!(core.read_register(6), Ok(20 + 1));
-    assert_eq!(core.read_register(7), Ok(0 + 1));
+    assert_eq!(core.read_register(7), Ok(1));
 
     let next_pc = common::MEMORY_BASE_ADDRESS as GprUnsigned + 4 * 4;
     println!("next_pc: {:#x}", next_pc);
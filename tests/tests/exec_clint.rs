@@ -0,0 +1,108 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_clint.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use cpu_peripherals::{DeviceAddress, MIP_MSIP, MIP_MTIP};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrw mie, t2
+const CSRW_MIE_INSTR: u32 = 0x30439073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// sw a1, 0(a0)
+const SW_LOW_INSTR: u32 = 0x00b52023;
+// sw zero, 4(a0)
+const SW_HIGH_INSTR: u32 = 0x00052223;
+// wfi
+const WFI_INSTR: u32 = 0x10500073;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+
+const MACHINE_SOFTWARE_INTERRUPT: GprUnsigned = 0x8000_0003;
+const MACHINE_TIMER_INTERRUPT: GprUnsigned = 0x8000_0007;
+const MSTATUS_MIE: GprUnsigned = 0x8;
+
+const MSIP: GprUnsigned = common::CLINT_BASE_ADDRESS as GprUnsigned;
+const MTIMECMP: GprUnsigned = MSIP + 0x4000;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// the stores follow the three setup instructions
+const STORES: GprUnsigned = BASE + 12;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Runs the setup code, which enables the machine software and timer
+/// interrupts, then stores `a1` to the CLINT register at `a0` and waits
+/// for an interrupt
+fn run_program(register: GprUnsigned, value: GprUnsigned) -> Simulator {
+    // common::setup_tracing();
+
+    let program = [
+        CSRW_MTVEC_INSTR,
+        CSRW_MIE_INSTR,
+        CSRS_MSTATUS_INSTR,
+        SW_LOW_INSTR,
+        SW_HIGH_INSTR,
+        WFI_INSTR,
+        NOP_INSTR,
+    ];
+    let options = common::SimOptions {
+        clint: true,
+        ..common::SimOptions::RV32
+    };
+    let mut sim = common::creat_sim_with_options(&program, options);
+    common::load_program(&mut sim, HANDLER, &[NOP_INSTR]);
+
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("t2", MIP_MSIP | MIP_MTIP).unwrap();
+    core.write_reg_by_name("t3", MSTATUS_MIE).unwrap();
+    core.write_reg_by_name("a0", register).unwrap();
+    core.write_reg_by_name("a1", value).unwrap();
+    sim
+}
+
+#[test]
+fn test_timer_interrupt() {
+    let mut sim = run_program(MTIMECMP, 1000);
+    // the setup, the stores and WFI
+    sim.run(Some(6)).expect("Simulation failed");
+    assert!(sim.get_core().is_waiting());
+
+    // the hart sleeps until mtime reaches mtimecmp
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), HANDLER + 4);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_TIMER_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(STORES + 12));
+    assert_eq!(sim.get_bus().mtime(), Some(1001));
+    assert_eq!(core.read_csr(CSR_MIP), Ok(MIP_MTIP));
+}
+
+#[test]
+fn test_software_interrupt() {
+    // the interrupt is taken right after the store to msip
+    let mut sim = run_program(MSIP, 1);
+    sim.run(Some(4)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_SOFTWARE_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(STORES + 4));
+    assert_eq!(core.read_csr(CSR_MIP), Ok(MIP_MSIP));
+
+    // clearing msip clears MSIP
+    sim.get_bus_mut()
+        .write_word(MSIP as DeviceAddress, 0)
+        .unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MIP), Ok(0));
+}
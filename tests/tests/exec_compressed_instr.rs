@@ -0,0 +1,302 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_compressed_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::{
+    bus::{Bus, DevicePointer},
+    mem::Mem,
+    DeviceAddress,
+};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// c.li   a0, 5
+const C_LI_INSTR: u16 = 0x4515;
+// c.mv   a2, a1
+const C_MV_INSTR: u16 = 0x862e;
+// c.jal  6
+const C_JAL_INSTR: u16 = 0x2019;
+// c.nop
+const C_NOP_INSTR: u16 = 0x0001;
+// c.jr   ra
+const C_JR_INSTR: u16 = 0x8082;
+// addi   a1, a0, 1
+const ADDI_INSTR: u32 = 0x00150593;
+// csrrs  x0, mstatus, t0
+const ENABLE_FS_INSTR: u32 = 0x3002a073;
+// c.flw  fa0, 0(a0)
+const C_FLW_INSTR: u16 = 0x6108;
+// c.fsw  fa0, 4(a0)
+const C_FSW_INSTR: u16 = 0xe148;
+// c.fswsp fa0, 8(sp)
+const C_FSWSP_INSTR: u16 = 0xe42a;
+// c.flwsp fa1, 8(sp)
+const C_FLWSP_INSTR: u16 = 0x65a2;
+// c.fld  fa0, 0(a0)
+const C_FLD_INSTR: u16 = 0x2108;
+// c.fsd  fa0, 8(a0)
+const C_FSD_INSTR: u16 = 0xa508;
+// c.fsdsp fa0, 16(sp)
+const C_FSDSP_INSTR: u16 = 0xa82a;
+// c.fldsp fa1, 16(sp)
+const C_FLDSP_INSTR: u16 = 0x25c2;
+// csrc   misa, t1
+const CLEAR_MISA_INSTR: u32 = 0x30133073;
+
+// mstatus.FS = Initial
+const MSTATUS_FS_INITIAL: GprUnsigned = 0x2000;
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+const MISA_C: GprUnsigned = 0x4;
+const ONE: u32 = 0x3f80_0000;
+const ONE_D: u64 = 0x3ff0_0000_0000_0000;
+
+const BASE: ProgramCounter = common::MEMORY_BASE_ADDRESS as ProgramCounter;
+const DATA_ADDRESS: DeviceAddress = common::MEMORY_BASE_ADDRESS + 0x100;
+
+/// Creates a simulator running the 16 and 32-bit instructions of `program`,
+/// packed into the little-endian words `common::load_program` writes
+fn creat_sim_with_bytes(program: &[u8]) -> Simulator {
+    let words: Vec<u32> = program
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_le_bytes(word)
+        })
+        .collect();
+    common::creat_sim_with_program(&words)
+}
+
+#[test]
+fn test_mixed_16_and_32_bit_instructions() {
+    // common::setup_tracing();
+
+    // step 1. create a simulator and load the program, the addi is only 2-byte aligned
+    let mut program = Vec::new();
+    program.extend_from_slice(&C_LI_INSTR.to_le_bytes());
+    program.extend_from_slice(&ADDI_INSTR.to_le_bytes());
+    program.extend_from_slice(&C_MV_INSTR.to_le_bytes());
+    let mut sim = creat_sim_with_bytes(&program);
+
+    // step 2. run the simulator and check the PC advance after each instruction
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 2);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(5));
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 6);
+    assert_eq!(sim.get_core().read_reg_by_name("a1"), Ok(6));
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 8);
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(6));
+}
+
+#[test]
+fn test_c_jal_links_to_next_halfword() {
+    // step 1. create a simulator and load the program
+    let mut program = Vec::new();
+    for instr in [C_JAL_INSTR, C_NOP_INSTR, C_NOP_INSTR, C_JR_INSTR] {
+        program.extend_from_slice(&instr.to_le_bytes());
+    }
+    let mut sim = creat_sim_with_bytes(&program);
+
+    // step 2. c.jal jumps over the two c.nop and links to the next halfword
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 6);
+    assert_eq!(
+        sim.get_core().read_reg_by_name("ra"),
+        Ok((BASE + 2) as GprUnsigned)
+    );
+
+    // step 3. c.jr returns to the first c.nop
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 2);
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), BASE + 4);
+}
+
+#[test]
+fn test_fetch_across_device_boundary() {
+    // step 1. create a bus with two adjacent memories
+    const FIRST_BASE: DeviceAddress = 0x1_0000;
+    const SECOND_BASE: DeviceAddress = 0x1_0100;
+    const SIZE: usize = 0x100;
+    let mut bus = Bus::new();
+    let _ = bus.add_device(FIRST_BASE, SIZE, DevicePointer::new(Mem::new(SIZE)));
+    let _ = bus.add_device(SECOND_BASE, SIZE, DevicePointer::new(Mem::new(SIZE)));
+    let mut sim = Simulator::new(bus);
+
+    // step 2. the addi starts 2 bytes before the end of the first memory
+    let bus = sim.get_bus_mut();
+    bus.write_halfword(SECOND_BASE - 4, C_LI_INSTR).unwrap();
+    bus.write_halfword(SECOND_BASE - 2, ADDI_INSTR as u16)
+        .unwrap();
+    bus.write_halfword(SECOND_BASE, (ADDI_INSTR >> 16) as u16)
+        .unwrap();
+    sim.set_reset_vector((SECOND_BASE - 4) as ProgramCounter);
+
+    // step 3. run the simulator
+    sim.run(Some(2)).expect("Simulation failed");
+
+    // step 4. check the result
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a1"), Ok(6));
+    assert_eq!(core.get_pc(), (SECOND_BASE + 2) as ProgramCounter);
+}
+
+#[test]
+fn test_compressed_disassembly() {
+    // step 1. create a simulator with a log file
+    let mut program = Vec::new();
+    program.extend_from_slice(&C_LI_INSTR.to_le_bytes());
+    program.extend_from_slice(&ADDI_INSTR.to_le_bytes());
+    let mut sim = creat_sim_with_bytes(&program);
+    let log_path = std::env::temp_dir().join("rrv_exec_compressed_instr.log");
+    sim.prepare_log_file(log_path.to_str().unwrap());
+
+    // step 2. run the simulator
+    sim.run(Some(2)).expect("Simulation failed");
+    drop(sim);
+
+    // step 3. the compressed instruction is shown with its c. mnemonic
+    let log = std::fs::read_to_string(&log_path).unwrap();
+    let _ = std::fs::remove_file(&log_path);
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines[0], "0x00010000 (0x4515) C.LI x10, x0, 5");
+    assert_eq!(lines[1], "0x00010002 (0x00150593) ADDI x11, x10, 1");
+}
+
+/// Creates a simulator that enables mstatus.FS and then runs the compressed
+/// `instrs`, with a0 and sp both pointing to the data
+fn creat_sim_with_fp_program(instrs: &[u16]) -> Simulator {
+    let mut program = Vec::new();
+    program.extend_from_slice(&ENABLE_FS_INSTR.to_le_bytes());
+    for instr in instrs {
+        program.extend_from_slice(&instr.to_le_bytes());
+    }
+    let mut sim = creat_sim_with_bytes(&program);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t0", MSTATUS_FS_INITIAL).unwrap();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("sp", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    sim
+}
+
+/// Runs each compressed instruction alone on `isa` and checks it is illegal
+fn assert_illegal_on_isa(isa: &str, instrs: &[u16]) {
+    for &instr in instrs {
+        // step 1. create a simulator for the isa
+        let mut sim = creat_sim_with_bytes(&instr.to_le_bytes());
+        sim.set_isa(isa.parse().unwrap());
+        sim.set_reset_vector(BASE);
+
+        // step 2. run the simulator
+        sim.run(Some(1)).expect("Simulation failed");
+
+        // step 3. the instruction is illegal and mtval holds its 16 bits
+        let core = sim.get_core();
+        assert_eq!(core.read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+        assert_eq!(core.read_csr(CSR_MTVAL), Ok(instr as GprUnsigned));
+    }
+}
+
+#[test]
+fn test_compressed_fp_word_load_store() {
+    // step 1. create a simulator and load the program
+    let mut sim =
+        creat_sim_with_fp_program(&[C_FLW_INSTR, C_FSW_INSTR, C_FSWSP_INSTR, C_FLWSP_INSTR]);
+
+    // step 2. prepare the data
+    sim.get_bus_mut().write_word(DATA_ADDRESS, ONE).unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(5)).expect("Simulation failed");
+
+    // step 4. check the result
+    assert_eq!(sim.get_core().get_pc(), BASE + 12);
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(0));
+    let bus = sim.get_bus();
+    assert_eq!(bus.read_word(DATA_ADDRESS + 4), Ok(ONE));
+    assert_eq!(bus.read_word(DATA_ADDRESS + 8), Ok(ONE));
+    assert_eq!(sim.get_core().read_fregister(11).unwrap() as u32, ONE);
+}
+
+#[test]
+fn test_compressed_fp_word_load_store_without_f() {
+    assert_illegal_on_isa(
+        "rv32imac_zicsr",
+        &[C_FLW_INSTR, C_FSW_INSTR, C_FSWSP_INSTR, C_FLWSP_INSTR],
+    );
+}
+
+#[test]
+fn test_compressed_fp_doubleword_load_store() {
+    // step 1. create a simulator and load the program
+    let mut sim =
+        creat_sim_with_fp_program(&[C_FLD_INSTR, C_FSD_INSTR, C_FSDSP_INSTR, C_FLDSP_INSTR]);
+
+    // step 2. prepare the data
+    sim.get_bus_mut()
+        .write_doubleword(DATA_ADDRESS, ONE_D)
+        .unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(5)).expect("Simulation failed");
+
+    // step 4. check the result
+    assert_eq!(sim.get_core().get_pc(), BASE + 12);
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(0));
+    let bus = sim.get_bus();
+    assert_eq!(bus.read_doubleword(DATA_ADDRESS + 8), Ok(ONE_D));
+    assert_eq!(bus.read_doubleword(DATA_ADDRESS + 16), Ok(ONE_D));
+    assert_eq!(sim.get_core().read_fregister(11), Ok(ONE_D));
+}
+
+#[test]
+fn test_compressed_fp_doubleword_load_store_without_d() {
+    assert_illegal_on_isa(
+        "rv32imafc_zicsr",
+        &[C_FLD_INSTR, C_FSD_INSTR, C_FSDSP_INSTR, C_FLDSP_INSTR],
+    );
+}
+
+#[test]
+fn test_compressed_fp_load_traps_while_fs_off() {
+    // step 1. create a simulator, mstatus.FS is Off after reset
+    let mut sim = creat_sim_with_bytes(&C_FLW_INSTR.to_le_bytes());
+
+    // step 2. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 3. mtval holds the 16 fetched bits, not those of the expanded flw
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(C_FLW_INSTR as GprUnsigned));
+}
+
+#[test]
+fn test_misa_c_can_not_be_cleared() {
+    // step 1. the csrc is followed by an instruction only 2-byte aligned
+    let mut program = Vec::new();
+    program.extend_from_slice(&CLEAR_MISA_INSTR.to_le_bytes());
+    program.extend_from_slice(&C_NOP_INSTR.to_le_bytes());
+    let mut sim = creat_sim_with_bytes(&program);
+    sim.get_core_mut().write_reg_by_name("t1", MISA_C).unwrap();
+
+    // step 2. misa is read-only, the compressed instruction still executes
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_ne!(sim.get_core().read_csr(CSR_MISA).unwrap() & MISA_C, 0);
+    assert_eq!(sim.get_core().get_pc(), BASE + 6);
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(0));
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_counter_instr.rs
+
+use rv_core::inst_csr_reg::*;
+
+mod common;
+
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+// rdcycle   a2
+const RDCYCLE_INSTR: u32 = 0xc0002673;
+// rdinstret a3
+const RDINSTRET_INSTR: u32 = 0xc02026f3;
+// rdtime    a4
+const RDTIME_INSTR: u32 = 0xc0102773;
+// rdtimeh   a5
+const RDTIMEH_INSTR: u32 = 0xc81027f3;
+// csrw      mcycle, zero
+const CLEAR_MCYCLE_INSTR: u32 = 0xb0001073;
+// ecall
+const ECALL_INSTR: u32 = 0x00000073;
+
+// the time CSR reads mtime from the CLINT
+const WITH_CLINT: common::SimOptions = common::SimOptions {
+    clint: true,
+    ..common::SimOptions::RV32
+};
+
+#[test]
+fn test_read_counters() {
+    let program = [
+        NOP_INSTR,
+        NOP_INSTR,
+        RDCYCLE_INSTR,
+        RDINSTRET_INSTR,
+        RDTIME_INSTR,
+        RDTIMEH_INSTR,
+    ];
+    let mut sim = common::creat_sim_with_options(&program, WITH_CLINT);
+
+    sim.run(Some(program.len())).expect("Simulation failed");
+
+    // each counter reads the instructions before the one reading it
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a2"), Ok(2));
+    assert_eq!(core.read_reg_by_name("a3"), Ok(3));
+    // time follows the CLINT mtime, which advances once per cycle
+    assert_eq!(core.read_reg_by_name("a4"), Ok(4));
+    assert_eq!(core.read_reg_by_name("a5"), Ok(0));
+    assert_eq!(sim.get_bus().mtime(), Some(program.len() as u64));
+}
+
+#[test]
+fn test_time_reads_mtime_high_half() {
+    let mut sim = common::creat_sim_with_options(&[RDTIME_INSTR, RDTIMEH_INSTR], WITH_CLINT);
+    sim.get_bus_mut()
+        .write_doubleword(common::CLINT_BASE_ADDRESS + 0xbff8, 0x5_ffff_fffe)
+        .unwrap();
+
+    sim.run(Some(2)).expect("Simulation failed");
+
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a4"), Ok(0xffff_fffe));
+    assert_eq!(core.read_reg_by_name("a5"), Ok(5));
+}
+
+#[test]
+fn test_counter_write_and_exceptions() {
+    let program = [NOP_INSTR, CLEAR_MCYCLE_INSTR, ECALL_INSTR];
+    let mut sim = common::creat_sim_with_options(&program, WITH_CLINT);
+
+    sim.run(Some(3)).expect("Simulation failed");
+
+    // mcycle restarts from the written value after the csrw, the ecall counts
+    // a cycle but does not retire
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCYCLE), Ok(1));
+    assert_eq!(core.read_csr(CSR_MINSTRET), Ok(2));
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_csr_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use sim_lib::simulator::Simulator;
+use sim_lib::MachineInfo;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrr a0, mhartid
+const CSRR_MHARTID_INSTR: u32 = 0xf1402573;
+// csrr a0, mvendorid
+const CSRR_MVENDORID_INSTR: u32 = 0xf1102573;
+// csrw mhartid, a1
+const CSRW_MHARTID_INSTR: u32 = 0xf1459073;
+// csrrs a0, mvendorid, a1
+const CSRRS_MVENDORID_INSTR: u32 = 0xf115a573;
+// csrr a0, 0x7c0, a custom CSR which is not implemented
+const CSRR_CUSTOM_INSTR: u32 = 0x7c002573;
+// csrrwi a0, mscratch, 5
+const CSRRWI_MSCRATCH_INSTR: u32 = 0x3402d573;
+// csrrci a0, mscratch, 4
+const CSRRCI_MSCRATCH_INSTR: u32 = 0x34027573;
+// csrw mtvec, a1
+const CSRW_MTVEC_A1_INSTR: u32 = 0x30559073;
+// csrw mstatus, a1
+const CSRW_MSTATUS_INSTR: u32 = 0x30059073;
+// csrw menvcfg, a1
+const CSRW_MENVCFG_INSTR: u32 = 0x30a59073;
+
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The instruction under test follows the setup instruction
+const CODE: GprUnsigned = BASE + 4;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Runs the setup code, which sets mtvec, then `instruction` with `a1`
+/// holding `value`
+fn run_instruction(
+    instruction: u32,
+    value: GprUnsigned,
+    configure: impl FnOnce(&mut Simulator),
+) -> Simulator {
+    // common::setup_tracing();
+
+    let program = [CSRW_MTVEC_INSTR, instruction];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    configure(&mut sim);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("a0", 0x55).unwrap();
+    core.write_reg_by_name("a1", value).unwrap();
+
+    sim.run(Some(2)).expect("Simulation failed");
+    sim
+}
+
+/// Asserts the instruction under test trapped as illegal and left a0 alone
+fn assert_illegal_instruction(sim: &Simulator, bits: u32) {
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(CODE));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(bits as GprUnsigned));
+    assert_eq!(core.read_reg_by_name("a0"), Ok(0x55));
+}
+
+#[test]
+fn test_machine_info() {
+    let fe310 = |sim: &mut Simulator| sim.set_machine_info(MachineInfo::FE310_G002);
+
+    let sim = run_instruction(CSRR_MVENDORID_INSTR, 0, fe310);
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x489));
+
+    let sim = run_instruction(CSRR_MHARTID_INSTR, 0, |sim| {
+        sim.set_machine_info(MachineInfo {
+            mhartid: 3,
+            ..MachineInfo::default()
+        })
+    });
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(3));
+
+    // the machine information CSRs are read-only
+    let sim = run_instruction(CSRW_MHARTID_INSTR, 1, fe310);
+    assert_illegal_instruction(&sim, CSRW_MHARTID_INSTR);
+    assert_eq!(sim.get_core().read_csr(CSR_MHARTID), Ok(0));
+}
+
+#[test]
+fn test_write_suppression() {
+    // CSRRS with rs1 other than x0 writes the CSR even if the mask is zero
+    let sim = run_instruction(CSRRS_MVENDORID_INSTR, 0, |_| {});
+    assert_illegal_instruction(&sim, CSRRS_MVENDORID_INSTR);
+
+    // the immediate is the rs1 field, not the register it names
+    let sim = run_instruction(CSRRWI_MSCRATCH_INSTR, 0, |sim| {
+        sim.get_core_mut().write_reg_by_name("t0", 0x1234).unwrap();
+    });
+    assert_eq!(sim.get_core().read_csr(CSR_MSCRATCH), Ok(5));
+
+    let sim = run_instruction(CSRRCI_MSCRATCH_INSTR, 0, |sim| {
+        sim.get_core_mut().write_csr(CSR_MSCRATCH, 0xf).unwrap();
+    });
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0xf));
+    assert_eq!(sim.get_core().read_csr(CSR_MSCRATCH), Ok(0xb));
+}
+
+#[test]
+fn test_unknown_csr() {
+    let sim = run_instruction(CSRR_CUSTOM_INSTR, 0, |_| {});
+    assert_illegal_instruction(&sim, CSRR_CUSTOM_INSTR);
+}
+
+#[test]
+fn test_warl_fields() {
+    // the reserved modes of mtvec keep the current mode
+    let sim = run_instruction(CSRW_MTVEC_A1_INSTR, HANDLER + 0x103, |_| {});
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+    assert_eq!(sim.get_core().read_csr(CSR_MTVEC), Ok(HANDLER + 0x100));
+
+    // as does the reserved mstatus.MPP
+    let sim = run_instruction(CSRW_MSTATUS_INSTR, 0x1008, |_| {});
+    assert_eq!(sim.get_core().read_csr(CSR_MSTATUS), Ok(0x1808));
+
+    // menvcfg only implements FIOM
+    let sim = run_instruction(CSRW_MENVCFG_INSTR, 0xff, |_| {});
+    assert_eq!(sim.get_core().read_csr(CSR_MENVCFG), Ok(1));
+}
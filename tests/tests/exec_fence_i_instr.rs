@@ -0,0 +1,64 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_fence_i_instr.rs
+
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// addi a2, a2, 1
+const ADDI_1_INSTR: u32 = 0x00160613;
+// addi a2, a2, 16
+const ADDI_16_INSTR: u32 = 0x01060613;
+// sw   a1, 0(a0)
+const SW_INSTR: u32 = 0x00b52023;
+// fence.i
+const FENCE_I_INSTR: u32 = 0x0000100f;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+// j    -12
+const J_INSTR: u32 = 0xff5ff06f;
+
+/// Runs a loop that overwrites its first instruction, `addi a2, a2, 1`, with
+/// `addi a2, a2, 16` and then `sync_instr`. Returns a2 after the first
+/// instruction ran twice.
+fn run_self_modifying_loop(sync_instr: u32) -> GprUnsigned {
+    // step 1. create a simulator and load the program into memory
+    let mut sim: Simulator = common::creat_sim_for_test();
+    let program = [ADDI_1_INSTR, SW_INSTR, sync_instr, J_INSTR];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+
+    // step 2. prepare the environment
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", common::MEMORY_BASE_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", ADDI_16_INSTR as GprUnsigned)
+        .unwrap();
+
+    // step 3. run the simulator, the store reaches memory either way
+    sim.run(Some(5)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_bus().read_word(common::MEMORY_BASE_ADDRESS),
+        Ok(ADDI_16_INSTR)
+    );
+
+    // step 4. return the result
+    sim.get_core().read_reg_by_name("a2").unwrap()
+}
+
+#[test]
+fn test_fence_i_makes_stores_visible_to_fetch() {
+    assert_eq!(run_self_modifying_loop(FENCE_I_INSTR), 1 + 16);
+}
+
+#[test]
+fn test_stale_code_runs_without_fence_i() {
+    assert_eq!(run_self_modifying_loop(NOP_INSTR), 1 + 1);
+}
@@ -0,0 +1,409 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_fp_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use cpu_peripherals::DeviceAddress;
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrrs  x0, mstatus, t0
+const ENABLE_FS_INSTR: u32 = 0x3002a073;
+// csrrw  x0, frm, a3
+const WRITE_FRM_INSTR: u32 = 0x00269073;
+// fmv.w.x fa1, a1
+const FMV_W_X_FA1_INSTR: u32 = 0xf00585d3;
+// fmv.w.x fa2, a2
+const FMV_W_X_FA2_INSTR: u32 = 0xf0060653;
+// fmv.w.x fa3, a3
+const FMV_W_X_FA3_INSTR: u32 = 0xf00686d3;
+// fmv.x.w a0, fa0
+const FMV_X_W_INSTR: u32 = 0xe0050553;
+// fadd.s fa0, fa1, fa2 (dynamic rounding mode)
+const FADD_S_INSTR: u32 = 0x00c5f553;
+// fadd.s fa0, fa1, fa2 with the reserved rounding mode 0b101
+const FADD_S_RESERVED_RM_INSTR: u32 = 0x00c5d553;
+// fdiv.s fa0, fa1, fa2
+const FDIV_S_INSTR: u32 = 0x18c5f553;
+// fmadd.s fa0, fa1, fa2, fa3
+const FMADD_S_INSTR: u32 = 0x68c5f543;
+// fcvt.w.s a4, fa1, rtz
+const FCVT_W_S_RTZ_INSTR: u32 = 0xc0059753;
+// flw fa1, 0(a0)
+const FLW_INSTR: u32 = 0x00052587;
+// fsw fa0, 4(a0)
+const FSW_INSTR: u32 = 0x00a52227;
+// fld fa1, 0(a0)
+const FLD_FA1_INSTR: u32 = 0x00053587;
+// fld fa2, 16(a0)
+const FLD_FA2_INSTR: u32 = 0x01053607;
+// fsd fa0, 8(a0)
+const FSD_INSTR: u32 = 0x00a53427;
+// fadd.d fa0, fa1, fa2
+const FADD_D_INSTR: u32 = 0x02c5f553;
+// fcvt.s.d fa0, fa1
+const FCVT_S_D_INSTR: u32 = 0x4015f553;
+// fcvt.d.s fa0, fa1
+const FCVT_D_S_INSTR: u32 = 0x4205f553;
+
+// mstatus.FS = Initial
+const MSTATUS_FS_INITIAL: GprUnsigned = 0x2000;
+const MSTATUS_FS: GprUnsigned = 0x6000;
+const MSTATUS_SD: GprUnsigned = 0x8000_0000;
+
+// fflags
+const FLAG_NX: GprUnsigned = 0x01;
+const FLAG_DZ: GprUnsigned = 0x08;
+
+const ONE: GprUnsigned = 0x3f80_0000;
+const DATA_ADDRESS: DeviceAddress = common::MEMORY_BASE_ADDRESS + 0x100;
+
+/// Creates a simulator running `program`, t0 holds the mstatus.FS value set
+/// by ENABLE_FS_INSTR
+fn creat_fp_sim(program: &[u32]) -> Simulator {
+    let mut sim = common::creat_sim_with_program(program);
+    sim.get_core_mut()
+        .write_reg_by_name("t0", MSTATUS_FS_INITIAL)
+        .unwrap();
+    sim
+}
+
+/// Runs `fa0 = fa1 op fa2` with a1 and a2 moved to fa1 and fa2, and the
+/// rounding mode in frm set to `frm`. Returns the result and fflags.
+fn run_fp_r_instruction(
+    instr: u32,
+    rs1_val: GprUnsigned,
+    rs2_val: GprUnsigned,
+    frm: GprUnsigned,
+) -> (GprUnsigned, GprUnsigned) {
+    // step 1. create a simulator and load the program into memory
+    let program = [
+        ENABLE_FS_INSTR,
+        WRITE_FRM_INSTR,
+        FMV_W_X_FA1_INSTR,
+        FMV_W_X_FA2_INSTR,
+        instr,
+        FMV_X_W_INSTR,
+    ];
+    let mut sim = creat_fp_sim(&program);
+
+    // step 2. prepare the environment
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a1", rs1_val).unwrap();
+    core.write_reg_by_name("a2", rs2_val).unwrap();
+    core.write_reg_by_name("a3", frm).unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(program.len())).expect("Simulation failed");
+
+    // step 4. return the result
+    let core = sim.get_core();
+    (
+        core.read_reg_by_name("a0").unwrap(),
+        core.read_csr(CSR_FFLAGS).unwrap(),
+    )
+}
+
+#[test]
+fn test_fp_instruction_traps_while_fs_off() {
+    // common::setup_tracing();
+
+    // step 1. mstatus.FS is Off after reset
+    let mut sim = creat_fp_sim(&[FADD_S_INSTR]);
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MSTATUS).unwrap() & MSTATUS_FS,
+        0
+    );
+
+    // step 2. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 3. check the illegal instruction exception
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(FADD_S_INSTR as GprUnsigned));
+    assert_eq!(
+        core.read_csr(CSR_MEPC),
+        Ok(common::MEMORY_BASE_ADDRESS as GprUnsigned)
+    );
+}
+
+#[test]
+fn test_fp_csr_traps_while_fs_off() {
+    // step 1. csrrw x0, frm, a3 with mstatus.FS Off
+    let mut sim = creat_fp_sim(&[WRITE_FRM_INSTR]);
+    sim.get_core_mut().write_reg_by_name("a3", 1).unwrap();
+
+    // step 2. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 3. check the illegal instruction exception, frm is not written
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(core.read_csr(CSR_FRM), Ok(0));
+}
+
+#[test]
+fn test_fadd_s_marks_fs_dirty() {
+    // step 1. enable the FP unit and run fadd.s
+    let program = [
+        ENABLE_FS_INSTR,
+        FMV_W_X_FA1_INSTR,
+        FMV_W_X_FA2_INSTR,
+        FADD_S_INSTR,
+        FMV_X_W_INSTR,
+    ];
+    let mut sim = creat_fp_sim(&program);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a1", ONE).unwrap();
+    // 2.0
+    core.write_reg_by_name("a2", 0x4000_0000).unwrap();
+
+    // step 2. mstatus.FS is Initial until an f register is written
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MSTATUS).unwrap() & (MSTATUS_SD | MSTATUS_FS),
+        MSTATUS_FS_INITIAL
+    );
+
+    // step 3. run the rest of the program
+    sim.run(Some(4)).expect("Simulation failed");
+
+    // step 4. check the result, 1.0 + 2.0 is exact
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a0"), Ok(0x4040_0000));
+    // single-precision values are NaN-boxed in the 64-bit f registers
+    assert_eq!(core.read_fregister(10), Ok(0xffff_ffff_4040_0000));
+    assert_eq!(core.read_csr(CSR_FFLAGS), Ok(0));
+    assert_eq!(
+        core.read_csr(CSR_MSTATUS).unwrap() & (MSTATUS_SD | MSTATUS_FS),
+        MSTATUS_SD | MSTATUS_FS
+    );
+}
+
+#[test]
+fn test_fadd_s_rounding_modes() {
+    // 1.0 + 2^-24 is halfway between 1.0 and the next float
+    const HALF_ULP: GprUnsigned = 0x3380_0000;
+    // round to nearest, ties to even
+    assert_eq!(
+        run_fp_r_instruction(FADD_S_INSTR, ONE, HALF_ULP, 0b000),
+        (ONE, FLAG_NX)
+    );
+    // round towards zero
+    assert_eq!(
+        run_fp_r_instruction(FADD_S_INSTR, ONE, HALF_ULP, 0b001),
+        (ONE, FLAG_NX)
+    );
+    // round up
+    assert_eq!(
+        run_fp_r_instruction(FADD_S_INSTR, ONE, HALF_ULP, 0b011),
+        (ONE + 1, FLAG_NX)
+    );
+    // round to nearest, ties to max magnitude
+    assert_eq!(
+        run_fp_r_instruction(FADD_S_INSTR, ONE, HALF_ULP, 0b100),
+        (ONE + 1, FLAG_NX)
+    );
+}
+
+#[test]
+fn test_fdiv_s_by_zero() {
+    assert_eq!(
+        run_fp_r_instruction(FDIV_S_INSTR, ONE, 0, 0),
+        (0x7f80_0000, FLAG_DZ)
+    );
+    // 0 / 0 is the canonical NaN, with the invalid flag
+    assert_eq!(
+        run_fp_r_instruction(FDIV_S_INSTR, 0, 0, 0),
+        (0x7fc0_0000, 0x10)
+    );
+}
+
+#[test]
+fn test_reserved_rounding_mode_traps() {
+    // step 1. a static reserved rounding mode
+    let mut sim = creat_fp_sim(&[ENABLE_FS_INSTR, FADD_S_RESERVED_RM_INSTR]);
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MTVAL),
+        Ok(FADD_S_RESERVED_RM_INSTR as GprUnsigned)
+    );
+
+    // step 2. the dynamic rounding mode with an invalid frm
+    let mut sim = creat_fp_sim(&[ENABLE_FS_INSTR, WRITE_FRM_INSTR, FADD_S_INSTR]);
+    sim.get_core_mut().write_reg_by_name("a3", 0b101).unwrap();
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MEPC),
+        Ok(common::MEMORY_BASE_ADDRESS as GprUnsigned + 8)
+    );
+}
+
+#[test]
+fn test_fcvt_w_s_static_rounding_mode() {
+    // -2.5 rounds towards zero, the dynamic mode in frm (round down) is ignored
+    let mut sim = creat_fp_sim(&[
+        ENABLE_FS_INSTR,
+        WRITE_FRM_INSTR,
+        FMV_W_X_FA1_INSTR,
+        FCVT_W_S_RTZ_INSTR,
+    ]);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a1", 0xc020_0000).unwrap();
+    core.write_reg_by_name("a3", 0b010).unwrap();
+    sim.run(Some(4)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_reg_by_name("a4"),
+        Ok(-2_i32 as u32 as GprUnsigned)
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_FFLAGS), Ok(FLAG_NX));
+}
+
+#[test]
+fn test_flw_fmadd_s_fsw() {
+    // step 1. create a simulator and load the program into memory
+    let program = [
+        ENABLE_FS_INSTR,
+        FLW_INSTR,
+        FMV_W_X_FA2_INSTR,
+        FMV_W_X_FA3_INSTR,
+        FMADD_S_INSTR,
+        FSW_INSTR,
+    ];
+    let mut sim = creat_fp_sim(&program);
+
+    // step 2. prepare the environment, 1.5 * 2.0 + 0.25
+    sim.get_bus_mut()
+        .write_word(DATA_ADDRESS, 0x3fc0_0000)
+        .unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a2", 0x4000_0000).unwrap();
+    core.write_reg_by_name("a3", 0x3e80_0000).unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(program.len())).expect("Simulation failed");
+
+    // step 4. check the result, 3.25
+    assert_eq!(sim.get_bus().read_word(DATA_ADDRESS + 4), Ok(0x4050_0000));
+    assert_eq!(sim.get_core().read_csr(CSR_FFLAGS), Ok(0));
+}
+
+/// Loads the program, with the doubles `data` stored from DATA_ADDRESS and a0 pointing to them
+fn creat_sim_with_data(program: &[u32], data: &[f64]) -> Simulator {
+    let mut sim = creat_fp_sim(program);
+    for (i, value) in data.iter().enumerate() {
+        let address = DATA_ADDRESS + 8 * i as DeviceAddress;
+        sim.get_bus_mut()
+            .write_doubleword(address, value.to_bits())
+            .unwrap();
+    }
+    sim.get_core_mut()
+        .write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    sim
+}
+
+#[test]
+fn test_fld_fadd_d_fsd() {
+    // step 1. 1.5 + 0.25, fa2 is loaded from DATA_ADDRESS + 16
+    let program = [
+        ENABLE_FS_INSTR,
+        FLD_FA1_INSTR,
+        FLD_FA2_INSTR,
+        FADD_D_INSTR,
+        FSD_INSTR,
+    ];
+    let mut sim = creat_sim_with_data(&program, &[1.5, 0.0, 0.25]);
+
+    // step 2. run the simulator
+    sim.run(Some(program.len())).expect("Simulation failed");
+
+    // step 3. check the result
+    assert_eq!(
+        sim.get_bus().read_doubleword(DATA_ADDRESS + 8),
+        Ok(1.75f64.to_bits())
+    );
+    assert_eq!(sim.get_core().read_fregister(10), Ok(1.75f64.to_bits()));
+    assert_eq!(sim.get_core().read_csr(CSR_FFLAGS), Ok(0));
+}
+
+#[test]
+fn test_unboxed_single_is_canonical_nan() {
+    // step 1. fa1 holds a double, which is not a valid NaN-boxed single
+    let program = [
+        ENABLE_FS_INSTR,
+        FLD_FA1_INSTR,
+        FMV_W_X_FA2_INSTR,
+        FADD_S_INSTR,
+    ];
+    let mut sim = creat_sim_with_data(&program, &[1.0]);
+    sim.get_core_mut().write_reg_by_name("a2", ONE).unwrap();
+
+    // step 2. run the simulator
+    sim.run(Some(program.len())).expect("Simulation failed");
+
+    // step 3. fa1 is read as the canonical NaN, a quiet NaN raises no flag
+    let core = sim.get_core();
+    assert_eq!(core.read_fregister(12), Ok(0xffff_ffff_3f80_0000));
+    assert_eq!(core.read_fregister(10), Ok(0xffff_ffff_7fc0_0000));
+    assert_eq!(core.read_csr(CSR_FFLAGS), Ok(0));
+}
+
+#[test]
+fn test_fcvt_s_d_and_d_s() {
+    // step 1. 0.1 is not exact in single precision
+    let mut sim = creat_sim_with_data(&[ENABLE_FS_INSTR, FLD_FA1_INSTR, FCVT_S_D_INSTR], &[0.1]);
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_fregister(10),
+        Ok(0xffff_ffff_0000_0000 | 0.1f32.to_bits() as u64)
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_FFLAGS), Ok(FLAG_NX));
+
+    // step 2. widening is always exact
+    let program = [ENABLE_FS_INSTR, FMV_W_X_FA1_INSTR, FCVT_D_S_INSTR];
+    let mut sim = creat_fp_sim(&program);
+    sim.get_core_mut()
+        .write_reg_by_name("a1", 0.1f32.to_bits() as GprUnsigned)
+        .unwrap();
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_fregister(10),
+        Ok((0.1f32 as f64).to_bits())
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_FFLAGS), Ok(0));
+}
+
+#[test]
+fn test_d_instruction_traps_without_misa_d() {
+    // step 1. a rv32imafc configuration, misa.D is clear
+    let program = [ENABLE_FS_INSTR, FMV_W_X_FA1_INSTR, FADD_D_INSTR];
+    let mut sim = creat_fp_sim(&program);
+    sim.set_isa("rv32imafc_zicsr".parse().unwrap());
+    assert_eq!(sim.get_core().read_csr(CSR_MISA).unwrap() & (1 << 3), 0);
+
+    // step 2. the single-precision instruction still executes
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(0));
+
+    // step 3. the double-precision one raises an illegal instruction exception
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(2));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(FADD_D_INSTR as GprUnsigned));
+    assert_eq!(
+        core.read_csr(CSR_MEPC),
+        Ok(common::MEMORY_BASE_ADDRESS as GprUnsigned + 8)
+    );
+}
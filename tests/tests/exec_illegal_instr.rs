@@ -0,0 +1,88 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_illegal_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use sim_lib::simulator::{Simulator, UnhandledTrap};
+use sim_lib::SimulatorError;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// an instruction of the custom-0 opcode
+const CUSTOM_0_INSTR: u32 = 0x0000000b;
+// c.lwsp zero, 0(sp), reserved, followed by a c.nop
+const C_LWSP_ZERO_INSTR: u32 = 0x0001_4002;
+
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The instruction under test follows the setup instruction
+const CODE: GprUnsigned = BASE + 4;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Creates a simulator running the setup code, which sets mtvec to
+/// `handler`, then `instruction`
+fn create_sim(instruction: u32, handler: GprUnsigned) -> Simulator {
+    // common::setup_tracing();
+
+    let program = [CSRW_MTVEC_INSTR, instruction];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    sim.get_core_mut().write_reg_by_name("t1", handler).unwrap();
+    sim
+}
+
+/// Asserts the instruction under test trapped with its bits as mtval
+fn assert_illegal_instruction(sim: &Simulator, bits: GprUnsigned) {
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(CODE));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(bits));
+}
+
+#[test]
+fn test_unknown_instruction() {
+    let mut sim = create_sim(CUSTOM_0_INSTR, HANDLER);
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_illegal_instruction(&sim, CUSTOM_0_INSTR as GprUnsigned);
+}
+
+#[test]
+fn test_reserved_compressed_instruction() {
+    // mtval holds the 16 bits of the compressed instruction
+    let mut sim = create_sim(C_LWSP_ZERO_INSTR, HANDLER);
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_illegal_instruction(&sim, 0x4002);
+}
+
+#[test]
+fn test_unhandled_trap() {
+    // by default the trap is taken to the trap vector 0
+    let mut sim = create_sim(CUSTOM_0_INSTR, 0);
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), 0);
+    assert_eq!(sim.get_core().read_csr(CSR_MEPC), Ok(CODE));
+
+    let mut sim = create_sim(CUSTOM_0_INSTR, 0);
+    sim.set_unhandled_trap(UnhandledTrap::Stop);
+    let result = sim.run(Some(2));
+    assert!(matches!(result, Err(SimulatorError::UnhandledTrap(_))));
+    assert_eq!(sim.get_core().get_pc(), CODE);
+
+    // a trap with a handler is taken
+    let mut sim = create_sim(CUSTOM_0_INSTR, HANDLER);
+    sim.set_unhandled_trap(UnhandledTrap::Stop);
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_illegal_instruction(&sim, CUSTOM_0_INSTR as GprUnsigned);
+}
@@ -5,7 +5,6 @@
 
 // tests/tests/exec_asm_instr_of_md5.rs
 
-use rv_core::GprSigned;
 use rv_core::GprUnsigned;
 // use rv_core::{GprSigned, GprUnsigned};
 use rv_core::inst_csr_reg::*;
@@ -117,7 +116,7 @@ fn test_execution_mv() {
     assert_eq!(core.get_pc(), MEMORY_BASE_ADDRESS as ProgramCounter);
 
     let exepect_val = 10;
-    let _ = core.write_reg_by_name("sp", exepect_val).unwrap();
+    core.write_reg_by_name("sp", exepect_val).unwrap();
     assert_eq!(core.read_reg_by_name("sp"), Ok(exepect_val));
 
     // step 3. run the simulator
@@ -138,7 +137,7 @@ fn test_execution_addi() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t0", 10).unwrap();
+    core.write_reg_by_name("t0", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -159,7 +158,7 @@ fn test_execution_csrw() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t0", 0x5678).unwrap();
+    core.write_reg_by_name("t0", 0x5678).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -179,7 +178,7 @@ fn test_execution_li() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t0", 10).unwrap();
+    core.write_reg_by_name("t0", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -200,8 +199,8 @@ fn test_execution_bgeu() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t0", 10).unwrap();
-    let _ = core.write_reg_by_name("t1", 10).unwrap();
+    core.write_reg_by_name("t0", 10).unwrap();
+    core.write_reg_by_name("t1", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -214,7 +213,7 @@ fn test_execution_bgeu() {
     {
         sim.set_reset_vector(MEMORY_BASE_ADDRESS.try_into().unwrap());
         let core = sim.get_core_mut();
-        let _ = core.write_reg_by_name("t1", 9).unwrap();
+        core.write_reg_by_name("t1", 9).unwrap();
         sim.run(Some(1)).expect("Simulation failed");
         let core = sim.get_core();
         assert_eq!(core.get_pc(), MEMORY_BASE_ADDRESS as ProgramCounter + 16);
@@ -224,7 +223,7 @@ fn test_execution_bgeu() {
     {
         sim.set_reset_vector(MEMORY_BASE_ADDRESS.try_into().unwrap());
         let core = sim.get_core_mut();
-        let _ = core.write_reg_by_name("t1", 11).unwrap();
+        core.write_reg_by_name("t1", 11).unwrap();
         sim.run(Some(1)).expect("Simulation failed");
         let core = sim.get_core();
         assert_eq!(core.get_pc(), MEMORY_BASE_ADDRESS as ProgramCounter + 4);
@@ -241,9 +240,9 @@ fn test_execution_sw() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t2", 0x1234abcd).unwrap();
+    core.write_reg_by_name("t2", 0x1234abcd).unwrap();
     let mem_addr = MEMORY_BASE_ADDRESS as ProgramCounter + 8;
-    let _ = core.write_reg_by_name("t0", mem_addr).unwrap();
+    core.write_reg_by_name("t0", mem_addr).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -336,10 +335,9 @@ fn test_execution_lw() {
     let core = sim.get_core_mut();
     let s0_val = MEMORY_BASE_ADDRESS as ProgramCounter + 40;
     let mem_addr = s0_val - 20;
-    let _ = core.write_reg_by_name("s0", s0_val).unwrap();
+    core.write_reg_by_name("s0", s0_val).unwrap();
     let bus = sim.get_bus_mut();
-    _ = bus
-        .write_word(mem_addr.try_into().unwrap(), 0xabcd_1234)
+    bus.write_word(mem_addr.try_into().unwrap(), 0xabcd_1234)
         .unwrap();
 
     // step 3. run the simulator
@@ -361,8 +359,8 @@ fn test_execution_sll() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 0x1234).unwrap();
-    let _ = core.write_reg_by_name("a4", 16).unwrap();
+    core.write_reg_by_name("a5", 0x1234).unwrap();
+    core.write_reg_by_name("a4", 16).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -383,7 +381,7 @@ fn test_execution_neg() {
     // step 2. prepare the environment
     let core = sim.get_core_mut();
     let val = -10_i32;
-    let _ = core.write_reg_by_name("a3", val as GprUnsigned).unwrap();
+    core.write_reg_by_name("a3", val as GprUnsigned).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -403,7 +401,7 @@ fn test_execution_andi() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a3", 0x1f1f).unwrap();
+    core.write_reg_by_name("a3", 0x1f1f).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -423,8 +421,8 @@ fn test_execution_srl() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 0x1234_0000).unwrap();
-    let _ = core.write_reg_by_name("a3", 16).unwrap();
+    core.write_reg_by_name("a5", 0x1234_0000).unwrap();
+    core.write_reg_by_name("a3", 16).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -444,8 +442,8 @@ fn test_execution_or() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 0x12345a5a).unwrap();
-    let _ = core.write_reg_by_name("a4", 0x1234a5a5).unwrap();
+    core.write_reg_by_name("a5", 0x12345a5a).unwrap();
+    core.write_reg_by_name("a4", 0x1234a5a5).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -526,8 +524,8 @@ fn test_execution_add() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 4).unwrap();
-    let _ = core.write_reg_by_name("a6", 6).unwrap();
+    core.write_reg_by_name("a4", 4).unwrap();
+    core.write_reg_by_name("a6", 6).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -548,8 +546,8 @@ fn test_execution_sltu() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a1", 1).unwrap();
-    let _ = core.write_reg_by_name("a4", 4).unwrap();
+    core.write_reg_by_name("a1", 1).unwrap();
+    core.write_reg_by_name("a4", 4).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -562,7 +560,7 @@ fn test_execution_sltu() {
     {
         sim.set_reset_vector(MEMORY_BASE_ADDRESS.try_into().unwrap());
         let core = sim.get_core_mut();
-        let _ = core.write_reg_by_name("a1", 4).unwrap();
+        core.write_reg_by_name("a1", 4).unwrap();
         sim.run(Some(1)).expect("Simulation failed");
         let core = sim.get_core();
         assert_eq!(core.read_reg_by_name("a1"), Ok(0));
@@ -572,7 +570,7 @@ fn test_execution_sltu() {
     {
         sim.set_reset_vector(MEMORY_BASE_ADDRESS.try_into().unwrap());
         let core = sim.get_core_mut();
-        let _ = core.write_reg_by_name("a1", 5).unwrap();
+        core.write_reg_by_name("a1", 5).unwrap();
         sim.run(Some(1)).expect("Simulation failed");
         let core = sim.get_core();
         assert_eq!(core.read_reg_by_name("a1"), Ok(0));
@@ -591,9 +589,9 @@ fn test_execution_lbu() {
     // step 2. prepare the environment
     let core = sim.get_core_mut();
     let mem_addr = MEMORY_BASE_ADDRESS as ProgramCounter + 40;
-    let _ = core.write_reg_by_name("a4", mem_addr).unwrap();
+    core.write_reg_by_name("a4", mem_addr).unwrap();
     let bus = sim.get_bus_mut();
-    let _ = bus.write_byte(mem_addr as DeviceAddress, 0xab).unwrap();
+    bus.write_byte(mem_addr as DeviceAddress, 0xab).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -616,8 +614,8 @@ fn test_execution_sb() {
     let core = sim.get_core_mut();
     let a5_val = MEMORY_BASE_ADDRESS as ProgramCounter + 40;
     let mem_addr = a5_val + 24;
-    let _ = core.write_reg_by_name("a4", 0x12ab).unwrap();
-    let _ = core.write_reg_by_name("a5", a5_val).unwrap();
+    core.write_reg_by_name("a4", 0x12ab).unwrap();
+    core.write_reg_by_name("a5", a5_val).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -638,7 +636,7 @@ fn test_execution_bnez() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 10).unwrap();
+    core.write_reg_by_name("a5", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -653,7 +651,7 @@ fn test_execution_bnez() {
     {
         sim.set_reset_vector(MEMORY_BASE_ADDRESS.try_into().unwrap());
         let core = sim.get_core_mut();
-        let _ = core.write_reg_by_name("a5", 0).unwrap();
+        core.write_reg_by_name("a5", 0).unwrap();
         sim.run(Some(1)).expect("Simulation failed");
         let core = sim.get_core();
         assert_eq!(core.get_pc(), (MEMORY_BASE_ADDRESS as ProgramCounter + 4));
@@ -671,7 +669,7 @@ fn test_execution_slli() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 10).unwrap();
+    core.write_reg_by_name("a5", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -692,8 +690,8 @@ fn test_execution_bltu() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 4).unwrap();
-    let _ = core.write_reg_by_name("a5", 5).unwrap();
+    core.write_reg_by_name("a4", 4).unwrap();
+    core.write_reg_by_name("a5", 5).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -702,7 +700,7 @@ fn test_execution_bltu() {
     let core = sim.get_core();
     let offset = -268_i32;
     let mut new_pc = MEMORY_BASE_ADDRESS as ProgramCounter;
-    new_pc = new_pc.wrapping_add(offset as u32);
+    new_pc = new_pc.wrapping_add(offset as ProgramCounter);
     assert_eq!(core.get_pc(), new_pc);
 }
 
@@ -716,8 +714,8 @@ fn test_execution_sub() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 4).unwrap();
-    let _ = core.write_reg_by_name("a5", 5).unwrap();
+    core.write_reg_by_name("a4", 4).unwrap();
+    core.write_reg_by_name("a5", 5).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -726,7 +724,7 @@ fn test_execution_sub() {
     let core = sim.get_core();
     assert_eq!(
         core.read_reg_by_name("a5"),
-        Ok(-1 as GprSigned as GprUnsigned)
+        Ok(-1_i32 as u32 as GprUnsigned)
     );
 }
 
@@ -742,7 +740,7 @@ fn test_execution_srli() {
     // step 2. prepare the environment
     let core = sim.get_core_mut();
     // let _ = core.write_reg_by_name("a3", 10).unwrap();
-    let _ = core.write_reg_by_name("a4", 0x8000_0000).unwrap();
+    core.write_reg_by_name("a4", 0x8000_0000).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -762,8 +760,8 @@ fn test_execution_beq() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 10).unwrap();
-    let _ = core.write_reg_by_name("a5", 10).unwrap();
+    core.write_reg_by_name("a4", 10).unwrap();
+    core.write_reg_by_name("a5", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -784,7 +782,7 @@ fn test_execution_beqz() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 0).unwrap();
+    core.write_reg_by_name("a5", 0).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -804,8 +802,8 @@ fn test_execution_and() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 0x1234a5a5).unwrap();
-    let _ = core.write_reg_by_name("a5", 0x12345a5a).unwrap();
+    core.write_reg_by_name("a4", 0x1234a5a5).unwrap();
+    core.write_reg_by_name("a5", 0x12345a5a).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -827,7 +825,7 @@ fn test_execution_not() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 0xa5a55a5a).unwrap();
+    core.write_reg_by_name("a5", 0xa5a55a5a).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -847,8 +845,8 @@ fn test_execution_xor() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a4", 0xa5a55a5a).unwrap();
-    let _ = core.write_reg_by_name("a5", 0x5a5aa5a5).unwrap();
+    core.write_reg_by_name("a4", 0xa5a55a5a).unwrap();
+    core.write_reg_by_name("a5", 0x5a5aa5a5).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -868,8 +866,8 @@ fn test_execution_blt() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a5", 10).unwrap();
-    let _ = core.write_reg_by_name("a3", 13).unwrap();
+    core.write_reg_by_name("a5", 10).unwrap();
+    core.write_reg_by_name("a3", 13).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -889,8 +887,8 @@ fn test_execution_bne() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a7", 7).unwrap();
-    let _ = core.write_reg_by_name("a4", 4).unwrap();
+    core.write_reg_by_name("a7", 7).unwrap();
+    core.write_reg_by_name("a4", 4).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -898,7 +896,7 @@ fn test_execution_bne() {
     // step 4. check the result
     let core = sim.get_core();
     let mut new_pc = MEMORY_BASE_ADDRESS as ProgramCounter;
-    new_pc = new_pc.wrapping_add(-16_i32 as u32 as ProgramCounter);
+    new_pc = new_pc.wrapping_add(-16_i32 as ProgramCounter);
     assert_eq!(core.get_pc(), new_pc);
 }
 
@@ -913,7 +911,7 @@ fn test_execution_snez() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("a0", 10).unwrap();
+    core.write_reg_by_name("a0", 10).unwrap();
 
     // step 3. run the simulator
     sim.run(Some(1)).expect("Simulation failed");
@@ -933,12 +931,13 @@ fn test_execution_fence() {
 
     // step 2. prepare the environment
     let core = sim.get_core_mut();
-    let _ = core.write_reg_by_name("t0", 10).unwrap();
+    core.write_reg_by_name("t0", 10).unwrap();
 
     // step 3. run the simulator
-    // sim.run(Some(1)).expect("Simulation failed"); // TODO
+    sim.run(Some(1)).expect("Simulation failed");
 
-    // step 4. check the result
+    // step 4. check the result, nothing but the pc changes
     let core = sim.get_core();
     assert_eq!(core.read_reg_by_name("t0"), Ok(10));
+    assert_eq!(core.get_pc(), MEMORY_BASE_ADDRESS as ProgramCounter + 4);
 }
@@ -0,0 +1,216 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_interrupt.rs
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::{DeviceAddress, MIP_MEIP, MIP_MSIP, MIP_MTIP};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrw stvec, a2
+const CSRW_STVEC_INSTR: u32 = 0x10561073;
+// csrw mie, t2
+const CSRW_MIE_INSTR: u32 = 0x30439073;
+// csrw mideleg, t4
+const CSRW_MIDELEG_INSTR: u32 = 0x303e9073;
+// csrs mip, t5
+const CSRS_MIP_INSTR: u32 = 0x344f2073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// csrw mepc, t0
+const CSRW_MEPC_INSTR: u32 = 0x34129073;
+// csrc mstatus, a6
+const CSRC_MSTATUS_INSTR: u32 = 0x30083073;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// j 8
+const J_INSTR: u32 = 0x0080006f;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+
+const INTERRUPT: GprUnsigned = 0x8000_0000;
+const SUPERVISOR_TIMER_INTERRUPT: GprUnsigned = INTERRUPT | 5;
+const MACHINE_TIMER_INTERRUPT: GprUnsigned = INTERRUPT | 7;
+const MACHINE_EXTERNAL_INTERRUPT: GprUnsigned = INTERRUPT | 11;
+
+const MSTATUS_MIE: GprUnsigned = 0x8;
+const MSTATUS_MPIE: GprUnsigned = 0x80;
+const MSTATUS_MPP: GprUnsigned = 0x1800;
+const MIP_STIP: GprUnsigned = 0x20;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The code follows the six setup instructions
+const CODE: GprUnsigned = BASE + 24;
+const S_HANDLER: GprUnsigned = BASE + 0x80;
+const M_HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Runs the setup code, which enables the interrupts of `mie`, delegates
+/// those of `mideleg` and sets the bits `mip_set` in mip, then sets the bits
+/// `mstatus_set` in mstatus. Returns the simulator with the interrupt lines
+/// of its device, which are asserted to `lines` from the start.
+fn run_setup(
+    code: &[u32],
+    mie: GprUnsigned,
+    mideleg: GprUnsigned,
+    mip_set: GprUnsigned,
+    mstatus_set: GprUnsigned,
+    lines: u64,
+) -> (Simulator, Rc<Cell<u64>>) {
+    // common::setup_tracing();
+
+    let mut program = vec![
+        CSRW_MTVEC_INSTR,
+        CSRW_STVEC_INSTR,
+        CSRW_MIE_INSTR,
+        CSRW_MIDELEG_INSTR,
+        CSRS_MIP_INSTR,
+        CSRS_MSTATUS_INSTR,
+    ];
+    program.extend_from_slice(code);
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    let lines = common::add_interrupt_source(&mut sim, lines, None);
+
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", M_HANDLER).unwrap();
+    core.write_reg_by_name("a2", S_HANDLER).unwrap();
+    core.write_reg_by_name("t2", mie).unwrap();
+    core.write_reg_by_name("t4", mideleg).unwrap();
+    core.write_reg_by_name("t5", mip_set).unwrap();
+    core.write_reg_by_name("t3", mstatus_set).unwrap();
+
+    sim.run(Some(6)).expect("Simulation failed");
+    (sim, lines)
+}
+
+#[test]
+fn test_machine_timer_interrupt() {
+    let (mut sim, lines) = run_setup(&[NOP_INSTR, NOP_INSTR], MIP_MTIP, 0, 0, MSTATUS_MIE, 0);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+
+    // the interrupt is taken after the next instruction, mepc is the one after it
+    lines.set(MIP_MTIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), M_HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_TIMER_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(CODE + 8));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(0));
+    let mstatus = core.read_csr(CSR_MSTATUS).unwrap();
+    assert_eq!(mstatus & (MSTATUS_MIE | MSTATUS_MPIE), MSTATUS_MPIE);
+    assert_eq!(mstatus & MSTATUS_MPP, MSTATUS_MPP);
+    assert_eq!(core.read_csr(CSR_MIP), Ok(MIP_MTIP));
+
+    // the device deasserts it
+    let _ = sim.load_bin_program(&NOP_INSTR.to_le_bytes(), M_HANDLER as DeviceAddress);
+    lines.set(0);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MIP), Ok(0));
+}
+
+#[test]
+fn test_interrupt_priority() {
+    // the interrupts are taken as soon as mstatus.MIE is set
+    let (sim, _) = run_setup(
+        &[],
+        MIP_MSIP | MIP_MTIP | MIP_MEIP,
+        0,
+        0,
+        MSTATUS_MIE,
+        MIP_MSIP | MIP_MTIP | MIP_MEIP,
+    );
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), M_HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_EXTERNAL_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(CODE));
+
+    // the machine timer interrupt comes before the supervisor ones
+    let (sim, _) = run_setup(&[], MIP_MTIP | MIP_STIP, 0, MIP_STIP, MSTATUS_MIE, MIP_MTIP);
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MCAUSE),
+        Ok(MACHINE_TIMER_INTERRUPT)
+    );
+}
+
+#[test]
+fn test_masked_interrupts() {
+    // disabled in mie
+    let (mut sim, _) = run_setup(&[NOP_INSTR], MIP_MTIP, 0, 0, MSTATUS_MIE, MIP_MEIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+
+    // disabled by mstatus.MIE in M-mode
+    let (mut sim, _) = run_setup(&[NOP_INSTR], MIP_MTIP, 0, 0, 0, MIP_MTIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), CODE + 4);
+    assert_eq!(core.read_csr(CSR_MIP), Ok(MIP_MTIP));
+}
+
+#[test]
+fn test_interrupt_after_jump() {
+    let (mut sim, lines) = run_setup(&[J_INSTR], MIP_MEIP, 0, 0, MSTATUS_MIE, 0);
+    lines.set(MIP_MEIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), M_HANDLER);
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(CODE + 8));
+}
+
+#[test]
+fn test_vectored_interrupt() {
+    let (mut sim, lines) = run_setup(&[CSRW_MTVEC_INSTR], MIP_MTIP, 0, 0, MSTATUS_MIE, 0);
+    sim.get_core_mut()
+        .write_reg_by_name("t1", M_HANDLER | 1)
+        .unwrap();
+    lines.set(MIP_MTIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), M_HANDLER + 4 * 7);
+}
+
+#[test]
+fn test_supervisor_interrupt() {
+    // STIP is set by software, M-mode does not take the delegated interrupt
+    let code = [CSRW_MEPC_INSTR, CSRC_MSTATUS_INSTR, MRET_INSTR];
+    let (mut sim, _) = run_setup(&code, MIP_STIP, MIP_STIP, MIP_STIP, MSTATUS_MIE, 0);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t0", CODE + 0x40).unwrap();
+    core.write_reg_by_name("a6", MSTATUS_MPP).unwrap();
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), CODE + 8);
+
+    // it is taken in S-mode after MRET to U-mode
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), S_HANDLER);
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Supervisor);
+    assert_eq!(core.read_csr(CSR_SCAUSE), Ok(SUPERVISOR_TIMER_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_SEPC), Ok(CODE + 0x40));
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(0));
+
+    // without delegation M-mode takes it from U-mode
+    let (mut sim, _) = run_setup(&code, MIP_STIP, 0, MIP_STIP, 0, 0);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t0", CODE + 0x40).unwrap();
+    core.write_reg_by_name("a6", MSTATUS_MPP).unwrap();
+    sim.run(Some(3)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), M_HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(SUPERVISOR_TIMER_INTERRUPT));
+}
@@ -0,0 +1,180 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_misaligned.rs
+
+use rv_core::core::MisalignedAccess;
+use rv_core::isa::Extension;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::{bus::DevicePointer, mem::Mem, DeviceAddress};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// lw a0, 0(a1)
+const LW_INSTR: u32 = 0x0005a503;
+// lh a0, 0(a1)
+const LH_INSTR: u32 = 0x00059503;
+// sw a2, 0(a1)
+const SW_INSTR: u32 = 0x00c5a023;
+// beq zero, zero, 6
+const BEQ_INSTR: u32 = 0x00000363;
+// jal ra, 6
+const JAL_INSTR: u32 = 0x006000ef;
+// jalr ra, 0(a5)
+const JALR_INSTR: u32 = 0x000780e7;
+
+const INSTRUCTION_ADDRESS_MISALIGNED: GprUnsigned = 0;
+const LOAD_ADDRESS_MISALIGNED: GprUnsigned = 4;
+const STORE_AMO_ADDRESS_MISALIGNED: GprUnsigned = 6;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The instruction under test follows the setup instruction
+const CODE: GprUnsigned = BASE + 4;
+const HANDLER: GprUnsigned = BASE + 0x100;
+const DATA: GprUnsigned = BASE + 0x1000;
+// The memory ends at 0x3_0000, a second one follows it in some tests
+const MEMORY_END: GprUnsigned = 0x3_0000;
+
+/// Runs the setup code, which sets mtvec, then `instruction` with `a1`
+/// pointing at `address` and `a2` holding the value to store
+fn run_instruction(
+    instruction: u32,
+    address: GprUnsigned,
+    policy: MisalignedAccess,
+    configure: impl FnOnce(&mut Simulator),
+) -> Simulator {
+    let program = [CSRW_MTVEC_INSTR, instruction];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.get_bus_mut()
+        .write_doubleword(DATA as DeviceAddress, 0x1122_3344_5566_7788)
+        .unwrap();
+    sim.set_misaligned_access(policy);
+    configure(&mut sim);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("a0", 0x55).unwrap();
+    core.write_reg_by_name("a1", address).unwrap();
+    core.write_reg_by_name("a2", 0xaabb_ccdd).unwrap();
+
+    sim.run(Some(2)).expect("Simulation failed");
+    sim
+}
+
+/// Adds a second memory right after the one of the simulator
+fn add_memory_after_end(sim: &mut Simulator) {
+    let memory = DevicePointer::new(Mem::new(0x1000));
+    let _ = sim
+        .get_bus_mut()
+        .add_device(MEMORY_END as DeviceAddress, 0x1000, memory);
+}
+
+#[test]
+fn test_emulated_load_and_store() {
+    let sim = run_instruction(LW_INSTR, DATA + 3, MisalignedAccess::Emulate, |_| {});
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x2233_4455));
+
+    let sim = run_instruction(LH_INSTR, DATA + 1, MisalignedAccess::Emulate, |_| {});
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x6677));
+
+    let sim = run_instruction(SW_INSTR, DATA + 1, MisalignedAccess::Emulate, |_| {});
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+    assert_eq!(
+        sim.get_bus().read_doubleword(DATA as DeviceAddress),
+        Ok(0x1122_33aa_bbcc_dd88)
+    );
+}
+
+#[test]
+fn test_emulated_access_across_devices() {
+    let sim = run_instruction(
+        SW_INSTR,
+        MEMORY_END - 2,
+        MisalignedAccess::Emulate,
+        add_memory_after_end,
+    );
+    assert_eq!(sim.get_core().get_pc(), CODE + 4);
+    let bus = sim.get_bus();
+    assert_eq!(
+        bus.read_halfword((MEMORY_END - 2) as DeviceAddress),
+        Ok(0xccdd)
+    );
+    assert_eq!(bus.read_halfword(MEMORY_END as DeviceAddress), Ok(0xaabb));
+
+    let sim = run_instruction(LW_INSTR, MEMORY_END - 1, MisalignedAccess::Emulate, |sim| {
+        add_memory_after_end(sim);
+        let bus = sim.get_bus_mut();
+        bus.write_word((MEMORY_END - 4) as DeviceAddress, 0x4400_0000)
+            .unwrap();
+        bus.write_word(MEMORY_END as DeviceAddress, 0x0011_2233)
+            .unwrap();
+    });
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1122_3344));
+}
+
+#[test]
+fn test_trapped_load_and_store() {
+    let sim = run_instruction(LW_INSTR, DATA + 2, MisalignedAccess::Trap, |_| {});
+    common::assert_trapped(&sim, LOAD_ADDRESS_MISALIGNED, CODE, DATA + 2);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x55));
+
+    let sim = run_instruction(SW_INSTR, DATA + 1, MisalignedAccess::Trap, |_| {});
+    common::assert_trapped(&sim, STORE_AMO_ADDRESS_MISALIGNED, CODE, DATA + 1);
+    assert_eq!(
+        sim.get_bus().read_word(DATA as DeviceAddress),
+        Ok(0x5566_7788)
+    );
+
+    // aligned accesses are not affected
+    let sim = run_instruction(LW_INSTR, DATA + 4, MisalignedAccess::Trap, |_| {});
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1122_3344));
+}
+
+#[test]
+fn test_misaligned_jump_targets() {
+    let without_c = |sim: &mut Simulator| sim.set_extension_enabled(Extension::C, false);
+
+    // with the C extension the targets only need to be 2-byte aligned
+    let sim = run_instruction(JAL_INSTR, 0, MisalignedAccess::Emulate, |_| {});
+    assert_eq!(sim.get_core().get_pc(), CODE + 6);
+
+    let sim = run_instruction(JAL_INSTR, 0, MisalignedAccess::Emulate, without_c);
+    common::assert_trapped(&sim, INSTRUCTION_ADDRESS_MISALIGNED, CODE, CODE + 6);
+    // rd is not written
+    assert_eq!(sim.get_core().read_reg_by_name("ra"), Ok(0));
+
+    let sim = run_instruction(BEQ_INSTR, 0, MisalignedAccess::Emulate, without_c);
+    common::assert_trapped(&sim, INSTRUCTION_ADDRESS_MISALIGNED, CODE, CODE + 6);
+}
+
+#[test]
+fn test_jalr_target() {
+    // the lowest bit of the target is cleared
+    let sim = run_instruction(JALR_INSTR, 0, MisalignedAccess::Emulate, |sim| {
+        sim.get_core_mut()
+            .write_reg_by_name("a5", HANDLER + 1)
+            .unwrap();
+    });
+    assert_eq!(sim.get_core().get_pc(), HANDLER);
+    assert_eq!(sim.get_core().read_reg_by_name("ra"), Ok(CODE + 4));
+
+    // without the C extension the target must be 4-byte aligned
+    let sim = run_instruction(JALR_INSTR, 0, MisalignedAccess::Emulate, |sim| {
+        sim.set_extension_enabled(Extension::C, false);
+        sim.get_core_mut()
+            .write_reg_by_name("a5", HANDLER + 3)
+            .unwrap();
+    });
+    common::assert_trapped(&sim, INSTRUCTION_ADDRESS_MISALIGNED, CODE, HANDLER + 2);
+    assert_eq!(sim.get_core().read_reg_by_name("ra"), Ok(0));
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_mul_div_instr.rs
+
+use rv_core::GprUnsigned;
+
+mod common;
+
+// mul    a2, a0, a1 ---- mul x12, x10, x11 ; mul rd, rs1, rs2
+const MUL_INSTR: u32 = 0x02b50633;
+// mulh   a2, a0, a1
+const MULH_INSTR: u32 = 0x02b51633;
+// mulhsu a2, a0, a1
+const MULHSU_INSTR: u32 = 0x02b52633;
+// mulhu  a2, a0, a1
+const MULHU_INSTR: u32 = 0x02b53633;
+// div    a2, a0, a1
+const DIV_INSTR: u32 = 0x02b54633;
+// divu   a2, a0, a1
+const DIVU_INSTR: u32 = 0x02b55633;
+// rem    a2, a0, a1
+const REM_INSTR: u32 = 0x02b56633;
+// remu   a2, a0, a1
+const REMU_INSTR: u32 = 0x02b57633;
+
+fn test_m_instruction_execution_x(instr: u32, rs1_val: u32, rs2_val: u32, expected: u32) {
+    // common::setup_tracing();
+
+    // step 1. create a simulator
+    let mut sim = common::creat_sim_for_test();
+
+    // step 2. load the program into memory
+    let _ = sim.load_bin_program(&instr.to_le_bytes(), common::MEMORY_BASE_ADDRESS);
+
+    // step 3. prepare the environment
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS.try_into().unwrap());
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", rs1_val as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", rs2_val as GprUnsigned)
+        .unwrap();
+
+    // step 4. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 5. check the result
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a2"), Ok(expected as GprUnsigned));
+    assert_eq!(core.read_reg_by_name("a0"), Ok(rs1_val as GprUnsigned));
+    assert_eq!(core.read_reg_by_name("a1"), Ok(rs2_val as GprUnsigned));
+}
+
+// The simulator is an RV32 one
+const INT_MIN: u32 = i32::MIN as u32;
+const MINUS_ONE: u32 = -1_i32 as u32;
+
+#[test]
+fn test_mul_instruction_execution() {
+    test_m_instruction_execution_x(MUL_INSTR, 6, 7, 42);
+    test_m_instruction_execution_x(MUL_INSTR, -3_i32 as u32, 7, -21_i32 as u32);
+    // only the lower XLEN bits are kept
+    test_m_instruction_execution_x(MUL_INSTR, 0x8000_0001, 2, 2);
+}
+
+#[test]
+fn test_mulh_instruction_execution() {
+    test_m_instruction_execution_x(MULH_INSTR, 0x8000_0000, 2, MINUS_ONE);
+    test_m_instruction_execution_x(MULH_INSTR, INT_MIN, INT_MIN, 0x4000_0000);
+    test_m_instruction_execution_x(MULH_INSTR, MINUS_ONE, MINUS_ONE, 0);
+}
+
+#[test]
+fn test_mulhsu_instruction_execution() {
+    // -1 * 0xffffffff = -0xffffffff
+    test_m_instruction_execution_x(MULHSU_INSTR, MINUS_ONE, MINUS_ONE, MINUS_ONE);
+    test_m_instruction_execution_x(MULHSU_INSTR, 2, 0x8000_0000, 1);
+}
+
+#[test]
+fn test_mulhu_instruction_execution() {
+    test_m_instruction_execution_x(MULHU_INSTR, MINUS_ONE, MINUS_ONE, 0xffff_fffe);
+    test_m_instruction_execution_x(MULHU_INSTR, 0x8000_0000, 2, 1);
+}
+
+#[test]
+fn test_div_instruction_execution() {
+    test_m_instruction_execution_x(DIV_INSTR, 42, 6, 7);
+    // rounds towards zero
+    test_m_instruction_execution_x(DIV_INSTR, -7_i32 as u32, 2, -3_i32 as u32);
+    // division by zero
+    test_m_instruction_execution_x(DIV_INSTR, 42, 0, MINUS_ONE);
+    // overflow
+    test_m_instruction_execution_x(DIV_INSTR, INT_MIN, MINUS_ONE, INT_MIN);
+}
+
+#[test]
+fn test_divu_instruction_execution() {
+    test_m_instruction_execution_x(DIVU_INSTR, MINUS_ONE, 2, 0x7fff_ffff);
+    // division by zero
+    test_m_instruction_execution_x(DIVU_INSTR, 42, 0, u32::MAX);
+}
+
+#[test]
+fn test_rem_instruction_execution() {
+    test_m_instruction_execution_x(REM_INSTR, 43, 6, 1);
+    // the sign of the result equals the sign of the dividend
+    test_m_instruction_execution_x(REM_INSTR, -7_i32 as u32, 2, MINUS_ONE);
+    // division by zero
+    test_m_instruction_execution_x(REM_INSTR, 42, 0, 42);
+    // overflow
+    test_m_instruction_execution_x(REM_INSTR, INT_MIN, MINUS_ONE, 0);
+}
+
+#[test]
+fn test_remu_instruction_execution() {
+    test_m_instruction_execution_x(REMU_INSTR, MINUS_ONE, 16, 15);
+    // division by zero
+    test_m_instruction_execution_x(REMU_INSTR, 42, 0, 42);
+}
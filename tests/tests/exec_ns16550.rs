@@ -0,0 +1,142 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_ns16550.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::ns16550::{self, Ns16550};
+use cpu_peripherals::serial::MemoryBackend;
+use cpu_peripherals::{bus::DevicePointer, plic::Plic, DeviceAddress, DeviceSize, MIP_MEIP};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrw mie, t2
+const CSRW_MIE_INSTR: u32 = 0x30439073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// sb a2, 0(a1)
+const SB_H_INSTR: u32 = 0x00c58023;
+// sb a3, 0(a1)
+const SB_I_INSTR: u32 = 0x00d58023;
+// wfi
+const WFI_INSTR: u32 = 0x10500073;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+// lw a2, 0(a0)
+const LW_CLAIM_INSTR: u32 = 0x00052603;
+// lbu a3, 0(a1)
+const LBU_RBR_INSTR: u32 = 0x0005c683;
+// sw a2, 0(a0)
+const SW_COMPLETE_INSTR: u32 = 0x00c52023;
+
+const MACHINE_EXTERNAL_INTERRUPT: GprUnsigned = 0x8000_000b;
+const MSTATUS_MIE: GprUnsigned = 0x8;
+
+const PLIC_BASE_ADDRESS: DeviceAddress = 0x0c00_0000;
+const PLIC_SIZE: DeviceSize = 0x400_0000;
+const PLIC_CLAIM: GprUnsigned = PLIC_BASE_ADDRESS as GprUnsigned + 0x20_0004;
+// the UART0 of the QEMU virt machine
+const UART_BASE_ADDRESS: DeviceAddress = 0x1000_0000;
+const UART_SIZE: DeviceSize = 0x100;
+const UART_PLIC_SOURCE: usize = 10;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// WFI follows the three setup instructions and the two stores
+const WFI: GprUnsigned = BASE + 20;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Creates a simulator with an NS16550 whose interrupts in `ier` are
+/// enabled, routed to the machine external interrupt through a PLIC. It
+/// runs the setup code, which enables the interrupt, sends "hi" then WFI.
+/// The handler claims the interrupt, reads RBR and completes the interrupt.
+fn create_sim(ier: u8) -> (Simulator, MemoryBackend) {
+    // common::setup_tracing();
+
+    let program = [
+        CSRW_MTVEC_INSTR,
+        CSRW_MIE_INSTR,
+        CSRS_MSTATUS_INSTR,
+        SB_H_INSTR,
+        SB_I_INSTR,
+        WFI_INSTR,
+        NOP_INSTR,
+    ];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+    let handler: Vec<u8> = [LW_CLAIM_INSTR, LBU_RBR_INSTR, SW_COMPLETE_INSTR]
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let plic = Plic::new();
+    let backend = MemoryBackend::new();
+    let mut uart = Ns16550::new("UART0");
+    uart.set_backend(Box::new(backend.clone()));
+    uart.set_interrupt_line(plic.interrupt_line(UART_PLIC_SOURCE).unwrap());
+    let bus = sim.get_bus_mut();
+    let _ = bus.add_device(PLIC_BASE_ADDRESS, PLIC_SIZE, DevicePointer::new(plic));
+    let _ = bus.add_device(UART_BASE_ADDRESS, UART_SIZE, DevicePointer::new(uart));
+    bus.write_word(PLIC_BASE_ADDRESS + 4 * UART_PLIC_SOURCE, 1)
+        .unwrap();
+    bus.write_word(PLIC_BASE_ADDRESS + 0x2000, 1 << UART_PLIC_SOURCE)
+        .unwrap();
+    bus.write_byte(UART_BASE_ADDRESS + ns16550::UART_IER, ier)
+        .unwrap();
+
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    let _ = sim.load_bin_program(&handler, HANDLER as DeviceAddress);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("t2", MIP_MEIP).unwrap();
+    core.write_reg_by_name("t3", MSTATUS_MIE).unwrap();
+    core.write_reg_by_name("a0", PLIC_CLAIM).unwrap();
+    core.write_reg_by_name("a1", UART_BASE_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a2", b'h' as GprUnsigned).unwrap();
+    core.write_reg_by_name("a3", b'i' as GprUnsigned).unwrap();
+    (sim, backend)
+}
+
+#[test]
+fn test_transmit() {
+    let (mut sim, backend) = create_sim(0);
+    sim.run(Some(6)).expect("Simulation failed");
+    assert!(sim.get_core().is_waiting());
+    assert!(backend.output().is_empty());
+
+    // the frames are sent while the hart waits
+    sim.run(Some(4)).expect("Simulation failed");
+    assert!(sim.get_core().is_waiting());
+    assert_eq!(backend.output(), b"hi");
+}
+
+#[test]
+fn test_receive_interrupt() {
+    let (mut sim, backend) = create_sim(ns16550::UART_IER_RDI);
+    sim.run(Some(16)).expect("Simulation failed");
+    assert!(sim.get_core().is_waiting());
+    assert_eq!(backend.output(), b"hi");
+
+    // the hart wakes up once the byte is received, then the handler claims
+    // the interrupt, reads the byte and completes the interrupt
+    backend.push_input(b"x");
+    sim.run(Some(3)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_EXTERNAL_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(WFI + 4));
+    assert_eq!(core.get_pc(), HANDLER + 12);
+    assert_eq!(
+        core.read_reg_by_name("a2"),
+        Ok(UART_PLIC_SOURCE as GprUnsigned)
+    );
+    assert_eq!(core.read_reg_by_name("a3"), Ok(b'x' as GprUnsigned));
+    assert_eq!(core.read_csr(CSR_MIP), Ok(0));
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_plic.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::{
+    bus::DevicePointer, plic::Plic, DeviceAddress, DeviceSize, InterruptLine, MIP_MEIP,
+};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrw mie, t2
+const CSRW_MIE_INSTR: u32 = 0x30439073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+// lw a2, 0(a0)
+const LW_CLAIM_INSTR: u32 = 0x00052603;
+// sw a2, 0(a0)
+const SW_COMPLETE_INSTR: u32 = 0x00c52023;
+
+const MACHINE_EXTERNAL_INTERRUPT: GprUnsigned = 0x8000_000b;
+const MSTATUS_MIE: GprUnsigned = 0x8;
+
+const PLIC_BASE_ADDRESS: DeviceAddress = 0x0c00_0000;
+const PLIC_SIZE: DeviceSize = 0x400_0000;
+const PLIC_ENABLE: DeviceAddress = PLIC_BASE_ADDRESS + 0x2000;
+const PLIC_PENDING: DeviceAddress = PLIC_BASE_ADDRESS + 0x1000;
+const PLIC_CLAIM: GprUnsigned = PLIC_BASE_ADDRESS as GprUnsigned + 0x20_0004;
+
+// the UART0 source of the FE310-G002
+const SOURCE: usize = 3;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Creates a simulator with a PLIC whose source 3 is enabled with priority
+/// 1, running the setup code which enables the machine external interrupt,
+/// then nops. The handler claims and completes the interrupt.
+fn create_sim() -> (Simulator, InterruptLine) {
+    // common::setup_tracing();
+
+    let program = [
+        CSRW_MTVEC_INSTR,
+        CSRW_MIE_INSTR,
+        CSRS_MSTATUS_INSTR,
+        NOP_INSTR,
+    ];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+    let handler: Vec<u8> = [LW_CLAIM_INSTR, SW_COMPLETE_INSTR]
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let plic = Plic::new();
+    let line = plic.interrupt_line(SOURCE).unwrap();
+    let bus = sim.get_bus_mut();
+    let _ = bus.add_device(PLIC_BASE_ADDRESS, PLIC_SIZE, DevicePointer::new(plic));
+    bus.write_word(PLIC_BASE_ADDRESS + 4 * SOURCE, 1).unwrap();
+    bus.write_word(PLIC_ENABLE, 1 << SOURCE).unwrap();
+
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    let _ = sim.load_bin_program(&handler, HANDLER as DeviceAddress);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("t2", MIP_MEIP).unwrap();
+    core.write_reg_by_name("t3", MSTATUS_MIE).unwrap();
+    core.write_reg_by_name("a0", PLIC_CLAIM).unwrap();
+    (sim, line)
+}
+
+#[test]
+fn test_external_interrupt() {
+    let (mut sim, line) = create_sim();
+    line.raise();
+
+    // the interrupt is taken once enabled by the setup code
+    sim.run(Some(3)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_EXTERNAL_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MIP), Ok(MIP_MEIP));
+
+    // the claim returns the source and clears MEIP
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.read_reg_by_name("a2"), Ok(SOURCE as GprUnsigned));
+    assert_eq!(core.read_csr(CSR_MIP), Ok(0));
+
+    // a source lowered before its completion is not forwarded again
+    line.lower();
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MIP), Ok(0));
+    assert_eq!(sim.get_bus().read_word(PLIC_PENDING), Ok(0));
+}
+
+#[test]
+fn test_no_interrupt_without_a_raised_line() {
+    let (mut sim, _line) = create_sim();
+    sim.run(Some(4)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), BASE + 16);
+    assert_eq!(core.read_csr(CSR_MIP), Ok(0));
+}
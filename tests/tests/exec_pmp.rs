@@ -0,0 +1,160 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_pmp.rs
+
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use cpu_peripherals::DeviceAddress;
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw pmpaddr0, a2
+const CSRW_PMPADDR0_INSTR: u32 = 0x3b061073;
+// csrw pmpaddr1, a3
+const CSRW_PMPADDR1_INSTR: u32 = 0x3b169073;
+// csrw pmpcfg0, a4
+const CSRW_PMPCFG0_INSTR: u32 = 0x3a071073;
+// csrw mepc, t0
+const CSRW_MEPC_INSTR: u32 = 0x34129073;
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrc mstatus, t2
+const CSRC_MSTATUS_INSTR: u32 = 0x3003b073;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// lw a0, 0(a1)
+const LW_INSTR: u32 = 0x0005a503;
+// sw a0, 0(a1)
+const SW_INSTR: u32 = 0x00a5a023;
+// jr a5
+const JR_A5_INSTR: u32 = 0x00078067;
+
+const INSTRUCTION_ACCESS_FAULT: GprUnsigned = 1;
+const LOAD_ACCESS_FAULT: GprUnsigned = 5;
+const STORE_AMO_ACCESS_FAULT: GprUnsigned = 7;
+
+const MSTATUS_MPP: GprUnsigned = 0x1800;
+
+const PMP_R: GprUnsigned = 0x01;
+const PMP_X: GprUnsigned = 0x04;
+const PMP_A_TOR: GprUnsigned = 0x08;
+const PMP_A_NAPOT: GprUnsigned = 0x18;
+const PMP_L: GprUnsigned = 0x80;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The code run after MRET follows the seven setup instructions
+const RETURN_CODE: GprUnsigned = BASE + 28;
+const HANDLER: GprUnsigned = BASE + 0x100;
+// Entry 0 covers the code from 0 to CODE_END, entry 1 the 256 bytes at DATA
+const CODE_END: GprUnsigned = BASE + 0x200;
+const DATA: GprUnsigned = BASE + 0x1000;
+const DATA_NAPOT: GprUnsigned = (DATA >> 2) | 0x1f;
+const UNPROTECTED: GprUnsigned = BASE + 0x2000;
+
+const SETUP: [u32; 7] = [
+    CSRW_PMPADDR0_INSTR,
+    CSRW_PMPADDR1_INSTR,
+    CSRW_PMPCFG0_INSTR,
+    CSRW_MEPC_INSTR,
+    CSRW_MTVEC_INSTR,
+    CSRC_MSTATUS_INSTR,
+    MRET_INSTR,
+];
+
+/// Runs the setup code, which programs PMP entry 0 as a TOR region of
+/// executable code and entry 1 as a NAPOT region of data configured by
+/// `data_cfg`, then returns with MRET to `code` in the privilege mode `mpp`
+fn mret_to(code: &[u32], data_cfg: GprUnsigned, mpp: GprUnsigned) -> Simulator {
+    let registers = [
+        ("a2", CODE_END >> 2),
+        ("a3", DATA_NAPOT),
+        ("a4", (PMP_A_TOR | PMP_R | PMP_X) | data_cfg << 8),
+        ("t0", RETURN_CODE),
+        ("t1", HANDLER),
+        ("t2", MSTATUS_MPP & !mpp),
+        ("a1", DATA),
+    ];
+    common::run_after_mret(&SETUP, code, &registers, |sim| {
+        sim.get_bus_mut()
+            .write_word(DATA as DeviceAddress, 0x1234_5678)
+            .unwrap();
+    })
+}
+
+#[test]
+fn test_pmp_csrs() {
+    let sim = mret_to(&[], PMP_A_NAPOT | PMP_R, 0);
+    let core = sim.get_core();
+    assert_eq!(core.read_csr(CSR_PMPADDR0), Ok(CODE_END >> 2));
+    assert_eq!(core.read_csr(CSR_PMPADDR1), Ok(DATA_NAPOT));
+    assert_eq!(core.read_csr(CSR_PMPCFG0), Ok(0x190d));
+    assert_eq!(core.read_csr(CSR_PMPCFG1), Ok(0));
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::User);
+}
+
+#[test]
+fn test_user_load_and_store() {
+    let mut sim = mret_to(&[LW_INSTR, SW_INSTR], PMP_A_NAPOT | PMP_R, 0);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1234_5678));
+
+    // the data region is read-only
+    sim.get_core_mut().write_reg_by_name("a0", 0).unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(&sim, STORE_AMO_ACCESS_FAULT, RETURN_CODE + 4, DATA);
+    assert_eq!(
+        sim.get_bus().read_word(DATA as DeviceAddress),
+        Ok(0x1234_5678)
+    );
+}
+
+#[test]
+fn test_user_access_without_match() {
+    let mut sim = mret_to(&[LW_INSTR], PMP_A_NAPOT | PMP_R, 0);
+    sim.get_core_mut()
+        .write_reg_by_name("a1", UNPROTECTED)
+        .unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(&sim, LOAD_ACCESS_FAULT, RETURN_CODE, UNPROTECTED);
+
+    // the bytes of an emulated misaligned load are checked one by one, the
+    // first one after the region faults
+    let mut sim = mret_to(&[LW_INSTR], PMP_A_NAPOT | PMP_R, 0);
+    sim.get_core_mut()
+        .write_reg_by_name("a1", DATA + 0xfe)
+        .unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(&sim, LOAD_ACCESS_FAULT, RETURN_CODE, DATA + 0x100);
+}
+
+#[test]
+fn test_user_fetch_outside_code() {
+    let mut sim = mret_to(&[JR_A5_INSTR], PMP_A_NAPOT | PMP_R, 0);
+    sim.get_core_mut().write_reg_by_name("a5", DATA).unwrap();
+    sim.run(Some(2)).expect("Simulation failed");
+    common::assert_trapped(&sim, INSTRUCTION_ACCESS_FAULT, DATA, DATA);
+}
+
+#[test]
+fn test_locked_entry_applies_to_machine_mode() {
+    // M-mode ignores the unlocked entries
+    let mut sim = mret_to(&[SW_INSTR], PMP_A_NAPOT | PMP_R, MSTATUS_MPP);
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::Machine);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), RETURN_CODE + 4);
+
+    let mut sim = mret_to(
+        &[LW_INSTR, SW_INSTR],
+        PMP_A_NAPOT | PMP_R | PMP_L,
+        MSTATUS_MPP,
+    );
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1234_5678));
+    common::assert_trapped(&sim, STORE_AMO_ACCESS_FAULT, RETURN_CODE + 4, DATA);
+}
@@ -0,0 +1,247 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_rv64_instr.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprSigned, GprUnsigned, ProgramCounter, Xlen};
+
+use cpu_peripherals::DeviceAddress;
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// addw  a2, a0, a1
+const ADDW_INSTR: u32 = 0x00b5063b;
+// subw  a2, a0, a1
+const SUBW_INSTR: u32 = 0x40b5063b;
+// addiw a2, a0, 1
+const ADDIW_INSTR: u32 = 0x0015061b;
+// sllw  a2, a0, a1
+const SLLW_INSTR: u32 = 0x00b5163b;
+// sraiw a2, a0, 4
+const SRAIW_INSTR: u32 = 0x4045561b;
+// srli  a2, a0, 36
+const SRLI_INSTR: u32 = 0x02455613;
+// add   a2, a0, a1
+const ADD_INSTR: u32 = 0x00b50633;
+// mul   a2, a0, a1
+const MUL_INSTR: u32 = 0x02b50633;
+// mulh  a2, a0, a1
+const MULH_INSTR: u32 = 0x02b51633;
+// mulw  a2, a0, a1
+const MULW_INSTR: u32 = 0x02b5063b;
+// divw  a2, a0, a1
+const DIVW_INSTR: u32 = 0x02b5463b;
+// remuw a2, a0, a1
+const REMUW_INSTR: u32 = 0x02b5763b;
+// sd    a1, 8(a0)
+const SD_INSTR: u32 = 0x00b53423;
+// ld    a2, 8(a0)
+const LD_INSTR: u32 = 0x00853603;
+// lwu   a2, 8(a0)
+const LWU_INSTR: u32 = 0x00856603;
+// lr.d  a2, (a0)
+const LR_D_INSTR: u32 = 0x1005362f;
+// sc.d  a2, a1, (a0)
+const SC_D_INSTR: u32 = 0x18b5362f;
+// amoadd.d a2, a1, (a0)
+const AMOADD_D_INSTR: u32 = 0x00b5362f;
+// csrrs x0, mstatus, t0
+const ENABLE_FS_INSTR: u32 = 0x3002a073;
+// fcvt.d.l fa0, a0
+const FCVT_D_L_INSTR: u32 = 0xd2257553;
+// fcvt.l.d a2, fa0, rtz
+const FCVT_L_D_INSTR: u32 = 0xc2251653;
+// fmv.x.d  a2, fa0
+const FMV_X_D_INSTR: u32 = 0xe2050653;
+// c.addiw a0, -1 ; c.nop
+const C_ADDIW_INSTR: u32 = 0x0001_357d;
+
+const MSTATUS_FS_INITIAL: GprUnsigned = 0x2000;
+const DATA_ADDRESS: DeviceAddress = common::MEMORY_BASE_ADDRESS + 0x100;
+
+fn creat_rv64_sim_with_program(program: &[u32]) -> Simulator {
+    let mut sim = common::creat_sim_for_test();
+    sim.set_xlen(Xlen::Rv64);
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    sim
+}
+
+/// Runs `a2 = a0 op a1` on an RV64 core and returns a2
+fn run_r_instruction(instr: u32, rs1_val: GprUnsigned, rs2_val: GprUnsigned) -> GprUnsigned {
+    // common::setup_tracing();
+
+    // step 1. create a simulator and load the program into memory
+    let mut sim = creat_rv64_sim_with_program(&[instr]);
+
+    // step 2. prepare the environment
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", rs1_val).unwrap();
+    core.write_reg_by_name("a1", rs2_val).unwrap();
+
+    // step 3. run the simulator
+    sim.run(Some(1)).expect("Simulation failed");
+
+    // step 4. return the result
+    sim.get_core().read_reg_by_name("a2").unwrap()
+}
+
+fn sext(value: GprSigned) -> GprUnsigned {
+    value as GprUnsigned
+}
+
+#[test]
+fn test_misa_reports_rv64() {
+    let sim = creat_rv64_sim_with_program(&[]);
+    let misa = sim.get_core().read_csr(CSR_MISA).unwrap();
+    assert_eq!(misa >> 62, 2);
+}
+
+#[test]
+fn test_full_width_arithmetic() {
+    assert_eq!(run_r_instruction(ADD_INSTR, 0xffff_ffff, 1), 0x1_0000_0000);
+    assert_eq!(
+        run_r_instruction(SRLI_INSTR, 0xf000_0000_0000_0000, 0),
+        0xf00_0000
+    );
+    assert_eq!(
+        run_r_instruction(MUL_INSTR, 0x1_0000_0000, 0x1_0000_0000),
+        0
+    );
+    assert_eq!(
+        run_r_instruction(MULH_INSTR, 0x1_0000_0000, 0x1_0000_0000),
+        1
+    );
+    assert_eq!(run_r_instruction(MULH_INSTR, sext(-1), 1), sext(-1));
+}
+
+#[test]
+fn test_word_instructions_sign_extend() {
+    assert_eq!(
+        run_r_instruction(ADDW_INSTR, 0x7fff_ffff, 1),
+        sext(-0x8000_0000)
+    );
+    assert_eq!(run_r_instruction(ADDW_INSTR, 0x1_0000_0001, 1), 2);
+    assert_eq!(run_r_instruction(SUBW_INSTR, 0, 1), sext(-1));
+    assert_eq!(run_r_instruction(ADDIW_INSTR, 0xffff_ffff, 0), 0);
+    // only the low 5 bits of rs2 are used
+    assert_eq!(run_r_instruction(SLLW_INSTR, 1, 0x3f), sext(-0x8000_0000));
+    assert_eq!(
+        run_r_instruction(SRAIW_INSTR, 0x8000_0000, 0),
+        sext(-0x0800_0000)
+    );
+}
+
+#[test]
+fn test_word_mul_div() {
+    assert_eq!(
+        run_r_instruction(MULW_INSTR, 0x1_0000_0002, 0x4000_0000),
+        sext(-0x8000_0000)
+    );
+    assert_eq!(run_r_instruction(DIVW_INSTR, sext(-7), 2), sext(-3));
+    // division by zero returns all ones, overflow returns the dividend
+    assert_eq!(run_r_instruction(DIVW_INSTR, 7, 0), sext(-1));
+    assert_eq!(
+        run_r_instruction(DIVW_INSTR, 0x8000_0000, sext(-1)),
+        sext(-0x8000_0000)
+    );
+    assert_eq!(run_r_instruction(REMUW_INSTR, 0xffff_ffff, 0), sext(-1));
+}
+
+#[test]
+fn test_doubleword_load_store() {
+    let mut sim = creat_rv64_sim_with_program(&[SD_INSTR, LD_INSTR, LWU_INSTR]);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 0x8877_6655_4433_2211).unwrap();
+
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_bus().read_doubleword(DATA_ADDRESS + 8),
+        Ok(0x8877_6655_4433_2211)
+    );
+    assert_eq!(
+        sim.get_core().read_reg_by_name("a2"),
+        Ok(0x8877_6655_4433_2211)
+    );
+
+    // lwu zero-extends the word
+    sim.get_bus_mut()
+        .write_word(DATA_ADDRESS + 8, 0x8000_0000)
+        .unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0x8000_0000));
+}
+
+#[test]
+fn test_doubleword_atomics() {
+    let mut sim = creat_rv64_sim_with_program(&[LR_D_INSTR, SC_D_INSTR, AMOADD_D_INSTR]);
+    sim.get_bus_mut()
+        .write_doubleword(DATA_ADDRESS, 0x1_0000_0000)
+        .unwrap();
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", DATA_ADDRESS as GprUnsigned)
+        .unwrap();
+    core.write_reg_by_name("a1", 0xffff_ffff).unwrap();
+
+    // lr.d loads the doubleword, sc.d succeeds with the reservation held
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0x1_0000_0000));
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0));
+    assert_eq!(sim.get_bus().read_doubleword(DATA_ADDRESS), Ok(0xffff_ffff));
+
+    // amoadd.d carries into the upper word
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(0xffff_ffff));
+    assert_eq!(
+        sim.get_bus().read_doubleword(DATA_ADDRESS),
+        Ok(0x1_ffff_fffe)
+    );
+}
+
+#[test]
+fn test_compressed_addiw() {
+    let mut sim = creat_rv64_sim_with_program(&[C_ADDIW_INSTR]);
+    sim.get_core_mut()
+        .write_reg_by_name("a0", 0x1_0000_0000)
+        .unwrap();
+
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(sext(-1)));
+    assert_eq!(
+        sim.get_core().get_pc(),
+        common::MEMORY_BASE_ADDRESS as ProgramCounter + 2
+    );
+}
+
+#[test]
+fn test_fp_long_conversions() {
+    let program = [
+        ENABLE_FS_INSTR,
+        FCVT_D_L_INSTR,
+        FCVT_L_D_INSTR,
+        FMV_X_D_INSTR,
+    ];
+    let mut sim = creat_rv64_sim_with_program(&program);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t0", MSTATUS_FS_INITIAL).unwrap();
+    core.write_reg_by_name("a0", sext(-0x1_0000_0000)).unwrap();
+
+    sim.run(Some(3)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_reg_by_name("a2"),
+        Ok(sext(-0x1_0000_0000))
+    );
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_reg_by_name("a2"),
+        Ok((-4294967296.0_f64).to_bits())
+    );
+}
@@ -0,0 +1,241 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_supervisor_mode.rs
+
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw medeleg, a1
+const CSRW_MEDELEG_INSTR: u32 = 0x30259073;
+// csrw stvec, a2
+const CSRW_STVEC_INSTR: u32 = 0x10561073;
+// csrw mepc, t0
+const CSRW_MEPC_INSTR: u32 = 0x34129073;
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrc mstatus, t2
+const CSRC_MSTATUS_INSTR: u32 = 0x3003b073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// sret
+const SRET_INSTR: u32 = 0x10200073;
+// ecall
+const ECALL_INSTR: u32 = 0x00000073;
+// ebreak
+const EBREAK_INSTR: u32 = 0x00100073;
+// csrw sepc, a3
+const CSRW_SEPC_INSTR: u32 = 0x14169073;
+// csrr a0, satp
+const CSRR_SATP_INSTR: u32 = 0x18002573;
+// wfi
+const WFI_INSTR: u32 = 0x10500073;
+
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+const BREAKPOINT: GprUnsigned = 3;
+const ECALL_FROM_U_MODE: GprUnsigned = 8;
+const ECALL_FROM_S_MODE: GprUnsigned = 9;
+const ECALL_FROM_M_MODE: GprUnsigned = 11;
+
+const MSTATUS_SPP: GprUnsigned = 0x100;
+const MSTATUS_MPP: GprUnsigned = 0x1800;
+const MSTATUS_TVM: GprUnsigned = 0x100000;
+const MSTATUS_TW: GprUnsigned = 0x200000;
+const MSTATUS_TSR: GprUnsigned = 0x400000;
+const MISA_S: GprUnsigned = 0x40000;
+
+const MPP_USER: GprUnsigned = 0x0;
+const MPP_SUPERVISOR: GprUnsigned = 0x800;
+const MPP_MACHINE: GprUnsigned = 0x1800;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The code run after MRET follows the seven setup instructions
+const RETURN_CODE: GprUnsigned = BASE + 28;
+const S_HANDLER: GprUnsigned = BASE + 0x80;
+const M_HANDLER: GprUnsigned = BASE + 0x100;
+
+const SETUP: [u32; 7] = [
+    CSRW_MEDELEG_INSTR,
+    CSRW_STVEC_INSTR,
+    CSRW_MEPC_INSTR,
+    CSRW_MTVEC_INSTR,
+    CSRC_MSTATUS_INSTR,
+    CSRS_MSTATUS_INSTR,
+    MRET_INSTR,
+];
+
+/// Runs the setup code, which delegates the exceptions in `medeleg` and
+/// returns with MRET to `code` in the privilege mode `mpp`, with the other
+/// mstatus bits of `mstatus_set` set
+fn mret_to(
+    code: &[u32],
+    medeleg: GprUnsigned,
+    mpp: GprUnsigned,
+    mstatus_set: GprUnsigned,
+) -> Simulator {
+    let registers = [
+        ("a1", medeleg),
+        ("a2", S_HANDLER),
+        ("t0", RETURN_CODE),
+        ("t1", M_HANDLER),
+        ("t2", MSTATUS_MPP),
+        ("t3", mpp | mstatus_set),
+    ];
+    common::run_after_mret(&SETUP, code, &registers, |_| {})
+}
+
+#[test]
+fn test_misa_reports_supervisor_mode() {
+    let sim = common::creat_sim_for_test();
+    assert_ne!(sim.get_core().read_csr(CSR_MISA).unwrap() & MISA_S, 0);
+}
+
+#[test]
+fn test_delegated_ecall_from_u_mode() {
+    let mut sim = mret_to(&[ECALL_INSTR], 1 << ECALL_FROM_U_MODE, MPP_USER, 0);
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::User);
+
+    // the ECALL is taken in S-mode, M-mode does not see it
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Supervisor, ECALL_FROM_U_MODE))
+    );
+    let core = sim.get_core();
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Supervisor);
+    assert_eq!(core.read_csr(CSR_SEPC), Ok(RETURN_CODE));
+    assert_eq!(core.read_csr(CSR_SSTATUS).unwrap() & MSTATUS_SPP, 0);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(0));
+
+    // SRET returns to U-mode at sepc
+    common::load_program(&mut sim, S_HANDLER, &[CSRW_SEPC_INSTR, SRET_INSTR]);
+    sim.get_core_mut()
+        .write_reg_by_name("a3", RETURN_CODE + 4)
+        .unwrap();
+    sim.run(Some(2)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), RETURN_CODE + 4);
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::User);
+}
+
+#[test]
+fn test_delegated_ecall_from_s_mode() {
+    let mut sim = mret_to(&[ECALL_INSTR], 1 << ECALL_FROM_S_MODE, MPP_SUPERVISOR, 0);
+    assert_eq!(
+        *sim.get_core().get_privilege_mode(),
+        PrivilegeMode::Supervisor
+    );
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Supervisor, ECALL_FROM_S_MODE))
+    );
+    // sstatus.SPP records S-mode
+    assert_ne!(
+        sim.get_core().read_csr(CSR_SSTATUS).unwrap() & MSTATUS_SPP,
+        0
+    );
+
+    // SRET returns to S-mode
+    common::load_program(&mut sim, S_HANDLER, &[CSRW_SEPC_INSTR, SRET_INSTR]);
+    sim.get_core_mut()
+        .write_reg_by_name("a3", RETURN_CODE + 4)
+        .unwrap();
+    sim.run(Some(2)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), RETURN_CODE + 4);
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Supervisor);
+    assert_eq!(core.read_csr(CSR_SSTATUS).unwrap() & MSTATUS_SPP, 0);
+}
+
+#[test]
+fn test_traps_not_delegated() {
+    // only the ECALL from U-mode is delegated
+    let mut sim = mret_to(&[ECALL_INSTR], 1 << ECALL_FROM_U_MODE, MPP_SUPERVISOR, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ECALL_FROM_S_MODE))
+    );
+    let core = sim.get_core();
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Machine);
+    assert_eq!(
+        core.read_csr(CSR_MSTATUS).unwrap() & MSTATUS_MPP,
+        MPP_SUPERVISOR
+    );
+    assert_eq!(core.read_csr(CSR_SCAUSE), Ok(0));
+
+    // a trap raised in M-mode is never delegated
+    let mut sim = mret_to(&[EBREAK_INSTR], 1 << BREAKPOINT, MPP_MACHINE, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, BREAKPOINT))
+    );
+
+    // nor can the ECALL from M-mode be delegated
+    let sim = mret_to(&[], 1 << ECALL_FROM_M_MODE, MPP_MACHINE, 0);
+    assert_eq!(sim.get_core().read_csr(CSR_MEDELEG), Ok(0));
+}
+
+#[test]
+fn test_sret_privilege() {
+    // SRET is illegal in U-mode
+    let mut sim = mret_to(&[SRET_INSTR], 0, MPP_USER, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MTVAL),
+        Ok(SRET_INSTR as GprUnsigned)
+    );
+
+    // and in S-mode while mstatus.TSR is set
+    let mut sim = mret_to(&[SRET_INSTR], 0, MPP_SUPERVISOR, MSTATUS_TSR);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+}
+
+#[test]
+fn test_satp_trapped_by_tvm() {
+    let mut sim = mret_to(&[CSRR_SATP_INSTR], 0, MPP_SUPERVISOR, 0);
+    sim.get_core_mut().write_reg_by_name("a0", 1).unwrap();
+    assert_eq!(common::run_trapping(&mut sim), None);
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0));
+
+    let mut sim = mret_to(&[CSRR_SATP_INSTR], 0, MPP_SUPERVISOR, MSTATUS_TVM);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+}
+
+#[test]
+fn test_wfi_privilege() {
+    // WFI is illegal in U-mode
+    let mut sim = mret_to(&[WFI_INSTR], 0, MPP_USER, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+    assert!(!sim.get_core().is_waiting());
+
+    // and in S-mode while mstatus.TW is set
+    let mut sim = mret_to(&[WFI_INSTR], 0, MPP_SUPERVISOR, MSTATUS_TW);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+
+    let mut sim = mret_to(&[WFI_INSTR], 0, MPP_SUPERVISOR, 0);
+    assert_eq!(common::run_trapping(&mut sim), None);
+    assert!(sim.get_core().is_waiting());
+}
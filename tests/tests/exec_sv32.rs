@@ -0,0 +1,179 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_sv32.rs
+
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use cpu_peripherals::DeviceAddress;
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw satp, t4
+const CSRW_SATP_INSTR: u32 = 0x180e9073;
+// csrw mepc, t0
+const CSRW_MEPC_INSTR: u32 = 0x34129073;
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrc mstatus, t2
+const CSRC_MSTATUS_INSTR: u32 = 0x3003b073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// lw a0, 0(a1)
+const LW_INSTR: u32 = 0x0005a503;
+// sw a0, 4(a1)
+const SW_INSTR: u32 = 0x00a5a223;
+// sfence.vma
+const SFENCE_VMA_INSTR: u32 = 0x12000073;
+
+const INSTRUCTION_PAGE_FAULT: GprUnsigned = 12;
+const STORE_AMO_PAGE_FAULT: GprUnsigned = 15;
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+
+const MSTATUS_MPP: GprUnsigned = 0x1800;
+const MSTATUS_MPRV: GprUnsigned = 0x20000;
+const MPP_USER: GprUnsigned = 0x0;
+const MPP_SUPERVISOR: GprUnsigned = 0x800;
+
+const PTE_V: u32 = 0x01;
+const PTE_R: u32 = 0x02;
+const PTE_X: u32 = 0x08;
+const PTE_U: u32 = 0x10;
+const PTE_A: u32 = 0x40;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+const M_HANDLER: GprUnsigned = BASE + 0x100;
+const CODE: GprUnsigned = BASE + 0x1000;
+const DATA: GprUnsigned = BASE + 0x2000;
+const ROOT_TABLE: GprUnsigned = BASE + 0x4000;
+const LEAF_TABLE: GprUnsigned = BASE + 0x5000;
+
+// The code and data pages are mapped next to each other at 0x4000_0000
+const VIRTUAL_CODE: GprUnsigned = 0x4000_0000;
+const VIRTUAL_DATA: GprUnsigned = 0x4000_1000;
+const SATP: GprUnsigned = 0x8000_0000 | (ROOT_TABLE >> 12);
+
+fn write_word(sim: &mut Simulator, address: GprUnsigned, value: u32) {
+    sim.get_bus_mut()
+        .write_word(address as DeviceAddress, value)
+        .unwrap();
+}
+
+fn read_word(sim: &Simulator, address: GprUnsigned) -> u32 {
+    sim.get_bus().read_word(address as DeviceAddress).unwrap()
+}
+
+fn pte(physical: GprUnsigned, flags: u32) -> u32 {
+    ((physical >> 12) as u32) << 10 | flags
+}
+
+const SETUP: [u32; 6] = [
+    CSRW_SATP_INSTR,
+    CSRW_MEPC_INSTR,
+    CSRW_MTVEC_INSTR,
+    CSRC_MSTATUS_INSTR,
+    CSRS_MSTATUS_INSTR,
+    MRET_INSTR,
+];
+
+/// Builds the page tables, turns Sv32 on and returns with MRET to the
+/// virtual code page in the privilege mode `mpp`. Both pages are mapped
+/// with `flags` and the code page is also executable.
+fn run_mapped(code: &[u32], mpp: GprUnsigned, flags: u32) -> Simulator {
+    let registers = [
+        ("t4", SATP),
+        ("t0", VIRTUAL_CODE),
+        ("t1", M_HANDLER),
+        ("t2", MSTATUS_MPP),
+        ("t3", mpp),
+        ("a1", VIRTUAL_DATA),
+    ];
+    common::run_after_mret(&SETUP, &[], &registers, |sim| {
+        common::load_program(sim, CODE, code);
+        write_word(sim, ROOT_TABLE + 4 * 0x100, pte(LEAF_TABLE, PTE_V));
+        write_word(sim, LEAF_TABLE, pte(CODE, PTE_V | PTE_X | flags));
+        write_word(sim, LEAF_TABLE + 4, pte(DATA, PTE_V | flags));
+        write_word(sim, DATA, 0x1234_5678);
+    })
+}
+
+#[test]
+fn test_translated_fetch_and_load() {
+    let mut sim = run_mapped(&[LW_INSTR, SW_INSTR], MPP_SUPERVISOR, PTE_R);
+
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), VIRTUAL_CODE + 4);
+    assert_eq!(core.read_reg_by_name("a0"), Ok(0x1234_5678));
+    // the walks set the A bits
+    assert_ne!(read_word(&sim, LEAF_TABLE) & PTE_A, 0);
+    assert_ne!(read_word(&sim, LEAF_TABLE + 4) & PTE_A, 0);
+
+    // the data page is read-only
+    sim.run(Some(1)).expect("Simulation failed");
+    common::assert_trapped(
+        &sim,
+        STORE_AMO_PAGE_FAULT,
+        VIRTUAL_CODE + 4,
+        VIRTUAL_DATA + 4,
+    );
+    assert_eq!(read_word(&sim, DATA + 4), 0);
+}
+
+#[test]
+fn test_instruction_page_fault() {
+    // the code runs off the end of the code page into the data page, which
+    // is not executable
+    let mut program = vec![0x00000013; 1024];
+    program[1023] = LW_INSTR;
+    let mut sim = run_mapped(&program, MPP_SUPERVISOR, PTE_R);
+    sim.run(Some(1025)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), M_HANDLER);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(INSTRUCTION_PAGE_FAULT));
+    assert_eq!(core.read_csr(CSR_MTVAL), Ok(VIRTUAL_DATA));
+}
+
+#[test]
+fn test_user_pages() {
+    // U-mode only runs on U pages
+    let mut sim = run_mapped(&[LW_INSTR], MPP_USER, PTE_R);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MCAUSE),
+        Ok(INSTRUCTION_PAGE_FAULT)
+    );
+
+    let mut sim = run_mapped(&[LW_INSTR], MPP_USER, PTE_R | PTE_U);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1234_5678));
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::User);
+
+    // SFENCE.VMA is illegal in U-mode
+    let mut sim = run_mapped(&[SFENCE_VMA_INSTR], MPP_USER, PTE_R | PTE_U);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+}
+
+#[test]
+fn test_mprv_translates_machine_loads() {
+    // the store page fault enters the M-mode handler with MPP set to S
+    let mut sim = run_mapped(&[SW_INSTR], MPP_SUPERVISOR, PTE_R);
+    common::load_program(&mut sim, M_HANDLER, &[CSRS_MSTATUS_INSTR, LW_INSTR]);
+    sim.get_core_mut()
+        .write_reg_by_name("t3", MSTATUS_MPRV)
+        .unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), M_HANDLER);
+
+    // the M-mode code is not translated, its load is
+    sim.run(Some(2)).expect("Simulation failed");
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0x1234_5678));
+}
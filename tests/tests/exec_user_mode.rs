@@ -0,0 +1,144 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_user_mode.rs
+
+use rv_core::core::PrivilegeMode;
+use rv_core::inst_csr_reg::*;
+use rv_core::GprUnsigned;
+
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mepc, t0
+const CSRW_MEPC_INSTR: u32 = 0x34129073;
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrc mstatus, t2
+const CSRC_MSTATUS_INSTR: u32 = 0x3003b073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// mret
+const MRET_INSTR: u32 = 0x30200073;
+// ecall
+const ECALL_INSTR: u32 = 0x00000073;
+// csrr a0, mscratch
+const CSRR_MSCRATCH_INSTR: u32 = 0x34002573;
+// rdcycle a0
+const RDCYCLE_INSTR: u32 = 0xc0002573;
+
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+const ECALL_FROM_U_MODE: GprUnsigned = 8;
+const ECALL_FROM_M_MODE: GprUnsigned = 11;
+
+const MSTATUS_MPP: GprUnsigned = 0x1800;
+const MSTATUS_MPRV: GprUnsigned = 0x20000;
+const MISA_U: GprUnsigned = 0x100000;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// The code run after MRET follows the five setup instructions
+const RETURN_CODE: GprUnsigned = BASE + 20;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+const SETUP: [u32; 5] = [
+    CSRW_MEPC_INSTR,
+    CSRW_MTVEC_INSTR,
+    CSRC_MSTATUS_INSTR,
+    CSRS_MSTATUS_INSTR,
+    MRET_INSTR,
+];
+
+/// Runs the setup code, which returns with MRET to `code` in the privilege
+/// mode left in mstatus.MPP after clearing `mpp_clear` and setting `mstatus_set`
+fn mret_to(code: &[u32], mpp_clear: GprUnsigned, mstatus_set: GprUnsigned) -> Simulator {
+    let registers = [
+        ("t0", RETURN_CODE),
+        ("t1", HANDLER),
+        ("t2", mpp_clear),
+        ("t3", mstatus_set),
+    ];
+    common::run_after_mret(&SETUP, code, &registers, |_| {})
+}
+
+#[test]
+fn test_misa_reports_user_mode() {
+    let sim = common::creat_sim_for_test();
+    assert_ne!(sim.get_core().read_csr(CSR_MISA).unwrap() & MISA_U, 0);
+}
+
+#[test]
+fn test_ecall_cause_follows_privilege() {
+    // mstatus.MPP is M after reset
+    let mut sim = mret_to(&[ECALL_INSTR], 0, 0);
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::Machine);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ECALL_FROM_M_MODE))
+    );
+
+    let mut sim = mret_to(&[ECALL_INSTR], MSTATUS_MPP, 0);
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::User);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ECALL_FROM_U_MODE))
+    );
+    // the trap enters M-mode and records U in mstatus.MPP
+    assert_eq!(*sim.get_core().get_privilege_mode(), PrivilegeMode::Machine);
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MSTATUS).unwrap() & MSTATUS_MPP,
+        0
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_MEPC), Ok(RETURN_CODE));
+}
+
+#[test]
+fn test_user_mode_illegal_instructions() {
+    // M-mode CSRs are not accessible
+    let mut sim = mret_to(&[CSRR_MSCRATCH_INSTR], MSTATUS_MPP, 0);
+    sim.get_core_mut().write_reg_by_name("a0", 1).unwrap();
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(1));
+    assert_eq!(
+        sim.get_core().read_csr(CSR_MTVAL),
+        Ok(CSRR_MSCRATCH_INSTR as GprUnsigned)
+    );
+
+    // neither is MRET
+    let mut sim = mret_to(&[MRET_INSTR], MSTATUS_MPP, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_MEPC), Ok(RETURN_CODE));
+
+    // user counters need mcounteren
+    let mut sim = mret_to(&[RDCYCLE_INSTR], MSTATUS_MPP, 0);
+    assert_eq!(
+        common::run_trapping(&mut sim),
+        Some((PrivilegeMode::Machine, ILLEGAL_INSTRUCTION))
+    );
+}
+
+#[test]
+fn test_mprv() {
+    let sim = mret_to(&[], MSTATUS_MPP, MSTATUS_MPRV);
+    let core = sim.get_core();
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::User);
+    // returning to U-mode clears MPRV
+    assert_eq!(core.read_csr(CSR_MSTATUS).unwrap() & MSTATUS_MPRV, 0);
+    assert_eq!(core.data_privilege_mode(), Ok(PrivilegeMode::User));
+
+    // MPRV is kept when MRET returns to M-mode, loads and stores then use MPP
+    let sim = mret_to(&[], 0, MSTATUS_MPRV);
+    let core = sim.get_core();
+    assert_eq!(*core.get_privilege_mode(), PrivilegeMode::Machine);
+    assert_ne!(core.read_csr(CSR_MSTATUS).unwrap() & MSTATUS_MPRV, 0);
+    // MRET set MPP to U
+    assert_eq!(core.data_privilege_mode(), Ok(PrivilegeMode::User));
+}
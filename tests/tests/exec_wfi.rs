@@ -0,0 +1,120 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/exec_wfi.rs
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rv_core::inst_csr_reg::*;
+use rv_core::{GprUnsigned, ProgramCounter};
+
+use cpu_peripherals::{DeviceAddress, MIP_MEIP, MIP_MTIP};
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// csrw mtvec, t1
+const CSRW_MTVEC_INSTR: u32 = 0x30531073;
+// csrw mie, t2
+const CSRW_MIE_INSTR: u32 = 0x30439073;
+// csrs mstatus, t3
+const CSRS_MSTATUS_INSTR: u32 = 0x300e2073;
+// wfi
+const WFI_INSTR: u32 = 0x10500073;
+// nop
+const NOP_INSTR: u32 = 0x00000013;
+
+const MACHINE_TIMER_INTERRUPT: GprUnsigned = 0x8000_0007;
+const MSTATUS_MIE: GprUnsigned = 0x8;
+
+const BASE: GprUnsigned = common::MEMORY_BASE_ADDRESS as GprUnsigned;
+// WFI follows the three setup instructions
+const WFI: GprUnsigned = BASE + 12;
+const HANDLER: GprUnsigned = BASE + 0x100;
+
+/// Runs the setup code, which enables the interrupts of `mie` and sets the
+/// bits `mstatus_set` in mstatus, then WFI. The device asserts the
+/// interrupts `lines` from the start and MTIP after `timer` cycles.
+fn run_wfi(
+    mie: GprUnsigned,
+    mstatus_set: GprUnsigned,
+    lines: u64,
+    timer: Option<u64>,
+) -> (Simulator, Rc<Cell<u64>>) {
+    // common::setup_tracing();
+
+    let program = [
+        CSRW_MTVEC_INSTR,
+        CSRW_MIE_INSTR,
+        CSRS_MSTATUS_INSTR,
+        WFI_INSTR,
+        NOP_INSTR,
+    ];
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut sim = common::creat_sim_for_test();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    let _ = sim.load_bin_program(&NOP_INSTR.to_le_bytes(), HANDLER as DeviceAddress);
+    let lines = common::add_interrupt_source(&mut sim, lines, timer);
+
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("t1", HANDLER).unwrap();
+    core.write_reg_by_name("t2", mie).unwrap();
+    core.write_reg_by_name("t3", mstatus_set).unwrap();
+
+    sim.run(Some(4)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), WFI + 4);
+    (sim, lines)
+}
+
+#[test]
+fn test_wfi_skips_to_the_timer_interrupt() {
+    let (mut sim, _) = run_wfi(MIP_MTIP, MSTATUS_MIE, 0, Some(1_000_000));
+    assert!(sim.get_core().is_waiting());
+    let instructions = sim.get_run_instrctions();
+
+    // the idle cycles pass in one step, then the interrupt is taken with mepc
+    // after WFI and the handler runs
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert!(!core.is_waiting());
+    assert_eq!(core.get_pc(), HANDLER + 4);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(MACHINE_TIMER_INTERRUPT));
+    assert_eq!(core.read_csr(CSR_MEPC), Ok(WFI + 4));
+    assert!(core.read_csr(CSR_MCYCLE).unwrap() > 1_000_000);
+    assert_eq!(sim.get_run_instrctions(), instructions + 1);
+}
+
+#[test]
+fn test_wfi_wakes_up_with_interrupts_disabled() {
+    // without mstatus.MIE the hart resumes after WFI
+    let (mut sim, _) = run_wfi(MIP_MTIP, 0, 0, Some(1000));
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    assert_eq!(core.get_pc(), WFI + 8);
+    assert_eq!(core.read_csr(CSR_MCAUSE), Ok(0));
+}
+
+#[test]
+fn test_wfi_waits_for_an_enabled_interrupt() {
+    // MEIP is not enabled in mie and nothing is scheduled
+    let (mut sim, lines) = run_wfi(MIP_MTIP, MSTATUS_MIE, MIP_MEIP, None);
+    sim.run(Some(100)).expect("Simulation failed");
+    assert!(sim.get_core().is_waiting());
+    assert_eq!(sim.get_core().get_pc(), WFI + 4);
+
+    lines.set(MIP_MTIP);
+    sim.run(Some(1)).expect("Simulation failed");
+    assert_eq!(sim.get_core().get_pc(), HANDLER + 4);
+}
+
+#[test]
+fn test_wfi_with_pending_interrupt() {
+    // WFI completes at once
+    let (sim, _) = run_wfi(MIP_MTIP, 0, MIP_MTIP, None);
+    assert!(!sim.get_core().is_waiting());
+}
@@ -0,0 +1,104 @@
+// Copyright (c) 2024, zhao.shaowei <nsearchf@yeah.net>
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// tests/tests/test_isa.rs
+
+use rv_core::inst_csr_reg::*;
+use rv_core::isa::{Extension, Isa};
+use rv_core::{GprUnsigned, ProgramCounter, Xlen};
+
+use sim_lib::simulator::Simulator;
+
+mod common;
+
+// fadd.s fa0, fa0, fa1
+const FADD_S_INSTR: u32 = 0x00b57553;
+// c.addi a0, 1 ; c.nop
+const C_ADDI_INSTR: u32 = 0x0001_0505;
+// csrr   a0, mscratch
+const CSRR_INSTR: u32 = 0x34002573;
+// mul    a2, a0, a1
+const MUL_INSTR: u32 = 0x02b50633;
+
+const ILLEGAL_INSTRUCTION: GprUnsigned = 2;
+
+fn creat_sim_with_isa(isa: &str, program: &[u32]) -> Simulator {
+    let mut sim = common::creat_sim_for_test();
+    sim.set_isa(isa.parse().unwrap());
+    let bytes: Vec<u8> = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+    let _ = sim.load_bin_program(&bytes, common::MEMORY_BASE_ADDRESS);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    sim
+}
+
+/// Runs a single instruction and returns mcause and mtval, which are 0 if it did not trap
+fn run_single(sim: &mut Simulator) -> (GprUnsigned, GprUnsigned) {
+    sim.run(Some(1)).expect("Simulation failed");
+    let core = sim.get_core();
+    (
+        core.read_csr(CSR_MCAUSE).unwrap(),
+        core.read_csr(CSR_MTVAL).unwrap(),
+    )
+}
+
+#[test]
+fn test_misa_reflects_isa() {
+    let sim = creat_sim_with_isa("rv32imac_zicsr_zifencei_zba", &[]);
+    assert_eq!(sim.get_core().read_csr(CSR_MISA), Ok(0x4014_1105));
+    assert_eq!(sim.get_isa().to_string(), "rv32imac_zicsr_zifencei_zba");
+
+    let sim = creat_sim_with_isa("rv64gc", &[]);
+    assert_eq!(sim.get_core().get_xlen(), Xlen::Rv64);
+    assert_eq!(sim.get_core().read_csr(CSR_MISA), Ok(0x8000_0000_0014_112d));
+
+    // the default ISA keeps every extension
+    let sim = common::creat_sim_for_test();
+    assert_eq!(sim.get_isa(), &Isa::default());
+    assert_eq!(sim.get_core().read_csr(CSR_MISA), Ok(0x4014_112d));
+}
+
+#[test]
+fn test_disabled_extensions_are_illegal() {
+    let mut sim = creat_sim_with_isa("rv32imac_zicsr", &[FADD_S_INSTR]);
+    assert_eq!(
+        run_single(&mut sim),
+        (ILLEGAL_INSTRUCTION, FADD_S_INSTR as GprUnsigned)
+    );
+
+    // mtval holds the 16 bits of a compressed instruction
+    let mut sim = creat_sim_with_isa("rv32im_zicsr", &[C_ADDI_INSTR]);
+    assert_eq!(run_single(&mut sim), (ILLEGAL_INSTRUCTION, 0x0505));
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(0));
+
+    let mut sim = creat_sim_with_isa("rv32i", &[CSRR_INSTR]);
+    sim.set_extension_enabled(Extension::Zicsr, false);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", 1).unwrap();
+    sim.run(Some(1)).expect("Simulation failed");
+    // csrr is illegal without Zicsr, a0 is left unchanged
+    assert_eq!(sim.get_core().read_csr(CSR_MCAUSE), Ok(ILLEGAL_INSTRUCTION));
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(1));
+}
+
+#[test]
+fn test_enabled_extensions_execute() {
+    let mut sim = creat_sim_with_isa("rv32imac_zicsr", &[MUL_INSTR, C_ADDI_INSTR]);
+    let core = sim.get_core_mut();
+    core.write_reg_by_name("a0", 6).unwrap();
+    core.write_reg_by_name("a1", 7).unwrap();
+    assert_eq!(run_single(&mut sim), (0, 0));
+    assert_eq!(sim.get_core().read_reg_by_name("a2"), Ok(42));
+    assert_eq!(run_single(&mut sim), (0, 0));
+    assert_eq!(sim.get_core().read_reg_by_name("a0"), Ok(7));
+
+    // switching M off makes mul illegal
+    sim.set_extension_enabled(Extension::M, false);
+    sim.set_reset_vector(common::MEMORY_BASE_ADDRESS as ProgramCounter);
+    assert_eq!(
+        run_single(&mut sim),
+        (ILLEGAL_INSTRUCTION, MUL_INSTR as GprUnsigned)
+    );
+    assert_eq!(sim.get_core().read_csr(CSR_MISA), Ok(0x4014_0105));
+}
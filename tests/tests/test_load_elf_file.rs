@@ -38,3 +38,15 @@ fn test_load_elf_file() {
     assert_eq!(bus.read_word(0x00012594), Ok(0x00000000));
     assert_eq!(bus.read_word(0x0001254c), Ok(0x10));
 }
+
+#[test]
+fn test_load_invalid_elf_file_fails() {
+    let mut sim = common::creat_sim_for_test();
+    let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    // the errors of the loader are reported to the caller
+    let missing_path = project_root.join("../tests/tests/data/missing.elf");
+    assert!(sim.load_elf_file(missing_path.as_path()).is_err());
+    let not_elf_path = project_root.join("Cargo.toml");
+    assert!(sim.load_elf_file(not_elf_path.as_path()).is_err());
+}
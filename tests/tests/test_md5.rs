@@ -3,7 +3,6 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-
 use std::path::PathBuf;
 
 use cpu_peripherals::bus::{Bus, DevicePointer};
@@ -64,7 +63,7 @@ fn test_load_elf_file() {
     {
         let bus = sim.get_bus();
         let addr: DeviceAddress = 0x80080000_u32 as DeviceAddress;
-        assert_eq!(bus.read_byte(addr + 0), Ok(0x6d));
+        assert_eq!(bus.read_byte(addr), Ok(0x6d));
         assert_eq!(bus.read_byte(addr + 1), Ok(0x8d));
         assert_eq!(bus.read_byte(addr + 2), Ok(0x0b));
         assert_eq!(bus.read_byte(addr + 3), Ok(0x2c));